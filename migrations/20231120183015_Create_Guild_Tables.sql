@@ -0,0 +1,28 @@
+CREATE TYPE guild_rank as enum ('master', 'officer', 'member');
+
+create table guilds
+(
+    id           serial
+        constraint guilds_pk primary key,
+    name         varchar                                not null,
+    level        smallint    default 1                  not null,
+    notice_title varchar     default ''                 not null,
+    notice_body  text        default ''                 not null,
+    created_at   timestamp with time zone default now() not null
+);
+
+create unique index guilds_name_uindex on guilds (LOWER(name));
+
+create table guild_members
+(
+    guild_id     integer    not null
+        constraint guild_members_guilds_id_fk
+            references guilds ON DELETE CASCADE,
+    character_id integer    not null
+        constraint guild_members_characters_id_fk
+            references characters ON DELETE CASCADE,
+    rank         guild_rank not null default 'member',
+    joined_at    timestamp with time zone default now() not null,
+    constraint guild_members_pk
+        unique (character_id)
+);
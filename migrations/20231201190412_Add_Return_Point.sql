@@ -0,0 +1,4 @@
+ALTER TABLE characters ADD COLUMN return_region smallint;
+ALTER TABLE characters ADD COLUMN return_x real;
+ALTER TABLE characters ADD COLUMN return_y real;
+ALTER TABLE characters ADD COLUMN return_z real;
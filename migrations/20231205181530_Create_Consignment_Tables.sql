@@ -0,0 +1,25 @@
+create table consignments
+(
+    id            serial
+        constraint consignments_pk primary key,
+    character_id  integer                                not null
+        constraint consignments_characters_id_fk
+            references characters ON DELETE CASCADE,
+    item_obj_id   integer                                not null,
+    upgrade_level smallint    default 0                  not null,
+    variance      bigint,
+    amount        smallint    default 1                  not null,
+    price         bigint                                 not null,
+    deposit       bigint                                 not null,
+    fee           bigint                                 not null,
+    registered_at timestamp with time zone default now() not null,
+    expires_at    timestamp with time zone               not null,
+    buyer_id      integer
+        constraint consignments_buyer_id_fk
+            references characters ON DELETE SET NULL,
+    sold_at       timestamp with time zone
+);
+
+create index consignments_character_id_index on consignments (character_id);
+
+create index consignments_on_sale_index on consignments (expires_at) where sold_at is null;
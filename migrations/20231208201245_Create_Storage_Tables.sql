@@ -0,0 +1,20 @@
+create table user_storage_items
+(
+    id            serial
+        constraint user_storage_items_pk primary key,
+    user_id       integer             not null
+        constraint user_storage_items_users_id_fk
+            references users ON DELETE CASCADE,
+    server_id     integer             not null
+        constraint user_storage_items_servers_id_fk
+            references servers (id),
+    slot          smallint            not null,
+    item_obj_id   integer             not null,
+    upgrade_level smallint default 0  not null,
+    variance      bigint,
+    amount        smallint default 1  not null,
+    constraint user_storage_items_slot_uniq
+        unique (user_id, server_id, slot)
+);
+
+ALTER TABLE user_servers ADD COLUMN storage_gold bigint default 0 not null;
@@ -0,0 +1,4 @@
+ALTER TABLE characters ADD COLUMN pk_state smallint NOT NULL DEFAULT 0;
+ALTER TABLE characters ADD COLUMN pk_state_until timestamptz;
+ALTER TABLE characters ADD COLUMN pk_kills integer NOT NULL DEFAULT 0;
+ALTER TABLE characters ADD COLUMN pk_penalty integer NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+ALTER TABLE characters ADD COLUMN job_level smallint NOT NULL DEFAULT 1;
+ALTER TABLE characters ADD COLUMN job_exp bigint NOT NULL DEFAULT 0;
@@ -0,0 +1,14 @@
+create table bans
+(
+    id           serial
+        constraint bans_pk primary key,
+    user_id      integer                   not null
+        constraint bans_users_id_fk
+            references users ON DELETE CASCADE,
+    reason       text                      not null,
+    banned_at    timestamptz default now() not null,
+    banned_until timestamptz,
+    lifted_at    timestamptz
+);
+
+create index bans_user_id_index on bans (user_id);
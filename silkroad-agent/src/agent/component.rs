@@ -21,6 +21,8 @@ pub(crate) struct Agent {
     pub(crate) running_speed: f32,
     pub(crate) walking_speed: f32,
     pub(crate) berserk_speed: f32,
+    /// Movement speed increase from buffs, in percent.
+    pub(crate) speed_bonus: f32,
 }
 
 impl Default for Agent {
@@ -29,6 +31,7 @@ impl Default for Agent {
             running_speed: 50.0,
             walking_speed: 16.0,
             berserk_speed: 100.0,
+            speed_bonus: 0.0,
         }
     }
 }
@@ -39,6 +42,7 @@ impl Agent {
             running_speed,
             walking_speed,
             berserk_speed,
+            speed_bonus: 0.0,
         }
     }
 
@@ -47,15 +51,17 @@ impl Agent {
             running_speed: character_data.run_speed as f32,
             walking_speed: character_data.walk_speed as f32,
             berserk_speed: character_data.berserk_speed as f32,
+            speed_bonus: 0.0,
         }
     }
 
     pub(crate) fn get_speed_value(&self, speed: MovementSpeed) -> f32 {
-        match speed {
+        let base = match speed {
             MovementSpeed::Running => self.running_speed,
             MovementSpeed::Walking => self.walking_speed,
             MovementSpeed::Berserk => self.berserk_speed,
-        }
+        };
+        base * (1.0 + self.speed_bonus / 100.0).max(0.0)
     }
 
     pub(crate) fn set_speed(&mut self, speed: MovementSpeed, value: f32) {
@@ -1,8 +1,8 @@
 use crate::agent::event::{ActionFinished, MovementFinished};
 use crate::agent::states::{action, dead, movement, pickup, turning, update_target_location};
 use crate::agent::system::{
-    movement_input, transition_from_attacking, transition_from_idle, transition_from_moving, transition_from_sitting,
-    transition_to_idle,
+    movement_input, sitting_input, transition_from_attacking, transition_from_idle, transition_from_moving,
+    transition_from_sitting, transition_to_idle,
 };
 use bevy_app::{App, Plugin, PostUpdate, PreUpdate, Update};
 use bevy_ecs::prelude::*;
@@ -30,7 +30,7 @@ impl Plugin for AgentPlugin {
             .configure_sets(PreUpdate, AgentSet::Input)
             .configure_sets(Update, (AgentSet::Transition, AgentSet::Execute).chain())
             .configure_sets(PostUpdate, AgentSet::Broadcast)
-            .add_systems(PreUpdate, movement_input.in_set(AgentSet::Input))
+            .add_systems(PreUpdate, (movement_input, sitting_input).in_set(AgentSet::Input))
             .add_systems(
                 Update,
                 (
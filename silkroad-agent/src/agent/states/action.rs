@@ -1,28 +1,37 @@
-use crate::agent::states::Idle;
+use crate::agent::states::{Dead, Idle};
+use crate::buff::{buff_duration, debuff_of, Buffed};
 use crate::comp::gold::GoldPouch;
-use crate::comp::inventory::PlayerInventory;
+use crate::comp::inventory::{give_item, PlayerInventory};
+use crate::comp::monster::Monster;
 use crate::comp::net::Client;
-use crate::comp::player::Player;
+use crate::comp::player::StatPoints;
+use crate::comp::pos::Position;
+use crate::comp::visibility::Visibility;
 use crate::comp::{drop, EntityReference, GameEntity};
-use crate::event::{AttackDefinition, DamageReceiveEvent};
+use crate::event::{AttackDefinition, BuffApplyEvent, DamageReceiveEvent, ResurrectEvent};
+use crate::exchange::Exchange;
 use crate::ext::ActionIdCounter;
+use crate::game::combat::{calculate_hit, AreaOfEffect, AttackPower, Defense, Hit, SkillAttack};
+use crate::game::death::resurrection_of;
+use crate::game::mind::Mind;
+use crate::party::{Parties, PartyMember};
+use crate::world::WorldData;
 use bevy_ecs::prelude::*;
 use bevy_ecs::query::QueryEntityError;
 use bevy_time::{Time, Timer, TimerMode};
+use cgmath::{Deg, InnerSpace, Quaternion, Rotation3, Vector3};
 use log::debug;
 use rand::Rng;
 use silkroad_data::skilldata::{RefSkillData, SkillParam};
-use silkroad_data::DataEntry;
 use silkroad_definitions::inventory::EquipmentSlot;
-use silkroad_game_base::{GlobalLocation, Item, ItemTypeData};
+use silkroad_game_base::{GlobalLocation, ItemTypeData, Vector3Ext};
 use silkroad_protocol::combat::{DoActionResponseCode, PerformActionError, PerformActionResponse};
-use silkroad_protocol::inventory::{
-    InventoryItemBindingData, InventoryItemContentData, InventoryOperationError, InventoryOperationResult,
-};
 use silkroad_protocol::ServerPacket;
 use std::time::Duration;
 use tracing::error;
 
+const ITEM_SHARE_RANGE_SQUARED: f32 = 1000.0 * 1000.0;
+
 #[derive(Copy, Clone)]
 pub(crate) enum ActionTarget {
     None,
@@ -96,13 +105,27 @@ impl From<ActionDescription> for Action {
 pub(crate) struct Pickup(pub Entity, pub Option<Timer>);
 
 pub(crate) fn pickup(
-    mut query: Query<(Entity, &Client, &mut Pickup, &mut PlayerInventory, &mut GoldPouch)>,
+    mut query: Query<(
+        Entity,
+        &Client,
+        &Position,
+        &mut Pickup,
+        &mut PlayerInventory,
+        &mut GoldPouch,
+        Option<&PartyMember>,
+        Has<Exchange>,
+    )>,
+    mut party_members: Query<
+        (&Client, &Position, &mut PlayerInventory, &mut GoldPouch, Has<Exchange>),
+        Without<Pickup>,
+    >,
+    parties: Res<Parties>,
     time: Res<Time>,
     target_query: Query<&drop::Drop>,
     mut cmd: Commands,
 ) {
     let delta = time.delta();
-    for (entity, client, mut pickup, mut inventory, mut gold) in query.iter_mut() {
+    for (entity, client, position, mut pickup, mut inventory, mut gold, party, in_exchange) in query.iter_mut() {
         if let Some(cooldown) = pickup.1.as_mut() {
             if cooldown.tick(delta).just_finished() {
                 client.send(PerformActionResponse::Stop(PerformActionError::Completed));
@@ -123,150 +146,322 @@ pub(crate) fn pickup(
                 },
             };
 
-            cmd.entity(pickup.0).despawn();
-            pickup.1 = Some(Timer::from_seconds(1.0, TimerMode::Once));
+            // While the loot is still reserved, only the owner and their party members may pick it up.
+            let may_pick_up = drop.owner.is_none_or(|owner| {
+                owner.0 == entity
+                    || party
+                        .and_then(|party| parties.get(party.0))
+                        .is_some_and(|party| party.members().iter().any(|member| member.0 == owner.0))
+            });
+            // The offer of an exchange must not change behind the partner's back, which includes stacking items
+            // onto it.
+            let is_gold = matches!(drop.item.type_data, ItemTypeData::Gold { .. });
+            if !may_pick_up || (in_exchange && !is_gold) {
+                client.send(PerformActionResponse::Stop(PerformActionError::InvalidTarget));
+                cmd.entity(entity).remove::<Pickup>().insert(Idle);
+                continue;
+            }
+
+            // With item sharing enabled, the loot gets handed out among all party members close by. Items only go to
+            // members that still have room for them.
+            let share_with = party
+                .and_then(|party| parties.get(party.0))
+                .filter(|party| party.settings().item_shared)
+                .map(|party| {
+                    party
+                        .members()
+                        .iter()
+                        .filter(|member| member.0 != entity)
+                        .filter(|member| {
+                            party_members
+                                .get(member.0)
+                                .map(|(_, other, other_inventory, _, other_in_exchange)| {
+                                    position.distance_to(other) <= ITEM_SHARE_RANGE_SQUARED
+                                        && (is_gold || (!other_in_exchange && other_inventory.free_slots() > 0))
+                                })
+                                .unwrap_or(false)
+                        })
+                        .map(|member| member.0)
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
 
             match &drop.item.type_data {
                 ItemTypeData::Gold { amount } => {
-                    gold.gain(u64::from(*amount));
-                    client.send(PerformActionResponse::Do(DoActionResponseCode::Success));
-                },
-                ItemTypeData::Equipment { upgrade_level } => {
-                    if let Some(slot) = inventory.add_item(drop.item) {
-                        client.send(InventoryOperationResult::success_gain_item(
-                            slot,
-                            drop.item.reference.ref_id(),
-                            InventoryItemContentData::Equipment {
-                                plus_level: *upgrade_level,
-                                variance: drop.item.variance.unwrap_or_default(),
-                                durability: 1,
-                                magic: vec![],
-                                bindings_1: InventoryItemBindingData::new(1, 0),
-                                bindings_2: InventoryItemBindingData::new(2, 0),
-                                bindings_3: InventoryItemBindingData::new(3, 0),
-                                bindings_4: InventoryItemBindingData::new(4, 0),
-                            },
-                        ));
+                    let amount = u64::from(*amount);
+                    let share = amount / (share_with.len() as u64 + 1);
+                    for member in share_with.iter() {
+                        if let Ok((_, _, _, mut other_gold, _)) = party_members.get_mut(*member) {
+                            other_gold.gain(share);
+                        }
                     }
+                    gold.gain(amount - share * share_with.len() as u64);
+                    client.send(PerformActionResponse::Do(DoActionResponseCode::Success));
                 },
                 _ => {
-                    if let Some(slot) = inventory.add_item(drop.item) {
-                        client.send(InventoryOperationResult::success_gain_item(
-                            slot,
-                            drop.item.reference.ref_id(),
-                            InventoryItemContentData::Expendable {
-                                stack_size: drop.item.stack_size(),
-                            },
-                        ));
+                    // The picker only takes part in the draw if they have room themselves, but remains the last
+                    // resort for items that may still stack onto existing ones.
+                    let receiver = if inventory.free_slots() > 0 || share_with.is_empty() {
+                        rand::thread_rng().gen_range(0..=share_with.len())
                     } else {
-                        client.send(InventoryOperationResult::Error(InventoryOperationError::InventoryFull));
+                        rand::thread_rng().gen_range(1..=share_with.len())
+                    };
+                    let given = match receiver.checked_sub(1).map(|index| share_with[index]) {
+                        Some(member) => match party_members.get_mut(member) {
+                            Ok((other_client, _, mut other_inventory, _, _)) => {
+                                give_item(other_client, &mut other_inventory, drop.item)
+                            },
+                            Err(_) => give_item(client, &mut inventory, drop.item),
+                        },
+                        None => give_item(client, &mut inventory, drop.item),
+                    };
+
+                    if !given {
+                        // Nobody had room for the item, so it stays on the ground.
+                        client.send(PerformActionResponse::Stop(PerformActionError::Completed));
+                        cmd.entity(entity).remove::<Pickup>().insert(Idle);
+                        continue;
+                    }
+
+                    if !matches!(drop.item.type_data, ItemTypeData::Equipment { .. }) {
+                        client.send(PerformActionResponse::Stop(PerformActionError::Completed));
                     }
-                    client.send(PerformActionResponse::Stop(PerformActionError::Completed));
                 },
             }
+
+            cmd.entity(pickup.0).despawn();
+            pickup.1 = Some(Timer::from_seconds(1.0, TimerMode::Once));
         }
     }
 }
 
+fn attack_power_of(
+    inventory: Option<&PlayerInventory>,
+    stats: Option<&StatPoints>,
+    buffed: Option<&Buffed>,
+) -> AttackPower {
+    let power = match (inventory, stats) {
+        (Some(inventory), Some(stats)) => AttackPower::for_player(
+            stats.stats(),
+            inventory
+                .get_equipment_item(EquipmentSlot::Weapon)
+                .map(|item| item.reference),
+        ),
+        _ => AttackPower::default(),
+    };
+    match buffed {
+        Some(buffed) => power.with_buffs(&buffed.effects()),
+        None => power,
+    }
+}
+
+fn defense_of(
+    entity: &GameEntity,
+    inventory: Option<&PlayerInventory>,
+    stats: Option<&StatPoints>,
+    buffed: Option<&Buffed>,
+) -> Defense {
+    let defense = match (inventory, stats) {
+        (Some(inventory), Some(stats)) => {
+            Defense::for_player(stats.stats(), inventory.equipment_items().map(|(_, item)| item))
+        },
+        _ => WorldData::characters()
+            .find_id(entity.ref_id)
+            .map(Defense::for_monster)
+            .unwrap_or_default(),
+    };
+    match buffed {
+        Some(buffed) => defense.with_buffs(&buffed.effects()),
+        None => defense,
+    }
+}
+
 pub(crate) fn action(
     mut query: Query<(
         Entity,
         &GameEntity,
         &mut Action,
+        &Position,
+        Option<&Visibility>,
+        Option<&PlayerInventory>,
+        Option<&StatPoints>,
+        Option<&Buffed>,
+        Option<&mut Mind>,
+        Has<Monster>,
+    )>,
+    target_query: Query<(
+        &GameEntity,
+        &Position,
         Option<&PlayerInventory>,
-        Option<&Player>,
+        Option<&StatPoints>,
+        Option<&Buffed>,
+        Has<Monster>,
+        Has<Dead>,
     )>,
-    target_query: Query<&GameEntity>,
     time: Res<Time>,
     attack_instance_counter: Res<ActionIdCounter>,
     mut cmd: Commands,
-    mut damage_event: EventWriter<DamageReceiveEvent>,
+    (mut damage_event, mut buff_event, mut resurrect_event): (
+        EventWriter<DamageReceiveEvent>,
+        EventWriter<BuffApplyEvent>,
+        EventWriter<ResurrectEvent>,
+    ),
 ) {
     let delta = time.delta();
-    for (entity, game_entity, mut action, player_inventory, player) in query.iter_mut() {
+    let mut rng = rand::thread_rng();
+    for (entity, game_entity, mut action, position, visibility, inventory, stats, buffed, mind, is_monster) in
+        query.iter_mut()
+    {
         if action.progress.tick(delta).just_finished() {
             if let Some(next) = action.state.next() {
                 let time = next.get_time_for(action.skill).unwrap_or(0);
                 action.state = next;
                 action.progress = Timer::new(Duration::from_millis(time as u64), TimerMode::Once);
 
-                if next == ActionProgressState::Execution {
-                    let attack = action
-                        .skill
-                        .params
-                        .iter()
-                        .find(|param| matches!(param, SkillParam::Attack { .. }))
-                        .unwrap();
-                    match attack {
-                        SkillParam::Attack { .. } => {
-                            let ActionTarget::Entity(target) = action.target else {
-                                panic!();
-                            };
-                            let target_ = target_query.get(target).unwrap();
+                if next != ActionProgressState::Execution {
+                    continue;
+                }
+
+                let primary_target = match action.target {
+                    ActionTarget::Entity(target) => Some(target),
+                    ActionTarget::Own => Some(entity),
+                    _ => None,
+                };
+                let source = EntityReference(entity, *game_entity);
+                let attack = AttackDefinition {
+                    skill: action.skill,
+                    instance: attack_instance_counter.next(),
+                };
 
-                            if let Some(player_inventory) = player_inventory {
-                                if let Some(player) = player {
-                                    // This is a player attacking something
-                                    debug!("{:?} is attacking!", player);
-                                    let weapon: Option<&Item> =
-                                        player_inventory.get_equipment_item(EquipmentSlot::Weapon);
+                if let Some((max_level, experience_restore)) = resurrection_of(action.skill) {
+                    if let Some(mut mind) = mind {
+                        mind.cancel();
+                    }
+                    let Some(target) = primary_target.filter(|target| {
+                        target_query
+                            .get(*target)
+                            .is_ok_and(|(_, _, _, _, _, _, is_dead)| is_dead)
+                    }) else {
+                        continue;
+                    };
+                    resurrect_event.send(ResurrectEvent {
+                        target,
+                        experience_restore,
+                        max_level: Some(max_level).filter(|level| *level > 0),
+                    });
+                    continue;
+                }
 
-                                    match weapon {
-                                        Some(weapon) => {
-                                            debug!("Player has weapon: {:?}", weapon);
-                                            let auto_attack_pysical_attack_power_lower =
-                                                player.character.stats.strength() as f32
-                                                    * weapon.reference.physical_reinforce_lower
-                                                    + weapon.reference.physical_attack_power_lower;
+                if let Some(duration) = buff_duration(action.skill) {
+                    // Buffs only need to be cast once, so there's no reason to keep the goal around.
+                    if let Some(mut mind) = mind {
+                        mind.cancel();
+                    }
+                    let Some((target_entity, target)) = primary_target.and_then(|entity| {
+                        target_query
+                            .get(entity)
+                            .ok()
+                            .map(|(target, _, _, _, _, _, _)| (entity, *target))
+                    }) else {
+                        continue;
+                    };
+                    buff_event.send(BuffApplyEvent {
+                        source,
+                        target: EntityReference(target_entity, target),
+                        attack,
+                        duration,
+                    });
+                    continue;
+                }
 
-                                            let auto_attack_pysical_attack_power_upper =
-                                                player.character.stats.strength() as f32
-                                                    * weapon.reference.physical_reinforce_upper
-                                                    + weapon.reference.physical_attack_power_upper;
+                let is_attack = action
+                    .skill
+                    .params
+                    .iter()
+                    .any(|param| matches!(param, SkillParam::Attack { .. }));
+                if !is_attack {
+                    continue;
+                }
 
-                                            let dmg = rand::thread_rng().gen_range(
-                                                auto_attack_pysical_attack_power_lower as u32
-                                                    ..auto_attack_pysical_attack_power_upper as u32 + 1,
-                                            );
+                let targets = match AreaOfEffect::from_params(&action.skill.params) {
+                    Some(area) => {
+                        let target_location = match action.target {
+                            ActionTarget::Location(location) => Some(location),
+                            ActionTarget::Entity(target) => target_query
+                                .get(target)
+                                .ok()
+                                .map(|(_, target_position, _, _, _, _, _)| target_position.location()),
+                            _ => None,
+                        };
+                        let center = area.center_of(position.location(), target_location);
+                        // Lines reach towards the target, or wherever the caster is looking if there is none.
+                        let direction = target_location
+                            .map(|target| target.0 - position.location().0)
+                            .filter(|direction| direction.magnitude2() > 0.0)
+                            .unwrap_or_else(|| {
+                                (Quaternion::from_angle_y(Deg(position.rotation().0)) * Vector3::unit_x())
+                                    .to_flat_vec2()
+                            })
+                            .normalize();
+                        if let (ActionTarget::Location(_), Some(mut mind)) = (action.target, mind) {
+                            mind.cancel();
+                        }
 
-                                            damage_event.send(DamageReceiveEvent {
-                                                source: EntityReference(entity, *game_entity),
-                                                target: EntityReference(target, *target_),
-                                                attack: AttackDefinition {
-                                                    skill: action.skill,
-                                                    instance: attack_instance_counter.next(),
-                                                },
-                                                amount: dmg,
-                                            });
-                                        },
-                                        None => {
-                                            debug!("Player has no weapon");
-                                            damage_event.send(DamageReceiveEvent {
-                                                source: EntityReference(entity, *game_entity),
-                                                target: EntityReference(target, *target_),
-                                                attack: AttackDefinition {
-                                                    skill: action.skill,
-                                                    instance: attack_instance_counter.next(),
-                                                },
-                                                amount: 1,
-                                            });
-                                        },
-                                    }
-                                }
-                            } else {
-                                damage_event.send(DamageReceiveEvent {
-                                    source: EntityReference(entity, *game_entity),
-                                    target: EntityReference(target, *target_),
-                                    attack: AttackDefinition {
-                                        skill: action.skill,
-                                        instance: attack_instance_counter.next(),
+                        let nearby = visibility
+                            .into_iter()
+                            .flat_map(|visibility| visibility.entities_in_radius.iter())
+                            .map(|reference| reference.0)
+                            .filter(|other| Some(*other) != primary_target);
+                        // The primary target goes first, as it is the one being reported as the main target of
+                        // the attack.
+                        primary_target
+                            .into_iter()
+                            .chain(nearby)
+                            .filter(|other| {
+                                target_query.get(*other).is_ok_and(
+                                    |(_, other_position, _, _, _, other_is_monster, is_dead)| {
+                                        other_is_monster != is_monster
+                                            && !is_dead
+                                            && area.contains(center, direction, other_position.location())
                                     },
-                                    amount: 10,
-                                });
-                            }
-                        },
-                        _ => {},
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                    },
+                    None => primary_target.into_iter().collect(),
+                };
+
+                let power = attack_power_of(inventory, stats, buffed);
+                let skill_attack = SkillAttack::from_params(&action.skill.params);
+                for target in targets {
+                    let Ok((target_entity, _, target_inventory, target_stats, target_buffed, _, _)) =
+                        target_query.get(target)
+                    else {
+                        continue;
+                    };
+
+                    debug!("{:?} is attacking {:?}", entity, target);
+                    let target = EntityReference(target, *target_entity);
+                    let defense = defense_of(target_entity, target_inventory, target_stats, target_buffed);
+                    let hit = calculate_hit(&power, &skill_attack, &defense, &mut rng);
+
+                    if let Some((duration, chance)) = debuff_of(action.skill) {
+                        if hit != Hit::Blocked && rng.gen_range(0..100) < chance {
+                            buff_event.send(BuffApplyEvent {
+                                source,
+                                target,
+                                attack,
+                                duration,
+                            });
+                        }
                     }
+
+                    damage_event.send(DamageReceiveEvent {
+                        source,
+                        target,
+                        attack,
+                        hit,
+                    });
                 }
             } else {
                 cmd.entity(entity).remove::<Action>();
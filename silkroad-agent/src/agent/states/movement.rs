@@ -11,6 +11,8 @@ use bevy_time::Time;
 use cgmath::num_traits::Pow;
 use cgmath::{Array, Deg, InnerSpace, Quaternion, Rotation3, Vector3, Zero};
 use silkroad_game_base::{GlobalLocation, GlobalPosition, Heading, Vector3Ext};
+use silkroad_navmesh::pathfinding::PathError;
+use std::collections::VecDeque;
 use std::ops::Deref;
 use tracing::debug;
 
@@ -20,6 +22,21 @@ pub(crate) enum MovementGoal {
     Location(GlobalPosition),
     Direction(Heading),
     Entity(Entity, GlobalPosition, f32),
+    /// Walks through all the positions in order, the last one being the final destination.
+    Path(VecDeque<GlobalPosition>),
+}
+
+impl MovementGoal {
+    /// Creates the goal to walk to the given position, following the navmesh around any obstacles in between. If the
+    /// navmesh doesn't know about the area, we assume we can walk there directly. Returns `None` if there is no way
+    /// to reach the position.
+    pub(crate) fn walk_to(navmesh: &Navmesh, from: GlobalLocation, to: GlobalPosition) -> Option<MovementGoal> {
+        match navmesh.path_between(from, to) {
+            Ok(path) => Some(MovementGoal::Path(path.into())),
+            Err(PathError::Unreachable) => None,
+            Err(PathError::UnknownRegion(_) | PathError::OutsideMesh) => Some(MovementGoal::Location(to)),
+        }
+    }
 }
 
 #[derive(Component)]
@@ -69,14 +86,14 @@ pub(crate) fn turning(mut query: Query<(&mut Position, &PlayerInput), With<Idle>
 }
 
 pub(crate) fn movement(
-    mut query: Query<(Entity, &mut Position, &Agent, &Moving, &MovementState)>,
+    mut query: Query<(Entity, &mut Position, &Agent, &mut Moving, &MovementState)>,
     time: Res<Time>,
     mut cmd: Commands,
     navmesh: Res<Navmesh>,
     mut finish_movement: EventWriter<MovementFinished>,
 ) {
     let delta = time.delta_seconds_f64() as f32;
-    for (entity, mut pos, agent, movement, speed_state) in query.iter_mut() {
+    for (entity, mut pos, agent, mut movement, speed_state) in query.iter_mut() {
         let speed = agent.get_speed_value(*speed_state.deref());
         let (next_location, heading, finished) = match movement.0 {
             MovementGoal::Location(location) => get_next_step(delta, pos.location(), speed, location.to_location()),
@@ -88,23 +105,44 @@ pub(crate) fn movement(
                 (GlobalLocation(current_location_2d + movement), direction, false)
             },
             MovementGoal::Entity(_, location, _) => get_next_step(delta, pos.location(), speed, location.to_location()),
+            MovementGoal::Path(ref path) => match path.front() {
+                Some(waypoint) => get_next_step(delta, pos.location(), speed, waypoint.to_location()),
+                None => (pos.location(), pos.rotation(), true),
+            },
         };
 
-        move_with_step(&navmesh, &mut pos, next_location, heading);
+        let blocked = !move_with_step(&navmesh, &mut pos, next_location, heading);
+        let finished = finished || blocked;
 
-        if finished {
+        let reached_waypoint =
+            finished && !blocked && matches!(&movement.0, MovementGoal::Path(path) if path.len() > 1);
+        if reached_waypoint {
+            // Changing the path lets the clients know about the next part of the path we're now walking.
+            if let MovementGoal::Path(path) = &mut movement.0 {
+                path.pop_front();
+            }
+        } else if finished {
             cmd.entity(entity).remove::<Moving>().insert(Idle);
             finish_movement.send(MovementFinished(entity));
         }
     }
 }
 
-pub(crate) fn move_with_step(navmesh: &Navmesh, pos: &mut Position, target: GlobalLocation, heading: Heading) {
+/// Moves to the target location, unless an object is in the way. Returns `true` if we were able to move.
+pub(crate) fn move_with_step(navmesh: &Navmesh, pos: &mut Position, target: GlobalLocation, heading: Heading) -> bool {
+    if !navmesh.is_walkable(pos.location(), target) {
+        return false;
+    }
+
     let target_location = target.to_local();
-    let height = navmesh.height_for(target_location).unwrap_or(pos.position().0.y);
+    let current_height = pos.position().0.y;
+    let height = navmesh
+        .height_near(target_location, current_height)
+        .unwrap_or(current_height);
 
     let position = target.with_y(height);
     pos.update(position, heading);
+    true
 }
 
 pub(crate) fn get_next_step(
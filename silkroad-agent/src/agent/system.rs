@@ -1,16 +1,27 @@
 use crate::agent::event::{ActionFinished, MovementFinished};
-use crate::agent::states::{Action, Idle, MovementGoal, Moving, Sitting, StateTransitionQueue};
+use crate::agent::states::{Action, Dead, Idle, MovementGoal, Moving, Sitting, StateTransitionQueue};
 use crate::comp::net::Client;
 use crate::comp::pos::Position;
+use crate::ext::Navmesh;
 use crate::input::PlayerInput;
 use bevy_ecs::prelude::*;
 use cgmath::Vector3;
 use silkroad_game_base::{Heading, LocalPosition};
-use silkroad_protocol::movement::MovementTarget;
+use silkroad_protocol::movement::{CharacterActionKind, MovementTarget};
+use std::time::Duration;
 use tracing::debug;
 
 pub(crate) fn transition_to_idle(
-    mut query: Query<Entity, (Without<Idle>, Without<Moving>, Without<Action>, Without<Sitting>)>,
+    mut query: Query<
+        Entity,
+        (
+            Without<Idle>,
+            Without<Moving>,
+            Without<Action>,
+            Without<Sitting>,
+            Without<Dead>,
+        ),
+    >,
     mut cmd: Commands,
 ) {
     for entity in query.iter_mut() {
@@ -62,7 +73,10 @@ pub(crate) fn transition_from_attacking(
     }
 }
 
-pub(crate) fn movement_input(mut query: Query<(&Client, &PlayerInput, &mut StateTransitionQueue, &Position)>) {
+pub(crate) fn movement_input(
+    mut query: Query<(&Client, &PlayerInput, &mut StateTransitionQueue, &Position)>,
+    navmesh: Res<Navmesh>,
+) {
     for (client, input, mut agent, position) in query.iter_mut() {
         if let Some(kind) = input.movement {
             match kind {
@@ -70,7 +84,10 @@ pub(crate) fn movement_input(mut query: Query<(&Client, &PlayerInput, &mut State
                     let local_position = position.position().to_local();
                     let target_pos = LocalPosition(region.into(), Vector3::new(x.into(), y.into(), z.into()));
                     debug!(id = ?client.id(), "Movement: {} -> {}", local_position, target_pos);
-                    agent.request_transition(Moving(MovementGoal::Location(target_pos.to_global())));
+                    match MovementGoal::walk_to(&navmesh, position.location(), target_pos.to_global()) {
+                        Some(goal) => agent.request_transition(Moving(goal)),
+                        None => debug!(id = ?client.id(), "Movement target cannot be reached"),
+                    }
                 },
                 MovementTarget::Direction { unknown, angle } => {
                     let direction = Heading::from(angle);
@@ -81,3 +98,21 @@ pub(crate) fn movement_input(mut query: Query<(&Client, &PlayerInput, &mut State
         }
     }
 }
+
+pub(crate) fn sitting_input(mut query: Query<(&PlayerInput, &mut StateTransitionQueue, Has<Sitting>)>) {
+    for (input, mut agent, sitting) in query.iter_mut() {
+        let Some(ref action) = input.character_action else {
+            continue;
+        };
+
+        match action.kind {
+            CharacterActionKind::ToggleSit => {
+                if sitting {
+                    agent.request_transition(Idle);
+                } else {
+                    agent.request_transition(Sitting(Duration::ZERO));
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use crate::alchemy::system::handle_alchemy;
+use bevy_app::{App, Plugin, Update};
+
+mod system;
+
+pub(crate) struct AlchemyPlugin;
+
+impl Plugin for AlchemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, handle_alchemy);
+    }
+}
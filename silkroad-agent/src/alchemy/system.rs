@@ -0,0 +1,300 @@
+use crate::agent::states::Dead;
+use crate::comp::inventory::{item_content, PlayerInventory};
+use crate::comp::net::Client;
+use crate::config::{AlchemyConfig, GameConfig};
+use crate::exchange::Exchange;
+use crate::input::PlayerInput;
+use crate::stall::Stall;
+use bevy_ecs::prelude::*;
+use rand::{thread_rng, Rng};
+use silkroad_data::itemdata::RefItemData;
+use silkroad_data::DataEntry;
+use silkroad_definitions::type_id::{ObjectConsumable, ObjectEquippable, ObjectItem, ObjectType};
+use silkroad_game_base::{Inventory, ItemTypeData};
+use silkroad_protocol::alchemy::{AlchemyAction, AlchemyErrorCode, AlchemyKind, AlchemyResponse, ReinforcedItem};
+use silkroad_protocol::inventory::{InventoryItemData, RentInfo};
+use tracing::debug;
+
+/// The kind of equipment an elixir can be used on.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum ReinforceTarget {
+    Weapon,
+    Shield,
+    Armor,
+    Accessory,
+}
+
+impl ReinforceTarget {
+    fn of_item(reference: &RefItemData) -> Option<Self> {
+        match ObjectType::from_type_id(&reference.common.type_id)?.as_item()? {
+            ObjectItem::Equippable(ObjectEquippable::Weapon(_)) => Some(ReinforceTarget::Weapon),
+            ObjectItem::Equippable(ObjectEquippable::Shield(_)) => Some(ReinforceTarget::Shield),
+            ObjectItem::Equippable(ObjectEquippable::Clothing(_, _)) => Some(ReinforceTarget::Armor),
+            ObjectItem::Equippable(ObjectEquippable::Jewelry(_, _)) => Some(ReinforceTarget::Accessory),
+            _ => None,
+        }
+    }
+
+    fn of_elixir(code: &str) -> Option<Self> {
+        if code.contains("RECIPE_WEAPON") {
+            Some(ReinforceTarget::Weapon)
+        } else if code.contains("RECIPE_SHIELD") {
+            Some(ReinforceTarget::Shield)
+        } else if code.contains("RECIPE_ARMOR") {
+            Some(ReinforceTarget::Armor)
+        } else if code.contains("RECIPE_ACCESSARY") {
+            Some(ReinforceTarget::Accessory)
+        } else {
+            None
+        }
+    }
+}
+
+/// Items that can be fused with equipment to upgrade it. Elixirs and lucky powders share the same type, so we can
+/// only tell them apart by their code.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum AlchemyMaterial {
+    Elixir(ReinforceTarget),
+    LuckyPowder,
+}
+
+impl AlchemyMaterial {
+    fn of(reference: &RefItemData) -> Option<Self> {
+        let Some(ObjectItem::Consumable(ObjectConsumable::AlchemyUpgrade)) =
+            ObjectType::from_type_id(&reference.common.type_id)?.as_item()
+        else {
+            return None;
+        };
+
+        let code = reference.code();
+        if code.contains("REINFORCE_PROB_UP") {
+            Some(AlchemyMaterial::LuckyPowder)
+        } else {
+            ReinforceTarget::of_elixir(code).map(AlchemyMaterial::Elixir)
+        }
+    }
+}
+
+pub(crate) fn handle_alchemy(
+    mut query: Query<(
+        &Client,
+        &PlayerInput,
+        &mut PlayerInventory,
+        Has<Stall>,
+        Has<Dead>,
+        Has<Exchange>,
+    )>,
+    settings: Res<GameConfig>,
+) {
+    for (client, input, mut inventory, has_stall, dead, in_exchange) in query.iter_mut() {
+        let Some(ref request) = input.alchemy else {
+            continue;
+        };
+
+        match &request.action {
+            AlchemyAction::Cancel { .. } => client.send(AlchemyResponse::cancelled()),
+            AlchemyAction::Fuse {
+                kind: AlchemyKind::Elixir,
+                slots,
+            } => {
+                if has_stall || dead || in_exchange {
+                    client.send(AlchemyResponse::Error(AlchemyErrorCode::Busy));
+                    continue;
+                }
+
+                match fuse(slots, &mut inventory, &settings.alchemy, &mut thread_rng()) {
+                    Ok(response) => client.send(response),
+                    Err(error) => client.send(AlchemyResponse::Error(error)),
+                }
+            },
+        }
+    }
+}
+
+/// Fuses the equipment in the first slot with the elixir, and optionally a lucky powder, in the remaining slots. The
+/// materials are used up regardless of the outcome.
+fn fuse(
+    slots: &[u8],
+    inventory: &mut Inventory,
+    config: &AlchemyConfig,
+    rng: &mut impl Rng,
+) -> Result<AlchemyResponse, AlchemyErrorCode> {
+    let (&target_slot, material_slots) = slots.split_first().ok_or(AlchemyErrorCode::InvalidTarget)?;
+    if Inventory::is_equipment_slot(target_slot) {
+        return Err(AlchemyErrorCode::InvalidTarget);
+    }
+
+    let target = *inventory
+        .get_item_at(target_slot)
+        .ok_or(AlchemyErrorCode::InvalidTarget)?;
+    let ItemTypeData::Equipment { upgrade_level } = target.type_data else {
+        return Err(AlchemyErrorCode::InvalidTarget);
+    };
+    let target_kind = ReinforceTarget::of_item(target.reference).ok_or(AlchemyErrorCode::InvalidTarget)?;
+
+    let mut elixir = None;
+    let mut powder = None;
+    for &slot in material_slots {
+        if slot == target_slot || Inventory::is_equipment_slot(slot) {
+            return Err(AlchemyErrorCode::InvalidMaterial);
+        }
+
+        let material = inventory
+            .get_item_at(slot)
+            .and_then(|item| AlchemyMaterial::of(item.reference));
+        match material {
+            Some(AlchemyMaterial::Elixir(kind)) if kind == target_kind && elixir.is_none() => elixir = Some(slot),
+            Some(AlchemyMaterial::LuckyPowder) if powder.is_none() => powder = Some(slot),
+            _ => return Err(AlchemyErrorCode::InvalidMaterial),
+        }
+    }
+    let elixir = elixir.ok_or(AlchemyErrorCode::InvalidMaterial)?;
+
+    let success_rate = config
+        .success_rates
+        .get(upgrade_level as usize)
+        .copied()
+        .ok_or(AlchemyErrorCode::MaxLevelReached)?;
+    let success_rate = success_rate + powder.map(|_| config.lucky_powder_bonus).unwrap_or(0.);
+
+    for material in [Some(elixir), powder].into_iter().flatten() {
+        inventory
+            .take_from_slot(material, 1)
+            .map_err(|_| AlchemyErrorCode::InvalidMaterial)?;
+    }
+
+    let succeeded = rng.gen_range(0.0..100.0) < success_rate;
+    let new_level = if succeeded {
+        upgrade_level + 1
+    } else {
+        let break_rate = config.break_rates.get(upgrade_level as usize).copied().unwrap_or(0.);
+        if rng.gen_range(0.0..100.0) < break_rate {
+            inventory
+                .take_from_slot(target_slot, 1)
+                .expect("Equipment to break should still exist");
+            debug!("{} broke while trying to upgrade it.", target.reference.code());
+            return Ok(AlchemyResponse::fused(false, target_slot, ReinforcedItem::Destroyed));
+        }
+        upgrade_level.saturating_sub(1)
+    };
+
+    inventory
+        .set_upgrade_level(target_slot, new_level)
+        .expect("Equipment to upgrade should still exist");
+    debug!(
+        "Upgrading {} from +{} resulted in +{}.",
+        target.reference.code(),
+        upgrade_level,
+        new_level
+    );
+    let item = inventory
+        .get_item_at(target_slot)
+        .expect("Equipment to upgrade should still exist");
+    Ok(AlchemyResponse::fused(
+        succeeded,
+        target_slot,
+        ReinforcedItem::Remaining(InventoryItemData::new(
+            target_slot,
+            RentInfo::Empty,
+            item.reference.ref_id(),
+            item_content(item),
+        )),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::item_reference;
+    use once_cell::sync::Lazy;
+    use silkroad_definitions::type_id::TypeId;
+    use silkroad_game_base::Item;
+
+    static SWORD: Lazy<RefItemData> = Lazy::new(|| item_reference("ITEM_CH_SWORD_01_A", TypeId(3, 1, 6, 2), 1));
+    static WEAPON_ELIXIR: Lazy<RefItemData> =
+        Lazy::new(|| item_reference("ITEM_ETC_ARCHEMY_REINFORCE_RECIPE_WEAPON_B", TypeId(3, 3, 10, 1), 50));
+    static ARMOR_ELIXIR: Lazy<RefItemData> =
+        Lazy::new(|| item_reference("ITEM_ETC_ARCHEMY_REINFORCE_RECIPE_ARMOR_B", TypeId(3, 3, 10, 1), 50));
+
+    fn inventory(upgrade_level: u8) -> Inventory {
+        let mut inventory = Inventory::new(45);
+        inventory.set_item(
+            13,
+            Item {
+                reference: &SWORD,
+                variance: None,
+                type_data: ItemTypeData::Equipment { upgrade_level },
+            },
+        );
+        inventory.set_item(
+            14,
+            Item {
+                reference: &WEAPON_ELIXIR,
+                variance: None,
+                type_data: ItemTypeData::Consumable { amount: 2 },
+            },
+        );
+        inventory.set_item(
+            15,
+            Item {
+                reference: &ARMOR_ELIXIR,
+                variance: None,
+                type_data: ItemTypeData::Consumable { amount: 1 },
+            },
+        );
+        inventory
+    }
+
+    fn config(success_rate: f32, break_rate: f32) -> AlchemyConfig {
+        AlchemyConfig {
+            success_rates: vec![success_rate; 3],
+            break_rates: vec![break_rate; 3],
+            lucky_powder_bonus: 0.,
+        }
+    }
+
+    #[test]
+    pub fn test_successful_fuse_upgrades_item() {
+        let mut inventory = inventory(0);
+        assert!(fuse(&[13, 14], &mut inventory, &config(100., 0.), &mut thread_rng()).is_ok());
+        assert_eq!(inventory.get_item_at(13).map(|item| item.upgrade_level()), Some(1));
+        assert_eq!(inventory.get_item_at(14).map(|item| item.stack_size()), Some(1));
+
+        assert!(fuse(&[13, 14], &mut inventory, &config(100., 0.), &mut thread_rng()).is_ok());
+        assert!(inventory.get_item_at(14).is_none());
+        assert!(matches!(
+            fuse(&[13, 14], &mut inventory, &config(100., 0.), &mut thread_rng()),
+            Err(AlchemyErrorCode::InvalidMaterial)
+        ));
+    }
+
+    #[test]
+    pub fn test_failed_fuse_downgrades_or_breaks_item() {
+        let mut inventory = inventory(2);
+        assert!(fuse(&[13, 14], &mut inventory, &config(0., 0.), &mut thread_rng()).is_ok());
+        assert_eq!(inventory.get_item_at(13).map(|item| item.upgrade_level()), Some(1));
+
+        assert!(fuse(&[13, 14], &mut inventory, &config(0., 100.), &mut thread_rng()).is_ok());
+        assert!(inventory.get_item_at(13).is_none());
+    }
+
+    #[test]
+    pub fn test_fuse_requires_matching_elixir() {
+        let mut fresh = inventory(0);
+        assert!(matches!(
+            fuse(&[13, 15], &mut fresh, &config(100., 0.), &mut thread_rng()),
+            Err(AlchemyErrorCode::InvalidMaterial)
+        ));
+        assert!(matches!(
+            fuse(&[13], &mut fresh, &config(100., 0.), &mut thread_rng()),
+            Err(AlchemyErrorCode::InvalidMaterial)
+        ));
+
+        let mut maxed = inventory(3);
+        assert!(matches!(
+            fuse(&[13, 14], &mut maxed, &config(100., 0.), &mut thread_rng()),
+            Err(AlchemyErrorCode::MaxLevelReached)
+        ));
+        assert_eq!(maxed.get_item_at(14).map(|item| item.stack_size()), Some(2));
+    }
+}
@@ -0,0 +1,482 @@
+use bevy_ecs::prelude::*;
+use bevy_time::{Timer, TimerMode};
+use silkroad_data::skilldata::{RefSkillData, SkillParam};
+use silkroad_protocol::world::ActiveBuffData;
+use std::time::Duration;
+
+/// Checks if the skill applies a buff to its target and returns how long that buff lasts.
+pub(crate) fn buff_duration(skill: &RefSkillData) -> Option<Duration> {
+    if skill
+        .params
+        .iter()
+        .any(|param| matches!(param, SkillParam::Attack { .. }))
+    {
+        return None;
+    }
+
+    skill.params.iter().find_map(|param| match param {
+        SkillParam::Duration(duration) if *duration > 0 => Some(Duration::from_millis(*duration as u64)),
+        _ => None,
+    })
+}
+
+/// Checks if the attack skill may apply a debuff to the target it hit, returning how long the debuff lasts and the
+/// chance (in percent) for it to be applied.
+pub(crate) fn debuff_of(skill: &RefSkillData) -> Option<(Duration, u8)> {
+    skill.params.iter().find_map(|param| match param {
+        SkillParam::DecreasePhysicalDefense { duration, chance, .. }
+        | SkillParam::DecreaseMagicalDefense { duration, chance, .. } => {
+            Some((Duration::from_millis(*duration as u64), *chance))
+        },
+        _ => None,
+    })
+}
+
+pub(crate) struct ActiveBuff {
+    pub(crate) token: u32,
+    pub(crate) skill: &'static RefSkillData,
+    pub(crate) remaining: Timer,
+}
+
+impl ActiveBuff {
+    pub(crate) fn new(token: u32, skill: &'static RefSkillData, duration: Duration) -> Self {
+        ActiveBuff {
+            token,
+            skill,
+            remaining: Timer::new(duration, TimerMode::Once),
+        }
+    }
+
+    /// Checks if this buff cannot be active at the same time as a buff of the given skill. This is the case for
+    /// different levels of the same skill or skills that share an interference flag.
+    fn conflicts_with(&self, skill: &RefSkillData) -> bool {
+        self.skill.group == skill.group || (self.skill.buff_interference & skill.buff_interference) != 0
+    }
+}
+
+pub(crate) enum BuffAddResult {
+    /// The buff was added and replaced the contained buffs.
+    Added(Vec<ActiveBuff>),
+    /// A stronger version of the same skill is still active.
+    Rejected,
+}
+
+/// The combined modifiers of all buffs and debuffs that are currently active on an entity.
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub(crate) struct BuffEffects {
+    /// Physical defense increase, in percent. Debuffs make this negative.
+    pub(crate) physical_defense: f32,
+    /// Magical defense increase, in percent. Debuffs make this negative.
+    pub(crate) magical_defense: f32,
+    /// Additional chance to block an attack, in percent.
+    pub(crate) block_ratio: f32,
+    /// Physical attack power increase, in percent.
+    pub(crate) physical_attack: f32,
+    /// Magical attack power increase, in percent.
+    pub(crate) magical_attack: f32,
+    /// Additional chance to land a critical hit, in percent.
+    pub(crate) critical: f32,
+    /// Movement speed increase, in percent.
+    pub(crate) movement_speed: f32,
+    /// Maximum HP increase, in percent.
+    pub(crate) max_health: f32,
+    /// Maximum MP increase, in percent.
+    pub(crate) max_mana: f32,
+    /// Additional hit rate, countering the parry rate of the target.
+    pub(crate) hit_rate: f32,
+    /// Additional chance to parry an attack, in percent.
+    pub(crate) parry_rate: f32,
+}
+
+impl BuffEffects {
+    fn with_param(self, param: &SkillParam) -> Self {
+        match param {
+            SkillParam::IncreaseDefense { phys, mag, .. } => BuffEffects {
+                physical_defense: self.physical_defense + *phys as f32,
+                magical_defense: self.magical_defense + *mag as f32,
+                ..self
+            },
+            SkillParam::DecreasePhysicalDefense { value, .. } => BuffEffects {
+                physical_defense: self.physical_defense - *value as f32,
+                ..self
+            },
+            SkillParam::DecreaseMagicalDefense { value, .. } => BuffEffects {
+                magical_defense: self.magical_defense - *value as f32,
+                ..self
+            },
+            SkillParam::BlockRatio { percent, .. } => BuffEffects {
+                block_ratio: self.block_ratio + *percent as f32,
+                ..self
+            },
+            SkillParam::IncreaseAttackPower { physical, magical } => BuffEffects {
+                physical_attack: self.physical_attack + *physical as f32,
+                magical_attack: self.magical_attack + *magical as f32,
+                ..self
+            },
+            SkillParam::IncreaseCrit { amount, .. } => BuffEffects {
+                critical: self.critical + *amount as f32,
+                ..self
+            },
+            SkillParam::SetValue { var, value, .. } => self.with_variable(var, *value as f32),
+            _ => self,
+        }
+    }
+
+    /// Applies a variable set by a `setv` parameter. The variables are named after the parameters that modify the same
+    /// stat, with the value being the increase in percent.
+    fn with_variable(self, var: &str, value: f32) -> Self {
+        match var {
+            "hste" => BuffEffects {
+                movement_speed: self.movement_speed + value,
+                ..self
+            },
+            "hpi" => BuffEffects {
+                max_health: self.max_health + value,
+                ..self
+            },
+            "mpi" => BuffEffects {
+                max_mana: self.max_mana + value,
+                ..self
+            },
+            "hr" => BuffEffects {
+                hit_rate: self.hit_rate + value,
+                ..self
+            },
+            "er" => BuffEffects {
+                parry_rate: self.parry_rate + value,
+                ..self
+            },
+            _ => self,
+        }
+    }
+}
+
+/// Contains all buffs and debuffs that are currently active on an entity.
+#[derive(Component, Default)]
+pub(crate) struct Buffed {
+    buffs: Vec<ActiveBuff>,
+}
+
+impl Buffed {
+    pub(crate) fn buffs(&self) -> &[ActiveBuff] {
+        &self.buffs
+    }
+
+    /// Adds the buff, replacing all active buffs it conflicts with. Lower levels of an already active skill are
+    /// rejected instead.
+    pub(crate) fn add(&mut self, buff: ActiveBuff) -> BuffAddResult {
+        let stronger_active = self
+            .buffs
+            .iter()
+            .any(|active| active.skill.group == buff.skill.group && active.skill.level > buff.skill.level);
+        if stronger_active {
+            return BuffAddResult::Rejected;
+        }
+
+        let (replaced, remaining) = self
+            .buffs
+            .drain(..)
+            .partition::<Vec<_>, _>(|active| active.conflicts_with(buff.skill));
+        self.buffs = remaining;
+        self.buffs.push(buff);
+        BuffAddResult::Added(replaced)
+    }
+
+    /// Removes the buff of the given skill, if it is active.
+    pub(crate) fn remove_skill(&mut self, skill: u32) -> Option<ActiveBuff> {
+        let index = self.buffs.iter().position(|buff| buff.skill.ref_id == skill)?;
+        Some(self.buffs.remove(index))
+    }
+
+    /// Advances all buffs by the given time and removes the ones that ran out.
+    pub(crate) fn tick(&mut self, delta: Duration) -> Vec<ActiveBuff> {
+        let (expired, remaining) = self
+            .buffs
+            .drain(..)
+            .map(|mut buff| {
+                buff.remaining.tick(delta);
+                buff
+            })
+            .partition::<Vec<_>, _>(|buff| buff.remaining.finished());
+        self.buffs = remaining;
+        expired
+    }
+
+    pub(crate) fn has_debuffs(&self) -> bool {
+        self.buffs.iter().any(|buff| debuff_of(buff.skill).is_some())
+    }
+
+    /// Removes all debuffs, keeping the buffs active.
+    pub(crate) fn remove_debuffs(&mut self) -> Vec<ActiveBuff> {
+        let (debuffs, remaining) = self
+            .buffs
+            .drain(..)
+            .partition::<Vec<_>, _>(|buff| debuff_of(buff.skill).is_some());
+        self.buffs = remaining;
+        debuffs
+    }
+
+    pub(crate) fn clear(&mut self) -> Vec<ActiveBuff> {
+        std::mem::take(&mut self.buffs)
+    }
+
+    pub(crate) fn effects(&self) -> BuffEffects {
+        self.buffs
+            .iter()
+            .flat_map(|buff| buff.skill.params.iter())
+            .fold(BuffEffects::default(), BuffEffects::with_param)
+    }
+
+    pub(crate) fn as_active_buff_data(&self) -> Vec<ActiveBuffData> {
+        self.buffs
+            .iter()
+            .map(|buff| ActiveBuffData::new(buff.skill.ref_id, buff.token))
+            .collect()
+    }
+}
+
+/// Hands out the tokens that identify a single buff instance towards the client.
+#[derive(Resource)]
+pub(crate) struct BuffTokens(u32);
+
+impl Default for BuffTokens {
+    fn default() -> Self {
+        BuffTokens(1)
+    }
+}
+
+impl BuffTokens {
+    pub(crate) fn next(&mut self) -> u32 {
+        let token = self.0;
+        self.0 = self.0.wrapping_add(1).max(1);
+        token
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::agent::Agent;
+    use crate::comp::Health;
+    use silkroad_data::skilldata::{AutoAttack, SkillTimings, SkillType, TargetOption};
+    use silkroad_game_base::MovementSpeed;
+
+    fn skill(
+        ref_id: u32,
+        group: u32,
+        level: u8,
+        buff_interference: u32,
+        params: Vec<SkillParam>,
+    ) -> &'static RefSkillData {
+        Box::leak(Box::new(RefSkillData {
+            ref_id,
+            group,
+            id: format!("SKILL_TEST_{}", ref_id),
+            original: None,
+            level,
+            type_: SkillType::Action,
+            next_in_chain: None,
+            timings: SkillTimings {
+                preparation_time: 0,
+                cast_time: 0,
+                duration: 0,
+                cooldown: 0,
+                next_delay: 0,
+            },
+            projectile_speed: 0,
+            buff_interference,
+            auto_attack: AutoAttack::No,
+            range: 0,
+            requires_target: false,
+            target: TargetOption::SELF,
+            mastery: None,
+            mastery_level: None,
+            required_skills: vec![],
+            sp: 0,
+            race: 0,
+            weapon_requirements: [None, None],
+            consumed_hp: 0,
+            consumed_mp: 0,
+            usage_chance: 0,
+            usage_type: 0,
+            params,
+        }))
+    }
+
+    fn buff(token: u32, skill: &'static RefSkillData) -> ActiveBuff {
+        ActiveBuff::new(token, skill, Duration::from_secs(10))
+    }
+
+    #[test]
+    pub fn test_buff_replacement() {
+        let first = skill(1, 10, 1, 0b01, vec![SkillParam::Duration(1000)]);
+        let second = skill(2, 10, 2, 0, vec![SkillParam::Duration(1000)]);
+        let interfering = skill(3, 20, 1, 0b01, vec![SkillParam::Duration(1000)]);
+        let unrelated = skill(4, 30, 1, 0b10, vec![SkillParam::Duration(1000)]);
+
+        let mut buffed = Buffed::default();
+        assert!(matches!(buffed.add(buff(1, first)), BuffAddResult::Added(replaced) if replaced.is_empty()));
+        assert!(matches!(buffed.add(buff(2, unrelated)), BuffAddResult::Added(replaced) if replaced.is_empty()));
+        assert!(
+            matches!(buffed.add(buff(3, second)), BuffAddResult::Added(replaced) if replaced.len() == 1 && replaced[0].token == 1)
+        );
+        assert!(matches!(buffed.add(buff(4, first)), BuffAddResult::Rejected));
+        assert!(matches!(buffed.add(buff(5, interfering)), BuffAddResult::Added(replaced) if replaced.is_empty()));
+        let replacing = skill(5, 40, 1, 0b01, vec![SkillParam::Duration(1000)]);
+        assert!(
+            matches!(buffed.add(buff(6, replacing)), BuffAddResult::Added(replaced) if replaced.len() == 1 && replaced[0].token == 5)
+        );
+        assert_eq!(buffed.buffs().len(), 3);
+        assert!(buffed.remove_skill(4).is_some());
+        assert!(buffed.remove_skill(4).is_none());
+    }
+
+    #[test]
+    pub fn test_buff_expiry() {
+        let mut buffed = Buffed::default();
+        let _ = buffed.add(ActiveBuff::new(1, skill(1, 1, 1, 0, vec![]), Duration::from_secs(5)));
+        let _ = buffed.add(ActiveBuff::new(2, skill(2, 2, 1, 0, vec![]), Duration::from_secs(10)));
+
+        assert!(buffed.tick(Duration::from_secs(4)).is_empty());
+        let expired = buffed.tick(Duration::from_secs(2));
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].token, 1);
+        assert_eq!(buffed.as_active_buff_data().len(), 1);
+    }
+
+    #[test]
+    pub fn test_buff_effects() {
+        let mut buffed = Buffed::default();
+        let _ = buffed.add(buff(
+            1,
+            skill(
+                1,
+                1,
+                1,
+                0,
+                vec![
+                    SkillParam::Duration(1000),
+                    SkillParam::IncreaseDefense {
+                        phys: 20,
+                        mag: 10,
+                        unknown: 0,
+                    },
+                ],
+            ),
+        ));
+        let _ = buffed.add(buff(
+            2,
+            skill(
+                2,
+                2,
+                1,
+                0,
+                vec![SkillParam::DecreasePhysicalDefense {
+                    duration: 1000,
+                    chance: 100,
+                    level: 1,
+                    value: 5,
+                }],
+            ),
+        ));
+
+        let effects = buffed.effects();
+        assert_eq!(effects.physical_defense, 15.0);
+        assert_eq!(effects.magical_defense, 10.0);
+        assert_eq!(effects.block_ratio, 0.0);
+    }
+
+    #[test]
+    pub fn test_set_value_effects() {
+        let set_value = |var: &str, value: u32| SkillParam::SetValue {
+            var: var.to_owned(),
+            value,
+            value_2: 0,
+        };
+        let mut buffed = Buffed::default();
+        let _ = buffed.add(buff(
+            1,
+            skill(1, 1, 1, 0, vec![SkillParam::Duration(1000), set_value("hste", 20)]),
+        ));
+        let _ = buffed.add(buff(
+            2,
+            skill(
+                2,
+                2,
+                1,
+                0,
+                vec![
+                    SkillParam::Duration(1000),
+                    set_value("hpi", 10),
+                    set_value("er", 15),
+                    set_value("abcd", 50),
+                ],
+            ),
+        ));
+
+        let effects = buffed.effects();
+        assert_eq!(effects.movement_speed, 20.0);
+        assert_eq!(effects.max_health, 10.0);
+        assert_eq!(effects.parry_rate, 15.0);
+        assert_eq!(effects.max_mana, 0.0);
+
+        let mut agent = Agent::default();
+        agent.speed_bonus = effects.movement_speed;
+        assert_eq!(agent.get_speed_value(MovementSpeed::Running), 60.0);
+
+        let mut health = Health::new(1000);
+        health.set_bonus(effects.max_health);
+        assert_eq!(health.max_health, 1100);
+        health.set_bonus(0.0);
+        assert_eq!(health.max_health, 1000);
+        assert_eq!(health.current_health, 1000);
+    }
+
+    #[test]
+    pub fn test_remove_debuffs() {
+        let debuff = SkillParam::DecreaseMagicalDefense {
+            duration: 1000,
+            chance: 100,
+            level: 1,
+            value: 5,
+        };
+        let mut buffed = Buffed::default();
+        let _ = buffed.add(buff(1, skill(1, 1, 1, 0, vec![SkillParam::Duration(1000)])));
+        assert!(!buffed.has_debuffs());
+        let _ = buffed.add(buff(2, skill(2, 2, 1, 0, vec![debuff])));
+        assert!(buffed.has_debuffs());
+
+        let removed = buffed.remove_debuffs();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].token, 2);
+        assert!(!buffed.has_debuffs());
+        assert_eq!(buffed.buffs().len(), 1);
+    }
+
+    #[test]
+    pub fn test_buff_skills() {
+        assert_eq!(
+            buff_duration(skill(1, 1, 1, 0, vec![SkillParam::Duration(3000)])),
+            Some(Duration::from_secs(3))
+        );
+        let attack = SkillParam::Attack {
+            kind: 0,
+            phys: 100,
+            min: 0,
+            max: 0,
+            mag: 0,
+        };
+        assert_eq!(
+            buff_duration(skill(2, 2, 1, 0, vec![attack, SkillParam::Duration(3000)])),
+            None
+        );
+        assert_eq!(buff_duration(skill(3, 3, 1, 0, vec![])), None);
+    }
+
+    #[test]
+    pub fn test_buff_tokens() {
+        let mut tokens = BuffTokens(u32::MAX);
+        assert_eq!(tokens.next(), u32::MAX);
+        assert_eq!(tokens.next(), 1);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::buff::system::{
+    apply_buff_effects, apply_buffs, cancel_buffs, clear_buffs_on_death, cure_debuffs, tick_buffs,
+};
+use crate::event::{BuffApplyEvent, CureEvent};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+pub(crate) use component::*;
+
+mod component;
+mod system;
+
+pub(crate) struct BuffPlugin;
+
+impl Plugin for BuffPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuffTokens>()
+            .add_event::<BuffApplyEvent>()
+            .add_event::<CureEvent>()
+            .add_systems(
+                Update,
+                (
+                    tick_buffs,
+                    cancel_buffs,
+                    apply_buffs.after(tick_buffs),
+                    clear_buffs_on_death.after(apply_buffs),
+                    cure_debuffs.after(apply_buffs),
+                    apply_buff_effects
+                        .after(cancel_buffs)
+                        .after(clear_buffs_on_death)
+                        .after(cure_debuffs),
+                ),
+            );
+    }
+}
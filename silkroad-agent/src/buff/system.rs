@@ -0,0 +1,187 @@
+use crate::agent::Agent;
+use crate::buff::component::{buff_duration, debuff_of, ActiveBuff, BuffAddResult, BuffTokens, Buffed};
+use crate::comp::net::Client;
+use crate::comp::visibility::Visibility;
+use crate::comp::{EntityReference, GameEntity, Health, Mana};
+use crate::event::{BuffApplyEvent, CureEvent, EntityDeath};
+use crate::input::PlayerInput;
+use bevy_ecs::prelude::*;
+use bevy_time::Time;
+use silkroad_game_base::MovementSpeed;
+use silkroad_protocol::combat::{
+    ActionType, BuffAdded, BuffRemoved, DoActionType, PerformAction, PerformActionError, PerformActionUpdate,
+};
+use silkroad_protocol::movement::ChangeSpeed;
+use silkroad_protocol::ServerPacket;
+
+/// Sends the packet to the entity itself, if it is a player, and all players that can currently see it.
+fn send_to_observers<T: Into<ServerPacket> + Clone>(
+    target: EntityReference,
+    packet: T,
+    observers: &Query<(Entity, &Client, &Visibility)>,
+) {
+    for (entity, client, visibility) in observers.iter() {
+        if entity == target.0 || visibility.entities_in_radius.contains(&target) {
+            client.send(packet.clone());
+        }
+    }
+}
+
+fn send_removals(target: EntityReference, buffs: Vec<ActiveBuff>, observers: &Query<(Entity, &Client, &Visibility)>) {
+    for buff in buffs {
+        send_to_observers(target, BuffRemoved::new(buff.token), observers);
+    }
+}
+
+pub(crate) fn apply_buffs(
+    mut events: EventReader<BuffApplyEvent>,
+    mut query: Query<&mut Buffed>,
+    mut tokens: ResMut<BuffTokens>,
+    observers: Query<(Entity, &Client, &Visibility)>,
+    casters: Query<&Client>,
+) {
+    for event in events.read() {
+        let Ok(mut buffed) = query.get_mut(event.target.0) else {
+            continue;
+        };
+
+        let skill = event.attack.skill;
+        let token = tokens.next();
+        let is_buff_skill = buff_duration(skill).is_some();
+        match buffed.add(ActiveBuff::new(token, skill, event.duration)) {
+            BuffAddResult::Added(replaced) => {
+                send_removals(event.target, replaced, &observers);
+                send_to_observers(
+                    event.target,
+                    BuffAdded::new(event.target.1.unique_id, skill.ref_id, token),
+                    &observers,
+                );
+                if is_buff_skill {
+                    if let Ok(client) = casters.get(event.source.0) {
+                        client.send(PerformActionUpdate::success(
+                            skill.ref_id,
+                            event.source.1.unique_id,
+                            event.target.1.unique_id,
+                            event.attack.instance,
+                            ActionType::None,
+                        ));
+                    }
+                }
+            },
+            BuffAddResult::Rejected => {
+                if is_buff_skill {
+                    if let Ok(client) = casters.get(event.source.0) {
+                        client.send(PerformActionUpdate::Error(PerformActionError::BuffsIntersect));
+                    }
+                }
+            },
+        }
+    }
+}
+
+pub(crate) fn tick_buffs(
+    mut query: Query<(Entity, &GameEntity, &mut Buffed)>,
+    time: Res<Time>,
+    observers: Query<(Entity, &Client, &Visibility)>,
+) {
+    let delta = time.delta();
+    for (entity, game_entity, mut buffed) in query.iter_mut() {
+        if buffed.buffs().is_empty() {
+            continue;
+        }
+
+        let expired = buffed.tick(delta);
+        send_removals(EntityReference(entity, *game_entity), expired, &observers);
+    }
+}
+
+pub(crate) fn cancel_buffs(
+    mut query: Query<(Entity, &GameEntity, &PlayerInput, &mut Buffed)>,
+    observers: Query<(Entity, &Client, &Visibility)>,
+) {
+    for (entity, game_entity, input, mut buffed) in query.iter_mut() {
+        let Some(PerformAction::Do(DoActionType::CancelBuff { ref_id, .. })) = input.action else {
+            continue;
+        };
+
+        // Only beneficial buffs may be cancelled, debuffs have to run out or get cured.
+        let is_debuff = buffed
+            .buffs()
+            .iter()
+            .any(|buff| buff.skill.ref_id == ref_id && debuff_of(buff.skill).is_some());
+        if is_debuff {
+            continue;
+        }
+
+        if let Some(buff) = buffed.remove_skill(ref_id) {
+            send_removals(EntityReference(entity, *game_entity), vec![buff], &observers);
+        }
+    }
+}
+
+pub(crate) fn clear_buffs_on_death(
+    mut deaths: EventReader<EntityDeath>,
+    mut query: Query<&mut Buffed>,
+    observers: Query<(Entity, &Client, &Visibility)>,
+) {
+    for death in deaths.read() {
+        if let Ok(mut buffed) = query.get_mut(death.died.0) {
+            let removed = buffed.clear();
+            send_removals(death.died, removed, &observers);
+        }
+    }
+}
+
+pub(crate) fn cure_debuffs(
+    mut cures: EventReader<CureEvent>,
+    mut query: Query<&mut Buffed>,
+    observers: Query<(Entity, &Client, &Visibility)>,
+) {
+    for cure in cures.read() {
+        if let Ok(mut buffed) = query.get_mut(cure.target.0) {
+            let removed = buffed.remove_debuffs();
+            send_removals(cure.target, removed, &observers);
+        }
+    }
+}
+
+/// Applies the movement speed and maximum HP/MP changes of the active buffs, letting everyone around know about a
+/// changed speed.
+pub(crate) fn apply_buff_effects(
+    mut query: Query<
+        (
+            Entity,
+            &GameEntity,
+            &Buffed,
+            Option<&mut Agent>,
+            Option<&mut Health>,
+            Option<&mut Mana>,
+        ),
+        Changed<Buffed>,
+    >,
+    observers: Query<(Entity, &Client, &Visibility)>,
+) {
+    for (entity, game_entity, buffed, agent, health, mana) in query.iter_mut() {
+        let effects = buffed.effects();
+        if let Some(mut agent) = agent.filter(|agent| agent.speed_bonus != effects.movement_speed) {
+            agent.speed_bonus = effects.movement_speed;
+            send_to_observers(
+                EntityReference(entity, *game_entity),
+                ChangeSpeed {
+                    entity: game_entity.unique_id,
+                    walk_speed: agent.get_speed_value(MovementSpeed::Walking),
+                    running_speed: agent.get_speed_value(MovementSpeed::Running),
+                },
+                &observers,
+            );
+        }
+
+        if let Some(mut health) = health.filter(|health| health.bonus() != effects.max_health) {
+            health.set_bonus(effects.max_health);
+        }
+
+        if let Some(mut mana) = mana.filter(|mana| mana.bonus() != effects.max_mana) {
+            mana.set_bonus(effects.max_mana);
+        }
+    }
+}
@@ -138,11 +138,3 @@ pub(crate) fn handle_command(
         }
     }
 }
-
-pub(crate) fn handle_teleport(mut teleport_events: EventReader<PlayerTeleportEvent>, mut query: Query<&mut Position>) {
-    for event in teleport_events.read() {
-        if let Ok(mut pos) = query.get_mut(event.0) {
-            pos.move_to(event.1);
-        }
-    }
-}
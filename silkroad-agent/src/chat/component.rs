@@ -0,0 +1,14 @@
+use bevy_ecs::prelude::*;
+use chrono::{DateTime, Utc};
+use sqlx::Error;
+use tokio::sync::oneshot::Receiver;
+
+/// A ban issued by a GM, which is still waiting for the database. The player only gets kicked once the ban has been
+/// stored, as they could otherwise just log back in.
+#[derive(Component)]
+pub(crate) struct BanTask {
+    pub(crate) gm: Entity,
+    pub(crate) character_name: String,
+    pub(crate) until: DateTime<Utc>,
+    pub(crate) task: Receiver<Result<bool, Error>>,
+}
@@ -1,9 +1,10 @@
 pub(crate) mod command;
+mod component;
 mod system;
 
-use crate::chat::command::system::{handle_command, handle_teleport};
-use crate::chat::system::{handle_chat, handle_gm_commands};
-use crate::event::{PlayerCommandEvent, PlayerTeleportEvent};
+use crate::chat::command::system::handle_command;
+use crate::chat::system::{finish_bans, handle_ban_command, handle_chat, handle_gm_commands};
+use crate::event::PlayerCommandEvent;
 use bevy_app::{App, Plugin, Update};
 use bevy_ecs::prelude::*;
 
@@ -16,11 +17,11 @@ impl Plugin for ChatPlugin {
             (
                 handle_chat,
                 handle_gm_commands,
+                handle_ban_command,
+                finish_bans,
                 handle_command.after(handle_chat),
-                handle_teleport.after(handle_command),
             ),
         )
-        .add_event::<PlayerCommandEvent>()
-        .add_event::<PlayerTeleportEvent>();
+        .add_event::<PlayerCommandEvent>();
     }
 }
@@ -1,4 +1,5 @@
 use crate::chat::command::Command;
+use crate::chat::component::BanTask;
 use crate::comp::damage::Invincible;
 use crate::comp::monster::SpawnedBy;
 use crate::comp::net::Client;
@@ -6,20 +7,30 @@ use crate::comp::player::Player;
 use crate::comp::pos::Position;
 use crate::comp::visibility::{Invisible, Visibility};
 use crate::comp::GameEntity;
-use crate::event::{PlayerCommandEvent, SpawnMonster};
+use crate::config::GameConfig;
+use crate::db::user::ServerUser;
+use crate::event::{ClientDisconnectedEvent, PlayerCommandEvent, SpawnMonster};
+use crate::ext::DbPool;
 use crate::game::drop::SpawnDrop;
+use crate::guild::{GuildMember, Guilds};
 use crate::input::PlayerInput;
+use crate::party::{Parties, PartyMember};
+use crate::server_plugin::ServerId;
+use crate::stall::{Stall, StallVisitor};
+use crate::tasks::TaskCreator;
 use crate::world::{EntityLookup, WorldData};
 use bevy_ecs::entity::Entity;
 use bevy_ecs::event::EventWriter;
 use bevy_ecs::prelude::{Commands, Query, Res};
+use chrono::Utc;
 use silkroad_definitions::type_id::{ObjectConsumable, ObjectConsumableCurrency, ObjectItem, ObjectType};
 use silkroad_game_base::{Item, ItemTypeData};
 use silkroad_protocol::chat::{
     ChatErrorCode, ChatMessage, ChatMessageResponse, ChatMessageResult, ChatSource, ChatTarget, ChatUpdate,
 };
 use silkroad_protocol::gm::{GmCommand, GmResponse};
-use tracing::debug;
+use tokio::sync::oneshot::error::TryRecvError;
+use tracing::{debug, error, info, warn};
 
 fn can_send_message(message: &ChatMessage, player: &Player) -> bool {
     match message.target {
@@ -30,12 +41,24 @@ fn can_send_message(message: &ChatMessage, player: &Player) -> bool {
 }
 
 pub(crate) fn handle_chat(
-    mut query: Query<(Entity, &Client, &GameEntity, &PlayerInput, &Visibility, &Player)>,
+    mut query: Query<(
+        Entity,
+        &Client,
+        &GameEntity,
+        &PlayerInput,
+        &Visibility,
+        &Player,
+        Option<&PartyMember>,
+        Option<&GuildMember>,
+        Option<&StallVisitor>,
+    )>,
     lookup: Res<EntityLookup>,
     others: Query<(&Client, &Player)>,
+    stalls: Query<&Stall>,
+    (parties, guilds): (Res<Parties>, Res<Guilds>),
     mut command_events: EventWriter<PlayerCommandEvent>,
 ) {
-    for (entity, client, game_entity, input, visibility, player) in query.iter_mut() {
+    for (entity, client, game_entity, input, visibility, player, party, guild, visitor) in query.iter_mut() {
         for message in input.chat.iter() {
             debug!(id = ?client.0.id(), "Received chat message: {} @ {}", message.message, message.index);
             if !can_send_message(message, player) {
@@ -122,6 +145,100 @@ pub(crate) fn handle_chat(
                         },
                     }
                 },
+                ChatTarget::Party => match party.and_then(|party| parties.get(party.0)) {
+                    Some(party) => {
+                        party
+                            .members()
+                            .iter()
+                            .filter(|member| member.0 != entity)
+                            .filter_map(|member| others.get(member.0).ok())
+                            .for_each(|(client, _)| {
+                                client.send(ChatUpdate::new(
+                                    ChatSource::party(player.character.name.clone()),
+                                    message.message.clone(),
+                                ));
+                            });
+                        client.send(ChatMessageResponse::new(
+                            ChatMessageResult::Success,
+                            message.target,
+                            message.index,
+                        ));
+                    },
+                    None => {
+                        client.send(ChatMessageResponse::new(
+                            ChatMessageResult::error(ChatErrorCode::InvalidTarget),
+                            message.target,
+                            message.index,
+                        ));
+                    },
+                },
+                ChatTarget::Guild => match guild.and_then(|guild| guilds.get(guild.0)) {
+                    Some(guild) => {
+                        guild
+                            .online_members()
+                            .filter(|member| *member != entity)
+                            .filter_map(|member| others.get(member).ok())
+                            .for_each(|(client, _)| {
+                                client.send(ChatUpdate::new(
+                                    ChatSource::guild(player.character.name.clone()),
+                                    message.message.clone(),
+                                ));
+                            });
+                        client.send(ChatMessageResponse::new(
+                            ChatMessageResult::Success,
+                            message.target,
+                            message.index,
+                        ));
+                    },
+                    None => {
+                        client.send(ChatMessageResponse::new(
+                            ChatMessageResult::error(ChatErrorCode::InvalidTarget),
+                            message.target,
+                            message.index,
+                        ));
+                    },
+                },
+                ChatTarget::Stall => {
+                    // Both the owner and the visitors of a stall can talk to everyone in it.
+                    let owner = visitor.map(|visitor| visitor.stall).unwrap_or(entity);
+                    match stalls.get(owner) {
+                        Ok(stall) => {
+                            stall
+                                .visitors()
+                                .iter()
+                                .copied()
+                                .chain([owner])
+                                .filter(|member| *member != entity)
+                                .filter_map(|member| others.get(member).ok())
+                                .for_each(|(client, _)| {
+                                    client.send(ChatUpdate::new(
+                                        ChatSource::stall(player.character.name.clone()),
+                                        message.message.clone(),
+                                    ));
+                                });
+                            client.send(ChatMessageResponse::new(
+                                ChatMessageResult::Success,
+                                message.target,
+                                message.index,
+                            ));
+                        },
+                        Err(_) => {
+                            client.send(ChatMessageResponse::new(
+                                ChatMessageResult::error(ChatErrorCode::InvalidTarget),
+                                message.target,
+                                message.index,
+                            ));
+                        },
+                    }
+                },
+                // Unions are not supported yet, so nobody can be part of one.
+                ChatTarget::Union => {
+                    client.send(ChatMessageResponse::new(
+                        ChatMessageResult::error(ChatErrorCode::InvalidTarget),
+                        message.target,
+                        message.index,
+                    ));
+                },
                 _ => {},
             }
         }
@@ -188,8 +305,90 @@ pub(crate) fn handle_gm_commands(
                     commands.entity(entity).insert(Invisible::from_command());
                     client.send(GmResponse::success_message("Enabled invisibility".to_string()));
                 },
+                // Banning is handled by `handle_ban_command`.
+                GmCommand::BanUser { .. } => {},
                 _ => {},
             }
         }
     }
 }
+
+pub(crate) fn handle_ban_command(
+    query: Query<(Entity, &Client, &Player, &PlayerInput)>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+    server_id: Res<ServerId>,
+    settings: Res<GameConfig>,
+    mut commands: Commands,
+) {
+    for (entity, client, gm, input) in query.iter() {
+        let Some(GmCommand::BanUser { ref name }) = input.gm else {
+            continue;
+        };
+
+        if !gm.character.gm {
+            client.send(GmResponse::error());
+            continue;
+        }
+
+        let until = Utc::now() + chrono::Duration::hours(settings.ban_duration as i64);
+        let reason = format!("Banned by {}.", gm.character.name);
+        let task = task_creator.create_task(ServerUser::ban_owner_of(
+            name.clone(),
+            server_id.0,
+            reason,
+            until,
+            pool.clone(),
+        ));
+        commands.spawn(BanTask {
+            gm: entity,
+            character_name: name.clone(),
+            until,
+            task,
+        });
+    }
+}
+
+pub(crate) fn finish_bans(
+    mut tasks: Query<(Entity, &mut BanTask)>,
+    gms: Query<(&Client, &Player)>,
+    players: Query<(Entity, &Player)>,
+    mut disconnects: EventWriter<ClientDisconnectedEvent>,
+    mut commands: Commands,
+) {
+    for (task_entity, mut ban) in tasks.iter_mut() {
+        let result = match ban.task.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => continue,
+            Err(TryRecvError::Closed) => Err(sqlx::Error::WorkerCrashed),
+        };
+
+        commands.entity(task_entity).despawn();
+        let gm = gms.get(ban.gm).ok();
+        let gm_name = gm.map(|(_, player)| player.character.name.as_str()).unwrap_or_default();
+        let name = &ban.character_name;
+        let response = match result {
+            Ok(true) => {
+                info!(character = name, gm = gm_name, "Banned user of character.");
+                // The user can no longer log in, but we also need to get them out of the game if they're currently
+                // playing.
+                if let Some((entity, _)) = players.iter().find(|(_, player)| player.character.name == *name) {
+                    disconnects.send(ClientDisconnectedEvent(entity));
+                }
+                GmResponse::success_message(format!("Banned {} until {}", name, ban.until))
+            },
+            Ok(false) => {
+                warn!(character = name, "Could not ban unknown character.");
+                GmResponse::error()
+            },
+            Err(e) => {
+                error!(error = %e, character = name, "Could not ban user of character.");
+                GmResponse::error()
+            },
+        };
+
+        if let Some((client, _)) = gm {
+            client.send(response);
+        }
+    }
+}
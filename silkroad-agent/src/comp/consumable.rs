@@ -0,0 +1,52 @@
+use bevy_ecs::prelude::*;
+use bevy_time::{Timer, TimerMode};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// A group of consumables sharing the same cooldown, such that using any item of the group prevents the use of all
+/// other items of the same group for a while.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub(crate) enum CooldownGroup {
+    Health,
+    Mana,
+    Vigor,
+    Cure,
+    Resurrection,
+    Return,
+}
+
+/// The item groups that are currently on cooldown for a player.
+#[derive(Component, Default)]
+pub(crate) struct ItemCooldowns(HashMap<CooldownGroup, Timer>);
+
+impl ItemCooldowns {
+    pub fn is_ready(&self, group: CooldownGroup) -> bool {
+        self.0.get(&group).is_none_or(|timer| timer.finished())
+    }
+
+    pub fn start(&mut self, group: CooldownGroup, duration: Duration) {
+        self.0.insert(group, Timer::new(duration, TimerMode::Once));
+    }
+
+    pub fn tick(&mut self, delta: Duration) {
+        self.0.retain(|_, timer| !timer.tick(delta).finished());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_cooldown_per_group() {
+        let mut cooldowns = ItemCooldowns::default();
+        cooldowns.start(CooldownGroup::Health, Duration::from_secs(1));
+        assert!(!cooldowns.is_ready(CooldownGroup::Health));
+        assert!(cooldowns.is_ready(CooldownGroup::Mana));
+
+        cooldowns.tick(Duration::from_millis(500));
+        assert!(!cooldowns.is_ready(CooldownGroup::Health));
+        cooldowns.tick(Duration::from_millis(500));
+        assert!(cooldowns.is_ready(CooldownGroup::Health));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::comp::pos::Position;
 use crate::comp::{Despawn, EntityReference, GameEntity};
 use bevy_ecs::prelude::*;
+use bevy_time::Timer;
 use silkroad_game_base::Item;
 
 #[derive(Component)]
@@ -9,6 +10,11 @@ pub(crate) struct Drop {
     pub item: Item,
 }
 
+/// Marks a drop as still being reserved for its owner, until the timer runs out.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct LootPriority(pub Timer);
+
 #[derive(Bundle)]
 pub(crate) struct DropBundle {
     pub(crate) drop: Drop,
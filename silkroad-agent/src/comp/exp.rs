@@ -7,7 +7,8 @@ use std::cmp::max;
 const EXP_PER_SP: u64 = 400;
 
 pub(crate) struct ExperienceGained {
-    pub(crate) exp: u64,
+    /// The amount of experience gained, which is negative if experience was lost instead.
+    pub(crate) exp: i64,
     pub(crate) sp_exp: u64,
     pub(crate) trigged_level_up: bool,
     pub(crate) from: Option<EntityReference>,
@@ -39,7 +40,7 @@ impl Experienced {
         self.experience += exp;
         self.sp_exp += sp_exp;
         self.experience_received.push(ExperienceGained {
-            exp,
+            exp: exp as i64,
             sp_exp,
             from,
             trigged_level_up: false,
@@ -54,6 +55,18 @@ impl Experienced {
         self.sp_exp
     }
 
+    /// Takes away the given amount of experience, leaving the player with `remaining` experience. The remaining
+    /// experience may be higher than the current one if the player lost a level due to the loss.
+    pub(crate) fn lose(&mut self, amount: u64, remaining: u64) {
+        self.experience = remaining;
+        self.experience_received.push(ExperienceGained {
+            exp: -(amount as i64),
+            sp_exp: 0,
+            from: None,
+            trigged_level_up: false,
+        });
+    }
+
     pub(crate) fn try_level_up(&mut self, required: u64) -> bool {
         if self.experience >= required {
             self.experience -= required;
@@ -77,6 +90,11 @@ impl Experienced {
     }
 }
 
+/// The experience a player lost when dying, which may be given back when being resurrected.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct LostExperience(pub u64);
+
 #[derive(Component, Constructor, Default)]
 pub(crate) struct SP {
     sp: u32,
@@ -133,6 +151,10 @@ impl Leveled {
         self.leveled_up > 0
     }
 
+    pub(crate) fn did_delevel(&self) -> bool {
+        self.leveled_up < 0
+    }
+
     pub fn new(level: u8, max_level: u8) -> Self {
         Self {
             level,
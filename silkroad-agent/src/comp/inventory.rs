@@ -1,3 +1,4 @@
+use crate::comp::net::Client;
 use crate::db::character::CharacterItem;
 use crate::persistence::ApplyToDatabase;
 use crate::world::WorldData;
@@ -5,8 +6,12 @@ use axum::async_trait;
 use bevy_ecs::prelude::*;
 use log::debug;
 use silkroad_data::itemdata::RefItemData;
+use silkroad_data::DataEntry;
 use silkroad_definitions::type_id::{ObjectItem, ObjectType};
 use silkroad_game_base::{ChangeTracked, Inventory, InventoryChange, Item, ItemTypeData};
+use silkroad_protocol::inventory::{
+    InventoryItemBindingData, InventoryItemContentData, InventoryOperationError, InventoryOperationResult,
+};
 use sqlx::PgPool;
 use std::ops::{Deref, DerefMut};
 
@@ -154,6 +159,56 @@ impl ApplyToDatabase for InventoryChange {
     }
 }
 
+/// Creates a new item of the given reference, with the upgrade level and amount only being applied if they make sense
+/// for the kind of item.
+pub(crate) fn new_item(reference: &'static RefItemData, upgrade_level: u8, variance: Option<u64>, amount: u16) -> Item {
+    let type_data = match ObjectType::from_type_id(&reference.common.type_id) {
+        Some(ObjectType::Item(ObjectItem::Equippable(_))) => ItemTypeData::Equipment { upgrade_level },
+        Some(ObjectType::Item(ObjectItem::Pet(_))) => ItemTypeData::COS,
+        _ => ItemTypeData::Consumable { amount },
+    };
+    Item {
+        reference,
+        variance,
+        type_data,
+    }
+}
+
+/// Puts the item into the inventory and lets the client know where it ended up. Returns `false` if there was no space
+/// left for the item.
+pub(crate) fn give_item(client: &Client, inventory: &mut PlayerInventory, item: Item) -> bool {
+    let Some(slot) = inventory.add_item(item) else {
+        client.send(InventoryOperationResult::Error(InventoryOperationError::InventoryFull));
+        return false;
+    };
+
+    client.send(InventoryOperationResult::success_gain_item(
+        slot,
+        item.reference.ref_id(),
+        item_content(&item),
+    ));
+    true
+}
+
+/// Provides the representation of the item's contents, like its upgrade level or stack size, for the client.
+pub(crate) fn item_content(item: &Item) -> InventoryItemContentData {
+    match item.type_data {
+        ItemTypeData::Equipment { upgrade_level } => InventoryItemContentData::Equipment {
+            plus_level: upgrade_level,
+            variance: item.variance.unwrap_or_default(),
+            durability: 1,
+            magic: vec![],
+            bindings_1: InventoryItemBindingData::new(1, 0),
+            bindings_2: InventoryItemBindingData::new(2, 0),
+            bindings_3: InventoryItemBindingData::new(3, 0),
+            bindings_4: InventoryItemBindingData::new(4, 0),
+        },
+        _ => InventoryItemContentData::Expendable {
+            stack_size: item.stack_size(),
+        },
+    }
+}
+
 impl PlayerInventory {
     fn from_db_inventory(items: &[CharacterItem], size: usize) -> Inventory {
         let item_map = WorldData::items();
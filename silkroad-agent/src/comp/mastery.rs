@@ -44,6 +44,10 @@ impl MasteryKnowledge {
         self.masteries.get(&ref_id).copied()
     }
 
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u32, u8)> + '_ {
+        self.masteries.iter().map(|(ref_id, level)| (*ref_id, *level))
+    }
+
     pub(crate) fn total(&self) -> u16 {
         self.masteries.values().map(|v| u16::from(*v)).sum()
     }
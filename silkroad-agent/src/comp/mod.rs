@@ -1,3 +1,4 @@
+pub(crate) mod consumable;
 pub(crate) mod damage;
 pub(crate) mod drop;
 pub(crate) mod exp;
@@ -9,6 +10,7 @@ pub(crate) mod net;
 pub(crate) mod npc;
 pub(crate) mod player;
 pub(crate) mod pos;
+pub(crate) mod regeneration;
 pub(crate) mod skill;
 pub(crate) mod spawner;
 pub(crate) mod visibility;
@@ -32,8 +34,12 @@ pub(crate) struct Playing(pub(crate) ServerUser, pub(crate) PlayingToken);
 #[derive(Component, Copy, Clone)]
 pub(crate) struct Health {
     pub current_health: u32,
+    /// The maximum including the increase of active buffs.
     pub max_health: u32,
     pub change: Option<i32>,
+    base_max: u32,
+    /// Increase of the maximum from buffs, in percent.
+    bonus: f32,
 }
 
 impl Reset for Health {
@@ -48,6 +54,8 @@ impl Health {
             current_health: max_health,
             max_health,
             change: None,
+            base_max: max_health,
+            bonus: 0.0,
         }
     }
 
@@ -59,8 +67,12 @@ impl Health {
 
     pub fn regenerate(&mut self, amount: u32) {
         let before = self.current_health;
-        self.current_health = self.current_health.saturating_add(amount);
-        self.add_change(before as i32)
+        self.current_health = self.current_health.saturating_add(amount).min(self.max_health);
+        self.add_change((self.current_health - before) as i32)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.current_health >= self.max_health
     }
 
     fn add_change(&mut self, amount: i32) {
@@ -75,14 +87,34 @@ impl Health {
     }
 
     pub fn upgrade(&mut self, new_max: u32) {
-        let diff = new_max - self.current_health;
-        self.max_health = new_max;
         self.increase_max(new_max);
+        let diff = self.max_health - self.current_health;
+        self.current_health = self.max_health;
         self.add_change(diff as i32)
     }
 
     pub fn increase_max(&mut self, new_max: u32) {
-        self.max_health = new_max;
+        self.base_max = new_max;
+        self.update_max();
+    }
+
+    /// Sets the increase of the maximum from buffs, in percent. The current value is capped to the new maximum.
+    pub fn set_bonus(&mut self, bonus: f32) {
+        self.bonus = bonus;
+        self.update_max();
+        if self.current_health > self.max_health {
+            let before = self.current_health;
+            self.current_health = self.max_health;
+            self.add_change(self.current_health as i32 - before as i32);
+        }
+    }
+
+    pub fn bonus(&self) -> f32 {
+        self.bonus
+    }
+
+    fn update_max(&mut self) {
+        self.max_health = (self.base_max as f32 * (1.0 + self.bonus / 100.0).max(0.0)).round() as u32;
     }
 
     pub fn collect_change(&self) -> Option<i32> {
@@ -93,8 +125,12 @@ impl Health {
 #[derive(Component)]
 pub(crate) struct Mana {
     pub current_mana: u32,
+    /// The maximum including the increase of active buffs.
     pub max_mana: u32,
     pub change: Option<i32>,
+    base_max: u32,
+    /// Increase of the maximum from buffs, in percent.
+    bonus: f32,
 }
 
 impl Reset for Mana {
@@ -109,9 +145,21 @@ impl Mana {
             current_mana: max,
             max_mana: max,
             change: None,
+            base_max: max,
+            bonus: 0.0,
         }
     }
 
+    pub fn regenerate(&mut self, amount: u32) {
+        let before = self.current_mana;
+        self.current_mana = self.current_mana.saturating_add(amount).min(self.max_mana);
+        self.add_change((self.current_mana - before) as i32)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.current_mana >= self.max_mana
+    }
+
     fn add_change(&mut self, amount: i32) {
         self.change = match self.change {
             Some(previous_change) => Some(previous_change + amount),
@@ -120,14 +168,34 @@ impl Mana {
     }
 
     pub fn upgrade(&mut self, new_max: u32) {
-        let diff = new_max - self.current_mana;
-        self.max_mana = new_max;
         self.increase_max(new_max);
+        let diff = self.max_mana - self.current_mana;
+        self.current_mana = self.max_mana;
         self.add_change(diff as i32)
     }
 
     pub fn increase_max(&mut self, new_max: u32) {
-        self.current_mana = new_max;
+        self.base_max = new_max;
+        self.update_max();
+    }
+
+    /// Sets the increase of the maximum from buffs, in percent. The current value is capped to the new maximum.
+    pub fn set_bonus(&mut self, bonus: f32) {
+        self.bonus = bonus;
+        self.update_max();
+        if self.current_mana > self.max_mana {
+            let before = self.current_mana;
+            self.current_mana = self.max_mana;
+            self.add_change(self.current_mana as i32 - before as i32);
+        }
+    }
+
+    pub fn bonus(&self) -> f32 {
+        self.bonus
+    }
+
+    fn update_max(&mut self) {
+        self.max_mana = (self.base_max as f32 * (1.0 + self.bonus / 100.0).max(0.0)).round() as u32;
     }
 
     pub fn collect_change(&self) -> Option<i32> {
@@ -1,5 +1,6 @@
 use crate::agent::states::StateTransitionQueue;
 use crate::agent::{Agent, MovementState};
+use crate::buff::Buffed;
 use crate::comp::damage::DamageReceiver;
 use crate::comp::pos::Position;
 use crate::comp::visibility::Visibility;
@@ -37,6 +38,7 @@ pub struct MonsterBundle {
     pub(crate) state_queue: StateTransitionQueue,
     pub(crate) movement_state: MovementState,
     pub(crate) damage_receiver: DamageReceiver,
+    pub(crate) buffed: Buffed,
 }
 
 #[derive(Bundle)]
@@ -1,19 +1,24 @@
 use crate::agent::states::StateTransitionQueue;
 use crate::agent::{Agent, MovementState};
+use crate::buff::Buffed;
+use crate::comp::consumable::ItemCooldowns;
 use crate::comp::damage::DamageReceiver;
 use crate::comp::exp::{Experienced, Leveled, SP};
 use crate::comp::gold::GoldPouch;
 use crate::comp::inventory::PlayerInventory;
 use crate::comp::mastery::MasteryKnowledge;
 use crate::comp::pos::Position;
+use crate::comp::regeneration::Regeneration;
 use crate::comp::skill::SkillBook;
 use crate::comp::visibility::Visibility;
 use crate::comp::{GameEntity, Health, Mana};
 use crate::db::character::CharacterData;
 use crate::db::user::ServerUser;
 use crate::game::mind::Mind;
+use crate::game::weather::KnownWeather;
 use crate::input::PlayerInput;
 use crate::persistence::Persistable;
+use crate::shop::BuyBackList;
 use crate::sync::Reset;
 use bevy_ecs::prelude::*;
 use derive_more::{Deref, From};
@@ -48,6 +53,8 @@ impl Player {
             gold: data.gold as u64,
             beginner_mark: data.beginner_mark,
             gm: data.gm,
+            job_level: data.job_level as u8,
+            job_exp: data.job_exp as u64,
             state: SpawningState::Loading,
             masteries: Vec::new(),
             skills: Vec::new(),
@@ -60,20 +67,17 @@ impl Player {
     }
 }
 
-#[derive(Component)]
-pub(crate) struct Buffed {
-    // pub buffs: Vec<Buff>
-}
-
 #[derive(Bundle)]
 pub(crate) struct PlayerBundle {
     player: Player,
     inventory: PlayerInventory,
+    item_cooldowns: ItemCooldowns,
     gold: GoldPouch,
     game_entity: GameEntity,
     agent: Agent,
     pos: Position,
     buff: Buffed,
+    buy_back: BuyBackList,
     visibility: Visibility,
     input: PlayerInput,
     state_queue: StateTransitionQueue,
@@ -81,6 +85,7 @@ pub(crate) struct PlayerBundle {
     damage_receiver: DamageReceiver,
     health: Health,
     mana: Mana,
+    regeneration: Regeneration,
     level: Leveled,
     sp: SP,
     exp: Experienced,
@@ -90,6 +95,7 @@ pub(crate) struct PlayerBundle {
     masteries: MasteryKnowledge,
     skills: SkillBook,
     race: CharacterRace,
+    weather: KnownWeather,
 }
 
 impl PlayerBundle {
@@ -117,9 +123,11 @@ impl PlayerBundle {
             player,
             game_entity,
             inventory,
+            item_cooldowns: ItemCooldowns::default(),
             agent,
             pos,
-            buff: Buffed {},
+            buff: Buffed::default(),
+            buy_back: BuyBackList::default(),
             visibility,
             gold,
             input: Default::default(),
@@ -128,6 +136,7 @@ impl PlayerBundle {
             damage_receiver: DamageReceiver::default(),
             health: Health::new(max_hp),
             mana: Mana::with_max(max_mana),
+            regeneration: Regeneration::default(),
             sp: SP::new(sp),
             level: Leveled::new(level, max_level),
             exp: Experienced::new(exp, sp_exp as u64),
@@ -137,6 +146,7 @@ impl PlayerBundle {
             masteries: master_knowledge,
             skills,
             race,
+            weather: KnownWeather::default(),
         }
     }
 }
@@ -0,0 +1,62 @@
+use bevy_ecs::prelude::*;
+use bevy_time::{Timer, TimerMode};
+use std::time::Duration;
+
+/// How often an entity regenerates health and mana.
+const REGENERATION_INTERVAL: Duration = Duration::from_secs(2);
+/// How long an entity is considered to be in combat after dealing or receiving damage.
+const COMBAT_COOLDOWN: Duration = Duration::from_secs(10);
+
+/// Keeps track of when an entity may regenerate health and mana next.
+#[derive(Component)]
+pub(crate) struct Regeneration {
+    timer: Timer,
+    combat: Duration,
+}
+
+impl Regeneration {
+    /// Pauses the regeneration until the entity has been out of combat for a while.
+    pub fn enter_combat(&mut self) {
+        self.combat = COMBAT_COOLDOWN;
+        self.timer.reset();
+    }
+
+    pub fn in_combat(&self) -> bool {
+        !self.combat.is_zero()
+    }
+
+    pub fn tick(&mut self, delta: Duration) -> bool {
+        if self.in_combat() {
+            self.combat = self.combat.saturating_sub(delta);
+            return false;
+        }
+        self.timer.tick(delta).just_finished()
+    }
+}
+
+impl Default for Regeneration {
+    fn default() -> Self {
+        Regeneration {
+            timer: Timer::new(REGENERATION_INTERVAL, TimerMode::Repeating),
+            combat: Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_pauses_in_combat() {
+        let mut regeneration = Regeneration::default();
+        assert!(regeneration.tick(REGENERATION_INTERVAL));
+
+        regeneration.enter_combat();
+        assert!(!regeneration.tick(REGENERATION_INTERVAL));
+        assert!(regeneration.in_combat());
+        assert!(!regeneration.tick(COMBAT_COOLDOWN));
+        assert!(!regeneration.in_combat());
+        assert!(regeneration.tick(REGENERATION_INTERVAL));
+    }
+}
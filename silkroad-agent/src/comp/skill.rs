@@ -26,6 +26,10 @@ impl SkillBook {
         self.new_skills.push((skill.group, skill.level));
     }
 
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u32, u8)> + '_ {
+        self.skills.iter().map(|(group, level)| (*group, *level))
+    }
+
     pub(crate) fn has_required_skills_for(&self, skill: &RefSkillData) -> bool {
         return skill
             .required_skills
@@ -1,8 +1,10 @@
 use bevy_ecs_macros::Resource;
+use cgmath::Vector3;
 use config::{ConfigError, FileFormat};
 use log::LevelFilter;
 use once_cell::sync::Lazy;
 use serde::Deserialize;
+use silkroad_game_base::{GlobalPosition, LocalPosition};
 use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
 use sqlx::{ConnectOptions, PgPool};
 use tracing::debug;
@@ -51,6 +53,21 @@ pub(crate) struct GameConfig {
     pub(crate) max_follow_distance: f32,
     pub(crate) masteries: MasteryConfig,
     pub(crate) persist_interval: u64,
+    /// Slots of the storage every account has on each server.
+    pub(crate) storage_size: usize,
+    /// Hours a user stays banned when a game master bans them in-game.
+    pub(crate) ban_duration: u64,
+    pub(crate) party: PartyConfig,
+    pub(crate) guild: GuildConfig,
+    pub(crate) drops: DropConfig,
+    pub(crate) death: DeathConfig,
+    pub(crate) teleport: TeleportConfig,
+    pub(crate) consignment: ConsignmentConfig,
+    pub(crate) alchemy: AlchemyConfig,
+    pub(crate) exchange: ExchangeConfig,
+    pub(crate) pvp: PvpConfig,
+    pub(crate) job: JobConfig,
+    pub(crate) weather: WeatherConfig,
 }
 
 #[derive(Deserialize, Default, Clone, Debug)]
@@ -82,6 +99,141 @@ pub(crate) struct MasteryConfig {
     pub(crate) chinese_per_level: u16,
 }
 
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PartyConfig {
+    pub(crate) max_members: usize,
+    pub(crate) invitation_timeout: u64,
+    /// Additional experience, in percent, for each party member besides the first one when sharing experience.
+    pub(crate) exp_bonus_per_member: u64,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct GuildConfig {
+    pub(crate) creation_cost: u64,
+    pub(crate) min_level: u8,
+    pub(crate) max_members: usize,
+    pub(crate) invitation_timeout: u64,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct PvpConfig {
+    /// Seconds it takes to put on or take off a PvP cape.
+    pub(crate) cape_delay: u64,
+    /// Seconds a player stays an aggressor after attacking an innocent player.
+    pub(crate) aggressor_duration: u64,
+    /// Seconds a player stays a murderer after killing an innocent player, added up for every further kill.
+    pub(crate) murderer_duration: u64,
+    /// Penalty points a murderer receives for every kill. Every point raises the experience lost on death by one
+    /// percent, until the murderer status runs out.
+    pub(crate) murder_penalty: u32,
+    /// Experience lost by murderers when dying, in percent of the experience required for the current level.
+    pub(crate) murderer_exp_loss: f32,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct JobConfig {
+    /// Job experience received for killing a player of a hostile job.
+    pub(crate) kill_experience: u64,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct WeatherConfig {
+    /// Regions along each side of an area, which all share the same weather.
+    pub(crate) area_size: u8,
+    /// Relative chance of clear weather being rolled for an area.
+    pub(crate) clear_chance: f32,
+    /// Relative chance of rain being rolled for an area.
+    pub(crate) rain_chance: f32,
+    /// Relative chance of snow being rolled for an area.
+    pub(crate) snow_chance: f32,
+    /// Minimum seconds the weather lasts before it gets rolled again.
+    pub(crate) min_duration: u64,
+    /// Maximum seconds the weather lasts before it gets rolled again.
+    pub(crate) max_duration: u64,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct DropConfig {
+    /// Multiplier for the chance of monsters dropping items.
+    pub(crate) item_rate: f32,
+    /// Multiplier for the amount of gold dropped by monsters.
+    pub(crate) gold_rate: f32,
+    /// Seconds for which only the killer of a monster, and their party, may pick up its drops.
+    pub(crate) owner_priority: u64,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct DeathConfig {
+    /// Experience lost when dying, in percent of the experience required for the current level.
+    pub(crate) exp_loss: f32,
+    /// The level from which on players lose experience when dying.
+    pub(crate) exp_loss_min_level: u8,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct TeleportConfig {
+    /// Seconds it takes to cast a return scroll.
+    pub(crate) return_cast_time: u64,
+    /// The town players return to if they haven't designated a return point yet.
+    pub(crate) default_return: TownConfig,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct ConsignmentConfig {
+    /// Gold paid when registering an item, in percent of its price. It's refunded once the item has been sold.
+    pub(crate) deposit_rate: u64,
+    /// Gold kept from the earnings of a sold item, in percent of its price.
+    pub(crate) fee_rate: u64,
+    /// Hours an item stays on sale before it expires and has to be taken back.
+    pub(crate) duration: u64,
+    pub(crate) max_listings: usize,
+    pub(crate) page_size: usize,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct AlchemyConfig {
+    /// Chance, in percent, of successfully upgrading an item from the level at the same position. Items cannot be
+    /// upgraded beyond the last level in this table.
+    pub(crate) success_rates: Vec<f32>,
+    /// Chance, in percent, of an item breaking when failing to upgrade it from the level at the same position.
+    /// Items that don't break lose a level instead.
+    pub(crate) break_rates: Vec<f32>,
+    /// Additional chance of success, in percent, when a lucky powder is added.
+    pub(crate) lucky_powder_bonus: f32,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct ExchangeConfig {
+    /// Distance at which an open exchange gets cancelled when the players move away from each other.
+    pub(crate) max_distance: f32,
+    pub(crate) invitation_timeout: u64,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+pub(crate) struct TownConfig {
+    pub(crate) region: u16,
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) z: f32,
+}
+
+impl TownConfig {
+    pub(crate) fn position(&self) -> GlobalPosition {
+        LocalPosition(self.region.into(), Vector3::new(self.x, self.y, self.z)).to_global()
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) struct GameServerConfig {
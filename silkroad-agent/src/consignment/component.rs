@@ -0,0 +1,207 @@
+use crate::comp::inventory::new_item;
+use crate::consignment::db::{DbConsignment, DbConsignmentUpdate, DbSearchPage, NewConsignment};
+use crate::world::WorldData;
+use bevy_ecs::prelude::*;
+use chrono::Utc;
+use silkroad_data::itemdata::RefItemData;
+use silkroad_data::DataEntry;
+use silkroad_game_base::Item;
+use silkroad_protocol::inventory::{ConsignmentItem, ConsignmentSearch, ConsignmentStatus};
+use sqlx::Error;
+use std::collections::HashMap;
+use tokio::sync::oneshot::Receiver;
+
+/// A consignment operation of a player, which is still waiting for the database. Players can only have a single
+/// operation running at a time. It lives on its own entity, such that items and gold which are on their way to or
+/// from the consignment don't get lost if the player disconnects before it finishes.
+#[derive(Component)]
+pub(crate) struct ConsignmentTask {
+    pub(crate) player: Entity,
+    pub(crate) character_id: u32,
+    pub(crate) operation: ConsignmentOperation,
+}
+
+pub(crate) enum ConsignmentOperation {
+    List(Receiver<Result<Vec<DbConsignment>, Error>>),
+    Register {
+        task: Receiver<Result<Option<Vec<DbConsignment>>, Error>>,
+        item: Item,
+        consignment: NewConsignment,
+    },
+    Abort(Receiver<Result<DbConsignmentUpdate, Error>>),
+    Settle(Receiver<Result<DbConsignmentUpdate, Error>>),
+    Search {
+        task: Receiver<Result<DbSearchPage, Error>>,
+        page: u16,
+    },
+    /// A purchase, for which the price has already been taken from the buyer.
+    Buy {
+        task: Receiver<Result<Option<DbConsignment>, Error>>,
+        price: u64,
+    },
+}
+
+/// The prices of the listings a player has been shown by their last search, such that the gold for buying one of
+/// them can be taken before the purchase is made.
+#[derive(Component)]
+pub(crate) struct ConsignmentPrices(HashMap<u32, u64>);
+
+impl ConsignmentPrices {
+    pub(crate) fn price_of(&self, listing_id: u32) -> Option<u64> {
+        self.0.get(&listing_id).copied()
+    }
+}
+
+impl FromIterator<(u32, u64)> for ConsignmentPrices {
+    fn from_iter<T: IntoIterator<Item = (u32, u64)>>(iter: T) -> Self {
+        ConsignmentPrices(iter.into_iter().collect())
+    }
+}
+
+/// Restricts the items shown when searching the consignment. Restrictions which are not set match any item.
+#[derive(Copy, Clone, Default)]
+pub(crate) struct ConsignmentFilter {
+    category: Option<u8>,
+    sub_category: Option<u8>,
+    min_level: Option<u8>,
+    max_level: Option<u8>,
+}
+
+impl ConsignmentFilter {
+    fn is_empty(&self) -> bool {
+        self.category.is_none() && self.sub_category.is_none() && self.min_level.is_none() && self.max_level.is_none()
+    }
+
+    pub(crate) fn matches(&self, item: &RefItemData) -> bool {
+        let type_id = &item.common.type_id;
+        let level = item.required_level.map(|level| level.get()).unwrap_or(0);
+        self.category.is_none_or(|category| type_id.2 == category)
+            && self.sub_category.is_none_or(|sub_category| type_id.3 == sub_category)
+            && self.min_level.is_none_or(|min| level >= min)
+            && self.max_level.is_none_or(|max| level <= max)
+    }
+
+    /// Provides the ref ids of all items matching this filter, or `None` if all items match anyway.
+    pub(crate) fn matching_items(&self) -> Option<Vec<i32>> {
+        if self.is_empty() {
+            return None;
+        }
+
+        Some(
+            WorldData::items()
+                .iter()
+                .filter(|item| self.matches(item))
+                .map(|item| item.ref_id() as i32)
+                .collect(),
+        )
+    }
+}
+
+impl From<&ConsignmentSearch> for ConsignmentFilter {
+    fn from(search: &ConsignmentSearch) -> Self {
+        ConsignmentFilter {
+            category: Some(search.category).filter(|category| *category != 0),
+            sub_category: Some(search.sub_category).filter(|sub_category| *sub_category != 0),
+            min_level: Some(search.min_level).filter(|level| *level != 0),
+            max_level: Some(search.max_level).filter(|level| *level != 0),
+        }
+    }
+}
+
+impl DbConsignment {
+    pub(crate) fn status(&self) -> ConsignmentStatus {
+        if self.sold {
+            ConsignmentStatus::Sold
+        } else if self.expires_at <= Utc::now() {
+            ConsignmentStatus::Expired
+        } else {
+            ConsignmentStatus::OnSale
+        }
+    }
+
+    /// The gold the seller receives when settling this consignment after it was sold.
+    pub(crate) fn earnings(&self) -> u64 {
+        (self.price - self.fee + self.deposit).max(0) as u64
+    }
+
+    pub(crate) fn item(&self) -> Option<Item> {
+        let reference = WorldData::items().find_id(self.item_obj_id as u32)?;
+        Some(new_item(
+            reference,
+            self.upgrade_level as u8,
+            self.variance.map(|variance| variance as u64),
+            self.amount as u16,
+        ))
+    }
+
+    /// Provides the consignment as a new one that has already expired, such that its item can be registered again for
+    /// its owner to collect later.
+    pub(crate) fn as_expired(&self) -> NewConsignment {
+        NewConsignment {
+            item_obj_id: self.item_obj_id as u32,
+            upgrade_level: self.upgrade_level as u8,
+            variance: self.variance.map(|variance| variance as u64),
+            amount: self.amount as u16,
+            price: self.price as u64,
+            deposit: self.deposit as u64,
+            fee: self.fee as u64,
+            expires_at: Utc::now(),
+        }
+    }
+
+    pub(crate) fn as_protocol(&self) -> ConsignmentItem {
+        ConsignmentItem::new(
+            self.id as u32,
+            self.status(),
+            self.item_obj_id as u32,
+            self.amount as u32,
+            self.price as u64,
+            self.deposit as u64,
+            self.fee as u64,
+            self.expires_at.timestamp() as u32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::item_reference;
+    use silkroad_definitions::type_id::TypeId;
+    use std::num::NonZeroU8;
+
+    #[test]
+    pub fn test_filter_matches_category_and_level() {
+        let mut item = item_reference("ITEM_CH_SWORD_01_A", TypeId(3, 1, 6, 2), 1);
+        item.required_level = NonZeroU8::new(20);
+
+        assert!(ConsignmentFilter::default().matches(&item));
+
+        let search = ConsignmentSearch {
+            category: 6,
+            sub_category: 2,
+            min_level: 10,
+            max_level: 0,
+            page: 0,
+        };
+        assert!(ConsignmentFilter::from(&search).matches(&item));
+
+        let search = ConsignmentSearch {
+            category: 6,
+            sub_category: 3,
+            min_level: 0,
+            max_level: 0,
+            page: 0,
+        };
+        assert!(!ConsignmentFilter::from(&search).matches(&item));
+
+        let search = ConsignmentSearch {
+            category: 0,
+            sub_category: 0,
+            min_level: 0,
+            max_level: 19,
+            page: 0,
+        };
+        assert!(!ConsignmentFilter::from(&search).matches(&item));
+    }
+}
@@ -0,0 +1,242 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgPool};
+use std::borrow::Borrow;
+
+#[derive(sqlx::FromRow, Clone)]
+pub(crate) struct DbConsignment {
+    pub(crate) id: i32,
+    pub(crate) item_obj_id: i32,
+    pub(crate) upgrade_level: i16,
+    pub(crate) variance: Option<i64>,
+    pub(crate) amount: i16,
+    pub(crate) price: i64,
+    pub(crate) deposit: i64,
+    pub(crate) fee: i64,
+    pub(crate) expires_at: DateTime<Utc>,
+    pub(crate) sold: bool,
+}
+
+#[derive(sqlx::FromRow, Clone)]
+pub(crate) struct DbListing {
+    pub(crate) id: i32,
+    pub(crate) seller: String,
+    pub(crate) item_obj_id: i32,
+    pub(crate) upgrade_level: i16,
+    pub(crate) amount: i16,
+    pub(crate) price: i64,
+    pub(crate) expires_at: DateTime<Utc>,
+}
+
+pub(crate) struct DbSearchPage {
+    pub(crate) total: i64,
+    pub(crate) listings: Vec<DbListing>,
+}
+
+/// The consignments that got removed by an operation, together with the ones the character still has registered.
+pub(crate) struct DbConsignmentUpdate {
+    pub(crate) removed: Vec<DbConsignment>,
+    pub(crate) remaining: Vec<DbConsignment>,
+}
+
+#[derive(Clone)]
+pub(crate) struct NewConsignment {
+    pub(crate) item_obj_id: u32,
+    pub(crate) upgrade_level: u8,
+    pub(crate) variance: Option<u64>,
+    pub(crate) amount: u16,
+    pub(crate) price: u64,
+    pub(crate) deposit: u64,
+    pub(crate) fee: u64,
+    pub(crate) expires_at: DateTime<Utc>,
+}
+
+pub(crate) async fn load_consignments<T: Borrow<PgPool>>(
+    character_id: u32,
+    pool: T,
+) -> Result<Vec<DbConsignment>, Error> {
+    sqlx::query_as!(
+        DbConsignment,
+        "SELECT id, item_obj_id, upgrade_level, variance, amount, price, deposit, fee, expires_at, sold_at IS NOT NULL as \"sold!\" FROM consignments WHERE character_id = $1 ORDER BY id ASC",
+        character_id as i32
+    )
+    .fetch_all(pool.borrow())
+    .await
+}
+
+/// Puts the item up for sale, unless the character already has the maximum amount of items registered.
+pub(crate) async fn register_consignment<T: Borrow<PgPool>>(
+    character_id: u32,
+    consignment: NewConsignment,
+    max_listings: usize,
+    pool: T,
+) -> Result<Option<Vec<DbConsignment>>, Error> {
+    let inserted = sqlx::query!(
+        "INSERT INTO consignments(character_id, item_obj_id, upgrade_level, variance, amount, price, deposit, fee, expires_at) SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9 WHERE (SELECT COUNT(*) FROM consignments WHERE character_id = $1) < $10",
+        character_id as i32,
+        consignment.item_obj_id as i32,
+        consignment.upgrade_level as i16,
+        consignment.variance.map(|variance| variance as i64),
+        consignment.amount as i16,
+        consignment.price as i64,
+        consignment.deposit as i64,
+        consignment.fee as i64,
+        consignment.expires_at,
+        max_listings as i64
+    )
+    .execute(pool.borrow())
+    .await?;
+
+    if inserted.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    load_consignments(character_id, pool).await.map(Some)
+}
+
+/// Takes an item off the market again, as long as it hasn't been sold yet.
+pub(crate) async fn abort_consignment<T: Borrow<PgPool>>(
+    character_id: u32,
+    consignment_id: u32,
+    pool: T,
+) -> Result<DbConsignmentUpdate, Error> {
+    let removed = sqlx::query_as!(
+        DbConsignment,
+        "DELETE FROM consignments WHERE id = $1 AND character_id = $2 AND sold_at IS NULL RETURNING id, item_obj_id, upgrade_level, variance, amount, price, deposit, fee, expires_at, false as \"sold!\"",
+        consignment_id as i32,
+        character_id as i32
+    )
+    .fetch_all(pool.borrow())
+    .await?;
+
+    let remaining = load_consignments(character_id, pool).await?;
+    Ok(DbConsignmentUpdate { removed, remaining })
+}
+
+/// Removes all sold items of the character, as well as up to `max_expired` items that did not sell in time, such
+/// that their earnings and items can be handed to the character.
+pub(crate) async fn settle_consignments<T: Borrow<PgPool>>(
+    character_id: u32,
+    max_expired: usize,
+    pool: T,
+) -> Result<DbConsignmentUpdate, Error> {
+    let mut transaction = pool.borrow().begin().await?;
+    let mut removed = sqlx::query_as!(
+        DbConsignment,
+        "DELETE FROM consignments WHERE character_id = $1 AND sold_at IS NOT NULL RETURNING id, item_obj_id, upgrade_level, variance, amount, price, deposit, fee, expires_at, true as \"sold!\"",
+        character_id as i32
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+    let expired = sqlx::query_as!(
+        DbConsignment,
+        "DELETE FROM consignments WHERE id IN (SELECT id FROM consignments WHERE character_id = $1 AND sold_at IS NULL AND expires_at <= NOW() ORDER BY id ASC LIMIT $2) RETURNING id, item_obj_id, upgrade_level, variance, amount, price, deposit, fee, expires_at, false as \"sold!\"",
+        character_id as i32,
+        max_expired as i64
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+    removed.extend(expired);
+
+    let remaining = load_consignments(character_id, pool).await?;
+    Ok(DbConsignmentUpdate { removed, remaining })
+}
+
+/// Puts an item, which could not be handed to its owner, back as an expired consignment, such that it can be
+/// collected at a later point.
+pub(crate) async fn restore_consignment<T: Borrow<PgPool>>(
+    character_id: u32,
+    consignment: NewConsignment,
+    pool: T,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "INSERT INTO consignments(character_id, item_obj_id, upgrade_level, variance, amount, price, deposit, fee, expires_at) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        character_id as i32,
+        consignment.item_obj_id as i32,
+        consignment.upgrade_level as i16,
+        consignment.variance.map(|variance| variance as i64),
+        consignment.amount as i16,
+        consignment.price as i64,
+        consignment.deposit as i64,
+        consignment.fee as i64,
+        consignment.expires_at
+    )
+    .execute(pool.borrow())
+    .await?;
+    Ok(())
+}
+
+/// Gives gold to a character that is no longer online, which could not be handed to them while they were.
+pub(crate) async fn refund_gold<T: Borrow<PgPool>>(character_id: u32, amount: u64, pool: T) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE characters SET gold = gold + $1 WHERE id = $2",
+        amount as i64,
+        character_id as i32
+    )
+    .execute(pool.borrow())
+    .await?;
+    Ok(())
+}
+
+/// Finds the items currently on sale on the given server, optionally restricted to the given item references.
+pub(crate) async fn search_consignments<T: Borrow<PgPool>>(
+    server_id: u16,
+    items: Option<Vec<i32>>,
+    page: u16,
+    page_size: usize,
+    pool: T,
+) -> Result<DbSearchPage, Error> {
+    let total = sqlx::query_scalar!(
+        "SELECT COUNT(*) as \"count!\" FROM consignments JOIN characters ON characters.id = consignments.character_id WHERE characters.server_id = $1 AND consignments.sold_at IS NULL AND consignments.expires_at > NOW() AND ($2::integer[] IS NULL OR consignments.item_obj_id = ANY($2))",
+        server_id as i32,
+        items.as_deref()
+    )
+    .fetch_one(pool.borrow())
+    .await?;
+
+    let listings = sqlx::query_as!(
+        DbListing,
+        "SELECT consignments.id, characters.charname as seller, consignments.item_obj_id, consignments.upgrade_level, consignments.amount, consignments.price, consignments.expires_at FROM consignments JOIN characters ON characters.id = consignments.character_id WHERE characters.server_id = $1 AND consignments.sold_at IS NULL AND consignments.expires_at > NOW() AND ($2::integer[] IS NULL OR consignments.item_obj_id = ANY($2)) ORDER BY consignments.registered_at DESC LIMIT $3 OFFSET $4",
+        server_id as i32,
+        items.as_deref(),
+        page_size as i64,
+        page as i64 * page_size as i64
+    )
+    .fetch_all(pool.borrow())
+    .await?;
+
+    Ok(DbSearchPage { total, listings })
+}
+
+/// Marks the item as sold to the given character, provided it's still on sale, it's not their own and they can
+/// afford it. The earnings stay with the consignment until the seller settles it, which also allows selling items
+/// of characters that are currently offline.
+pub(crate) async fn buy_consignment<T: Borrow<PgPool>>(
+    buyer_id: u32,
+    server_id: u16,
+    consignment_id: u32,
+    max_price: u64,
+    pool: T,
+) -> Result<Option<DbConsignment>, Error> {
+    sqlx::query_as!(
+        DbConsignment,
+        "UPDATE consignments SET buyer_id = $2, sold_at = NOW() WHERE id = $1 AND character_id <> $2 AND sold_at IS NULL AND expires_at > NOW() AND price <= $4 AND character_id IN (SELECT id FROM characters WHERE server_id = $3) RETURNING id, item_obj_id, upgrade_level, variance, amount, price, deposit, fee, expires_at, true as \"sold!\"",
+        consignment_id as i32,
+        buyer_id as i32,
+        server_id as i32,
+        max_price as i64
+    )
+    .fetch_optional(pool.borrow())
+    .await
+}
+
+/// Puts an item back on sale after the purchase could not be completed.
+pub(crate) async fn revert_purchase<T: Borrow<PgPool>>(consignment_id: u32, pool: T) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE consignments SET buyer_id = NULL, sold_at = NULL WHERE id = $1",
+        consignment_id as i32
+    )
+    .execute(pool.borrow())
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use crate::consignment::system::{handle_consignment_requests, receive_consignment_results};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+pub(crate) use component::*;
+
+mod component;
+mod db;
+mod system;
+
+pub(crate) struct ConsignmentPlugin;
+
+impl Plugin for ConsignmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                handle_consignment_requests,
+                receive_consignment_results.after(handle_consignment_requests),
+            ),
+        );
+    }
+}
@@ -0,0 +1,533 @@
+use crate::comp::gold::GoldPouch;
+use crate::comp::inventory::{give_item, PlayerInventory};
+use crate::comp::net::Client;
+use crate::comp::player::Player;
+use crate::config::GameConfig;
+use crate::consignment::db::{
+    abort_consignment, buy_consignment, load_consignments, refund_gold, register_consignment, restore_consignment,
+    revert_purchase, search_consignments, settle_consignments, DbConsignment, NewConsignment,
+};
+use crate::consignment::{ConsignmentFilter, ConsignmentOperation, ConsignmentPrices, ConsignmentTask};
+use crate::exchange::Exchange;
+use crate::ext::DbPool;
+use crate::input::PlayerInput;
+use crate::server_plugin::ServerId;
+use crate::tasks::TaskCreator;
+use bevy_ecs::prelude::*;
+use chrono::Utc;
+use silkroad_data::DataEntry;
+use silkroad_game_base::{Inventory, Item};
+use silkroad_protocol::inventory::{
+    ConsignmentAbortResponse, ConsignmentBuyResponse, ConsignmentErrorCode, ConsignmentListing, ConsignmentRegister,
+    ConsignmentRegisterResponse, ConsignmentResponse, ConsignmentResult, ConsignmentSearchResponse,
+    ConsignmentSettleResponse,
+};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::future::Future;
+use std::ops::Add;
+use tokio::sync::oneshot::error::TryRecvError;
+use tokio::sync::oneshot::Receiver;
+use tracing::{debug, error};
+
+fn persist<F>(task_creator: &TaskCreator, character_id: u32, task: F)
+where
+    F: Future<Output = Result<(), sqlx::Error>> + Send + 'static,
+{
+    task_creator.spawn(async move {
+        if let Err(e) = task.await {
+            error!(error = %e, character_id = character_id, "Could not update consignment");
+        }
+    });
+}
+
+/// Checks if the task has finished, providing its result if it has.
+fn poll<T>(task: &mut Receiver<Result<T, sqlx::Error>>) -> Option<Result<T, sqlx::Error>> {
+    match task.try_recv() {
+        Ok(result) => Some(result),
+        Err(TryRecvError::Empty) => None,
+        Err(TryRecvError::Closed) => Some(Err(sqlx::Error::WorkerCrashed)),
+    }
+}
+
+fn as_result(consignments: &[DbConsignment]) -> ConsignmentResult {
+    ConsignmentResult::success(consignments.iter().map(DbConsignment::as_protocol).collect())
+}
+
+/// Takes the item out of the inventory and pays the deposit for it, such that it can be put up for sale.
+fn prepare_registration(
+    register: &ConsignmentRegister,
+    inventory: &mut PlayerInventory,
+    gold: &mut GoldPouch,
+    settings: &GameConfig,
+) -> Result<(NewConsignment, Item), ConsignmentErrorCode> {
+    if register.price == 0 || Inventory::is_equipment_slot(register.slot) {
+        return Err(ConsignmentErrorCode::InvalidItem);
+    }
+
+    let item = inventory
+        .get_item_at(register.slot)
+        .filter(|item| register.amount > 0 && register.amount <= item.stack_size())
+        .ok_or(ConsignmentErrorCode::InvalidItem)?;
+
+    let config = &settings.consignment;
+    let deposit = register.price.saturating_mul(config.deposit_rate) / 100;
+    if gold.amount() < deposit {
+        return Err(ConsignmentErrorCode::NotEnoughGold);
+    }
+
+    let reference = item.reference;
+    let item = inventory
+        .take_from_slot(register.slot, register.amount)
+        .map_err(|_| ConsignmentErrorCode::InvalidItem)?;
+    gold.spend(deposit);
+    debug!(
+        "Registering {} of {} for {} gold.",
+        register.amount,
+        reference.code(),
+        register.price
+    );
+
+    let consignment = NewConsignment {
+        item_obj_id: reference.ref_id(),
+        upgrade_level: item.upgrade_level(),
+        variance: item.variance,
+        amount: item.stack_size(),
+        price: register.price,
+        deposit,
+        fee: register.price.saturating_mul(config.fee_rate) / 100,
+        expires_at: Utc::now().add(chrono::Duration::hours(config.duration as i64)),
+    };
+    Ok((consignment, item))
+}
+
+pub(crate) fn handle_consignment_requests(
+    mut query: Query<(
+        Entity,
+        &Client,
+        &Player,
+        &PlayerInput,
+        &mut PlayerInventory,
+        &mut GoldPouch,
+        Option<&ConsignmentPrices>,
+        Has<Exchange>,
+    )>,
+    tasks: Query<&ConsignmentTask>,
+    settings: Res<GameConfig>,
+    server_id: Res<ServerId>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+    mut cmd: Commands,
+) {
+    let config = &settings.consignment;
+    let waiting = tasks.iter().map(|task| task.player).collect::<HashSet<_>>();
+    for (entity, client, player, input, mut inventory, mut gold, prices, in_exchange) in query.iter_mut() {
+        let character_id = player.character.id;
+        let busy = waiting.contains(&entity);
+        // Anything that puts items into or takes them out of the inventory has to wait until an exchange is done.
+        let locked = busy || in_exchange;
+        let pool = PgPool::clone(&pool);
+        let operation = if input.consignment_list.is_some() {
+            if busy {
+                client.send(ConsignmentResponse::new(ConsignmentResult::error(
+                    ConsignmentErrorCode::Busy,
+                )));
+                continue;
+            }
+
+            ConsignmentOperation::List(task_creator.create_task(load_consignments(character_id, pool)))
+        } else if let Some(ref register) = input.consignment_register {
+            if locked {
+                client.send(ConsignmentRegisterResponse::new(ConsignmentResult::error(
+                    ConsignmentErrorCode::Busy,
+                )));
+                continue;
+            }
+
+            match prepare_registration(register, &mut inventory, &mut gold, &settings) {
+                Ok((consignment, item)) => {
+                    let task = task_creator.create_task(register_consignment(
+                        character_id,
+                        consignment.clone(),
+                        config.max_listings,
+                        pool,
+                    ));
+                    ConsignmentOperation::Register {
+                        task,
+                        item,
+                        consignment,
+                    }
+                },
+                Err(code) => {
+                    client.send(ConsignmentRegisterResponse::new(ConsignmentResult::error(code)));
+                    continue;
+                },
+            }
+        } else if let Some(ref abort) = input.consignment_abort {
+            if locked {
+                client.send(ConsignmentAbortResponse::new(ConsignmentResult::error(
+                    ConsignmentErrorCode::Busy,
+                )));
+                continue;
+            }
+
+            if inventory.free_slots() == 0 {
+                client.send(ConsignmentAbortResponse::new(ConsignmentResult::error(
+                    ConsignmentErrorCode::InventoryFull,
+                )));
+                continue;
+            }
+
+            ConsignmentOperation::Abort(task_creator.create_task(abort_consignment(
+                character_id,
+                abort.personal_id,
+                pool,
+            )))
+        } else if input.consignment_settle.is_some() {
+            if locked {
+                client.send(ConsignmentSettleResponse::new(ConsignmentResult::error(
+                    ConsignmentErrorCode::Busy,
+                )));
+                continue;
+            }
+
+            ConsignmentOperation::Settle(task_creator.create_task(settle_consignments(
+                character_id,
+                inventory.free_slots(),
+                pool,
+            )))
+        } else if let Some(ref search) = input.consignment_search {
+            if busy {
+                client.send(ConsignmentSearchResponse::Error(ConsignmentErrorCode::Busy));
+                continue;
+            }
+
+            let items = ConsignmentFilter::from(search).matching_items();
+            let task = task_creator.create_task(search_consignments(
+                server_id.0,
+                items,
+                search.page,
+                config.page_size,
+                pool,
+            ));
+            ConsignmentOperation::Search {
+                task,
+                page: search.page,
+            }
+        } else if let Some(ref buy) = input.consignment_buy {
+            if locked {
+                client.send(ConsignmentBuyResponse::Error(ConsignmentErrorCode::Busy));
+                continue;
+            }
+
+            if inventory.free_slots() == 0 {
+                client.send(ConsignmentBuyResponse::Error(ConsignmentErrorCode::InventoryFull));
+                continue;
+            }
+
+            let Some(price) = prices.and_then(|prices| prices.price_of(buy.listing_id)) else {
+                client.send(ConsignmentBuyResponse::Error(ConsignmentErrorCode::ListingUnavailable));
+                continue;
+            };
+
+            if gold.amount() < price {
+                client.send(ConsignmentBuyResponse::Error(ConsignmentErrorCode::NotEnoughGold));
+                continue;
+            }
+
+            // The gold is taken right away, such that it cannot be spent on anything else while the purchase is made.
+            gold.spend(price);
+            let task =
+                task_creator.create_task(buy_consignment(character_id, server_id.0, buy.listing_id, price, pool));
+            ConsignmentOperation::Buy { task, price }
+        } else {
+            continue;
+        };
+
+        cmd.spawn(ConsignmentTask {
+            player: entity,
+            character_id,
+            operation,
+        });
+    }
+}
+
+pub(crate) fn receive_consignment_results(
+    mut tasks: Query<(Entity, &mut ConsignmentTask)>,
+    mut players: Query<(&Client, &mut PlayerInventory, &mut GoldPouch)>,
+    settings: Res<GameConfig>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+    mut cmd: Commands,
+) {
+    for (task_entity, mut task) in tasks.iter_mut() {
+        let ConsignmentTask {
+            player,
+            character_id,
+            operation,
+        } = &mut *task;
+        let character_id = *character_id;
+        match operation {
+            ConsignmentOperation::List(task) => {
+                let Some(result) = poll(task) else {
+                    continue;
+                };
+
+                let Ok((client, _, _)) = players.get(*player) else {
+                    cmd.entity(task_entity).despawn();
+                    continue;
+                };
+
+                match result {
+                    Ok(consignments) => client.send(ConsignmentResponse::new(as_result(&consignments))),
+                    Err(e) => {
+                        error!(error = %e, character_id = character_id, "Could not load consignments");
+                        client.send(ConsignmentResponse::new(ConsignmentResult::error(
+                            ConsignmentErrorCode::Failed,
+                        )));
+                    },
+                }
+            },
+            ConsignmentOperation::Register {
+                task,
+                item,
+                consignment,
+            } => {
+                let Some(result) = poll(task) else {
+                    continue;
+                };
+
+                let code = match result {
+                    Ok(Some(consignments)) => {
+                        if let Ok((client, _, _)) = players.get(*player) {
+                            client.send(ConsignmentRegisterResponse::new(as_result(&consignments)));
+                        }
+                        cmd.entity(task_entity).despawn();
+                        continue;
+                    },
+                    Ok(None) => ConsignmentErrorCode::TooManyListings,
+                    Err(e) => {
+                        error!(error = %e, character_id = character_id, "Could not register consignment");
+                        ConsignmentErrorCode::Failed
+                    },
+                };
+
+                let Ok((client, mut inventory, mut gold)) = players.get_mut(*player) else {
+                    restore(consignment.clone(), character_id, &task_creator, &pool);
+                    refund(consignment.deposit, character_id, &task_creator, &pool);
+                    cmd.entity(task_entity).despawn();
+                    continue;
+                };
+
+                gold.gain(consignment.deposit);
+                if !give_item(client, &mut inventory, *item) {
+                    restore(consignment.clone(), character_id, &task_creator, &pool);
+                }
+                client.send(ConsignmentRegisterResponse::new(ConsignmentResult::error(code)));
+            },
+            ConsignmentOperation::Abort(task) => {
+                let Some(result) = poll(task) else {
+                    continue;
+                };
+
+                let Ok((client, mut inventory, _)) = players.get_mut(*player) else {
+                    if let Ok(update) = result {
+                        for consignment in update.removed {
+                            restore(consignment.as_expired(), character_id, &task_creator, &pool);
+                        }
+                    }
+                    cmd.entity(task_entity).despawn();
+                    continue;
+                };
+
+                match result {
+                    Ok(update) => {
+                        if update.removed.is_empty() {
+                            client.send(ConsignmentAbortResponse::new(ConsignmentResult::error(
+                                ConsignmentErrorCode::ListingUnavailable,
+                            )));
+                        } else {
+                            for consignment in update.removed {
+                                hand_out(consignment, client, &mut inventory, character_id, &task_creator, &pool);
+                            }
+                            client.send(ConsignmentAbortResponse::new(as_result(&update.remaining)));
+                        }
+                    },
+                    Err(e) => {
+                        error!(error = %e, character_id = character_id, "Could not abort consignment");
+                        client.send(ConsignmentAbortResponse::new(ConsignmentResult::error(
+                            ConsignmentErrorCode::Failed,
+                        )));
+                    },
+                }
+            },
+            ConsignmentOperation::Settle(task) => {
+                let Some(result) = poll(task) else {
+                    continue;
+                };
+
+                let Ok((client, mut inventory, mut gold)) = players.get_mut(*player) else {
+                    if let Ok(update) = result {
+                        for consignment in update.removed {
+                            if consignment.sold {
+                                refund(consignment.earnings(), character_id, &task_creator, &pool);
+                            } else {
+                                restore(consignment.as_expired(), character_id, &task_creator, &pool);
+                            }
+                        }
+                    }
+                    cmd.entity(task_entity).despawn();
+                    continue;
+                };
+
+                match result {
+                    Ok(update) => {
+                        for consignment in update.removed {
+                            if consignment.sold {
+                                gold.gain(consignment.earnings());
+                            } else {
+                                hand_out(consignment, client, &mut inventory, character_id, &task_creator, &pool);
+                            }
+                        }
+                        client.send(ConsignmentSettleResponse::new(as_result(&update.remaining)));
+                    },
+                    Err(e) => {
+                        error!(error = %e, character_id = character_id, "Could not settle consignments");
+                        client.send(ConsignmentSettleResponse::new(ConsignmentResult::error(
+                            ConsignmentErrorCode::Failed,
+                        )));
+                    },
+                }
+            },
+            ConsignmentOperation::Search { task, page } => {
+                let Some(result) = poll(task) else {
+                    continue;
+                };
+
+                let Ok((client, _, _)) = players.get(*player) else {
+                    cmd.entity(task_entity).despawn();
+                    continue;
+                };
+
+                match result {
+                    Ok(found) => {
+                        let page_size = settings.consignment.page_size.max(1) as i64;
+                        let total_pages = (found.total + page_size - 1) / page_size;
+                        let prices = found
+                            .listings
+                            .iter()
+                            .map(|listing| (listing.id as u32, listing.price as u64))
+                            .collect::<ConsignmentPrices>();
+                        let listings = found
+                            .listings
+                            .into_iter()
+                            .map(|listing| {
+                                ConsignmentListing::new(
+                                    listing.id as u32,
+                                    listing.seller,
+                                    listing.item_obj_id as u32,
+                                    listing.upgrade_level as u8,
+                                    listing.amount as u16,
+                                    listing.price as u64,
+                                    listing.expires_at.timestamp() as u32,
+                                )
+                            })
+                            .collect();
+                        client.send(ConsignmentSearchResponse::success(*page, total_pages as u16, listings));
+                        cmd.entity(*player).insert(prices);
+                    },
+                    Err(e) => {
+                        error!(error = %e, character_id = character_id, "Could not search consignments");
+                        client.send(ConsignmentSearchResponse::Error(ConsignmentErrorCode::Failed));
+                    },
+                }
+            },
+            ConsignmentOperation::Buy { task, price } => {
+                let Some(result) = poll(task) else {
+                    continue;
+                };
+
+                let price = *price;
+                let Ok((client, mut inventory, mut gold)) = players.get_mut(*player) else {
+                    // The buyer has already paid, so they either collect the item later or get their gold back.
+                    match result {
+                        Ok(Some(consignment)) => restore(consignment.as_expired(), character_id, &task_creator, &pool),
+                        _ => refund(price, character_id, &task_creator, &pool),
+                    }
+                    cmd.entity(task_entity).despawn();
+                    continue;
+                };
+
+                match result {
+                    Ok(Some(consignment)) => {
+                        let given = consignment
+                            .item()
+                            .is_some_and(|item| give_item(client, &mut inventory, item));
+                        if given {
+                            client.send(ConsignmentBuyResponse::Success {
+                                listing_id: consignment.id as u32,
+                            });
+                        } else {
+                            // Space might have been used up while the purchase was in progress.
+                            persist(
+                                &task_creator,
+                                character_id,
+                                revert_purchase(consignment.id as u32, PgPool::clone(&pool)),
+                            );
+                            gold.gain(price);
+                            client.send(ConsignmentBuyResponse::Error(ConsignmentErrorCode::ListingUnavailable));
+                        }
+                    },
+                    Ok(None) => {
+                        gold.gain(price);
+                        client.send(ConsignmentBuyResponse::Error(ConsignmentErrorCode::ListingUnavailable));
+                    },
+                    Err(e) => {
+                        error!(error = %e, character_id = character_id, "Could not buy consignment");
+                        gold.gain(price);
+                        client.send(ConsignmentBuyResponse::Error(ConsignmentErrorCode::Failed));
+                    },
+                }
+            },
+        }
+
+        cmd.entity(task_entity).despawn();
+    }
+}
+
+/// Registers the item again as an expired consignment of the character, such that they can collect it later.
+fn restore(consignment: NewConsignment, character_id: u32, task_creator: &TaskCreator, pool: &PgPool) {
+    persist(
+        task_creator,
+        character_id,
+        restore_consignment(character_id, consignment, PgPool::clone(pool)),
+    );
+}
+
+/// Gives the gold to the character directly in the database, for when they're no longer online to receive it.
+fn refund(amount: u64, character_id: u32, task_creator: &TaskCreator, pool: &PgPool) {
+    persist(
+        task_creator,
+        character_id,
+        refund_gold(character_id, amount, PgPool::clone(pool)),
+    );
+}
+
+/// Gives the item of the consignment back to its owner. If that's not possible, because they e.g. ran out of
+/// space in the meantime, it is kept in the consignment for them to collect later.
+fn hand_out(
+    consignment: DbConsignment,
+    client: &Client,
+    inventory: &mut PlayerInventory,
+    character_id: u32,
+    task_creator: &TaskCreator,
+    pool: &PgPool,
+) {
+    if let Some(item) = consignment.item() {
+        if give_item(client, inventory, item) {
+            return;
+        }
+    }
+
+    restore(consignment.as_expired(), character_id, task_creator, pool);
+}
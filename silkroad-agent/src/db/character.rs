@@ -61,6 +61,16 @@ pub struct CharacterData {
     pub beginner_mark: bool,
     pub gm: bool,
     pub last_logout: Option<DateTime<Utc>>,
+    pub return_region: Option<i16>,
+    pub return_x: Option<f32>,
+    pub return_y: Option<f32>,
+    pub return_z: Option<f32>,
+    pub pk_state: i16,
+    pub pk_state_until: Option<DateTime<Utc>>,
+    pub pk_kills: i32,
+    pub pk_penalty: i32,
+    pub job_level: i16,
+    pub job_exp: i64,
 }
 
 impl CharacterData {
@@ -71,7 +81,7 @@ impl CharacterData {
     ) -> Result<Vec<CharacterData>, Error> {
         sqlx::query_as!(
             CharacterData,
-            "SELECT id, user_id, server_id, character_type, scale, level, exp, strength, intelligence, stat_points, current_hp, current_mp, charname, deletion_end, sp, x, y, z, max_level, region, berserk_points, gold, sp_exp, beginner_mark, gm, last_logout, rotation, race as \"race!: DbRace\" FROM characters WHERE user_id = $1 AND server_id = $2 AND (deletion_end > NOW() OR deletion_end is null) ORDER BY id ASC",
+            "SELECT id, user_id, server_id, character_type, scale, level, exp, strength, intelligence, stat_points, current_hp, current_mp, charname, deletion_end, sp, x, y, z, max_level, region, berserk_points, gold, sp_exp, beginner_mark, gm, last_logout, rotation, return_region, return_x, return_y, return_z, pk_state, pk_state_until, pk_kills, pk_penalty, job_level, job_exp, race as \"race!: DbRace\" FROM characters WHERE user_id = $1 AND server_id = $2 AND (deletion_end > NOW() OR deletion_end is null) ORDER BY id ASC",
             user,
             shard as i32
         ).fetch_all(pool.borrow()).await
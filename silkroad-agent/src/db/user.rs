@@ -65,6 +65,41 @@ impl ServerUser {
         Ok(Some(server_user))
     }
 
+    /// Assigns the job to the user on the server, unless they already have one. Returns `true` if the job was
+    /// assigned.
+    pub async fn assign_job<T: Borrow<PgPool>>(id: i32, server: u16, job: i16, pool: T) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            "UPDATE user_servers SET job = $1 WHERE user_id = $2 AND server_id = $3 AND job = 0",
+            job,
+            id,
+            server as i32
+        )
+        .execute(pool.borrow())
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Bans the user owning the character with the given name on the given server until the given time. Returns `true`
+    /// if there is such a character.
+    pub async fn ban_owner_of<T: Borrow<PgPool>>(
+        character_name: String,
+        server_id: u16,
+        reason: String,
+        until: DateTime<Utc>,
+        pool: T,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            "INSERT INTO bans(user_id, reason, banned_until) SELECT user_id, $2, $3 FROM characters WHERE charname = $1 AND server_id = $4",
+            character_name,
+            reason,
+            until,
+            server_id as i32
+        )
+        .execute(pool.borrow())
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn fetch_job_distribution<T: Borrow<PgPool>>(shard: u16, pool: T) -> (u32, u32) {
         let result = sqlx::query!(
             "SELECT COUNT(job) as \"count!\", job FROM user_servers WHERE job <> 0 AND server_id = $1 GROUP BY job",
@@ -1,9 +1,11 @@
 use crate::chat::command::Command;
 use crate::comp::monster::SpawnedBy;
 use crate::comp::{EntityReference, GameEntity};
+use crate::game::combat::Hit;
 use bevy_ecs::prelude::*;
 use silkroad_data::skilldata::RefSkillData;
 use silkroad_game_base::{GlobalLocation, GlobalPosition};
+use std::time::Duration;
 
 #[derive(Event)]
 pub(crate) struct ClientConnectedEvent(pub Entity);
@@ -23,6 +25,7 @@ pub(crate) struct UniqueKilledEvent {
     pub unique: GameEntity,
 }
 
+#[derive(Copy, Clone)]
 pub(crate) struct AttackDefinition {
     pub skill: &'static RefSkillData,
     pub instance: u32,
@@ -33,7 +36,22 @@ pub(crate) struct DamageReceiveEvent {
     pub source: EntityReference,
     pub target: EntityReference,
     pub attack: AttackDefinition,
-    pub amount: u32,
+    pub hit: Hit,
+}
+
+/// Applies the buff (or debuff) of the skill to the target for the given duration.
+#[derive(Event)]
+pub(crate) struct BuffApplyEvent {
+    pub source: EntityReference,
+    pub target: EntityReference,
+    pub attack: AttackDefinition,
+    pub duration: Duration,
+}
+
+/// Removes all debuffs from the target, like when using a cure pill.
+#[derive(Event)]
+pub(crate) struct CureEvent {
+    pub target: EntityReference,
 }
 
 #[derive(Event)]
@@ -48,6 +66,15 @@ pub(crate) struct EntityDeath {
     pub killer: Option<EntityReference>,
 }
 
+/// Brings a dead player back to life, giving back the given percentage of the experience they lost when dying.
+#[derive(Event)]
+pub(crate) struct ResurrectEvent {
+    pub target: Entity,
+    pub experience_restore: u8,
+    /// The highest level the target may have to be resurrected, if there is a limit.
+    pub max_level: Option<u8>,
+}
+
 #[derive(Event)]
 pub(crate) struct SpawnMonster {
     pub ref_id: u32,
@@ -0,0 +1,235 @@
+use bevy_ecs::prelude::*;
+use bevy_time::{Timer, TimerMode};
+use silkroad_data::DataEntry;
+use silkroad_game_base::{InventoryChange, Item};
+use silkroad_protocol::exchange::ExchangeErrorCode;
+use sqlx::Error;
+use std::time::Duration;
+use tokio::sync::oneshot::Receiver;
+
+/// The amount of items each player can offer in a single exchange.
+pub(crate) const MAX_EXCHANGE_ITEMS: usize = 12;
+
+/// An open request of the inviter to exchange items with the entity.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct ExchangeInvitation {
+    pub(crate) inviter: Entity,
+    pub(crate) timeout: Timer,
+}
+
+impl ExchangeInvitation {
+    pub(crate) fn new(inviter: Entity, timeout: Duration) -> Self {
+        ExchangeInvitation {
+            inviter,
+            timeout: Timer::new(timeout, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) enum ExchangeState {
+    /// The offer can still be changed.
+    Open,
+    /// The offer has been locked and the player waits for their partner to lock theirs as well.
+    Confirmed,
+    /// The player agreed to exchange the offers of both sides.
+    Approved,
+}
+
+/// An item offered in an exchange, as it has been shown to the partner.
+#[derive(Copy, Clone)]
+pub(crate) struct OfferedItem {
+    pub(crate) slot: u8,
+    pub(crate) item: Item,
+}
+
+impl OfferedItem {
+    /// Checks if the given item is still the one that was offered, such that the partner doesn't receive something
+    /// other than what they agreed to.
+    pub(crate) fn matches(&self, item: &Item) -> bool {
+        self.item.reference.ref_id() == item.reference.ref_id()
+            && self.item.variance == item.variance
+            && self.item.type_data == item.type_data
+    }
+}
+
+/// The side of the player in an exchange with another player, containing what the player offers to their partner.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct Exchange {
+    pub(crate) partner: Entity,
+    items: Vec<OfferedItem>,
+    gold: u64,
+    state: ExchangeState,
+}
+
+impl Exchange {
+    pub(crate) fn new(partner: Entity) -> Self {
+        Exchange {
+            partner,
+            items: Vec::new(),
+            gold: 0,
+            state: ExchangeState::Open,
+        }
+    }
+
+    /// The offered items, in the order they have been offered.
+    pub(crate) fn items(&self) -> &[OfferedItem] {
+        &self.items
+    }
+
+    pub(crate) fn gold(&self) -> u64 {
+        self.gold
+    }
+
+    pub(crate) fn state(&self) -> ExchangeState {
+        self.state
+    }
+
+    fn ensure_open(&self) -> Result<(), ExchangeErrorCode> {
+        match self.state {
+            ExchangeState::Open => Ok(()),
+            _ => Err(ExchangeErrorCode::NotAllowed),
+        }
+    }
+
+    /// Offers the whole stack in the given inventory slot.
+    pub(crate) fn offer_item(&mut self, inventory_slot: u8, item: Item) -> Result<(), ExchangeErrorCode> {
+        self.ensure_open()?;
+        if self.items.iter().any(|offered| offered.slot == inventory_slot) {
+            return Err(ExchangeErrorCode::NotAllowed);
+        }
+
+        if self.items.len() >= MAX_EXCHANGE_ITEMS {
+            return Err(ExchangeErrorCode::InventoryFull);
+        }
+
+        self.items.push(OfferedItem {
+            slot: inventory_slot,
+            item,
+        });
+        Ok(())
+    }
+
+    /// Takes the item at the given position of the exchange window back out of the offer, providing the inventory
+    /// slot it came from.
+    pub(crate) fn withdraw_item(&mut self, exchange_slot: u8) -> Result<u8, ExchangeErrorCode> {
+        self.ensure_open()?;
+        if exchange_slot as usize >= self.items.len() {
+            return Err(ExchangeErrorCode::NotAllowed);
+        }
+
+        Ok(self.items.remove(exchange_slot as usize).slot)
+    }
+
+    pub(crate) fn offer_gold(&mut self, amount: u64) -> Result<(), ExchangeErrorCode> {
+        self.ensure_open()?;
+        self.gold = amount;
+        Ok(())
+    }
+
+    pub(crate) fn confirm(&mut self) -> Result<(), ExchangeErrorCode> {
+        self.ensure_open()?;
+        self.state = ExchangeState::Confirmed;
+        Ok(())
+    }
+
+    /// Agrees to the exchange, which is only possible once both sides have locked their offers.
+    pub(crate) fn approve(&mut self, partner_state: ExchangeState) -> Result<(), ExchangeErrorCode> {
+        if self.state != ExchangeState::Confirmed || partner_state == ExchangeState::Open {
+            return Err(ExchangeErrorCode::NotAllowed);
+        }
+
+        self.state = ExchangeState::Approved;
+        Ok(())
+    }
+}
+
+/// What one side of an exchange gave and received, such that the exchange can be undone if it cannot be stored.
+pub(crate) struct ExchangeParticipant {
+    pub(crate) entity: Entity,
+    pub(crate) character_id: u32,
+    pub(crate) given: Vec<OfferedItem>,
+    pub(crate) given_gold: u64,
+    pub(crate) received_slots: Vec<u8>,
+    pub(crate) received_gold: u64,
+    /// The changes to the inventory from before the exchange, which are stored together with it. They need to be
+    /// persisted again if the exchange fails.
+    pub(crate) pending_changes: Vec<InventoryChange>,
+}
+
+/// An exchange whose items have already been swapped, but which still waits for the database to store the new
+/// state of both players. It lives on its own entity, such that it can be finished even if one of the players
+/// disconnects in the meantime.
+#[derive(Component)]
+pub(crate) struct ExchangeCommit {
+    pub(crate) task: Receiver<Result<(), Error>>,
+    pub(crate) participants: [ExchangeParticipant; 2],
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::item_reference;
+    use once_cell::sync::Lazy;
+    use silkroad_data::itemdata::RefItemData;
+    use silkroad_definitions::type_id::TypeId;
+    use silkroad_game_base::ItemTypeData;
+
+    static SWORD: Lazy<RefItemData> = Lazy::new(|| item_reference("ITEM_CH_SWORD_01_A", TypeId(3, 1, 6, 2), 1));
+
+    fn sword(upgrade_level: u8) -> Item {
+        Item {
+            reference: &SWORD,
+            variance: None,
+            type_data: ItemTypeData::Equipment { upgrade_level },
+        }
+    }
+
+    #[test]
+    pub fn test_offer_is_locked_after_confirming() {
+        let mut exchange = Exchange::new(Entity::from_raw(1));
+        assert!(exchange.offer_item(13, sword(0)).is_ok());
+        assert!(exchange.offer_item(13, sword(0)).is_err());
+        assert!(exchange.offer_item(14, sword(1)).is_ok());
+        assert!(exchange.offer_gold(100).is_ok());
+        assert_eq!(exchange.withdraw_item(0).ok(), Some(13));
+        assert_eq!(
+            exchange.items().iter().map(|offered| offered.slot).collect::<Vec<_>>(),
+            vec![14]
+        );
+
+        assert!(exchange.approve(ExchangeState::Confirmed).is_err());
+        assert!(exchange.confirm().is_ok());
+        assert!(exchange.offer_item(15, sword(2)).is_err());
+        assert!(exchange.offer_gold(200).is_err());
+        assert_eq!(exchange.gold(), 100);
+
+        assert!(exchange.approve(ExchangeState::Open).is_err());
+        assert!(exchange.approve(ExchangeState::Confirmed).is_ok());
+        assert_eq!(exchange.state(), ExchangeState::Approved);
+    }
+
+    #[test]
+    pub fn test_offer_is_limited() {
+        let mut exchange = Exchange::new(Entity::from_raw(1));
+        for slot in 0..MAX_EXCHANGE_ITEMS as u8 {
+            assert!(exchange.offer_item(13 + slot, sword(slot)).is_ok());
+        }
+        assert!(matches!(
+            exchange.offer_item(40, sword(0)),
+            Err(ExchangeErrorCode::InventoryFull)
+        ));
+    }
+
+    #[test]
+    pub fn test_offered_item_must_stay_the_same() {
+        let offered = OfferedItem {
+            slot: 13,
+            item: sword(3),
+        };
+        assert!(offered.matches(&sword(3)));
+        assert!(!offered.matches(&sword(0)));
+    }
+}
@@ -0,0 +1,62 @@
+use silkroad_game_base::Item;
+use sqlx::{Error, PgConnection, PgPool};
+use std::borrow::Borrow;
+
+/// The state of the inventory slots and gold of a character that changed since they were last stored.
+pub(crate) struct CharacterPossessions {
+    pub(crate) character_id: u32,
+    /// The content of every changed inventory slot, where `None` means the slot is empty now.
+    pub(crate) slots: Vec<(u8, Option<Item>)>,
+    pub(crate) gold: u64,
+}
+
+async fn store_possessions(possessions: &CharacterPossessions, connection: &mut PgConnection) -> Result<(), Error> {
+    let character_id = possessions.character_id as i32;
+    for (slot, item) in possessions.slots.iter() {
+        match item {
+            Some(item) => {
+                sqlx::query!(
+                    "INSERT INTO character_items(character_id, item_obj_id, upgrade_level, slot, variance, amount) VALUES($1, $2, $3, $4, $5, $6) ON CONFLICT(character_id, slot) DO UPDATE SET item_obj_id = EXCLUDED.item_obj_id, upgrade_level = EXCLUDED.upgrade_level, variance = EXCLUDED.variance, amount = EXCLUDED.amount",
+                    character_id,
+                    item.reference.common.ref_id as i32,
+                    item.type_data.upgrade_level().map(|a| a as i16).unwrap_or(0),
+                    *slot as i16,
+                    item.variance.map(|a| a as i64),
+                    item.type_data.amount() as i16
+                )
+                .execute(&mut *connection)
+                .await?;
+            },
+            None => {
+                sqlx::query!(
+                    "DELETE FROM character_items WHERE character_id = $1 AND slot = $2",
+                    character_id,
+                    *slot as i16,
+                )
+                .execute(&mut *connection)
+                .await?;
+            },
+        }
+    }
+
+    sqlx::query!(
+        "UPDATE characters SET gold = $1 WHERE id = $2",
+        possessions.gold as i64,
+        character_id
+    )
+    .execute(&mut *connection)
+    .await?;
+    Ok(())
+}
+
+/// Stores the possessions of all given characters, where either all of them get stored or none.
+pub(crate) async fn store_exchange<T: Borrow<PgPool>>(
+    possessions: Vec<CharacterPossessions>,
+    pool: T,
+) -> Result<(), Error> {
+    let mut transaction = pool.borrow().begin().await?;
+    for character in possessions.iter() {
+        store_possessions(character, &mut transaction).await?;
+    }
+    transaction.commit().await
+}
@@ -0,0 +1,29 @@
+use crate::exchange::system::{
+    cancel_exchanges, finish_exchanges, handle_exchange_confirmations, handle_exchange_invitations,
+    handle_exchange_offers, handle_exchange_requests,
+};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+pub(crate) use component::*;
+
+mod component;
+mod db;
+mod system;
+
+pub(crate) struct ExchangePlugin;
+
+impl Plugin for ExchangePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                finish_exchanges,
+                handle_exchange_requests.after(finish_exchanges),
+                handle_exchange_invitations.after(handle_exchange_requests),
+                handle_exchange_offers.after(handle_exchange_invitations),
+                handle_exchange_confirmations.after(handle_exchange_offers),
+                cancel_exchanges.after(handle_exchange_confirmations),
+            ),
+        );
+    }
+}
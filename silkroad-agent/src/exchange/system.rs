@@ -0,0 +1,809 @@
+use crate::agent::states::Dead;
+use crate::comp::gold::GoldPouch;
+use crate::comp::inventory::{item_content, PlayerInventory};
+use crate::comp::net::Client;
+use crate::comp::player::Player;
+use crate::comp::pos::Position;
+use crate::comp::GameEntity;
+use crate::config::GameConfig;
+use crate::event::ClientDisconnectedEvent;
+use crate::exchange::component::{
+    Exchange, ExchangeCommit, ExchangeInvitation, ExchangeParticipant, ExchangeState, OfferedItem,
+};
+use crate::exchange::db::{store_exchange, CharacterPossessions};
+use crate::ext::DbPool;
+use crate::guild::GuildInvitation;
+use crate::input::PlayerInput;
+use crate::party::PartyInvitation;
+use crate::persistence::{restore_changes, take_changes, ApplyToDatabase};
+use crate::stall::Stall;
+use crate::tasks::TaskCreator;
+use crate::teleport::Teleporting;
+use crate::world::EntityLookup;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::{Has, QueryItem};
+use bevy_time::Time;
+use silkroad_data::DataEntry;
+use silkroad_game_base::{ChangeTracked, Inventory, InventoryChange, Item, ToOptimizedChange};
+use silkroad_protocol::exchange::{
+    ApproveExchangeResponse, CancelExchangeResponse, ConfirmExchangeResponse, ExchangeApproved, ExchangeCancelled,
+    ExchangeCompleted, ExchangeConfirmed, ExchangeErrorCode, ExchangeOfferUpdate, ExchangeResult, ExchangeStarted,
+    StartExchangeResponse,
+};
+use silkroad_protocol::inventory::{
+    InventoryItemData, InventoryOperationError, InventoryOperationRequest, InventoryOperationResponseData,
+    InventoryOperationResult, RentInfo,
+};
+use silkroad_protocol::party::PlayerInvitationRequest;
+use sqlx::PgPool;
+use std::collections::{BTreeSet, HashSet};
+use std::mem;
+use std::time::Duration;
+use tokio::sync::oneshot::error::TryRecvError;
+use tracing::error;
+
+type ExchangeTargets<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Client,
+        &'static Position,
+        Has<Exchange>,
+        Has<ExchangeInvitation>,
+        Has<PartyInvitation>,
+        Has<GuildInvitation>,
+        Has<Stall>,
+    ),
+    With<Player>,
+>;
+
+type Possessions<'a> = (
+    &'a Player,
+    &'a Client,
+    &'a mut Exchange,
+    &'a mut PlayerInventory,
+    &'a mut GoldPouch,
+);
+
+fn find_partner<'a>(
+    target_id: u32,
+    requester: Entity,
+    position: &Position,
+    lookup: &EntityLookup,
+    targets: &'a ExchangeTargets,
+    max_distance: f32,
+    invited: &HashSet<Entity>,
+) -> Result<(Entity, &'a Client), ExchangeErrorCode> {
+    let target = lookup
+        .get_entity_for_id(target_id)
+        .filter(|target| *target != requester)
+        .ok_or(ExchangeErrorCode::InvalidTarget)?;
+    let (client, target_position, in_exchange, has_invitation, has_party_invitation, has_guild_invitation, has_stall) =
+        targets.get(target).map_err(|_| ExchangeErrorCode::InvalidTarget)?;
+    if position.distance_to(target_position) > max_distance.powf(2.0) {
+        return Err(ExchangeErrorCode::TooFarAway);
+    }
+
+    if in_exchange
+        || has_invitation
+        || has_party_invitation
+        || has_guild_invitation
+        || has_stall
+        || invited.contains(&target)
+    {
+        return Err(ExchangeErrorCode::TargetBusy);
+    }
+
+    Ok((target, client))
+}
+
+pub(crate) fn handle_exchange_requests(
+    query: Query<(
+        Entity,
+        &GameEntity,
+        &Client,
+        &PlayerInput,
+        &Position,
+        Has<Exchange>,
+        Has<Stall>,
+        Has<Dead>,
+    )>,
+    targets: ExchangeTargets,
+    lookup: Res<EntityLookup>,
+    settings: Res<GameConfig>,
+    mut cmd: Commands,
+) {
+    let timeout = Duration::from_secs(settings.exchange.invitation_timeout);
+    let mut invited = HashSet::new();
+    for (entity, game_entity, client, input, position, in_exchange, has_stall, dead) in query.iter() {
+        let Some(ref start) = input.exchange_start else {
+            continue;
+        };
+
+        let target = if in_exchange || has_stall || dead {
+            Err(ExchangeErrorCode::NotAllowed)
+        } else {
+            find_partner(
+                start.target,
+                entity,
+                position,
+                &lookup,
+                &targets,
+                settings.exchange.max_distance,
+                &invited,
+            )
+        };
+
+        match target {
+            Ok((target, target_client)) => {
+                invited.insert(target);
+                target_client.send(PlayerInvitationRequest::exchange(game_entity.unique_id));
+                cmd.entity(target).insert(ExchangeInvitation::new(entity, timeout));
+            },
+            Err(code) => client.send(StartExchangeResponse::new(ExchangeResult::error(code))),
+        }
+    }
+}
+
+pub(crate) fn handle_exchange_invitations(
+    mut query: Query<(
+        Entity,
+        &GameEntity,
+        &Client,
+        &PlayerInput,
+        Has<Exchange>,
+        &mut ExchangeInvitation,
+    )>,
+    requesters: Query<(&GameEntity, &Client, Has<Exchange>)>,
+    time: Res<Time>,
+    mut cmd: Commands,
+) {
+    let delta = time.delta();
+    let mut started = HashSet::new();
+    for (entity, game_entity, client, input, in_exchange, mut invitation) in query.iter_mut() {
+        let accepted = match input.invitation_response {
+            Some(response) => response.accepted(),
+            None if invitation.timeout.tick(delta).finished() => false,
+            None => continue,
+        };
+
+        cmd.entity(entity).remove::<ExchangeInvitation>();
+        let requester = invitation.inviter;
+        let Ok((requester_game_entity, requester_client, requester_in_exchange)) = requesters.get(requester) else {
+            continue;
+        };
+
+        if !accepted {
+            requester_client.send(StartExchangeResponse::new(ExchangeResult::error(
+                ExchangeErrorCode::Declined,
+            )));
+            continue;
+        }
+
+        if in_exchange || requester_in_exchange || started.contains(&entity) || started.contains(&requester) {
+            requester_client.send(StartExchangeResponse::new(ExchangeResult::error(
+                ExchangeErrorCode::TargetBusy,
+            )));
+            continue;
+        }
+
+        started.insert(entity);
+        started.insert(requester);
+        cmd.entity(entity).insert(Exchange::new(requester));
+        cmd.entity(requester).insert(Exchange::new(entity));
+        requester_client.send(StartExchangeResponse::new(ExchangeResult::Success));
+        requester_client.send(ExchangeStarted::new(game_entity.unique_id));
+        client.send(ExchangeStarted::new(requester_game_entity.unique_id));
+    }
+}
+
+fn offer_update(unique_id: u32, exchange: &Exchange) -> ExchangeOfferUpdate {
+    let items = exchange
+        .items()
+        .iter()
+        .enumerate()
+        .map(|(position, offered)| {
+            InventoryItemData::new(
+                position as u8,
+                RentInfo::Empty,
+                offered.item.reference.ref_id(),
+                item_content(&offered.item),
+            )
+        })
+        .collect();
+    ExchangeOfferUpdate::new(unique_id, exchange.gold(), items)
+}
+
+pub(crate) fn handle_exchange_offers(
+    mut query: Query<(
+        &GameEntity,
+        &Client,
+        &PlayerInput,
+        &PlayerInventory,
+        &GoldPouch,
+        &mut Exchange,
+    )>,
+    clients: Query<&Client>,
+) {
+    for (game_entity, client, input, inventory, gold, mut exchange) in query.iter_mut() {
+        let Some(ref operation) = input.inventory else {
+            continue;
+        };
+
+        let result = match operation.data {
+            InventoryOperationRequest::AddToExchange { inventory_slot } => {
+                match inventory
+                    .get_item_at(inventory_slot)
+                    .filter(|_| !Inventory::is_equipment_slot(inventory_slot))
+                {
+                    Some(item) => exchange
+                        .offer_item(inventory_slot, *item)
+                        .map(|_| InventoryOperationResponseData::AddToExchange { inventory_slot })
+                        .map_err(|_| InventoryOperationError::InvalidTarget),
+                    None => Err(InventoryOperationError::InvalidTarget),
+                }
+            },
+            InventoryOperationRequest::RemoveFromExchange { exchange_slot } => exchange
+                .withdraw_item(exchange_slot)
+                .map(|_| InventoryOperationResponseData::RemoveFromExchange { exchange_slot })
+                .map_err(|_| InventoryOperationError::InvalidTarget),
+            InventoryOperationRequest::SetExchangeGold { amount } => {
+                if amount > gold.amount() {
+                    Err(InventoryOperationError::NotEnoughGold)
+                } else {
+                    exchange
+                        .offer_gold(amount)
+                        .map(|_| InventoryOperationResponseData::SetExchangeGold { amount })
+                        .map_err(|_| InventoryOperationError::InvalidTarget)
+                }
+            },
+            _ => continue,
+        };
+
+        match result {
+            Ok(response) => {
+                client.send(InventoryOperationResult::Success(response));
+                let update = offer_update(game_entity.unique_id, &exchange);
+                if let Ok(partner_client) = clients.get(exchange.partner) {
+                    partner_client.send(update.clone());
+                }
+                client.send(update);
+            },
+            Err(error) => client.send(InventoryOperationResult::Error(error)),
+        }
+    }
+}
+
+/// Finds the first empty slot that isn't reserved for equipment.
+fn empty_slot(inventory: &Inventory) -> Option<u8> {
+    (0..inventory.size() as u8)
+        .find(|slot| !Inventory::is_equipment_slot(*slot) && inventory.get_item_at(*slot).is_none())
+}
+
+fn check_offer(inventory: &Inventory, offer: &[OfferedItem], received: usize) -> Result<(), ExchangeErrorCode> {
+    if offer.iter().any(|offered| {
+        Inventory::is_equipment_slot(offered.slot)
+            || inventory
+                .get_item_at(offered.slot)
+                .is_none_or(|item| !offered.matches(item))
+    }) {
+        return Err(ExchangeErrorCode::Failed);
+    }
+
+    if inventory.free_slots() + offer.len() < received {
+        return Err(ExchangeErrorCode::InventoryFull);
+    }
+
+    Ok(())
+}
+
+fn take_offer(inventory: &mut Inventory, offer: &[OfferedItem]) -> Vec<Item> {
+    offer
+        .iter()
+        .map(|offered| {
+            let amount = inventory
+                .get_item_at(offered.slot)
+                .expect("Offered item should still exist after checking")
+                .stack_size();
+            inventory
+                .take_from_slot(offered.slot, amount)
+                .expect("Offered item should be removable after checking")
+        })
+        .collect()
+}
+
+fn receive_offer(inventory: &mut Inventory, items: Vec<Item>) -> Vec<u8> {
+    items
+        .into_iter()
+        .map(|item| {
+            let slot = empty_slot(inventory).expect("Inventory should have enough space after checking");
+            inventory
+                .place_item(slot, item)
+                .expect("Empty slot should accept the item");
+            slot
+        })
+        .collect()
+}
+
+fn check_swap(
+    first: &Inventory,
+    first_offer: &[OfferedItem],
+    second: &Inventory,
+    second_offer: &[OfferedItem],
+) -> Result<(), ExchangeErrorCode> {
+    check_offer(first, first_offer, second_offer.len())?;
+    check_offer(second, second_offer, first_offer.len())
+}
+
+/// Moves the items offered by both sides into the inventory of the other side. Either all items get moved or, if
+/// any of the offered items changed since it was offered or doesn't fit into the other inventory, none of them.
+/// Provides the slots the received items have been put into for each side.
+fn swap_items(
+    first: &mut Inventory,
+    first_offer: &[OfferedItem],
+    second: &mut Inventory,
+    second_offer: &[OfferedItem],
+) -> Result<(Vec<u8>, Vec<u8>), ExchangeErrorCode> {
+    check_swap(first, first_offer, second, second_offer)?;
+
+    let first_items = take_offer(first, first_offer);
+    let second_items = take_offer(second, second_offer);
+    Ok((receive_offer(first, second_items), receive_offer(second, first_items)))
+}
+
+/// Takes the received items back out of the inventory and puts the given items back into the slots they came from.
+fn undo_swap(inventory: &mut Inventory, participant: &ExchangeParticipant) {
+    for slot in participant.received_slots.iter() {
+        if let Some(amount) = inventory.get_item_at(*slot).map(Item::stack_size) {
+            let _ = inventory.take_from_slot(*slot, amount);
+        }
+    }
+
+    for given in participant.given.iter() {
+        if inventory.place_item(given.slot, given.item).is_err() && inventory.add_item(given.item).is_none() {
+            error!(
+                character_id = participant.character_id,
+                "Could not give back item {} after a failed exchange.",
+                given.item.reference.code()
+            );
+        }
+    }
+}
+
+/// Provides every inventory slot whose content is affected by any of the changes.
+fn touched_slots<'a>(changes: impl Iterator<Item = &'a InventoryChange>) -> BTreeSet<u8> {
+    let mut slots = BTreeSet::new();
+    for change in changes {
+        match change {
+            InventoryChange::AddItem { slot, .. }
+            | InventoryChange::ChangeTypeData { slot, .. }
+            | InventoryChange::RemoveItem { slot } => {
+                slots.insert(*slot);
+            },
+            InventoryChange::MoveItem {
+                source_slot: first,
+                target_slot: second,
+            }
+            | InventoryChange::Swap {
+                first_slot: first,
+                second_slot: second,
+            } => {
+                slots.insert(*first);
+                slots.insert(*second);
+            },
+        }
+    }
+    slots
+}
+
+/// Stores the swapped items and gold of both sides in a single transaction. Any changes to the inventories that
+/// haven't been persisted yet are stored along with it, as they could otherwise be applied after the exchange and
+/// overwrite parts of it.
+fn commit_exchange(mut participants: [ExchangeParticipant; 2]) -> impl FnOnce(&mut World) + Send {
+    move |world: &mut World| {
+        let mut possessions = Vec::with_capacity(participants.len());
+        for participant in participants.iter_mut() {
+            let mut pending = take_changes::<PlayerInventory>(world, participant.entity);
+            pending.append(&mut participant.pending_changes);
+            participant.pending_changes = pending;
+
+            let Some(gold) = world.get::<GoldPouch>(participant.entity).map(GoldPouch::amount) else {
+                continue;
+            };
+            let Some(mut inventory) = world.get_mut::<PlayerInventory>(participant.entity) else {
+                continue;
+            };
+
+            // The exchange itself is only stored by the transaction, never through the regular persistence.
+            let exchanged = inventory.bypass_change_detection().changes();
+            let slots = touched_slots(participant.pending_changes.iter().chain(exchanged.iter()))
+                .into_iter()
+                .map(|slot| (slot, inventory.get_item_at(slot).copied()))
+                .collect();
+            possessions.push(CharacterPossessions {
+                character_id: participant.character_id,
+                slots,
+                gold,
+            });
+        }
+
+        let pool = PgPool::clone(world.resource::<DbPool>());
+        let task = world
+            .resource::<TaskCreator>()
+            .create_task(store_exchange(possessions, pool));
+        world.spawn(ExchangeCommit { task, participants });
+    }
+}
+
+fn start_swap(
+    entities: [Entity; 2],
+    [mut first, mut second]: [QueryItem<Possessions>; 2],
+    cmd: &mut Commands,
+) -> Result<(), ExchangeErrorCode> {
+    if first.2.gold() > first.4.amount() || second.2.gold() > second.4.amount() {
+        return Err(ExchangeErrorCode::Failed);
+    }
+
+    // Changes from before the exchange are kept apart from the ones of the exchange, such that they can still be
+    // persisted if the exchange has to be undone.
+    check_swap(&first.3, first.2.items(), &second.3, second.2.items())?;
+    let first_pending = first.3.changes();
+    let second_pending = second.3.changes();
+    let (first_received, second_received) = swap_items(&mut first.3, first.2.items(), &mut second.3, second.2.items())?;
+    let (first_gold, second_gold) = (first.2.gold(), second.2.gold());
+    first.4.spend(first_gold);
+    first.4.gain(second_gold);
+    second.4.spend(second_gold);
+    second.4.gain(first_gold);
+
+    cmd.add(commit_exchange([
+        ExchangeParticipant {
+            entity: entities[0],
+            character_id: first.0.character.id,
+            given: first.2.items().to_vec(),
+            given_gold: first_gold,
+            received_slots: first_received,
+            received_gold: second_gold,
+            pending_changes: first_pending,
+        },
+        ExchangeParticipant {
+            entity: entities[1],
+            character_id: second.0.character.id,
+            given: second.2.items().to_vec(),
+            given_gold: second_gold,
+            received_slots: second_received,
+            received_gold: first_gold,
+            pending_changes: second_pending,
+        },
+    ]));
+    Ok(())
+}
+
+pub(crate) fn handle_exchange_confirmations(
+    query: Query<(Entity, &Client, &PlayerInput), With<Exchange>>,
+    mut exchanges: Query<Possessions>,
+    mut cmd: Commands,
+) {
+    for (entity, client, input) in query.iter() {
+        if input.exchange_confirm.is_some() {
+            let result = exchanges
+                .get_mut(entity)
+                .map_err(|_| ExchangeErrorCode::NotExchanging)
+                .and_then(|(_, _, mut exchange, _, _)| exchange.confirm().map(|_| exchange.partner));
+            if let Ok(Ok((_, partner_client, ..))) = result.map(|partner| exchanges.get(partner)) {
+                partner_client.send(ExchangeConfirmed);
+            }
+            client.send(ConfirmExchangeResponse::new(result.map(|_| ()).into()));
+        }
+
+        if input.exchange_approve.is_some() {
+            let Ok(partner) = exchanges.get(entity).map(|(_, _, exchange, _, _)| exchange.partner) else {
+                continue;
+            };
+            let Ok([mut own, partner_possessions]) = exchanges.get_many_mut([entity, partner]) else {
+                client.send(ApproveExchangeResponse::new(ExchangeResult::error(
+                    ExchangeErrorCode::NotExchanging,
+                )));
+                continue;
+            };
+
+            let partner_state = partner_possessions.2.state();
+            if let Err(code) = own.2.approve(partner_state) {
+                client.send(ApproveExchangeResponse::new(ExchangeResult::error(code)));
+                continue;
+            }
+
+            let partner_client = partner_possessions.1;
+            if partner_state != ExchangeState::Approved {
+                client.send(ApproveExchangeResponse::new(ExchangeResult::Success));
+                partner_client.send(ExchangeApproved);
+                continue;
+            }
+
+            match start_swap([entity, partner], [own, partner_possessions], &mut cmd) {
+                Ok(_) => {
+                    client.send(ApproveExchangeResponse::new(ExchangeResult::Success));
+                    partner_client.send(ExchangeApproved);
+                },
+                Err(code) => {
+                    client.send(ApproveExchangeResponse::new(ExchangeResult::error(code)));
+                    client.send(ExchangeCancelled);
+                    partner_client.send(ExchangeCancelled);
+                    cmd.entity(entity).remove::<Exchange>();
+                    cmd.entity(partner).remove::<Exchange>();
+                },
+            }
+        }
+    }
+}
+
+pub(crate) fn finish_exchanges(
+    mut commits: Query<(Entity, &mut ExchangeCommit)>,
+    mut players: Query<(&Client, &mut PlayerInventory, &mut GoldPouch)>,
+    (task_creator, pool): (Res<TaskCreator>, Res<DbPool>),
+    mut cmd: Commands,
+) {
+    for (commit_entity, mut commit) in commits.iter_mut() {
+        let result = match commit.task.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => continue,
+            Err(TryRecvError::Closed) => Err(sqlx::Error::WorkerCrashed),
+        };
+
+        cmd.entity(commit_entity).despawn();
+        match result {
+            Ok(_) => {
+                for participant in commit.participants.iter() {
+                    let Ok((client, inventory, _)) = players.get(participant.entity) else {
+                        continue;
+                    };
+
+                    let received_items = participant
+                        .received_slots
+                        .iter()
+                        .filter_map(|slot| inventory.get_item_at(*slot).map(|item| (*slot, item)))
+                        .map(|(slot, item)| {
+                            InventoryItemData::new(slot, RentInfo::Empty, item.reference.ref_id(), item_content(item))
+                        })
+                        .collect();
+                    client.send(ExchangeCompleted::new(
+                        participant.given.iter().map(|given| given.slot).collect(),
+                        received_items,
+                    ));
+                    cmd.entity(participant.entity).remove::<Exchange>();
+                }
+            },
+            Err(e) => {
+                error!(error = %e, "Could not store exchange, reverting it.");
+                for participant in commit.participants.iter_mut() {
+                    let pending = mem::take(&mut participant.pending_changes);
+                    let Ok((client, mut inventory, mut gold)) = players.get_mut(participant.entity) else {
+                        // Without the player, the changes from before the exchange can only be written directly.
+                        let character_id = participant.character_id;
+                        let pool = PgPool::clone(&pool);
+                        task_creator.spawn(async move {
+                            for change in pending.optimize() {
+                                if let Err(e) = change.apply(character_id, &pool).await {
+                                    error!(error = %e, character_id = character_id, "Could not apply update");
+                                }
+                            }
+                        });
+                        continue;
+                    };
+
+                    // The changes from before the exchange need to be persisted ahead of the ones undoing it.
+                    cmd.add(restore_changes::<PlayerInventory>(participant.entity, pending));
+                    undo_swap(&mut inventory, participant);
+                    gold.spend(participant.received_gold);
+                    gold.gain(participant.given_gold);
+                    client.send(ExchangeCancelled);
+                    cmd.entity(participant.entity).remove::<Exchange>();
+                }
+            },
+        }
+    }
+}
+
+pub(crate) fn cancel_exchanges(
+    query: Query<(
+        Entity,
+        &Client,
+        &PlayerInput,
+        &Position,
+        &Exchange,
+        Has<Dead>,
+        Has<Teleporting>,
+    )>,
+    partners: Query<(&Client, &Position, &Exchange)>,
+    commits: Query<&ExchangeCommit>,
+    mut disconnects: EventReader<ClientDisconnectedEvent>,
+    settings: Res<GameConfig>,
+    mut cmd: Commands,
+) {
+    let disconnected = disconnects.read().map(|event| event.0).collect::<HashSet<_>>();
+    let max_distance = settings.exchange.max_distance.powf(2.0);
+    let committing = commits
+        .iter()
+        .flat_map(|commit| commit.participants.iter().map(|participant| participant.entity))
+        .collect::<HashSet<_>>();
+    let mut cancelled = HashSet::new();
+    for (entity, client, input, position, exchange, dead, teleporting) in query.iter() {
+        if cancelled.contains(&entity) {
+            continue;
+        }
+
+        let partner = partners
+            .get(exchange.partner)
+            .ok()
+            .filter(|(_, _, partner_exchange)| partner_exchange.partner == entity);
+        let explicit = input.exchange_cancel.is_some();
+
+        // Once both sides agreed, the items are already on their way and the exchange can no longer be stopped.
+        let agreed = partner.is_some_and(|(_, _, partner_exchange)| {
+            exchange.state() == ExchangeState::Approved && partner_exchange.state() == ExchangeState::Approved
+        });
+        if agreed || committing.contains(&entity) {
+            if explicit {
+                client.send(CancelExchangeResponse::new(ExchangeResult::error(
+                    ExchangeErrorCode::NotAllowed,
+                )));
+            }
+            continue;
+        }
+
+        let should_cancel = explicit
+            || dead
+            || teleporting
+            || disconnected.contains(&entity)
+            || match partner {
+                None => true,
+                Some((_, partner_position, _)) => {
+                    disconnected.contains(&exchange.partner) || position.distance_to(partner_position) > max_distance
+                },
+            };
+        if !should_cancel {
+            continue;
+        }
+
+        cancelled.insert(entity);
+        if let Some(mut entity_commands) = cmd.get_entity(entity) {
+            entity_commands.remove::<Exchange>();
+        }
+        if explicit {
+            client.send(CancelExchangeResponse::new(ExchangeResult::Success));
+        } else {
+            client.send(ExchangeCancelled);
+        }
+
+        if let Some((partner_client, ..)) = partner {
+            cancelled.insert(exchange.partner);
+            if let Some(mut partner_commands) = cmd.get_entity(exchange.partner) {
+                partner_commands.remove::<Exchange>();
+            }
+            partner_client.send(ExchangeCancelled);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::item_reference;
+    use once_cell::sync::Lazy;
+    use silkroad_data::itemdata::RefItemData;
+    use silkroad_definitions::type_id::TypeId;
+    use silkroad_game_base::ItemTypeData;
+
+    static SWORD: Lazy<RefItemData> = Lazy::new(|| item_reference("ITEM_CH_SWORD_01_A", TypeId(3, 1, 6, 2), 1));
+
+    fn sword(upgrade_level: u8) -> Item {
+        Item {
+            reference: &SWORD,
+            variance: None,
+            type_data: ItemTypeData::Equipment { upgrade_level },
+        }
+    }
+
+    fn inventory(swords: &[u8]) -> Inventory {
+        let mut inventory = Inventory::new(15);
+        for slot in swords {
+            inventory.set_item(*slot, sword(*slot));
+        }
+        inventory
+    }
+
+    fn offer(swords: &[u8]) -> Vec<OfferedItem> {
+        swords
+            .iter()
+            .map(|slot| OfferedItem {
+                slot: *slot,
+                item: sword(*slot),
+            })
+            .collect()
+    }
+
+    #[test]
+    pub fn test_swap_moves_items_to_free_slots() {
+        let mut first = inventory(&[13]);
+        let mut second = inventory(&[13, 14]);
+        let (first_received, second_received) =
+            swap_items(&mut first, &offer(&[13]), &mut second, &offer(&[14])).expect("Swap should succeed");
+
+        assert_eq!(first_received, vec![13]);
+        assert_eq!(second_received, vec![14]);
+        assert_eq!(first.get_item_at(13).map(|item| item.upgrade_level()), Some(14));
+        assert_eq!(second.get_item_at(14).map(|item| item.upgrade_level()), Some(13));
+    }
+
+    #[test]
+    pub fn test_swap_leaves_inventories_untouched_when_full() {
+        let mut first = inventory(&[13]);
+        let mut second = inventory(&[13, 14]);
+        assert!(matches!(
+            swap_items(&mut first, &[], &mut second, &offer(&[13, 14])),
+            Err(ExchangeErrorCode::InventoryFull)
+        ));
+        assert!(matches!(
+            swap_items(&mut first, &offer(&[14]), &mut second, &offer(&[13])),
+            Err(ExchangeErrorCode::Failed)
+        ));
+        assert_eq!(first.items().count(), 1);
+        assert_eq!(second.items().count(), 2);
+    }
+
+    #[test]
+    pub fn test_swap_fails_when_offered_item_changed() {
+        let mut first = inventory(&[13]);
+        let mut second = inventory(&[13]);
+        let replaced = [OfferedItem {
+            slot: 13,
+            item: sword(7),
+        }];
+        assert!(matches!(
+            swap_items(&mut first, &replaced, &mut second, &offer(&[13])),
+            Err(ExchangeErrorCode::Failed)
+        ));
+        assert_eq!(first.get_item_at(13).map(|item| item.upgrade_level()), Some(13));
+        assert_eq!(second.get_item_at(13).map(|item| item.upgrade_level()), Some(13));
+    }
+
+    #[test]
+    pub fn test_undo_swap_restores_given_items() {
+        let mut first = inventory(&[13]);
+        let mut second = inventory(&[13, 14]);
+        let (first_received, _) =
+            swap_items(&mut first, &offer(&[13]), &mut second, &offer(&[14])).expect("Swap should succeed");
+        let participant = ExchangeParticipant {
+            entity: Entity::from_raw(0),
+            character_id: 1,
+            given: offer(&[13]),
+            given_gold: 0,
+            received_slots: first_received,
+            received_gold: 0,
+            pending_changes: Vec::new(),
+        };
+
+        undo_swap(&mut first, &participant);
+        assert_eq!(first.items().count(), 1);
+        assert_eq!(first.get_item_at(13).map(|item| item.upgrade_level()), Some(13));
+    }
+
+    #[test]
+    pub fn test_touched_slots_contain_both_sides_of_a_move() {
+        let changes = [
+            InventoryChange::RemoveItem { slot: 13 },
+            InventoryChange::MoveItem {
+                source_slot: 14,
+                target_slot: 20,
+            },
+            InventoryChange::AddItem {
+                slot: 13,
+                item: sword(1),
+            },
+        ];
+        assert_eq!(
+            touched_slots(changes.iter()).into_iter().collect::<Vec<_>>(),
+            vec![13, 14, 20]
+        );
+    }
+}
@@ -2,7 +2,8 @@ use bevy_ecs_macros::Resource;
 use derive_more::{Deref, DerefMut, From};
 use id_pool::IdPool;
 use silkroad_data::npc_pos::NpcPosition;
-use silkroad_game_base::LocalLocation;
+use silkroad_game_base::{GlobalLocation, GlobalPosition, LocalLocation};
+use silkroad_navmesh::pathfinding::PathError;
 use silkroad_navmesh::GlobalNavmesh;
 use silkroad_network::server::SilkroadServer;
 use sqlx::PgPool;
@@ -24,15 +25,54 @@ pub struct DbPool(PgPool);
 pub struct Navmesh(GlobalNavmesh);
 
 impl Navmesh {
-    fn height_for_location(&self, local: LocalLocation) -> Option<f32> {
+    fn height_for_location(&self, local: LocalLocation, reference: Option<f32>) -> Option<f32> {
         self.0
-            .mesh_for(local.0)
-            .and_then(|mesh| mesh.heightmap().height_at_position(local.1.x, local.1.y))
+            .mesh_ref_for(local.0)
+            .and_then(|mesh| mesh.height_at(local.1.x, local.1.y, reference))
     }
 
     pub fn height_for<T: Into<LocalLocation>>(&self, location: T) -> Option<f32> {
         let local = location.into();
-        self.height_for_location(local)
+        self.height_for_location(local, None)
+    }
+
+    /// Provides the height at the given location that is closest to the height we're currently at. This makes sure we
+    /// stay on a bridge instead of falling through it, or stay below instead of climbing on top of it.
+    pub fn height_near<T: Into<LocalLocation>>(&self, location: T, current_height: f32) -> Option<f32> {
+        let local = location.into();
+        self.height_for_location(local, Some(current_height))
+    }
+
+    /// Checks if we can walk in a straight line between the two locations without walking through an object. The
+    /// locations are expected to be close to each other, such that they're at most in neighbouring regions.
+    pub fn is_walkable(&self, from: GlobalLocation, to: GlobalLocation) -> bool {
+        let local = from.to_local();
+        let to_local = to.0 - (from.0 - local.1);
+        self.0
+            .mesh_ref_for(local.0)
+            .is_none_or(|mesh| !mesh.is_blocked(local.1, to_local))
+    }
+
+    /// Finds a path from one location to a position that walks around any obstacles on the way. The result contains
+    /// all positions that need to be walked through, ending with the target position.
+    pub fn path_between(&self, from: GlobalLocation, to: GlobalPosition) -> Result<Vec<GlobalPosition>, PathError> {
+        let start = from.to_local();
+        let goal = to.to_location().to_local();
+        let mut path = self
+            .0
+            .find_path((start.0, start.1), (goal.0, goal.1))?
+            .into_iter()
+            .map(|(region, location)| {
+                let location = LocalLocation(region, location);
+                let height = self.height_for_location(location, None).unwrap_or(to.y);
+                location.to_global().with_y(height)
+            })
+            .collect::<Vec<_>>();
+        // The goal lost its height when looking for the path, so make sure we end up exactly where we wanted to.
+        if let Some(last) = path.last_mut() {
+            *last = to;
+        }
+        Ok(path)
     }
 }
 
@@ -0,0 +1,250 @@
+use crate::friends::db::{DbFriendGroup, DbFriendList};
+use bevy_ecs::prelude::*;
+use bevy_time::{Timer, TimerMode};
+use derive_more::{Deref, DerefMut};
+use silkroad_protocol::community::{FriendListEntry, FriendListGroup, FriendListInfo};
+use std::time::Duration;
+use tokio::sync::oneshot::Receiver;
+use tracing::warn;
+
+/// The group every friend belongs to if they haven't been assigned to a group explicitly.
+pub(crate) const UNASSIGNED_GROUP: u16 = 0;
+
+pub(crate) struct Friend {
+    pub(crate) character_id: u32,
+    pub(crate) name: String,
+    pub(crate) ref_id: u32,
+    pub(crate) group: u16,
+}
+
+impl Friend {
+    pub(crate) fn as_entry(&self, online: bool) -> FriendListEntry {
+        FriendListEntry::new(self.character_id, self.name.clone(), self.ref_id, self.group, !online)
+    }
+}
+
+/// A group of friends. The id is only valid for the character owning the group and gets assigned when the friend
+/// list is loaded, as the client can only handle small ids. The id of the group in the database is kept separately.
+pub(crate) struct FriendGroup {
+    pub(crate) id: u16,
+    pub(crate) db_id: i32,
+    pub(crate) name: String,
+}
+
+#[derive(Component, Default)]
+pub(crate) struct FriendList {
+    groups: Vec<FriendGroup>,
+    friends: Vec<Friend>,
+}
+
+impl FriendList {
+    pub(crate) fn friends(&self) -> &[Friend] {
+        &self.friends
+    }
+
+    pub(crate) fn find(&self, character_id: u32) -> Option<&Friend> {
+        self.friends.iter().find(|friend| friend.character_id == character_id)
+    }
+
+    pub(crate) fn add_friend(&mut self, friend: Friend) {
+        if self.find(friend.character_id).is_none() {
+            self.friends.push(friend);
+        }
+    }
+
+    pub(crate) fn remove_friend(&mut self, character_id: u32) -> Option<Friend> {
+        let index = self
+            .friends
+            .iter()
+            .position(|friend| friend.character_id == character_id)?;
+        Some(self.friends.remove(index))
+    }
+
+    pub(crate) fn has_group(&self, id: u16) -> bool {
+        id == UNASSIGNED_GROUP || self.groups.iter().any(|group| group.id == id)
+    }
+
+    /// Provides the id the next added group would get, or `None` if all ids are already in use.
+    pub(crate) fn next_group_id(&self) -> Option<u16> {
+        (UNASSIGNED_GROUP + 1..=u16::MAX).find(|id| !self.has_group(*id))
+    }
+
+    /// Adds the group stored with the given id in the database, returning the id it got assigned in this list.
+    pub(crate) fn add_group(&mut self, db_id: i32, name: String) -> Option<&FriendGroup> {
+        let id = self.next_group_id()?;
+        self.groups.push(FriendGroup { id, db_id, name });
+        self.groups.last()
+    }
+
+    /// Provides the database id of the group with the given id, or `None` for the unassigned group.
+    pub(crate) fn db_group_id(&self, id: u16) -> Option<i32> {
+        self.groups.iter().find(|group| group.id == id).map(|group| group.db_id)
+    }
+
+    /// Removes the group with the given id and moves all friends of that group back into the unassigned group.
+    pub(crate) fn remove_group(&mut self, id: u16) -> Option<FriendGroup> {
+        if id == UNASSIGNED_GROUP {
+            return None;
+        }
+
+        let index = self.groups.iter().position(|group| group.id == id)?;
+        let group = self.groups.remove(index);
+        self.friends
+            .iter_mut()
+            .filter(|friend| friend.group == id)
+            .for_each(|friend| friend.group = UNASSIGNED_GROUP);
+        Some(group)
+    }
+
+    pub(crate) fn move_friend(&mut self, character_id: u32, group: u16) -> bool {
+        if !self.has_group(group) {
+            return false;
+        }
+
+        match self
+            .friends
+            .iter_mut()
+            .find(|friend| friend.character_id == character_id)
+        {
+            Some(friend) => {
+                friend.group = group;
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub(crate) fn as_info<F: Fn(&Friend) -> bool>(&self, is_online: F) -> FriendListInfo {
+        let mut groups = vec![FriendListGroup::not_assigned()];
+        groups.extend(
+            self.groups
+                .iter()
+                .map(|group| FriendListGroup::new(group.id, group.name.clone())),
+        );
+        let friends = self
+            .friends
+            .iter()
+            .map(|friend| friend.as_entry(is_online(friend)))
+            .collect();
+        FriendListInfo::new(groups, friends)
+    }
+}
+
+impl From<DbFriendList> for FriendList {
+    fn from(value: DbFriendList) -> Self {
+        let mut list = FriendList::default();
+        for group in value.groups {
+            if list.add_group(group.id, group.name).is_none() {
+                warn!(group = group.id, "Too many friend groups, skipping group");
+            }
+        }
+        for friend in value.friends {
+            // Friends of a group we could not add end up unassigned.
+            let group = friend
+                .group_id
+                .and_then(|db_id| list.groups.iter().find(|group| group.db_id == db_id))
+                .map(|group| group.id)
+                .unwrap_or(UNASSIGNED_GROUP);
+            list.add_friend(Friend {
+                character_id: friend.friend_id as u32,
+                name: friend.charname,
+                ref_id: friend.character_type as u32,
+                group,
+            });
+        }
+        list
+    }
+}
+
+#[derive(Component, Deref, DerefMut)]
+#[component(storage = "SparseSet")]
+pub(crate) struct FriendListLoading(pub(crate) Receiver<Result<DbFriendList, sqlx::Error>>);
+
+#[derive(Component, Deref, DerefMut)]
+#[component(storage = "SparseSet")]
+pub(crate) struct FriendGroupCreation(pub(crate) Receiver<Result<DbFriendGroup, sqlx::Error>>);
+
+/// A friend request of another player that still needs to be answered by this player.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct PendingFriendRequest {
+    pub(crate) requester: Entity,
+    pub(crate) friend: Friend,
+    pub(crate) timeout: Timer,
+}
+
+impl PendingFriendRequest {
+    pub(crate) fn new(requester: Entity, friend: Friend, timeout: Duration) -> Self {
+        PendingFriendRequest {
+            requester,
+            friend,
+            timeout: Timer::new(timeout, TimerMode::Once),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::friends::db::DbFriend;
+
+    fn friend(character_id: u32, group: u16) -> Friend {
+        Friend {
+            character_id,
+            name: format!("Friend{}", character_id),
+            ref_id: 1907,
+            group,
+        }
+    }
+
+    #[test]
+    pub fn test_remove_group_unassigns_friends() {
+        let mut list = FriendList::default();
+        let group = list.add_group(70_000, "Guild".to_string()).unwrap().id;
+        list.add_friend(friend(1, group));
+        list.add_friend(friend(2, UNASSIGNED_GROUP));
+
+        assert!(list.remove_group(UNASSIGNED_GROUP).is_none());
+        assert_eq!(list.remove_group(group).map(|group| group.db_id), Some(70_000));
+        assert!(list.friends().iter().all(|friend| friend.group == UNASSIGNED_GROUP));
+        assert!(!list.move_friend(1, group));
+    }
+
+    #[test]
+    pub fn test_group_ids_are_independent_of_database() {
+        let list = FriendList::from(DbFriendList {
+            groups: vec![
+                DbFriendGroup {
+                    id: 65_537,
+                    name: "Guild".to_string(),
+                },
+                DbFriendGroup {
+                    id: 1,
+                    name: "Party".to_string(),
+                },
+            ],
+            friends: vec![DbFriend {
+                friend_id: 3,
+                group_id: Some(65_537),
+                charname: "Friend3".to_string(),
+                character_type: 1907,
+            }],
+        });
+
+        assert_eq!(list.friends()[0].group, 1);
+        assert_eq!(list.db_group_id(1), Some(65_537));
+        assert_eq!(list.db_group_id(2), Some(1));
+        assert_eq!(list.db_group_id(UNASSIGNED_GROUP), None);
+        assert_eq!(list.next_group_id(), Some(3));
+    }
+
+    #[test]
+    pub fn test_no_duplicate_friends() {
+        let mut list = FriendList::default();
+        list.add_friend(friend(1, UNASSIGNED_GROUP));
+        list.add_friend(friend(1, UNASSIGNED_GROUP));
+        assert_eq!(list.friends().len(), 1);
+        assert!(list.remove_friend(1).is_some());
+        assert!(list.find(1).is_none());
+    }
+}
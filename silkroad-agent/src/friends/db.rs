@@ -0,0 +1,157 @@
+use sqlx::{Error, PgPool};
+use std::borrow::Borrow;
+
+#[derive(sqlx::FromRow, Clone)]
+pub(crate) struct DbFriendGroup {
+    pub(crate) id: i32,
+    pub(crate) name: String,
+}
+
+#[derive(sqlx::FromRow, Clone)]
+pub(crate) struct DbFriend {
+    pub(crate) friend_id: i32,
+    pub(crate) group_id: Option<i32>,
+    pub(crate) charname: String,
+    pub(crate) character_type: i32,
+}
+
+pub(crate) struct DbFriendList {
+    pub(crate) groups: Vec<DbFriendGroup>,
+    pub(crate) friends: Vec<DbFriend>,
+}
+
+pub(crate) async fn load_friend_list<T: Borrow<PgPool>>(character_id: u32, pool: T) -> Result<DbFriendList, Error> {
+    let groups = sqlx::query_as!(
+        DbFriendGroup,
+        "SELECT id, name FROM friends_groups WHERE character_id = $1 ORDER BY id ASC",
+        character_id as i32
+    )
+    .fetch_all(pool.borrow())
+    .await?;
+
+    let friends = sqlx::query_as!(
+        DbFriend,
+        "SELECT friends.friend_id, friends.group_id, characters.charname, characters.character_type FROM friends JOIN characters ON characters.id = friends.friend_id WHERE friends.character_id = $1 AND friends.status = 'ACCEPTED' AND characters.deletion_end IS NULL",
+        character_id as i32
+    )
+    .fetch_all(pool.borrow())
+    .await?;
+
+    Ok(DbFriendList { groups, friends })
+}
+
+pub(crate) async fn insert_friend_request<T: Borrow<PgPool>>(
+    character_id: u32,
+    friend_id: u32,
+    pool: T,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "INSERT INTO friends(character_id, friend_id, status) VALUES($1, $2, 'REQUESTED') ON CONFLICT (character_id, friend_id) DO UPDATE SET status = 'REQUESTED'",
+        character_id as i32,
+        friend_id as i32
+    )
+    .execute(pool.borrow())
+    .await?;
+    Ok(())
+}
+
+/// Marks the request of `character_id` as accepted and creates the entry in the other direction, as
+/// friendships always go both ways.
+pub(crate) async fn accept_friend_request<T: Borrow<PgPool>>(
+    character_id: u32,
+    friend_id: u32,
+    pool: T,
+) -> Result<(), Error> {
+    let mut transaction = pool.borrow().begin().await?;
+    for (from, to) in [(character_id, friend_id), (friend_id, character_id)] {
+        sqlx::query!(
+            "INSERT INTO friends(character_id, friend_id, status) VALUES($1, $2, 'ACCEPTED') ON CONFLICT (character_id, friend_id) DO UPDATE SET status = 'ACCEPTED'",
+            from as i32,
+            to as i32
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+    transaction.commit().await
+}
+
+pub(crate) async fn decline_friend_request<T: Borrow<PgPool>>(
+    character_id: u32,
+    friend_id: u32,
+    pool: T,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE friends SET status = 'DECLINED' WHERE character_id = $1 AND friend_id = $2",
+        character_id as i32,
+        friend_id as i32
+    )
+    .execute(pool.borrow())
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn delete_friend<T: Borrow<PgPool>>(character_id: u32, friend_id: u32, pool: T) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM friends WHERE (character_id = $1 AND friend_id = $2) OR (character_id = $2 AND friend_id = $1)",
+        character_id as i32,
+        friend_id as i32
+    )
+    .execute(pool.borrow())
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn create_friend_group<T: Borrow<PgPool>>(
+    character_id: u32,
+    name: String,
+    pool: T,
+) -> Result<DbFriendGroup, Error> {
+    sqlx::query_as!(
+        DbFriendGroup,
+        "INSERT INTO friends_groups(character_id, name) VALUES($1, $2) RETURNING id, name",
+        character_id as i32,
+        name
+    )
+    .fetch_one(pool.borrow())
+    .await
+}
+
+pub(crate) async fn delete_friend_group<T: Borrow<PgPool>>(
+    character_id: u32,
+    group_id: i32,
+    pool: T,
+) -> Result<(), Error> {
+    let mut transaction = pool.borrow().begin().await?;
+    sqlx::query!(
+        "UPDATE friends SET group_id = NULL WHERE character_id = $1 AND group_id = $2",
+        character_id as i32,
+        group_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM friends_groups WHERE character_id = $1 AND id = $2",
+        character_id as i32,
+        group_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await
+}
+
+pub(crate) async fn move_friend_to_group<T: Borrow<PgPool>>(
+    character_id: u32,
+    friend_id: u32,
+    group_id: Option<i32>,
+    pool: T,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE friends SET group_id = $3 WHERE character_id = $1 AND friend_id = $2",
+        character_id as i32,
+        friend_id as i32,
+        group_id
+    )
+    .execute(pool.borrow())
+    .await?;
+    Ok(())
+}
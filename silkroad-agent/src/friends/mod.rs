@@ -0,0 +1,30 @@
+use crate::friends::system::{
+    handle_friend_deletion, handle_friend_groups, handle_friend_request_answers, handle_friend_requests, load_friends,
+    notify_friends_offline, receive_created_groups, receive_friend_list,
+};
+use bevy_app::{App, Plugin, PostUpdate, PreUpdate, Update};
+pub(crate) use component::*;
+
+mod component;
+mod db;
+mod system;
+
+pub(crate) struct FriendsPlugin;
+
+impl Plugin for FriendsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, notify_friends_offline)
+            .add_systems(
+                Update,
+                (
+                    receive_friend_list,
+                    handle_friend_requests,
+                    handle_friend_request_answers,
+                    handle_friend_deletion,
+                    handle_friend_groups,
+                    receive_created_groups,
+                ),
+            )
+            .add_systems(PostUpdate, load_friends);
+    }
+}
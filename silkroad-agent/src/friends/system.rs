@@ -0,0 +1,387 @@
+use crate::comp::net::Client;
+use crate::comp::player::Player;
+use crate::comp::GameEntity;
+use crate::event::{ClientDisconnectedEvent, LoadingFinishedEvent};
+use crate::ext::DbPool;
+use crate::friends::db::{
+    accept_friend_request, create_friend_group, decline_friend_request, delete_friend, delete_friend_group,
+    insert_friend_request, load_friend_list, move_friend_to_group,
+};
+use crate::friends::{
+    Friend, FriendGroupCreation, FriendList, FriendListLoading, PendingFriendRequest, UNASSIGNED_GROUP,
+};
+use crate::input::PlayerInput;
+use crate::tasks::TaskCreator;
+use crate::world::EntityLookup;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::Has;
+use bevy_time::Time;
+use silkroad_protocol::community::{
+    AddFriendResponse, CreateFriendGroupResponse, DeleteFriendGroupResponse, DeleteFriendResponse, FriendErrorCode,
+    FriendListGroup, FriendRequest, FriendStatusUpdate, MoveFriendToGroupResponse,
+};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::oneshot::error::TryRecvError;
+use tracing::{error, warn};
+
+const FRIEND_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn persist<F>(task_creator: &TaskCreator, character_id: u32, task: F)
+where
+    F: Future<Output = Result<(), sqlx::Error>> + Send + 'static,
+{
+    task_creator.spawn(async move {
+        if let Err(e) = task.await {
+            error!(error = %e, character_id = character_id, "Could not update friend list");
+        }
+    });
+}
+
+fn friend_of(player: &Player, game_entity: &GameEntity) -> Friend {
+    Friend {
+        character_id: player.character.id,
+        name: player.character.name.clone(),
+        ref_id: game_entity.ref_id,
+        group: UNASSIGNED_GROUP,
+    }
+}
+
+pub(crate) fn load_friends(
+    mut events: EventReader<LoadingFinishedEvent>,
+    query: Query<&Player>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+    mut cmd: Commands,
+) {
+    for event in events.read() {
+        let Ok(player) = query.get(event.0) else {
+            continue;
+        };
+
+        let task = task_creator.create_task(load_friend_list(player.character.id, PgPool::clone(&pool)));
+        cmd.entity(event.0).insert(FriendListLoading(task));
+    }
+}
+
+pub(crate) fn receive_friend_list(
+    mut query: Query<(Entity, &Client, &Player, &mut FriendListLoading)>,
+    clients: Query<&Client>,
+    lookup: Res<EntityLookup>,
+    mut cmd: Commands,
+) {
+    for (entity, client, player, mut loading) in query.iter_mut() {
+        let friend_list = match loading.try_recv() {
+            Ok(Ok(friends)) => FriendList::from(friends),
+            Ok(Err(e)) => {
+                error!(error = %e, character_id = player.character.id, "Could not load friend list");
+                FriendList::default()
+            },
+            Err(TryRecvError::Empty) => continue,
+            Err(e) => {
+                warn!(
+                    character_id = player.character.id,
+                    "Error when loading friend list. {:?}", e
+                );
+                FriendList::default()
+            },
+        };
+
+        client.send(friend_list.as_info(|friend| lookup.get_entity_for_name(&friend.name).is_some()));
+
+        for friend in friend_list.friends() {
+            if let Some(friend_client) = lookup
+                .get_entity_for_name(&friend.name)
+                .and_then(|friend| clients.get(friend).ok())
+            {
+                friend_client.send(FriendStatusUpdate::Online {
+                    friend_character_id: player.character.id,
+                });
+            }
+        }
+
+        cmd.entity(entity).remove::<FriendListLoading>().insert(friend_list);
+    }
+}
+
+pub(crate) fn notify_friends_offline(
+    mut events: EventReader<ClientDisconnectedEvent>,
+    query: Query<(&Player, &FriendList)>,
+    clients: Query<&Client>,
+    lookup: Res<EntityLookup>,
+) {
+    for event in events.read() {
+        let Ok((player, friend_list)) = query.get(event.0) else {
+            continue;
+        };
+
+        for friend in friend_list.friends() {
+            if let Some(friend_client) = lookup
+                .get_entity_for_name(&friend.name)
+                .and_then(|friend| clients.get(friend).ok())
+            {
+                friend_client.send(FriendStatusUpdate::Offline {
+                    friend_character_id: player.character.id,
+                });
+            }
+        }
+    }
+}
+
+pub(crate) fn handle_friend_requests(
+    query: Query<(Entity, &Client, &GameEntity, &Player, &PlayerInput, &FriendList)>,
+    targets: Query<(&Client, &Player, Has<PendingFriendRequest>), With<FriendList>>,
+    lookup: Res<EntityLookup>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+    mut cmd: Commands,
+) {
+    let mut requested = HashSet::new();
+    for (entity, client, game_entity, player, input, friend_list) in query.iter() {
+        let Some(ref request) = input.friend_add else {
+            continue;
+        };
+
+        let target = lookup
+            .get_entity_for_name(&request.name)
+            .filter(|target| *target != entity)
+            .and_then(|target| targets.get(target).ok().map(|data| (target, data)));
+        let Some((target, (target_client, target_player, has_pending))) = target else {
+            client.send(AddFriendResponse::Error(FriendErrorCode::InvalidTarget));
+            continue;
+        };
+
+        if friend_list.find(target_player.character.id).is_some() {
+            client.send(AddFriendResponse::Error(FriendErrorCode::AlreadyFriends));
+            continue;
+        }
+
+        if has_pending || requested.contains(&target) {
+            client.send(AddFriendResponse::Error(FriendErrorCode::TargetBusy));
+            continue;
+        }
+
+        requested.insert(target);
+        target_client.send(FriendRequest::new(game_entity.unique_id, player.character.name.clone()));
+        cmd.entity(target).insert(PendingFriendRequest::new(
+            entity,
+            friend_of(player, game_entity),
+            FRIEND_REQUEST_TIMEOUT,
+        ));
+        persist(
+            &task_creator,
+            player.character.id,
+            insert_friend_request(player.character.id, target_player.character.id, PgPool::clone(&pool)),
+        );
+    }
+}
+
+pub(crate) fn handle_friend_request_answers(
+    mut query: Query<(Entity, &Player, &GameEntity, &PlayerInput, &mut PendingFriendRequest)>,
+    mut lists: Query<(&Client, &mut FriendList)>,
+    lookup: Res<EntityLookup>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+    time: Res<Time>,
+    mut cmd: Commands,
+) {
+    let delta = time.delta();
+    for (entity, player, game_entity, input, mut request) in query.iter_mut() {
+        let accepted = match input.friend_answer {
+            Some(ref answer) => answer.accepted,
+            None if request.timeout.tick(delta).finished() => false,
+            None => continue,
+        };
+
+        cmd.entity(entity).remove::<PendingFriendRequest>();
+        let requester_id = request.friend.character_id;
+        if !accepted {
+            if let Ok((requester_client, _)) = lists.get(request.requester) {
+                requester_client.send(AddFriendResponse::Error(FriendErrorCode::Declined));
+            }
+            persist(
+                &task_creator,
+                requester_id,
+                decline_friend_request(requester_id, player.character.id, PgPool::clone(&pool)),
+            );
+            continue;
+        }
+
+        let Ok([(requester_client, mut requester_list), (client, mut friend_list)]) =
+            lists.get_many_mut([request.requester, entity])
+        else {
+            continue;
+        };
+
+        let new_friend = friend_of(player, game_entity);
+        let requester = Friend {
+            character_id: requester_id,
+            name: request.friend.name.clone(),
+            ref_id: request.friend.ref_id,
+            group: UNASSIGNED_GROUP,
+        };
+
+        requester_client.send(AddFriendResponse::Success(new_friend.as_entry(true)));
+        client.send(AddFriendResponse::Success(
+            requester.as_entry(lookup.get_entity_for_name(&requester.name).is_some()),
+        ));
+        requester_list.add_friend(new_friend);
+        friend_list.add_friend(requester);
+
+        persist(
+            &task_creator,
+            requester_id,
+            accept_friend_request(requester_id, player.character.id, PgPool::clone(&pool)),
+        );
+    }
+}
+
+pub(crate) fn handle_friend_deletion(
+    query: Query<(Entity, &Player, &PlayerInput)>,
+    mut lists: Query<(&Client, &mut FriendList)>,
+    lookup: Res<EntityLookup>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+) {
+    for (entity, player, input) in query.iter() {
+        let Some(ref delete) = input.friend_delete else {
+            continue;
+        };
+
+        let removed = {
+            let Ok((client, mut friend_list)) = lists.get_mut(entity) else {
+                continue;
+            };
+
+            let Some(removed) = friend_list.remove_friend(delete.friend_character_id) else {
+                client.send(DeleteFriendResponse::Error(FriendErrorCode::InvalidTarget));
+                continue;
+            };
+
+            client.send(DeleteFriendResponse::Success {
+                friend_character_id: removed.character_id,
+            });
+            removed
+        };
+
+        if let Some((other_client, mut other_list)) = lookup
+            .get_entity_for_name(&removed.name)
+            .and_then(|other| lists.get_mut(other).ok())
+        {
+            if other_list.remove_friend(player.character.id).is_some() {
+                other_client.send(DeleteFriendResponse::Success {
+                    friend_character_id: player.character.id,
+                });
+            }
+        }
+
+        persist(
+            &task_creator,
+            player.character.id,
+            delete_friend(player.character.id, removed.character_id, PgPool::clone(&pool)),
+        );
+    }
+}
+
+pub(crate) fn handle_friend_groups(
+    mut query: Query<(
+        Entity,
+        &Client,
+        &Player,
+        &PlayerInput,
+        &mut FriendList,
+        Has<FriendGroupCreation>,
+    )>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+    mut cmd: Commands,
+) {
+    for (entity, client, player, input, mut friend_list, is_creating) in query.iter_mut() {
+        let character_id = player.character.id;
+
+        if let Some(ref create) = input.friend_group_create {
+            if is_creating || create.name.is_empty() || friend_list.next_group_id().is_none() {
+                client.send(CreateFriendGroupResponse::Error(FriendErrorCode::InvalidGroup));
+            } else {
+                let task = task_creator.create_task(create_friend_group(
+                    character_id,
+                    create.name.clone(),
+                    PgPool::clone(&pool),
+                ));
+                cmd.entity(entity).insert(FriendGroupCreation(task));
+            }
+        }
+
+        if let Some(ref delete) = input.friend_group_delete {
+            if let Some(group) = friend_list.remove_group(delete.id) {
+                client.send(DeleteFriendGroupResponse::Success { id: delete.id });
+                persist(
+                    &task_creator,
+                    character_id,
+                    delete_friend_group(character_id, group.db_id, PgPool::clone(&pool)),
+                );
+            } else {
+                client.send(DeleteFriendGroupResponse::Error(FriendErrorCode::InvalidGroup));
+            }
+        }
+
+        if let Some(ref move_friend) = input.friend_move {
+            if friend_list.move_friend(move_friend.friend_character_id, move_friend.group_id) {
+                client.send(MoveFriendToGroupResponse::Success {
+                    friend_character_id: move_friend.friend_character_id,
+                    group_id: move_friend.group_id,
+                });
+                let group = friend_list.db_group_id(move_friend.group_id);
+                persist(
+                    &task_creator,
+                    character_id,
+                    move_friend_to_group(
+                        character_id,
+                        move_friend.friend_character_id,
+                        group,
+                        PgPool::clone(&pool),
+                    ),
+                );
+            } else {
+                client.send(MoveFriendToGroupResponse::Error(FriendErrorCode::InvalidGroup));
+            }
+        }
+    }
+}
+
+pub(crate) fn receive_created_groups(
+    mut query: Query<(Entity, &Client, &Player, &mut FriendList, &mut FriendGroupCreation)>,
+    mut cmd: Commands,
+) {
+    for (entity, client, player, mut friend_list, mut creation) in query.iter_mut() {
+        match creation.try_recv() {
+            Ok(Ok(group)) => match friend_list.add_group(group.id, group.name) {
+                Some(group) => {
+                    client.send(CreateFriendGroupResponse::Success(FriendListGroup::new(
+                        group.id,
+                        group.name.clone(),
+                    )));
+                },
+                None => {
+                    warn!(character_id = player.character.id, "Too many friend groups");
+                    client.send(CreateFriendGroupResponse::Error(FriendErrorCode::InvalidGroup));
+                },
+            },
+            Ok(Err(e)) => {
+                // This is most likely caused by a group with the same name already existing.
+                warn!(error = %e, character_id = player.character.id, "Could not create friend group");
+                client.send(CreateFriendGroupResponse::Error(FriendErrorCode::InvalidGroup));
+            },
+            Err(TryRecvError::Empty) => continue,
+            Err(e) => {
+                warn!(
+                    character_id = player.character.id,
+                    "Error when creating friend group. {:?}", e
+                );
+            },
+        }
+        cmd.entity(entity).remove::<FriendGroupCreation>();
+    }
+}
@@ -5,17 +5,18 @@ use crate::game::mind::Mind;
 use crate::input::PlayerInput;
 use crate::world::{EntityLookup, WorldData};
 use bevy_ecs::prelude::*;
+use cgmath::Vector3;
 use log::debug;
+use silkroad_game_base::LocalPosition;
 use silkroad_protocol::combat::{ActionTarget, DoActionType, PerformAction, PerformActionError, PerformActionResponse};
-use tracing::warn;
 
 pub(crate) fn handle_action(
-    mut query: Query<(&Client, &PlayerInput, &mut Mind)>,
+    mut query: Query<(Entity, &GameEntity, &Client, &PlayerInput, &mut Mind)>,
     lookup: Res<EntityLookup>,
     target_query: Query<&GameEntity>,
     pickup_query: Query<&GameEntity, With<Drop>>,
 ) {
-    for (client, input, mut mind) in query.iter_mut() {
+    for (entity, game_entity, client, input, mut mind) in query.iter_mut() {
         let Some(ref action) = input.action else {
             continue;
         };
@@ -54,29 +55,40 @@ pub(crate) fn handle_action(
                     },
                     _ => continue,
                 },
-                DoActionType::UseSkill { ref_id, target } => match target {
-                    ActionTarget::Entity(unique_id) => {
-                        let Some(target) = lookup.get_entity_for_id(*unique_id) else {
-                            client.send(PerformActionResponse::Stop(PerformActionError::InvalidTarget));
-                            continue;
-                        };
+                DoActionType::UseSkill { ref_id, target } => {
+                    let Some(skill) = WorldData::skills().find_id(*ref_id) else {
+                        client.send(PerformActionResponse::Stop(PerformActionError::NotLearned));
+                        continue;
+                    };
 
-                        let Ok(found_target) = target_query.get(target) else {
-                            client.send(PerformActionResponse::Stop(PerformActionError::InvalidTarget));
-                            continue;
-                        };
+                    let target = match target {
+                        // Skills without a target, like most buffs, are cast on ourselves.
+                        ActionTarget::None | ActionTarget::Entity(0) => EntityReference(entity, *game_entity),
+                        ActionTarget::Entity(unique_id) => {
+                            let Some(target) = lookup.get_entity_for_id(*unique_id) else {
+                                client.send(PerformActionResponse::Stop(PerformActionError::InvalidTarget));
+                                continue;
+                            };
 
-                        let Some(skill) = WorldData::skills().find_id(*ref_id) else {
-                            client.send(PerformActionResponse::Stop(PerformActionError::NotLearned));
+                            let Ok(found_target) = target_query.get(target) else {
+                                client.send(PerformActionResponse::Stop(PerformActionError::InvalidTarget));
+                                continue;
+                            };
+                            EntityReference(target, *found_target)
+                        },
+                        ActionTarget::Area(location) => {
+                            let position = LocalPosition(
+                                location.region.into(),
+                                Vector3::new(location.pos_x, location.pos_y, location.pos_z),
+                            );
+                            mind.attack_at(position.to_global().to_location(), skill);
                             continue;
-                        };
+                        },
+                    };
 
-                        mind.attack_with(EntityReference(target, *found_target), skill)
-                    },
-                    _ => {
-                        warn!("Tried to use a skill on unsupported target.")
-                    },
+                    mind.attack_with(target, skill)
                 },
+                // Cancelling buffs is handled by the buff plugin.
                 DoActionType::CancelBuff { .. } => {},
             },
             PerformAction::Stop => mind.cancel(),
@@ -2,14 +2,15 @@ use crate::agent::states;
 use crate::agent::states::{Action, ActionDescription, MovementGoal, Moving, StateTransitionQueue};
 use crate::comp::inventory::PlayerInventory;
 use crate::comp::pos::Position;
-use crate::comp::{EntityReference, GameEntity};
+use crate::comp::GameEntity;
 use crate::ext::Navmesh;
+use crate::pvp::{AttackPermission, PvpStanding};
 use crate::world::WorldData;
 use cgmath::num_traits::Pow;
-use derive_more::Constructor;
-use silkroad_data::skilldata::RefSkillData;
+use cgmath::MetricSpace;
+use silkroad_data::skilldata::{RefSkillData, SkillParam};
 use silkroad_definitions::inventory::EquipmentSlot;
-use silkroad_game_base::{AttackSkill, AttackSkillError, Item};
+use silkroad_game_base::{AttackSkill, AttackSkillError, GlobalLocation, Item};
 
 pub struct Attack;
 
@@ -29,36 +30,81 @@ impl Attack {
     }
 }
 
-#[derive(Constructor)]
 pub(crate) struct AttackProcess<'a> {
     state: &'a mut StateTransitionQueue,
     position: &'a Position,
     skill: &'static RefSkillData,
     weapon: Option<&'a Item>,
-    target: &'a EntityReference,
-    target_pos: &'a Position,
+    target: states::ActionTarget,
+    target_location: GlobalLocation,
     navmesh: &'a Navmesh,
+    /// The standing of the attacker and the target, if a player is about to attack another player.
+    pvp: Option<(PvpStanding, PvpStanding)>,
 }
 
-impl AttackProcess<'_> {
+impl<'a> AttackProcess<'a> {
+    pub(crate) fn new(
+        state: &'a mut StateTransitionQueue,
+        position: &'a Position,
+        skill: &'static RefSkillData,
+        weapon: Option<&'a Item>,
+        target: states::ActionTarget,
+        target_location: GlobalLocation,
+        navmesh: &'a Navmesh,
+    ) -> Self {
+        AttackProcess {
+            state,
+            position,
+            skill,
+            weapon,
+            target,
+            target_location,
+            navmesh,
+            pvp: None,
+        }
+    }
+
+    /// Subjects the attack to the PvP rules, given the standing of the attacker and the target.
+    pub(crate) fn between_players(mut self, pvp: Option<(PvpStanding, PvpStanding)>) -> Self {
+        self.pvp = pvp;
+        self
+    }
+
     pub(crate) fn try_attack(&mut self) -> Result<(), AttackSkillError> {
-        let description = ActionDescription(self.skill, states::ActionTarget::Entity(self.target.0));
+        if let Some((attacker, target)) = self.pvp {
+            let is_attack = self
+                .skill
+                .params
+                .iter()
+                .any(|param| matches!(param, SkillParam::Attack { .. }));
+            if is_attack && attacker.permission(&target) == AttackPermission::Denied {
+                return Err(AttackSkillError::NotAllowed);
+            }
+        }
+
+        let description = ActionDescription(self.skill, self.target);
         let range = AttackSkill::get_range_for_attack(self.skill, self.weapon.map(|item| item.reference));
         let range_squared = range.pow(2);
-        if self.position.distance_to(self.target_pos) <= range_squared {
+        let distance = self.position.location().0.distance2(self.target_location.0);
+        if distance <= range_squared {
             self.state.request_transition(Action::from(description));
         } else {
             let new_target_position = self
                 .position
                 .position()
                 .to_location()
-                .point_in_line_with_range(self.target_pos.location(), range);
+                .point_in_line_with_range(self.target_location, range);
             let new_height = self
                 .navmesh
                 .height_for(new_target_position)
-                .unwrap_or(self.target_pos.position().y);
-            self.state
-                .request_transition(Moving(MovementGoal::Location(new_target_position.with_y(new_height))));
+                .unwrap_or(self.position.position().y);
+            let goal = MovementGoal::walk_to(
+                self.navmesh,
+                self.position.location(),
+                new_target_position.with_y(new_height),
+            )
+            .ok_or(AttackSkillError::Unreachable)?;
+            self.state.request_transition(Moving(goal));
         }
 
         Ok(())
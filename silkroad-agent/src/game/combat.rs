@@ -0,0 +1,531 @@
+use crate::buff::BuffEffects;
+use cgmath::{InnerSpace, MetricSpace, Vector2};
+use rand::Rng;
+use silkroad_data::characterdata::RefCharacterData;
+use silkroad_data::itemdata::RefItemData;
+use silkroad_data::skilldata::SkillParam;
+use silkroad_game_base::{GlobalLocation, Item, Stats};
+
+/// The fraction of the attack that always goes through, no matter how high the defense of the target is.
+const MIN_DAMAGE_RATIO: f32 = 0.1;
+const CRITICAL_MULTIPLIER: f32 = 2.0;
+const MAX_BLOCK_CHANCE: f32 = 50.0;
+const MAX_PARRY_CHANCE: f32 = 50.0;
+const AREA_ORIGIN_CASTER: u8 = 1;
+const AREA_TYPE_LINE: u8 = 1;
+const LINE_WIDTH: f32 = 20.0;
+const UNARMED_REINFORCE_LOWER: f32 = 0.3;
+const UNARMED_REINFORCE_UPPER: f32 = 0.5;
+
+/// The outcome of a single attack against a target.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Hit {
+    Damage { amount: u32, critical: bool },
+    Blocked,
+}
+
+impl Hit {
+    pub(crate) fn amount(&self) -> u32 {
+        match self {
+            Hit::Damage { amount, .. } => *amount,
+            Hit::Blocked => 0,
+        }
+    }
+}
+
+/// The raw attack power of an entity, before any skill or defense has been applied. Monsters don't have any attack
+/// power of their own, as the character data doesn't contain any. The damage they deal is defined by the attack
+/// power of their skills alone.
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub(crate) struct AttackPower {
+    pub(crate) physical_min: f32,
+    pub(crate) physical_max: f32,
+    pub(crate) magical_min: f32,
+    pub(crate) magical_max: f32,
+    /// Chance to land a critical hit, in percent.
+    pub(crate) critical: f32,
+    /// Counters the parry rate of the target, in percent.
+    pub(crate) hit_rate: f32,
+}
+
+impl AttackPower {
+    pub(crate) fn for_player(stats: Stats, weapon: Option<&RefItemData>) -> Self {
+        let strength = stats.strength() as f32;
+        let intelligence = stats.intelligence() as f32;
+        match weapon {
+            Some(weapon) => AttackPower {
+                physical_min: weapon.physical_attack_power_lower + strength * weapon.physical_reinforce_lower,
+                physical_max: weapon.physical_attack_power_upper + strength * weapon.physical_reinforce_upper,
+                magical_min: weapon.magical_attack_power_lower + intelligence * weapon.magical_reinforce_lower,
+                magical_max: weapon.magical_attack_power_upper + intelligence * weapon.magical_reinforce_upper,
+                critical: weapon.critical,
+                hit_rate: 0.0,
+            },
+            None => AttackPower {
+                physical_min: strength * UNARMED_REINFORCE_LOWER,
+                physical_max: strength * UNARMED_REINFORCE_UPPER,
+                magical_min: intelligence * UNARMED_REINFORCE_LOWER,
+                magical_max: intelligence * UNARMED_REINFORCE_UPPER,
+                critical: 0.0,
+                hit_rate: 0.0,
+            },
+        }
+    }
+
+    pub(crate) fn with_buffs(self, effects: &BuffEffects) -> Self {
+        let physical = 1.0 + effects.physical_attack / 100.0;
+        let magical = 1.0 + effects.magical_attack / 100.0;
+        AttackPower {
+            physical_min: self.physical_min * physical,
+            physical_max: self.physical_max * physical,
+            magical_min: self.magical_min * magical,
+            magical_max: self.magical_max * magical,
+            critical: self.critical + effects.critical,
+            hit_rate: self.hit_rate + effects.hit_rate,
+        }
+    }
+}
+
+/// The defensive values of an entity that reduce or avoid incoming damage.
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub(crate) struct Defense {
+    pub(crate) physical: f32,
+    pub(crate) magical: f32,
+    /// Chance to block an incoming attack, in percent.
+    pub(crate) block_ratio: f32,
+    /// Reduces the chance of the attacker landing a critical hit, in percent.
+    pub(crate) critical_resist: f32,
+    /// Chance to parry an incoming attack, in percent. The hit rate of the attacker is subtracted from it.
+    pub(crate) parry_rate: f32,
+}
+
+impl Defense {
+    pub(crate) fn for_player<'a>(stats: Stats, equipment: impl Iterator<Item = &'a Item>) -> Self {
+        let strength = stats.strength() as f32;
+        let intelligence = stats.intelligence() as f32;
+        equipment.fold(Defense::default(), |defense, item| {
+            let reference = item.reference;
+            Defense {
+                physical: defense.physical
+                    + reference.physical_defense
+                    + strength * reference.physical_defense_reinforce,
+                magical: defense.magical
+                    + reference.magical_defense
+                    + intelligence * reference.magical_defense_reinforce,
+                block_ratio: defense.block_ratio + reference.block_ratio,
+                ..defense
+            }
+        })
+    }
+
+    pub(crate) fn for_monster(reference: &RefCharacterData) -> Self {
+        Defense {
+            physical: reference.physical_defense,
+            magical: reference.magical_defense,
+            block_ratio: reference.block_ratio as f32,
+            critical_resist: reference.critical_resist as f32,
+            parry_rate: 0.0,
+        }
+    }
+
+    pub(crate) fn with_buffs(self, effects: &BuffEffects) -> Self {
+        Defense {
+            physical: self.physical * (1.0 + effects.physical_defense / 100.0).max(0.0),
+            magical: self.magical * (1.0 + effects.magical_defense / 100.0).max(0.0),
+            block_ratio: self.block_ratio + effects.block_ratio,
+            parry_rate: self.parry_rate + effects.parry_rate,
+            ..self
+        }
+    }
+}
+
+/// The attack related values of a skill.
+///
+/// `physical_ratio` and `magical_ratio` define how much (in percent) of the physical and magical attack power goes
+/// into the attack, while the skill's own attack power between `min` and `max` is added on top of both. Skills
+/// without an attack are treated as a plain physical attack.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct SkillAttack {
+    pub(crate) physical_ratio: u32,
+    pub(crate) magical_ratio: u32,
+    pub(crate) min: u32,
+    pub(crate) max: u32,
+    /// Additional chance to land a critical hit, in percent.
+    pub(crate) critical: f32,
+}
+
+impl Default for SkillAttack {
+    fn default() -> Self {
+        SkillAttack {
+            physical_ratio: 100,
+            magical_ratio: 0,
+            min: 0,
+            max: 0,
+            critical: 0.0,
+        }
+    }
+}
+
+impl SkillAttack {
+    pub(crate) fn from_params(params: &[SkillParam]) -> Self {
+        params.iter().fold(SkillAttack::default(), |attack, param| match param {
+            SkillParam::Attack {
+                phys, min, max, mag, ..
+            } if *phys > 0 || *mag > 0 => SkillAttack {
+                physical_ratio: *phys,
+                magical_ratio: *mag,
+                min: *min,
+                max: *max,
+                ..attack
+            },
+            SkillParam::Attack { min, max, .. } => SkillAttack {
+                min: *min,
+                max: *max,
+                ..attack
+            },
+            SkillParam::IncreaseCrit { amount, .. } => SkillAttack {
+                critical: attack.critical + *amount as f32,
+                ..attack
+            },
+            _ => attack,
+        })
+    }
+}
+
+/// The entity an area of effect is centered on.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum AreaCenter {
+    Caster,
+    Target,
+}
+
+impl From<u8> for AreaCenter {
+    fn from(origin: u8) -> Self {
+        match origin {
+            AREA_ORIGIN_CASTER => AreaCenter::Caster,
+            _ => AreaCenter::Target,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum AreaShape {
+    Circle,
+    /// A strip of `LINE_WIDTH` that reaches from the center along the direction of the attack.
+    Line,
+}
+
+impl From<u8> for AreaShape {
+    fn from(area_type: u8) -> Self {
+        match area_type {
+            AREA_TYPE_LINE => AreaShape::Line,
+            _ => AreaShape::Circle,
+        }
+    }
+}
+
+/// The area around a center in which a skill hits all valid targets.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct AreaOfEffect {
+    pub(crate) center: AreaCenter,
+    pub(crate) shape: AreaShape,
+    /// The radius of a circle or the length of a line.
+    pub(crate) size: f32,
+}
+
+impl AreaOfEffect {
+    pub(crate) fn from_params(params: &[SkillParam]) -> Option<Self> {
+        params.iter().find_map(|param| match param {
+            SkillParam::AOE {
+                origin,
+                area_type,
+                area_size,
+                ..
+            } if *area_size > 0 => Some(AreaOfEffect {
+                center: AreaCenter::from(*origin),
+                shape: AreaShape::from(*area_type),
+                size: *area_size as f32,
+            }),
+            _ => None,
+        })
+    }
+
+    /// Picks the center of the area, depending on whether it surrounds the caster or the target. Without a target,
+    /// the area is always centered on the caster.
+    pub(crate) fn center_of(&self, caster: GlobalLocation, target: Option<GlobalLocation>) -> GlobalLocation {
+        match (self.center, target) {
+            (AreaCenter::Target, Some(target)) => target,
+            _ => caster,
+        }
+    }
+
+    /// Checks if the location lies within the area around the center. `direction` is the normalized direction the
+    /// attack is heading in, which only matters for lines.
+    pub(crate) fn contains(&self, center: GlobalLocation, direction: Vector2<f32>, location: GlobalLocation) -> bool {
+        match self.shape {
+            AreaShape::Circle => center.0.distance2(location.0) <= self.size.powi(2),
+            AreaShape::Line => {
+                let offset = location.0 - center.0;
+                let along = offset.dot(direction);
+                let across = offset.perp_dot(direction).abs();
+                (0.0..=self.size).contains(&along) && across <= LINE_WIDTH / 2.0
+            },
+        }
+    }
+}
+
+fn roll<R: Rng>(rng: &mut R, min: f32, max: f32) -> f32 {
+    if max > min {
+        rng.gen_range(min..=max)
+    } else {
+        min
+    }
+}
+
+fn reduce_by_defense(power: f32, ratio: u32, defense: f32) -> f32 {
+    if ratio == 0 {
+        return 0.0;
+    }
+    let scaled = power * ratio as f32 / 100.0;
+    (scaled - defense).max(scaled * MIN_DAMAGE_RATIO)
+}
+
+/// Calculates the result of the attacker using a skill with the given attack against the defender. A parried attack
+/// is reported like a blocked one, as the client has no separate display for it.
+pub(crate) fn calculate_hit<R: Rng>(attack: &AttackPower, skill: &SkillAttack, defense: &Defense, rng: &mut R) -> Hit {
+    let block_chance = defense.block_ratio.clamp(0.0, MAX_BLOCK_CHANCE);
+    if rng.gen_bool((block_chance / 100.0) as f64) {
+        return Hit::Blocked;
+    }
+
+    let parry_chance = (defense.parry_rate - attack.hit_rate).clamp(0.0, MAX_PARRY_CHANCE);
+    if rng.gen_bool((parry_chance / 100.0) as f64) {
+        return Hit::Blocked;
+    }
+
+    let skill_power = roll(rng, skill.min as f32, skill.max as f32);
+    let physical = roll(rng, attack.physical_min, attack.physical_max) + skill_power;
+    let magical = roll(rng, attack.magical_min, attack.magical_max) + skill_power;
+    let damage = reduce_by_defense(physical, skill.physical_ratio, defense.physical)
+        + reduce_by_defense(magical, skill.magical_ratio, defense.magical);
+
+    let critical_chance = (attack.critical + skill.critical - defense.critical_resist).clamp(0.0, 100.0);
+    let critical = rng.gen_bool((critical_chance / 100.0) as f64);
+    let damage = if critical { damage * CRITICAL_MULTIPLIER } else { damage };
+
+    Hit::Damage {
+        amount: (damage.round() as u32).max(1),
+        critical,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    fn attack() -> AttackPower {
+        AttackPower {
+            physical_min: 100.0,
+            physical_max: 100.0,
+            magical_min: 50.0,
+            magical_max: 50.0,
+            critical: 0.0,
+            hit_rate: 0.0,
+        }
+    }
+
+    #[test]
+    pub fn test_defense_reduction() {
+        assert_eq!(reduce_by_defense(100.0, 100, 30.0), 70.0);
+        assert_eq!(reduce_by_defense(100.0, 150, 30.0), 120.0);
+        assert_eq!(reduce_by_defense(100.0, 100, 500.0), 10.0);
+        assert_eq!(reduce_by_defense(100.0, 0, 0.0), 0.0);
+    }
+
+    #[test]
+    pub fn test_roll_bounds() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let value = roll(&mut rng, 5.0, 10.0);
+            assert!((5.0..=10.0).contains(&value));
+        }
+        assert_eq!(roll(&mut rng, 7.0, 3.0), 7.0);
+    }
+
+    #[test]
+    pub fn test_skill_attack_from_params() {
+        let attack = SkillAttack::from_params(&[
+            SkillParam::Attack {
+                kind: 0,
+                phys: 0,
+                min: 10,
+                max: 20,
+                mag: 120,
+            },
+            SkillParam::IncreaseCrit { amount: 5, unknown: 9 },
+        ]);
+        assert_eq!(attack.physical_ratio, 0);
+        assert_eq!(attack.magical_ratio, 120);
+        assert_eq!((attack.min, attack.max), (10, 20));
+        assert_eq!(attack.critical, 5.0);
+
+        assert_eq!(SkillAttack::from_params(&[]), SkillAttack::default());
+    }
+
+    #[test]
+    pub fn test_hit_calculation() {
+        let defense = Defense {
+            physical: 10.0,
+            magical: 20.0,
+            ..Default::default()
+        };
+        // Never passes any probability check that isn't guaranteed.
+        let mut rng = StepRng::new(u64::MAX, 0);
+        let hit = calculate_hit(&attack(), &SkillAttack::default(), &defense, &mut rng);
+        assert_eq!(
+            hit,
+            Hit::Damage {
+                amount: 90,
+                critical: false
+            }
+        );
+
+        let mixed = SkillAttack {
+            physical_ratio: 50,
+            magical_ratio: 100,
+            ..Default::default()
+        };
+        assert_eq!(calculate_hit(&attack(), &mixed, &defense, &mut rng).amount(), 70);
+
+        let always_critical = AttackPower {
+            critical: 100.0,
+            ..attack()
+        };
+        assert_eq!(
+            calculate_hit(&always_critical, &SkillAttack::default(), &defense, &mut rng),
+            Hit::Damage {
+                amount: 180,
+                critical: true
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_block_ratio() {
+        let defense = Defense::default().with_buffs(&BuffEffects {
+            block_ratio: 80.0,
+            ..Default::default()
+        });
+        assert_eq!(defense.block_ratio, 80.0);
+
+        // Blocking is capped, so a low roll blocks while a high one never does.
+        let mut low = StepRng::new(0, 0);
+        assert_eq!(
+            calculate_hit(&attack(), &SkillAttack::default(), &defense, &mut low),
+            Hit::Blocked
+        );
+        let mut high = StepRng::new(u64::MAX, 0);
+        assert_ne!(
+            calculate_hit(&attack(), &SkillAttack::default(), &defense, &mut high),
+            Hit::Blocked
+        );
+    }
+
+    #[test]
+    pub fn test_parry_rate() {
+        let defense = Defense::default().with_buffs(&BuffEffects {
+            parry_rate: 30.0,
+            ..Default::default()
+        });
+        let mut low = StepRng::new(0, 0);
+        assert_eq!(
+            calculate_hit(&attack(), &SkillAttack::default(), &defense, &mut low),
+            Hit::Blocked
+        );
+
+        // An attacker whose hit rate outweighs the parry rate can't be parried at all.
+        let accurate = attack().with_buffs(&BuffEffects {
+            hit_rate: 30.0,
+            ..Default::default()
+        });
+        assert_ne!(
+            calculate_hit(&accurate, &SkillAttack::default(), &defense, &mut low),
+            Hit::Blocked
+        );
+    }
+
+    #[test]
+    pub fn test_buff_modifiers() {
+        let effects = BuffEffects {
+            physical_defense: 50.0,
+            magical_defense: -150.0,
+            physical_attack: 10.0,
+            critical: 5.0,
+            ..Default::default()
+        };
+        let defense = Defense {
+            physical: 100.0,
+            magical: 100.0,
+            ..Default::default()
+        }
+        .with_buffs(&effects);
+        assert_eq!(defense.physical, 150.0);
+        assert_eq!(defense.magical, 0.0);
+
+        let attack = attack().with_buffs(&effects);
+        assert_eq!(attack.physical_min, 110.0);
+        assert_eq!(attack.magical_max, 50.0);
+        assert_eq!(attack.critical, 5.0);
+    }
+
+    fn area(origin: u8, area_type: u8, area_size: u16) -> AreaOfEffect {
+        AreaOfEffect::from_params(&[SkillParam::AOE {
+            origin,
+            area_type,
+            area_size,
+            unknown_type: 0,
+            val1: 0,
+            val2: 0,
+        }])
+        .unwrap()
+    }
+
+    #[test]
+    pub fn test_area_of_effect() {
+        let area = area(0, 2, 50);
+        assert_eq!(area.center, AreaCenter::Target);
+        assert_eq!(area.shape, AreaShape::Circle);
+        let center = GlobalLocation(Vector2::new(100.0, 100.0));
+        let direction = Vector2::unit_x();
+        assert!(area.contains(center, direction, GlobalLocation(Vector2::new(130.0, 140.0))));
+        assert!(!area.contains(center, direction, GlobalLocation(Vector2::new(131.0, 141.0))));
+        assert!(AreaOfEffect::from_params(&[]).is_none());
+    }
+
+    #[test]
+    pub fn test_caster_centered_area() {
+        let area = area(AREA_ORIGIN_CASTER, 2, 50);
+        let caster = GlobalLocation(Vector2::new(100.0, 100.0));
+        let target = GlobalLocation(Vector2::new(400.0, 100.0));
+        let center = area.center_of(caster, Some(target));
+        assert_eq!(center.0, caster.0);
+
+        let direction = (target.0 - caster.0).normalize();
+        assert!(area.contains(center, direction, GlobalLocation(Vector2::new(120.0, 130.0))));
+        assert!(!area.contains(center, direction, target));
+        assert!(!area.contains(center, direction, GlobalLocation(Vector2::new(410.0, 100.0))));
+    }
+
+    #[test]
+    pub fn test_line_area() {
+        let area = area(AREA_ORIGIN_CASTER, AREA_TYPE_LINE, 100);
+        assert_eq!(area.shape, AreaShape::Line);
+        let center = GlobalLocation(Vector2::new(0.0, 0.0));
+        let direction = Vector2::unit_x();
+        assert!(area.contains(center, direction, GlobalLocation(Vector2::new(80.0, 5.0))));
+        assert!(!area.contains(center, direction, GlobalLocation(Vector2::new(80.0, 15.0))));
+        assert!(!area.contains(center, direction, GlobalLocation(Vector2::new(-10.0, 0.0))));
+        assert!(!area.contains(center, direction, GlobalLocation(Vector2::new(110.0, 0.0))));
+    }
+}
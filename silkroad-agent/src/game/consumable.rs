@@ -0,0 +1,291 @@
+use crate::buff::Buffed;
+use crate::comp::consumable::{CooldownGroup, ItemCooldowns};
+use crate::comp::exp::Leveled;
+use crate::comp::inventory::PlayerInventory;
+use crate::comp::net::Client;
+use crate::comp::{EntityReference, GameEntity, Health, Mana};
+use crate::config::GameConfig;
+use crate::event::{CureEvent, ResurrectEvent};
+use crate::exchange::Exchange;
+use crate::input::PlayerInput;
+use crate::teleport::ReturnScrollCast;
+use bevy_ecs::prelude::*;
+use bevy_time::Time;
+use silkroad_data::itemdata::RefItemData;
+use silkroad_definitions::type_id::{
+    ObjectConsumable, ObjectConsumableCure, ObjectConsumableItemMall, ObjectConsumableRecovery, ObjectConsumableScroll,
+    ObjectItem, ObjectType, TypeId,
+};
+use silkroad_game_base::Inventory;
+use silkroad_protocol::inventory::{InventoryOperationError, UseItem, UseItemResponse};
+use std::time::Duration;
+use tracing::debug;
+
+const RECOVERY_COOLDOWN: Duration = Duration::from_secs(1);
+const VIGOR_COOLDOWN: Duration = Duration::from_secs(15);
+const CURE_COOLDOWN: Duration = Duration::from_secs(1);
+const RESURRECTION_COOLDOWN: Duration = Duration::from_secs(1);
+const RETURN_COOLDOWN: Duration = Duration::from_secs(1);
+
+/// The effect of using a consumable item.
+#[derive(Debug)]
+enum ConsumableEffect {
+    Recover {
+        health: u32,
+        mana: u32,
+    },
+    /// Removes all debuffs.
+    Cure,
+    /// Brings the player back to life at the place they died, without losing any experience.
+    Resurrect,
+    /// Starts casting a return scroll, which brings the player back to their return point.
+    Return,
+}
+
+impl ConsumableEffect {
+    /// Determines the effect the item has, together with the cooldown group it belongs to, or `None` if we don't
+    /// support using this kind of item (yet).
+    fn of(
+        consumable: ObjectConsumable,
+        item: &RefItemData,
+        user: &ItemUser,
+    ) -> Option<(ConsumableEffect, CooldownGroup, Duration)> {
+        // Recovery items restore a flat amount and a percentage of the maximum, with the first two parameters
+        // being used for health and the last two for mana.
+        let [health_flat, health_percent, mana_flat, mana_percent] = item.params;
+        let health_amount = recovery_amount(user.health.max_health, health_flat, health_percent);
+        let mana_amount = recovery_amount(user.mana.max_mana, mana_flat, mana_percent);
+        match consumable {
+            ObjectConsumable::Recovery(ObjectConsumableRecovery::HP) => Some((
+                ConsumableEffect::Recover {
+                    health: health_amount,
+                    mana: 0,
+                },
+                CooldownGroup::Health,
+                RECOVERY_COOLDOWN,
+            )),
+            ObjectConsumable::Recovery(ObjectConsumableRecovery::MP) => Some((
+                ConsumableEffect::Recover {
+                    health: 0,
+                    mana: mana_amount,
+                },
+                CooldownGroup::Mana,
+                RECOVERY_COOLDOWN,
+            )),
+            ObjectConsumable::Recovery(ObjectConsumableRecovery::Vigor) => Some((
+                ConsumableEffect::Recover {
+                    health: health_amount,
+                    mana: mana_amount,
+                },
+                CooldownGroup::Vigor,
+                VIGOR_COOLDOWN,
+            )),
+            ObjectConsumable::Cure(
+                ObjectConsumableCure::Single
+                | ObjectConsumableCure::Full
+                | ObjectConsumableCure::Superset
+                | ObjectConsumableCure::Super,
+            ) => Some((ConsumableEffect::Cure, CooldownGroup::Cure, CURE_COOLDOWN)),
+            ObjectConsumable::ItemMall(ObjectConsumableItemMall::Resurrection) => Some((
+                ConsumableEffect::Resurrect,
+                CooldownGroup::Resurrection,
+                RESURRECTION_COOLDOWN,
+            )),
+            ObjectConsumable::Scroll(ObjectConsumableScroll::Return) => {
+                Some((ConsumableEffect::Return, CooldownGroup::Return, RETURN_COOLDOWN))
+            },
+            _ => None,
+        }
+    }
+}
+
+/// The state of the player using an item, which decides what the item does and whether it can be used at all.
+struct ItemUser<'a> {
+    health: &'a Health,
+    mana: &'a Mana,
+    level: &'a Leveled,
+    buffed: &'a Buffed,
+    returning: bool,
+}
+
+fn recovery_amount(max: u32, flat: isize, percent: isize) -> u32 {
+    let flat = flat.max(0) as u32;
+    let percent = percent.clamp(0, 100) as u64;
+    flat + (max as u64 * percent / 100) as u32
+}
+
+/// Checks if the item type the client sent matches the type id of the item. The client encodes the type id together
+/// with the cash item and bionic flags in the lowest two bits, which we don't care about.
+fn is_item_type(type_id: TypeId, item_type: u16) -> bool {
+    let TypeId(t1, t2, t3, t4) = type_id;
+    let expected = ((t1 as u16) << 2) | ((t2 as u16) << 5) | ((t3 as u16) << 7) | ((t4 as u16) << 11);
+    item_type & !0b11 == expected
+}
+
+pub(crate) fn tick_item_cooldowns(mut query: Query<&mut ItemCooldowns>, time: Res<Time>) {
+    for mut cooldowns in query.iter_mut() {
+        cooldowns.tick(time.delta());
+    }
+}
+
+pub(crate) fn use_item(
+    mut query: Query<(
+        Entity,
+        &GameEntity,
+        &Client,
+        &PlayerInput,
+        &mut PlayerInventory,
+        &mut ItemCooldowns,
+        &mut Health,
+        &mut Mana,
+        &Leveled,
+        &Buffed,
+        Has<ReturnScrollCast>,
+        Has<Exchange>,
+    )>,
+    mut resurrect_events: EventWriter<ResurrectEvent>,
+    mut cure_events: EventWriter<CureEvent>,
+    settings: Res<GameConfig>,
+    mut cmd: Commands,
+) {
+    for (
+        entity,
+        game_entity,
+        client,
+        input,
+        mut inventory,
+        mut cooldowns,
+        mut health,
+        mut mana,
+        level,
+        buffed,
+        returning,
+        in_exchange,
+    ) in query.iter_mut()
+    {
+        let Some(ref request) = input.use_item else {
+            continue;
+        };
+
+        if in_exchange {
+            client.send(UseItemResponse::Error(InventoryOperationError::Busy));
+            continue;
+        }
+
+        let user = ItemUser {
+            health: &health,
+            mana: &mana,
+            level,
+            buffed,
+            returning,
+        };
+        let (remaining, effect) = match consume(request, &mut inventory, &mut cooldowns, &user) {
+            Ok(result) => result,
+            Err(error) => {
+                client.send(UseItemResponse::Error(error));
+                continue;
+            },
+        };
+
+        match effect {
+            ConsumableEffect::Recover {
+                health: health_amount,
+                mana: mana_amount,
+            } => {
+                // Only touch the components that actually change, to avoid sending needless updates.
+                if health_amount > 0 && !health.is_full() {
+                    health.regenerate(health_amount);
+                }
+                if mana_amount > 0 && !mana.is_full() {
+                    mana.regenerate(mana_amount);
+                }
+            },
+            ConsumableEffect::Cure => cure_events.send(CureEvent {
+                target: EntityReference(entity, *game_entity),
+            }),
+            ConsumableEffect::Resurrect => resurrect_events.send(ResurrectEvent {
+                target: entity,
+                experience_restore: 100,
+                max_level: None,
+            }),
+            ConsumableEffect::Return => {
+                let cast_time = Duration::from_secs(settings.teleport.return_cast_time);
+                cmd.entity(entity).insert(ReturnScrollCast::new(cast_time));
+            },
+        }
+        client.send(UseItemResponse::success(request.slot, remaining, request.item_type));
+    }
+}
+
+fn consume(
+    request: &UseItem,
+    inventory: &mut PlayerInventory,
+    cooldowns: &mut ItemCooldowns,
+    user: &ItemUser,
+) -> Result<(u16, ConsumableEffect), InventoryOperationError> {
+    let slot = request.slot;
+    if Inventory::is_equipment_slot(slot) {
+        return Err(InventoryOperationError::Unusable);
+    }
+
+    let item = inventory
+        .get_item_at(slot)
+        .ok_or(InventoryOperationError::InvalidTarget)?;
+    let reference = item.reference;
+    if !is_item_type(reference.common.type_id, request.item_type) {
+        return Err(InventoryOperationError::InvalidTarget);
+    }
+
+    if reference
+        .required_level
+        .is_some_and(|required| required.get() > user.level.current_level())
+    {
+        return Err(InventoryOperationError::TooLowLevel);
+    }
+
+    let Some(ObjectItem::Consumable(consumable)) =
+        ObjectType::from_type_id(&reference.common.type_id).and_then(|object| object.as_item())
+    else {
+        return Err(InventoryOperationError::Unusable);
+    };
+    let (effect, group, cooldown) =
+        ConsumableEffect::of(consumable, reference, user).ok_or(InventoryOperationError::Unusable)?;
+    // The dead can only use items to bring them back to life, which the living obviously have no use for.
+    let resurrects = matches!(effect, ConsumableEffect::Resurrect);
+    let already_returning = user.returning && matches!(effect, ConsumableEffect::Return);
+    let nothing_to_cure = matches!(effect, ConsumableEffect::Cure) && !user.buffed.has_debuffs();
+    if !cooldowns.is_ready(group) || user.health.is_dead() != resurrects || already_returning || nothing_to_cure {
+        return Err(InventoryOperationError::Unusable);
+    }
+
+    inventory
+        .take_from_slot(slot, 1)
+        .map_err(|_| InventoryOperationError::InvalidTarget)?;
+    cooldowns.start(group, cooldown);
+    debug!("Used {} with effect {:?}.", reference.common.id, effect);
+
+    let remaining = inventory.get_item_at(slot).map(|item| item.stack_size()).unwrap_or(0);
+    Ok((remaining, effect))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_item_type() {
+        // A health potion, which is 3|3|1|1.
+        let type_id = TypeId(3, 3, 1, 1);
+        assert!(is_item_type(type_id, 0x08EC));
+        assert!(is_item_type(type_id, 0x08EC | 0b01));
+        assert!(!is_item_type(TypeId(3, 3, 1, 2), 0x08EC));
+    }
+
+    #[test]
+    pub fn test_recovery_amount() {
+        assert_eq!(recovery_amount(1000, 120, 0), 120);
+        assert_eq!(recovery_amount(1000, 0, 25), 250);
+        assert_eq!(recovery_amount(1000, 50, 10), 150);
+        assert_eq!(recovery_amount(1000, -1, 0), 0);
+    }
+}
@@ -5,6 +5,7 @@ use crate::comp::net::Client;
 use crate::comp::player::Player;
 use crate::comp::{GameEntity, Health};
 use crate::event::{DamageReceiveEvent, EntityDeath};
+use crate::game::combat::Hit;
 use crate::game::mind::Mind;
 use bevy_ecs::prelude::*;
 use silkroad_protocol::combat::{
@@ -12,6 +13,16 @@ use silkroad_protocol::combat::{
     SkillPartDamage,
 };
 
+/// All hits of a single attack instance, which may have hit multiple targets in case of area attacks. The client
+/// expects these to be reported together in a single update.
+struct AttackOutcome {
+    skill: u32,
+    attacker: u32,
+    instance: u32,
+    entities: Vec<PerEntityDamage>,
+    receivers: Vec<Entity>,
+}
+
 pub(crate) fn handle_damage(
     mut reader: EventReader<DamageReceiveEvent>,
     mut receiver_query: Query<(
@@ -19,14 +30,14 @@ pub(crate) fn handle_damage(
         &mut StateTransitionQueue,
         &mut DamageReceiver,
         Option<&Player>,
-        Option<&Client>,
         Option<&Invincible>,
     )>,
     sender_query: Query<(&GameEntity, Option<&Client>)>,
     mut entity_died: EventWriter<EntityDeath>,
 ) {
+    let mut outcomes: Vec<AttackOutcome> = Vec::new();
     for damage_event in reader.read() {
-        let Ok((mut health, mut controller, mut receiver, player, maybe_client, invincible)) =
+        let Ok((mut health, mut controller, mut receiver, player, invincible)) =
             receiver_query.get_mut(damage_event.target.0)
         else {
             continue;
@@ -44,47 +55,56 @@ pub(crate) fn handle_damage(
             continue;
         }
 
-        let amount = if !invincible.is_some() { damage_event.amount } else { 0 };
-
-        receiver.record_damage(attacker.unique_id, amount as u64);
-        health.reduce(amount);
-        let damage_data = if health.is_dead() {
-            SkillPartDamage::KillingBlow(DamageValue::new(DamageKind::Standard, amount))
+        let hit = if invincible.is_none() {
+            damage_event.hit
         } else {
-            SkillPartDamage::Default(DamageValue::new(DamageKind::Standard, amount))
+            Hit::Damage {
+                amount: 0,
+                critical: false,
+            }
+        };
+
+        receiver.record_damage(attacker.unique_id, hit.amount() as u64);
+        health.reduce(hit.amount());
+        let damage_data = match hit {
+            Hit::Blocked => SkillPartDamage::Blocked,
+            Hit::Damage { amount, critical } => {
+                let kind = if critical {
+                    DamageKind::Critical
+                } else {
+                    DamageKind::Standard
+                };
+                if health.is_dead() {
+                    SkillPartDamage::KillingBlow(DamageValue::new(kind, amount))
+                } else {
+                    SkillPartDamage::Default(DamageValue::new(kind, amount))
+                }
+            },
         };
-        if let Some(client) = attacker_client {
-            client.send(PerformActionUpdate::success(
-                damage_event.attack.skill.ref_id,
-                damage_event.source.1.unique_id,
-                damage_event.target.1.unique_id,
-                damage_event.attack.instance,
-                ActionType::Attack {
-                    damage: Some(DamageContent {
-                        damage_instances: 1,
-                        entities: vec![PerEntityDamage {
-                            target: damage_event.target.1.unique_id,
-                            damage: vec![damage_data],
-                        }],
-                    }),
-                },
-            ));
-        } else if let Some(client) = maybe_client {
-            client.send(PerformActionUpdate::success(
-                damage_event.attack.skill.ref_id,
-                damage_event.source.1.unique_id,
-                damage_event.target.1.unique_id,
-                damage_event.attack.instance,
-                ActionType::Attack {
-                    damage: Some(DamageContent {
-                        damage_instances: 1,
-                        entities: vec![PerEntityDamage {
-                            target: damage_event.target.1.unique_id,
-                            damage: vec![damage_data],
-                        }],
-                    }),
-                },
-            ));
+
+        let index = match outcomes
+            .iter()
+            .position(|outcome| outcome.instance == damage_event.attack.instance)
+        {
+            Some(index) => index,
+            None => {
+                outcomes.push(AttackOutcome {
+                    skill: damage_event.attack.skill.ref_id,
+                    attacker: damage_event.source.1.unique_id,
+                    instance: damage_event.attack.instance,
+                    entities: Vec::new(),
+                    receivers: vec![damage_event.source.0],
+                });
+                outcomes.len() - 1
+            },
+        };
+        let outcome = &mut outcomes[index];
+        outcome.entities.push(PerEntityDamage {
+            target: damage_event.target.1.unique_id,
+            damage: vec![damage_data],
+        });
+        if !outcome.receivers.contains(&damage_event.target.0) {
+            outcome.receivers.push(damage_event.target.0);
         }
 
         if health.is_dead() {
@@ -100,6 +120,27 @@ pub(crate) fn handle_damage(
             controller.request_transition(dead_state);
         }
     }
+
+    for outcome in outcomes {
+        let main_target = outcome.entities[0].target;
+        let update = PerformActionUpdate::success(
+            outcome.skill,
+            outcome.attacker,
+            main_target,
+            outcome.instance,
+            ActionType::Attack {
+                damage: Some(DamageContent {
+                    damage_instances: 1,
+                    entities: outcome.entities,
+                }),
+            },
+        );
+        for receiver in outcome.receivers {
+            if let Ok((_, Some(client))) = sender_query.get(receiver) {
+                client.send(update.clone());
+            }
+        }
+    }
 }
 
 pub(crate) fn attack_player(mut query: Query<&mut Mind, With<Monster>>, mut events: EventReader<DamageReceiveEvent>) {
@@ -0,0 +1,208 @@
+use crate::agent::states::{Dead, StateTransitionQueue};
+use crate::comp::exp::{Experienced, Leveled, LostExperience};
+use crate::comp::player::Player;
+use crate::comp::{EntityReference, GameEntity, Health, Mana};
+use crate::config::GameConfig;
+use crate::event::{EntityDeath, PlayerTeleportEvent, ResurrectEvent};
+use crate::game::exp::ReceiveExperienceEvent;
+use crate::input::PlayerInput;
+use crate::pvp::PlayerKillRecord;
+use crate::teleport::ReturnPoint;
+use crate::world::WorldData;
+use bevy_ecs::prelude::*;
+use silkroad_data::skilldata::{RefSkillData, SkillParam};
+use silkroad_protocol::world::ResurrectionKind;
+use std::collections::HashSet;
+use tracing::debug;
+
+/// Provides the maximum level of the target and the percentage of lost experience restored, if the skill is a
+/// resurrection skill.
+pub(crate) fn resurrection_of(skill: &RefSkillData) -> Option<(u8, u8)> {
+    skill.params.iter().find_map(|param| match param {
+        SkillParam::Resurrect {
+            max_level,
+            experience_restore,
+        } => Some((*max_level, *experience_restore)),
+        _ => None,
+    })
+}
+
+/// Provides the experience lost on death, in percent. Murderers lose more, which their penalty points raise further
+/// by one percent each.
+fn exp_loss_percent(base: f32, murderer_base: f32, player_kills: Option<&PlayerKillRecord>) -> f32 {
+    match player_kills.filter(|record| record.is_murderer()) {
+        Some(record) => (murderer_base + record.penalty() as f32).min(100.0),
+        None => base,
+    }
+}
+
+/// Calculates the level and experience after losing the given amount of experience. If the current experience
+/// doesn't cover the loss, the player loses a level and the rest is taken from the experience of the previous level.
+fn lose_experience(level: u8, experience: u64, loss: u64, required: impl Fn(u8) -> Option<u64>) -> (u8, u64) {
+    if experience >= loss {
+        return (level, experience - loss);
+    }
+
+    if level <= 1 {
+        return (level, 0);
+    }
+
+    let previous = level - 1;
+    let remaining = required(previous).unwrap_or(0).saturating_sub(loss - experience);
+    (previous, remaining)
+}
+
+pub(crate) fn apply_death_penalty(
+    mut death_events: EventReader<EntityDeath>,
+    mut query: Query<(&mut Leveled, &mut Experienced, Option<&PlayerKillRecord>), With<Player>>,
+    players: Query<(), With<Player>>,
+    settings: Res<GameConfig>,
+    mut cmd: Commands,
+) {
+    let level_map = WorldData::levels();
+    for event in death_events.read() {
+        let Ok((mut level, mut experienced, player_kills)) = query.get_mut(event.died.0) else {
+            continue;
+        };
+
+        let current_level = level.current_level();
+        if current_level < settings.death.exp_loss_min_level {
+            continue;
+        }
+
+        // Dying to another player doesn't cost anything, unless the player was a murderer, who lose more
+        // experience regardless of who killed them.
+        let murderer = player_kills.is_some_and(|record| record.is_murderer());
+        let killed_by_player = event.killer.is_some_and(|killer| players.contains(killer.0));
+        if killed_by_player && !murderer {
+            continue;
+        }
+
+        let exp_loss = exp_loss_percent(settings.death.exp_loss, settings.pvp.murderer_exp_loss, player_kills);
+        let required = level_map.get_exp_for_level(current_level).unwrap_or(0);
+        let loss = (required as f64 * exp_loss as f64 / 100.0) as u64;
+        if loss == 0 {
+            continue;
+        }
+
+        let (new_level, remaining) = lose_experience(current_level, experienced.experience(), loss, |level| {
+            level_map.get_exp_for_level(level)
+        });
+        experienced.lose(loss, remaining);
+        if new_level < current_level {
+            level.delevel();
+        }
+        cmd.entity(event.died.0).insert(LostExperience(loss));
+    }
+}
+
+pub(crate) fn handle_resurrect_input(
+    query: Query<(Entity, &PlayerInput, &ReturnPoint), With<Dead>>,
+    settings: Res<GameConfig>,
+    mut resurrect_events: EventWriter<ResurrectEvent>,
+    mut teleport_events: EventWriter<PlayerTeleportEvent>,
+) {
+    for (entity, input, return_point) in query.iter() {
+        let Some(ref resurrect) = input.resurrect else {
+            continue;
+        };
+
+        if let ResurrectionKind::ReturnToTown = resurrect.kind {
+            let position = return_point.position_or(settings.teleport.default_return.position());
+            teleport_events.send(PlayerTeleportEvent(entity, position));
+        }
+
+        resurrect_events.send(ResurrectEvent {
+            target: entity,
+            experience_restore: 0,
+            max_level: None,
+        });
+    }
+}
+
+pub(crate) fn resurrect(
+    mut resurrect_events: EventReader<ResurrectEvent>,
+    mut query: Query<
+        (
+            &GameEntity,
+            &Leveled,
+            &mut Health,
+            &mut Mana,
+            &mut StateTransitionQueue,
+            Option<&LostExperience>,
+        ),
+        With<Dead>,
+    >,
+    mut experience_events: EventWriter<ReceiveExperienceEvent>,
+    mut cmd: Commands,
+) {
+    // Removing the dead state only happens at the end of the stage, so we need to make sure not to resurrect
+    // someone twice in case they got resurrected by multiple sources at once.
+    let mut resurrected = HashSet::new();
+    for event in resurrect_events.read() {
+        let Ok((game_entity, level, mut health, mut mana, mut transitions, lost_experience)) =
+            query.get_mut(event.target)
+        else {
+            continue;
+        };
+
+        if event
+            .max_level
+            .is_some_and(|max_level| level.current_level() > max_level)
+        {
+            continue;
+        }
+
+        if !resurrected.insert(event.target) {
+            continue;
+        }
+
+        let max_health = health.max_health;
+        health.regenerate(max_health);
+        let max_mana = mana.max_mana;
+        mana.regenerate(max_mana);
+        // Anything the player tried to do while being dead should not happen once they're back.
+        transitions.clear();
+
+        let restored = lost_experience
+            .map(|lost| lost.0 * event.experience_restore.min(100) as u64 / 100)
+            .unwrap_or(0);
+        if restored > 0 {
+            experience_events.send(ReceiveExperienceEvent {
+                source: None,
+                target: EntityReference(event.target, *game_entity),
+                exp: restored,
+                sp: 0,
+            });
+        }
+
+        debug!("Resurrected {:?}, restoring {} experience.", event.target, restored);
+        cmd.entity(event.target).remove::<(Dead, LostExperience)>();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use silkroad_protocol::world::PlayerKillState;
+
+    #[test]
+    pub fn test_lose_experience() {
+        let required = |level: u8| Some(level as u64 * 100);
+        assert_eq!(lose_experience(10, 500, 200, required), (10, 300));
+        assert_eq!(lose_experience(10, 50, 200, required), (9, 750));
+        assert_eq!(lose_experience(1, 50, 200, required), (1, 0));
+    }
+
+    #[test]
+    pub fn test_murderer_exp_loss() {
+        let innocent = PlayerKillRecord::new(PlayerKillState::None, None, 0, 0);
+        assert_eq!(exp_loss_percent(1.0, 5.0, None), 1.0);
+        assert_eq!(exp_loss_percent(1.0, 5.0, Some(&innocent)), 1.0);
+
+        let murderer = PlayerKillRecord::new(PlayerKillState::Red, None, 3, 3);
+        assert_eq!(exp_loss_percent(1.0, 5.0, Some(&murderer)), 8.0);
+        let notorious = PlayerKillRecord::new(PlayerKillState::Red, None, 200, 200);
+        assert_eq!(exp_loss_percent(1.0, 5.0, Some(&notorious)), 100.0);
+    }
+}
@@ -1,13 +1,20 @@
-use crate::comp::drop::{Drop, DropBundle};
+use crate::comp::drop::{Drop, DropBundle, LootPriority};
+use crate::comp::inventory::new_item;
+use crate::comp::monster::Monster;
 use crate::comp::pos::Position;
 use crate::comp::{Despawn, EntityReference, GameEntity};
+use crate::config::GameConfig;
+use crate::event::EntityDeath;
 use crate::ext::{EntityIdPool, Navmesh};
+use crate::world::WorldData;
 use bevy_ecs::prelude::*;
-use bevy_time::Time;
+use bevy_time::{Time, Timer, TimerMode};
 use derive_more::Constructor;
-use rand::Rng;
+use rand::{thread_rng, Rng};
+use silkroad_data::dropdata::MonsterDrops;
 use silkroad_data::DataEntry;
 use silkroad_game_base::{GlobalLocation, GlobalPosition, Heading, Item, Vector2Ext};
+use std::time::Duration;
 
 #[derive(Constructor, Event)]
 pub(crate) struct SpawnDrop {
@@ -25,9 +32,99 @@ pub(crate) fn tick_drop(mut cmd: Commands, time: Res<Time>, mut drops: Query<(En
     }
 }
 
+pub(crate) fn tick_loot_priority(
+    mut cmd: Commands,
+    time: Res<Time>,
+    mut drops: Query<(Entity, &mut Drop, &mut LootPriority)>,
+) {
+    for (entity, mut drop, mut priority) in drops.iter_mut() {
+        if priority.0.tick(time.delta()).finished() {
+            drop.owner = None;
+            cmd.entity(entity).remove::<LootPriority>();
+        }
+    }
+}
+
+/// An item rolled from the drop table of a monster.
+#[derive(Debug, PartialEq)]
+struct RolledDrop {
+    item: u32,
+    upgrade_level: u8,
+    amount: u16,
+}
+
+/// Rolls which items get dropped, where `rate` is a multiplier for the chance of each drop to happen.
+fn roll_drops<R: Rng>(drops: &MonsterDrops, rate: f32, rng: &mut R) -> Vec<RolledDrop> {
+    let mut rolled = Vec::new();
+    for drop in drops.items.iter() {
+        if rng.gen::<f32>() < drop.chance * rate {
+            rolled.push(RolledDrop {
+                item: drop.item,
+                upgrade_level: drop.upgrade_level,
+                amount: rng.gen_range(drop.amount.clone()),
+            });
+        }
+    }
+
+    for group in drops.groups.iter() {
+        let total_weight: f32 = group.items.iter().map(|item| item.weight).sum();
+        if total_weight <= 0.0 || rng.gen::<f32>() >= group.chance * rate {
+            continue;
+        }
+
+        let mut remaining = rng.gen_range(0.0..total_weight);
+        let picked = group.items.iter().find(|item| {
+            if remaining < item.weight {
+                return true;
+            }
+            remaining -= item.weight;
+            false
+        });
+        if let Some(picked) = picked {
+            rolled.push(RolledDrop {
+                item: picked.item,
+                upgrade_level: 0,
+                amount: rng.gen_range(group.amount.clone()),
+            });
+        }
+    }
+    rolled
+}
+
+pub(crate) fn drop_items(
+    mut death_events: EventReader<EntityDeath>,
+    query: Query<(&GameEntity, &Position), With<Monster>>,
+    settings: Res<GameConfig>,
+    mut drop_events: EventWriter<SpawnDrop>,
+) {
+    let items = WorldData::items();
+    let drops = WorldData::drops();
+    for event in death_events.read() {
+        let Ok((game_entity, pos)) = query.get(event.died.0) else {
+            continue;
+        };
+        let Some(monster_drops) = drops.drops_of(game_entity.ref_id) else {
+            continue;
+        };
+
+        for rolled in roll_drops(monster_drops, settings.drops.item_rate, &mut thread_rng()) {
+            let Some(reference) = items.find_id(rolled.item) else {
+                continue;
+            };
+            let amount = rolled.amount.clamp(1, reference.max_stack_size.max(1));
+            drop_events.send(SpawnDrop {
+                item: new_item(reference, rolled.upgrade_level, None, amount),
+                relative_position: pos.location(),
+                owner: event.killer,
+            });
+        }
+    }
+}
+
 pub(crate) fn create_drops(
     mut reader: EventReader<SpawnDrop>,
     navmesh: Res<Navmesh>,
+    settings: Res<GameConfig>,
     mut id_gen: ResMut<EntityIdPool>,
     mut cmd: Commands,
 ) {
@@ -36,7 +133,7 @@ pub(crate) fn create_drops(
         let drop_id = id_gen.request_id().expect("Should be able to generate an id");
         let rotation = rand::thread_rng().gen_range(0..360) as f32;
 
-        cmd.spawn(DropBundle {
+        let mut drop = cmd.spawn(DropBundle {
             drop: Drop {
                 owner: spawn.owner,
                 item: spawn.item,
@@ -48,6 +145,12 @@ pub(crate) fn create_drops(
             },
             despawn: spawn.item.reference.common.despawn_time.into(),
         });
+        if spawn.owner.is_some() {
+            drop.insert(LootPriority(Timer::new(
+                Duration::from_secs(settings.drops.owner_priority),
+                TimerMode::Once,
+            )));
+        }
     }
 }
 
@@ -57,3 +160,55 @@ fn random_position_around(navmesh: &Navmesh, origin: GlobalLocation, radius: f32
     let drop_position = drop_position.with_height(navmesh.height_for(local_drop_pos).unwrap_or(0.0f32));
     GlobalPosition(drop_position)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use silkroad_data::dropdata::{DropGroupItem, GroupDrop, ItemDrop};
+
+    fn drops() -> MonsterDrops {
+        MonsterDrops {
+            items: vec![ItemDrop {
+                item: 1,
+                upgrade_level: 2,
+                amount: 1..=1,
+                chance: 0.5,
+            }],
+            groups: vec![GroupDrop {
+                items: vec![
+                    DropGroupItem { item: 10, weight: 0.0 },
+                    DropGroupItem { item: 11, weight: 1.0 },
+                ],
+                amount: 3..=5,
+                chance: 0.5,
+            }],
+        }
+    }
+
+    #[test]
+    pub fn test_rate_zero_drops_nothing() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            assert!(roll_drops(&drops(), 0.0, &mut rng).is_empty());
+        }
+    }
+
+    #[test]
+    pub fn test_guaranteed_drops() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let rolled = roll_drops(&drops(), 2.0, &mut rng);
+        assert_eq!(rolled.len(), 2);
+        assert_eq!(
+            rolled[0],
+            RolledDrop {
+                item: 1,
+                upgrade_level: 2,
+                amount: 1
+            }
+        );
+        assert_eq!(rolled[1].item, 11);
+        assert!((3..=5).contains(&rolled[1].amount));
+    }
+}
@@ -3,12 +3,17 @@ use crate::comp::exp::{Experienced, Leveled, SP};
 use crate::comp::player::StatPoints;
 use crate::comp::pos::Position;
 use crate::comp::{EntityReference, GameEntity, Health, Mana};
+use crate::config::GameConfig;
 use crate::event::EntityDeath;
+use crate::party::{Parties, PartyMember};
 use crate::world::{EntityLookup, WorldData};
 use bevy_ecs::prelude::*;
+use std::collections::HashMap;
 use tracing::warn;
 
 const EXP_RECEIVE_RANGE_SQUARED: f32 = 1000.0 * 1000.0;
+const KILL_EXP: u64 = 100;
+const KILL_SP: u64 = 100;
 
 #[derive(Event)]
 pub struct ReceiveExperienceEvent {
@@ -23,29 +28,73 @@ pub(crate) fn distribute_experience(
     mut experience_writer: EventWriter<ReceiveExperienceEvent>,
     dead_query: Query<(&DamageReceiver, &Position)>,
     lookup: Res<EntityLookup>,
-    receiver_query: Query<(&GameEntity, &Position)>,
+    receiver_query: Query<(&GameEntity, &Position, Option<&PartyMember>)>,
+    parties: Res<Parties>,
+    settings: Res<GameConfig>,
 ) {
     for event in death_events.read() {
         let Ok((damage_distribution, death_location)) = dead_query.get(event.died.0) else {
             continue;
         };
 
+        let mut shared_contributions: HashMap<u32, u64> = HashMap::new();
         for attacker_id in damage_distribution.all_attackers() {
-            if let Some(((game_entity, position), target_entity)) = lookup
+            if let Some(((game_entity, position, party), target_entity)) = lookup
                 .get_entity_for_id(attacker_id)
                 .and_then(|entity| receiver_query.get(entity).ok().zip(Some(entity)))
             {
+                if let Some(party) = party
+                    .and_then(|party| parties.get(party.0))
+                    .filter(|party| party.settings().exp_shared)
+                {
+                    *shared_contributions.entry(party.id()).or_default() += 1;
+                    continue;
+                }
+
                 if death_location.distance_to(position) <= EXP_RECEIVE_RANGE_SQUARED {
                     let event = ReceiveExperienceEvent {
                         source: Some(event.died),
                         target: EntityReference(target_entity, *game_entity),
-                        exp: 100,
-                        sp: 100,
+                        exp: KILL_EXP,
+                        sp: KILL_SP,
                     };
                     experience_writer.send(event);
                 }
             }
         }
+
+        for (party_id, contributions) in shared_contributions {
+            let Some(party) = parties.get(party_id) else {
+                continue;
+            };
+
+            let receivers = party
+                .members()
+                .iter()
+                .filter(|member| {
+                    receiver_query
+                        .get(member.0)
+                        .map(|(_, position, _)| death_location.distance_to(position) <= EXP_RECEIVE_RANGE_SQUARED)
+                        .unwrap_or(false)
+                })
+                .collect::<Vec<_>>();
+            if receivers.is_empty() {
+                continue;
+            }
+
+            let receiver_count = receivers.len() as u64;
+            let bonus = 100 + settings.party.exp_bonus_per_member * (receiver_count - 1);
+            let exp = (KILL_EXP * contributions * bonus / 100).div_ceil(receiver_count);
+            let sp = (KILL_SP * contributions * bonus / 100).div_ceil(receiver_count);
+            for receiver in receivers {
+                experience_writer.send(ReceiveExperienceEvent {
+                    source: Some(event.died),
+                    target: *receiver,
+                    exp,
+                    sp,
+                });
+            }
+        }
     }
 }
 
@@ -88,6 +137,9 @@ pub(crate) fn reset_health_mana_on_level(
         if leveled.did_level() {
             health.upgrade(stats.stats().max_health(leveled.current_level()));
             mana.upgrade(stats.stats().max_mana(leveled.current_level()));
+        } else if leveled.did_delevel() {
+            health.increase_max(stats.stats().max_health(leveled.current_level()));
+            mana.increase_max(stats.stats().max_mana(leveled.current_level()));
         }
     }
 }
@@ -1,6 +1,7 @@
 use crate::comp::monster::Monster;
 use crate::comp::pos::Position;
 use crate::comp::GameEntity;
+use crate::config::GameConfig;
 use crate::event::EntityDeath;
 use crate::game::drop::SpawnDrop;
 use crate::world::WorldData;
@@ -27,6 +28,7 @@ pub(crate) fn get_gold_ref_id(amount: u32) -> &'static RefItemData {
 pub(crate) fn drop_gold(
     mut death_events: EventReader<EntityDeath>,
     query: Query<(&GameEntity, &Position), With<Monster>>,
+    settings: Res<GameConfig>,
     mut drop_events: EventWriter<SpawnDrop>,
 ) {
     let characters = WorldData::characters();
@@ -39,7 +41,11 @@ pub(crate) fn drop_gold(
 
             let monster_level = monster_data.level;
             let gold_range = gold.get_for_level(monster_level);
-            let amount = thread_rng().gen_range(gold_range);
+            let amount = (thread_rng().gen_range(gold_range) as f32 * settings.drops.gold_rate) as u32;
+            if amount == 0 {
+                continue;
+            }
+
             drop_events.send(SpawnDrop {
                 item: Item {
                     reference: get_gold_ref_id(amount),
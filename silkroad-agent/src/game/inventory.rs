@@ -5,9 +5,11 @@ use crate::comp::net::Client;
 use crate::comp::player::{CharacterRace, Player};
 use crate::comp::pos::Position;
 use crate::comp::GameEntity;
+use crate::exchange::Exchange;
 use crate::game::drop::SpawnDrop;
 use crate::game::gold::get_gold_ref_id;
 use crate::input::PlayerInput;
+use crate::job::{job_of_suit, JobAlignment};
 use bevy_ecs::{prelude::*, world};
 use silkroad_definitions::type_id::{
     ObjectClothingPart, ObjectClothingType, ObjectConsumable, ObjectConsumableAmmo, ObjectEquippable, ObjectItem,
@@ -25,19 +27,24 @@ use std::cmp::max;
 use std::ops::{ControlFlow, Deref};
 
 pub(crate) fn handle_inventory_input(
-    mut query: Query<(
-        &GameEntity,
-        &Client,
-        &PlayerInput,
-        &Leveled,
-        &CharacterRace,
-        &mut PlayerInventory,
-        &mut GoldPouch,
-        &Position,
-    )>,
+    // The items offered in an exchange are referenced by their slot, so the inventory is locked while exchanging.
+    mut query: Query<
+        (
+            &GameEntity,
+            &Client,
+            &PlayerInput,
+            &Player,
+            &Leveled,
+            &CharacterRace,
+            &mut PlayerInventory,
+            &mut GoldPouch,
+            &Position,
+        ),
+        Without<Exchange>,
+    >,
     mut item_spawn: EventWriter<SpawnDrop>,
 ) {
-    for (game_entity, client, input, level, race, mut inventory, mut gold, position) in query.iter_mut() {
+    for (game_entity, client, input, player, level, race, mut inventory, mut gold, position) in query.iter_mut() {
         if let Some(ref action) = input.inventory {
             match action.data {
                 InventoryOperationRequest::DropGold { amount } => {
@@ -47,9 +54,27 @@ pub(crate) fn handle_inventory_input(
                 },
                 InventoryOperationRequest::PickupItem { unique_id } => {},
                 InventoryOperationRequest::Move { source, target, amount } => {
+                    if !is_suit_allowed(&inventory, source, target, JobAlignment::from_id(player.user.job)) {
+                        client.send(InventoryOperationResult::Error(InventoryOperationError::Indisposable));
+                        continue;
+                    }
                     handle_inventory_movement(inventory, source, target, level, race, client, game_entity, amount);
                 },
                 InventoryOperationRequest::DropItem { .. } => {},
+                // Trading with NPCs is handled by the shop plugin.
+                InventoryOperationRequest::BuyItem { .. }
+                | InventoryOperationRequest::SellItem { .. }
+                | InventoryOperationRequest::BuyBackItem { .. } => {},
+                // Moving items and gold in and out of the storage is handled by the storage plugin.
+                InventoryOperationRequest::MoveInStorage { .. }
+                | InventoryOperationRequest::DepositItem { .. }
+                | InventoryOperationRequest::WithdrawItem { .. }
+                | InventoryOperationRequest::DepositGold { .. }
+                | InventoryOperationRequest::WithdrawGold { .. } => {},
+                // Offering items and gold to another player is handled by the exchange plugin.
+                InventoryOperationRequest::AddToExchange { .. }
+                | InventoryOperationRequest::RemoveFromExchange { .. }
+                | InventoryOperationRequest::SetExchangeGold { .. } => {},
             }
         }
     }
@@ -180,6 +205,18 @@ fn player_equip_item(inventory: &Mut<'_, PlayerInventory>, slot_the_item_gets_eq
     }
 }
 
+/// Checks that a job suit ending up in an equipment slot by this move belongs to the side the player has chosen.
+fn is_suit_allowed(inventory: &Inventory, source: u8, target: u8, alignment: JobAlignment) -> bool {
+    let equipped = if Inventory::is_equipment_slot(target) {
+        inventory.get_item_at(source)
+    } else if Inventory::is_equipment_slot(source) {
+        inventory.get_item_at(target)
+    } else {
+        None
+    };
+    equipped.and_then(job_of_suit).map(|job| alignment.allows(job)).unwrap_or(true)
+}
+
 fn item_fits_into_equipment_slot(source_item: &Item, target: u8, level: &Leveled, race: &CharacterRace) -> bool {
     let type_id = source_item.reference.common.type_id;
     let object_type = ObjectType::from_type_id(&type_id)
@@ -294,6 +331,8 @@ fn does_object_type_match_race(user_race: Race, obj_type: ObjectType) -> bool {
                         _ => false,
                     }
                 },
+                // Job suits can be worn by either race, but only by the side of the suit.
+                ObjectEquippable::Flag(_) => return true,
                 _ => {},
             },
             ObjectItem::Consumable(ObjectConsumable::Ammo(kind)) => {
@@ -336,6 +375,9 @@ fn does_object_type_match_slot(slot: u8, obj_type: ObjectType) -> bool {
                 ObjectEquippable::Weapon(_) => {
                     return slot == 6;
                 },
+                ObjectEquippable::Flag(_) => {
+                    return slot == 12;
+                },
                 _ => {},
             },
             ObjectItem::Consumable(ObjectConsumable::Ammo(_)) => {
@@ -9,7 +9,6 @@ use bevy_ecs::prelude::*;
 use silkroad_game_base::SpawningState;
 use silkroad_protocol::character::CharacterStatsMessage;
 use silkroad_protocol::chat::{ChatSource, ChatUpdate, TextCharacterInitialization};
-use silkroad_protocol::community::{FriendListGroup, FriendListInfo};
 use silkroad_protocol::world::{CelestialUpdate, CharacterFinished};
 use tracing::debug;
 
@@ -26,6 +25,8 @@ pub(crate) fn load_finished(
         };
 
         debug!(id = ?client.0.id(), "Finished loading.");
+        // Teleporting also ends with a loading screen, but we only want to greet players when they join.
+        let joined = player.character.state == SpawningState::Loading;
         player.character.state = SpawningState::Finished;
         send_character_stats(client, stat_points, level.current_level());
         send_text_initialization(client);
@@ -37,12 +38,8 @@ pub(crate) fn load_finished(
             minute,
         });
         client.send(CharacterFinished::default());
-        client.send(FriendListInfo {
-            groups: vec![FriendListGroup::not_assigned()],
-            friends: vec![],
-        });
 
-        if let Some(notice) = &settings.join_notice {
+        if let Some(notice) = settings.join_notice.as_ref().filter(|_| joined) {
             client.send(ChatUpdate::new(ChatSource::Notice, notice.clone()));
         }
     }
@@ -1,10 +1,12 @@
-use crate::agent::states::{Dead, Idle, MovementGoal, Moving, Pickup, StateTransitionQueue};
+use crate::agent::states::{ActionTarget, Dead, Idle, MovementGoal, Moving, Pickup, StateTransitionQueue};
 use crate::comp::inventory::PlayerInventory;
 use crate::comp::net::Client;
 use crate::comp::pos::Position;
 use crate::comp::{EntityReference, GameEntity};
 use crate::ext::Navmesh;
 use crate::game::attack::{Attack, AttackProcess};
+use crate::game::death::resurrection_of;
+use crate::pvp::{PlayerKillRecord, PvpFlag, PvpStanding};
 use crate::world::WorldData;
 use bevy_app::{App, Plugin, PostUpdate, PreUpdate};
 use bevy_ecs::prelude::*;
@@ -12,7 +14,7 @@ use bevy_ecs_macros::Component;
 use bevy_time::common_conditions::on_timer;
 use silkroad_data::skilldata::RefSkillData;
 use silkroad_definitions::inventory::EquipmentSlot;
-use silkroad_game_base::AttackSkillError;
+use silkroad_game_base::{AttackSkillError, GlobalLocation};
 use silkroad_protocol::combat::{DoActionResponseCode, PerformActionError, PerformActionResponse};
 use std::time::Duration;
 use tracing::warn;
@@ -31,6 +33,10 @@ impl Mind {
         self.current_goal = Some(Goal::ExecuteSkill(target, skill))
     }
 
+    pub fn attack_at(&mut self, location: GlobalLocation, skill: &'static RefSkillData) {
+        self.current_goal = Some(Goal::ExecuteSkillAt(location, skill))
+    }
+
     pub fn cancel(&mut self) {
         self.current_goal = None;
     }
@@ -48,12 +54,46 @@ impl Mind {
 pub enum Goal {
     Attack(EntityReference),
     ExecuteSkill(EntityReference, &'static RefSkillData),
+    ExecuteSkillAt(GlobalLocation, &'static RefSkillData),
     PickUp(EntityReference),
 }
 
+/// Provides the position of the target if it can still be targeted. Only resurrection skills may target the dead,
+/// while everything else only targets the living.
+fn target_position<'a>(
+    query: &'a Query<(&Position, Has<Dead>)>,
+    target: Entity,
+    skill: Option<&RefSkillData>,
+) -> Option<&'a Position> {
+    let targets_dead = skill.is_some_and(|skill| resurrection_of(skill).is_some());
+    query
+        .get(target)
+        .ok()
+        .filter(|(_, is_dead)| *is_dead == targets_dead)
+        .map(|(position, _)| position)
+}
+
+/// Provides the standing of both sides if a player targets another player.
+fn pvp_standings(
+    query: &Query<(&PvpFlag, &PlayerKillRecord, &PlayerInventory)>,
+    attacker: Entity,
+    target: ActionTarget,
+) -> Option<(PvpStanding, PvpStanding)> {
+    let ActionTarget::Entity(target) = target else {
+        return None;
+    };
+    let (attacker_flag, attacker_record, attacker_inventory) = query.get(attacker).ok()?;
+    let (target_flag, target_record, target_inventory) = query.get(target).ok()?;
+    Some((
+        PvpStanding::of(attacker_flag, attacker_record, attacker_inventory),
+        PvpStanding::of(target_flag, target_record, target_inventory),
+    ))
+}
+
 fn enqueue_action(
     mut query: Query<
         (
+            Entity,
             &GameEntity,
             Option<&Client>,
             &mut Mind,
@@ -63,10 +103,11 @@ fn enqueue_action(
         ),
         With<Idle>,
     >,
-    target_query: Query<&Position, Without<Dead>>,
+    target_query: Query<(&Position, Has<Dead>)>,
+    standing_query: Query<(&PvpFlag, &PlayerKillRecord, &PlayerInventory)>,
     navmesh: Res<Navmesh>,
 ) {
-    for (entity, client, mut mind, position, mut state, inventory) in query.iter_mut() {
+    for (own_entity, entity, client, mut mind, position, mut state, inventory) in query.iter_mut() {
         if let Some(goal) = mind.current_goal.as_ref() {
             if matches!(goal, Goal::PickUp(_)) {
                 let Goal::PickUp(target) = goal else {
@@ -74,7 +115,7 @@ fn enqueue_action(
                     continue;
                 };
 
-                let Ok(target_pos) = target_query.get(target.0) else {
+                let Some(target_pos) = target_position(&target_query, target.0, None) else {
                     mind.cancel();
                     state.request_transition(Idle);
                     continue;
@@ -100,30 +141,37 @@ fn enqueue_action(
                     let target_movement_pos = my_location.point_in_line_with_range(target_pos.location(), range);
 
                     let target_height = navmesh.height_for(target_movement_pos).unwrap_or(position.position().y);
-                    state.request_transition(Moving(MovementGoal::Location(
-                        target_movement_pos.with_y(target_height),
-                    )));
+                    match MovementGoal::walk_to(&navmesh, my_location, target_movement_pos.with_y(target_height)) {
+                        Some(goal) => state.request_transition(Moving(goal)),
+                        None => {
+                            mind.cancel();
+                            state.request_transition(Idle);
+                        },
+                    }
                 }
             } else {
-                let (target, skill) = match goal {
-                    Goal::Attack(target) => (
-                        target,
-                        match inventory {
-                            Some(inv) => Attack::find_attack_for_player(inv).unwrap(),
-                            None => Attack::find_attack_for_monster(*entity).unwrap(),
-                        },
-                    ),
-                    Goal::ExecuteSkill(target, skill) => (target, *skill),
-                    _ => continue,
+                let skill = match goal {
+                    Goal::Attack(_) => match inventory {
+                        Some(inv) => Attack::find_attack_for_player(inv).unwrap(),
+                        None => Attack::find_attack_for_monster(*entity).unwrap(),
+                    },
+                    Goal::ExecuteSkill(_, skill) | Goal::ExecuteSkillAt(_, skill) => *skill,
+                    Goal::PickUp(_) => continue,
                 };
 
-                let target_pos = match target_query.get(target.0) {
-                    Ok(target_position) => target_position,
-                    Err(_) => {
-                        // Target most likely died.
-                        mind.cancel();
-                        continue;
+                let (target, target_location) = match goal {
+                    Goal::Attack(target) | Goal::ExecuteSkill(target, _) => {
+                        match target_position(&target_query, target.0, Some(skill)) {
+                            Some(target_pos) => (ActionTarget::Entity(target.0), target_pos.location()),
+                            None => {
+                                // Target most likely died.
+                                mind.cancel();
+                                continue;
+                            },
+                        }
                     },
+                    Goal::ExecuteSkillAt(location, _) => (ActionTarget::Location(*location), *location),
+                    Goal::PickUp(_) => continue,
                 };
 
                 let mut process = AttackProcess::new(
@@ -132,9 +180,10 @@ fn enqueue_action(
                     skill,
                     inventory.and_then(|inv| inv.get_equipment_item(EquipmentSlot::Weapon)),
                     target,
-                    target_pos,
+                    target_location,
                     &navmesh,
-                );
+                )
+                .between_players(pvp_standings(&standing_query, own_entity, target));
 
                 match process.try_attack() {
                     Ok(_) => {
@@ -152,6 +201,12 @@ fn enqueue_action(
                                 AttackSkillError::SkillNotFound => {
                                     client.send(PerformActionResponse::Stop(PerformActionError::NotLearned));
                                 },
+                                AttackSkillError::Unreachable => {
+                                    client.send(PerformActionResponse::Stop(PerformActionError::ObstacleInPath));
+                                },
+                                AttackSkillError::NotAllowed => {
+                                    client.send(PerformActionResponse::Stop(PerformActionError::InvalidTarget));
+                                },
                             }
                         } else {
                             warn!("Couldn't execute attack for monster");
@@ -166,6 +221,7 @@ fn enqueue_action(
 pub(crate) fn update_action(
     mut query: Query<
         (
+            Entity,
             &GameEntity,
             Option<&Client>,
             &mut Mind,
@@ -176,50 +232,55 @@ pub(crate) fn update_action(
         ),
         Without<Idle>,
     >,
-    target_query: Query<&Position, Without<Dead>>,
+    target_query: Query<(&Position, Has<Dead>)>,
+    standing_query: Query<(&PvpFlag, &PlayerKillRecord, &PlayerInventory)>,
     navmesh: Res<Navmesh>,
 ) {
-    for (entity, client, mut mind, position, mut state, inventory, moving) in query.iter_mut() {
+    for (own_entity, entity, client, mut mind, position, mut state, inventory, moving) in query.iter_mut() {
         let Some(goal) = mind.current_goal.as_ref() else {
             continue;
         };
 
         if let Goal::PickUp(target) = goal {
-            if target_query.get(target.0).is_err() {
+            if target_position(&target_query, target.0, None).is_none() {
                 mind.cancel();
                 state.request_transition(Idle);
                 continue;
             }
 
-            if !matches!(moving.0, MovementGoal::Location(_)) {
+            if !matches!(moving.0, MovementGoal::Location(_) | MovementGoal::Path(_)) {
                 mind.cancel();
                 continue;
             }
         } else {
-            let (target, skill) = match goal {
-                Goal::Attack(target) => (
-                    target,
-                    match inventory {
-                        Some(inv) => Attack::find_attack_for_player(inv).unwrap(),
-                        None => Attack::find_attack_for_monster(*entity).unwrap(),
-                    },
-                ),
-                Goal::ExecuteSkill(target, skill) => (target, *skill),
-                _ => continue,
+            let skill = match goal {
+                Goal::Attack(_) => match inventory {
+                    Some(inv) => Attack::find_attack_for_player(inv).unwrap(),
+                    None => Attack::find_attack_for_monster(*entity).unwrap(),
+                },
+                Goal::ExecuteSkill(_, skill) | Goal::ExecuteSkillAt(_, skill) => *skill,
+                Goal::PickUp(_) => continue,
             };
 
-            if !matches!(moving.0, MovementGoal::Location(_)) {
+            if !matches!(moving.0, MovementGoal::Location(_) | MovementGoal::Path(_)) {
                 // If we aren't moving to a specific location, we aren't moving to a target.
                 // Thus, we probably cancelled the attack.
                 mind.cancel();
                 continue;
             }
 
-            let Ok(entity_location) = target_query.get(target.0) else {
-                // Target probably died. We might need to send some "invalid target" response here?
-                mind.cancel();
-                state.request_transition(Idle);
-                continue;
+            let (target, target_location) = match goal {
+                Goal::Attack(target) | Goal::ExecuteSkill(target, _) => {
+                    let Some(entity_location) = target_position(&target_query, target.0, Some(skill)) else {
+                        // Target probably died. We might need to send some "invalid target" response here?
+                        mind.cancel();
+                        state.request_transition(Idle);
+                        continue;
+                    };
+                    (ActionTarget::Entity(target.0), entity_location.location())
+                },
+                Goal::ExecuteSkillAt(location, _) => (ActionTarget::Location(*location), *location),
+                Goal::PickUp(_) => continue,
             };
 
             let mut process = AttackProcess::new(
@@ -228,9 +289,10 @@ pub(crate) fn update_action(
                 skill,
                 inventory.and_then(|inv| inv.get_equipment_item(EquipmentSlot::Weapon)),
                 target,
-                entity_location,
+                target_location,
                 &navmesh,
-            );
+            )
+            .between_players(pvp_standings(&standing_query, own_entity, target));
 
             match process.try_attack() {
                 Ok(_) => {
@@ -248,6 +310,12 @@ pub(crate) fn update_action(
                             AttackSkillError::SkillNotFound => {
                                 client.send(PerformActionResponse::Stop(PerformActionError::NotLearned));
                             },
+                            AttackSkillError::Unreachable => {
+                                client.send(PerformActionResponse::Stop(PerformActionError::ObstacleInPath));
+                            },
+                            AttackSkillError::NotAllowed => {
+                                client.send(PerformActionResponse::Stop(PerformActionError::InvalidTarget));
+                            },
                         }
                     } else {
                         warn!("Couldn't execute attack for monster");
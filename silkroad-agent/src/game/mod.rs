@@ -7,14 +7,18 @@ use crate::comp::player::StatPoints;
 use crate::comp::pos::Position;
 use crate::comp::skill::SkillBook;
 use crate::comp::{Health, Mana};
+use crate::config::GameConfig;
 use crate::event::{
-    DamageReceiveEvent, EntityDeath, LoadingFinishedEvent, PlayerLevelUp, SpawnMonster, UniqueKilledEvent,
+    DamageReceiveEvent, EntityDeath, LoadingFinishedEvent, PlayerLevelUp, ResurrectEvent, SpawnMonster,
+    UniqueKilledEvent,
 };
 use crate::ext::ActionIdCounter;
 use crate::game::action::handle_action;
+use crate::game::consumable::{tick_item_cooldowns, use_item};
 use crate::game::damage::{attack_player, handle_damage};
 use crate::game::daylight::{advance_daylight, DaylightCycle};
-use crate::game::drop::{create_drops, tick_drop, SpawnDrop};
+use crate::game::death::{apply_death_penalty, handle_resurrect_input, resurrect};
+use crate::game::drop::{create_drops, drop_items, tick_drop, tick_loot_priority, SpawnDrop};
 use crate::game::exp::{
     distribute_experience, receive_experience, reset_health_mana_on_level, update_max_hp_mp_on_stat_change,
     ReceiveExperienceEvent,
@@ -27,11 +31,13 @@ use crate::game::mastery::{handle_mastery_levelup, learn_skill};
 use crate::game::mind::MindPlugin;
 use crate::game::movement::movement_monster;
 use crate::game::player_activity::{update_player_activity, PlayerActivity};
+use crate::game::regeneration::{mark_combat, regenerate};
 use crate::game::spawn::do_spawn_mobs;
 use crate::game::stats::increase_stats;
 use crate::game::target::{deselect_despawned, player_update_target};
 use crate::game::unique::{setup_unique_timers, unique_killed, unique_spawned, update_timers};
 use crate::game::visibility::{clear_visibility, player_visibility_update, visibility_update};
+use crate::game::weather::{advance_weather, update_player_weather, WeatherCycle};
 use crate::persistence::AppPersistanceExt;
 use crate::sync::SynchronizationStage;
 use bevy_app::{App, Last, Plugin, PostUpdate, PreUpdate, Startup, Update};
@@ -39,8 +45,11 @@ use bevy_ecs::prelude::*;
 
 mod action;
 pub(crate) mod attack;
+pub(crate) mod combat;
+mod consumable;
 mod damage;
 mod daylight;
+pub(crate) mod death;
 pub(crate) mod drop;
 pub(crate) mod exp;
 mod gold;
@@ -51,20 +60,29 @@ mod mastery;
 pub(crate) mod mind;
 mod movement;
 pub(crate) mod player_activity;
+mod regeneration;
 mod spawn;
 mod stats;
 pub(crate) mod target;
 mod unique;
 mod visibility;
+pub(crate) mod weather;
 
 pub(crate) struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
+        let weather = app
+            .world
+            .get_resource::<GameConfig>()
+            .expect("Game config should exist.")
+            .weather
+            .clone();
         app.add_plugins(ChatPlugin)
             .add_plugins(MindPlugin)
             .insert_resource(PlayerActivity::default())
             .insert_resource(DaylightCycle::official())
+            .insert_resource(WeatherCycle::new(weather))
             .insert_resource(ActionIdCounter::default())
             .add_event::<PlayerLevelUp>()
             .add_event::<LoadingFinishedEvent>()
@@ -72,6 +90,7 @@ impl Plugin for GamePlugin {
             .add_event::<SpawnDrop>()
             .add_event::<DamageReceiveEvent>()
             .add_event::<EntityDeath>()
+            .add_event::<ResurrectEvent>()
             .add_event::<ReceiveExperienceEvent>()
             .add_event::<SpawnMonster>()
             .add_systems(Startup, setup_unique_timers)
@@ -83,21 +102,25 @@ impl Plugin for GamePlugin {
                     increase_stats,
                     visibility_update,
                     movement_monster,
-                    tick_drop,
-                    handle_logout,
+                    (tick_drop, tick_loot_priority),
+                    (handle_logout, tick_logout),
                     handle_action,
-                    tick_logout,
-                    player_update_target,
-                    deselect_despawned,
+                    (tick_item_cooldowns, use_item.after(tick_item_cooldowns)),
+                    (player_update_target, deselect_despawned),
                     handle_damage,
                     attack_player,
                     distribute_experience.after(handle_damage),
-                    drop_gold.after(handle_damage),
+                    (drop_gold, drop_items).after(handle_damage),
                     receive_experience.after(distribute_experience),
+                    (
+                        apply_death_penalty.after(handle_damage),
+                        handle_resurrect_input,
+                        resurrect.after(handle_resurrect_input).before(receive_experience),
+                    ),
                     reset_health_mana_on_level.after(receive_experience),
                     update_max_hp_mp_on_stat_change.after(increase_stats),
-                    handle_mastery_levelup,
-                    learn_skill,
+                    (handle_mastery_levelup, learn_skill),
+                    (mark_combat, regenerate.after(mark_combat)),
                     do_spawn_mobs,
                 ),
             )
@@ -109,6 +132,7 @@ impl Plugin for GamePlugin {
                     unique_spawned,
                     unique_killed,
                     advance_daylight,
+                    (advance_weather, update_player_weather.after(advance_weather)),
                     create_drops,
                 ),
             )
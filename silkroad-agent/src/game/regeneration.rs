@@ -0,0 +1,77 @@
+use crate::agent::states::{Dead, Sitting};
+use crate::comp::exp::Leveled;
+use crate::comp::player::StatPoints;
+use crate::comp::regeneration::Regeneration;
+use crate::comp::{Health, Mana};
+use crate::event::DamageReceiveEvent;
+use bevy_ecs::prelude::*;
+use bevy_time::Time;
+
+/// How much faster an entity regenerates while sitting down.
+const SITTING_MULTIPLIER: u32 = 3;
+
+pub(crate) fn mark_combat(mut damage_events: EventReader<DamageReceiveEvent>, mut query: Query<&mut Regeneration>) {
+    for event in damage_events.read() {
+        for entity in [event.source.0, event.target.0] {
+            if let Ok(mut regeneration) = query.get_mut(entity) {
+                regeneration.enter_combat();
+            }
+        }
+    }
+}
+
+pub(crate) fn regenerate(
+    time: Res<Time>,
+    mut query: Query<
+        (
+            &mut Regeneration,
+            &mut Health,
+            &mut Mana,
+            &Leveled,
+            &StatPoints,
+            Has<Sitting>,
+        ),
+        Without<Dead>,
+    >,
+) {
+    for (mut regeneration, mut health, mut mana, level, stat_points, sitting) in query.iter_mut() {
+        if !regeneration.tick(time.delta()) {
+            continue;
+        }
+
+        let level = level.current_level();
+        let stats = stat_points.stats();
+        // Only touch the components that actually change, to avoid sending needless updates.
+        if !health.is_full() {
+            let amount = regeneration_amount(health.max_health, level, stats.strength(), sitting);
+            health.regenerate(amount);
+        }
+        if !mana.is_full() {
+            let amount = regeneration_amount(mana.max_mana, level, stats.intelligence(), sitting);
+            mana.regenerate(amount);
+        }
+    }
+}
+
+/// Calculates how much health or mana gets restored in a single regeneration tick. The amount scales with the
+/// maximum, the level, and the stat associated with it, i.e. strength for health and intelligence for mana.
+fn regeneration_amount(max: u32, level: u8, stat: u16, sitting: bool) -> u32 {
+    let amount = max / 100 + level as u32 + stat as u32 / 10;
+    if sitting {
+        amount * SITTING_MULTIPLIER
+    } else {
+        amount
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_regeneration_amount() {
+        assert_eq!(regeneration_amount(1000, 10, 50, false), 25);
+        assert_eq!(regeneration_amount(1000, 10, 50, true), 75);
+        assert_eq!(regeneration_amount(0, 1, 0, false), 1);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::agent::states::StateTransitionQueue;
 use crate::agent::{Agent, MovementState};
+use crate::buff::Buffed;
 use crate::comp::damage::DamageReceiver;
 use crate::comp::monster::{Monster, MonsterAiBundle, MonsterBundle, RandomStroll, SpawnedBy};
 use crate::comp::pos::Position;
@@ -55,6 +56,7 @@ pub(crate) fn do_spawn_mobs(
             state_queue: StateTransitionQueue::default(),
             movement_state: MovementState::default_monster(),
             damage_receiver: DamageReceiver::default(),
+            buffed: Buffed::default(),
         });
 
         if event.with_ai {
@@ -3,8 +3,10 @@ use crate::comp::net::Client;
 use crate::comp::npc::NPC;
 use crate::comp::player::Player;
 use crate::comp::pos::Position;
-use crate::comp::Health;
+use crate::comp::{GameEntity, Health};
 use crate::input::PlayerInput;
+use crate::shop::talk_options_of;
+use crate::teleport::TeleportBuilding;
 use crate::world::EntityLookup;
 use bevy_ecs::prelude::*;
 use cgmath::MetricSpace;
@@ -28,10 +30,12 @@ pub(crate) fn player_update_target(
     mut cmd: Commands,
     lookup: Res<EntityLookup>,
     target_lookup: Query<(
+        &GameEntity,
         &Position,
         Option<&Health>,
         Option<&Monster>,
-        Option<&NPC>,
+        Has<NPC>,
+        Has<TeleportBuilding>,
         Option<&Player>,
     )>,
 ) {
@@ -39,7 +43,9 @@ pub(crate) fn player_update_target(
         'target: {
             if let Some(ref target) = input.target {
                 if let Some(target_entity) = lookup.get_entity_for_id(target.unique_id) {
-                    if let Ok((target_pos, health, monster, npc, player)) = target_lookup.get(target_entity) {
+                    if let Ok((target_game_entity, target_pos, health, monster, npc, teleport, player)) =
+                        target_lookup.get(target_entity)
+                    {
                         let distance = target_pos.position().distance2(pos.position().0);
                         if distance >= MAX_TARGET_DISTANCE {
                             // Is this an adequate response?
@@ -49,19 +55,25 @@ pub(crate) fn player_update_target(
                             break 'target; // TODO
                         }
 
-                        match (health, monster, npc, player) {
+                        match (health, monster, npc || teleport, player) {
                             (Some(health), Some(_), _, _) => {
                                 client.send(TargetEntityResponse::new(TargetEntityResult::success_monster(
                                     target.unique_id,
                                     health.current_health,
                                 )));
                             },
-                            (_, _, Some(_), _) => {
+                            (_, _, true, _) => {
                                 client.send(TargetEntityResponse::new(TargetEntityResult::success_npc(
                                     target.unique_id,
+                                    talk_options_of(target_game_entity.ref_id),
+                                )));
+                            },
+                            (Some(health), _, _, Some(_)) => {
+                                client.send(TargetEntityResponse::new(TargetEntityResult::success_player(
+                                    target.unique_id,
+                                    health.current_health,
                                 )));
                             },
-                            (Some(health), _, _, Some(player)) => {},
                             _ => {
                                 client.send(TargetEntityResponse::new(TargetEntityResult::failure(
                                     TargetEntityError::InvalidTarget,
@@ -1,4 +1,5 @@
 use crate::agent::Agent;
+use crate::buff::Buffed;
 use crate::comp::drop::Drop;
 use crate::comp::inventory::PlayerInventory;
 use crate::comp::monster::Monster;
@@ -9,20 +10,26 @@ use crate::comp::pos::Position;
 use crate::comp::visibility::{Invisible, Visibility};
 use crate::comp::{EntityReference, GameEntity};
 use crate::game::player_activity::PlayerActivity;
+use crate::guild::{GuildMember, Guilds};
+use crate::job::active_job;
+use crate::pvp::{PlayerKillRecord, PvpFlag};
+use crate::stall::{Stall, DEFAULT_DECORATION};
+use crate::teleport::{ReturnScrollCast, TeleportBuilding, Teleporting};
 use bevy_ecs::prelude::*;
 use cgmath::num_traits::Pow;
 use silkroad_data::DataEntry;
 use silkroad_definitions::Region;
-use silkroad_game_base::ItemTypeData;
+use silkroad_game_base::{ItemTypeData, MovementSpeed};
 use silkroad_navmesh::region::GridRegion;
 use silkroad_protocol::community::GuildInformation;
 use silkroad_protocol::inventory::CharacterSpawnItemData;
 use silkroad_protocol::spawn::{
     DroppedItemSource, EntityTypeSpawnData, GroupEntitySpawnData, GroupEntitySpawnEnd, GroupEntitySpawnStart,
-    GroupSpawnDataContent, GroupSpawnType, ItemSpawnData,
+    GroupSpawnDataContent, GroupSpawnType, ItemSpawnData, StallSpawnInformation,
 };
 use silkroad_protocol::world::{
-    ActionState, ActiveScroll, AliveState, BodyState, EntityState, InteractOptions, JobType, PlayerKillState, PvpCape,
+    ActionState, ActiveScroll, AliveState, BodyState, EntityState, InteractMode, InteractOptions, JobType,
+    PlayerKillState, PvpCape,
 };
 use std::collections::{BTreeMap, HashSet};
 use tracing::{instrument, trace};
@@ -32,7 +39,7 @@ static EMPTY_VEC: Vec<(Entity, &Position, &GameEntity)> = vec![];
 #[instrument(level = "trace", skip_all)]
 pub(crate) fn visibility_update(
     activity: Res<PlayerActivity>,
-    mut query: Query<(Entity, &GameEntity, &mut Visibility, &Position)>,
+    mut query: Query<(Entity, &GameEntity, &mut Visibility, &Position), Without<Teleporting>>,
     lookup: Query<(Entity, &Position, &GameEntity)>,
 ) {
     let grouped = lookup.iter().fold(
@@ -96,16 +103,40 @@ pub(crate) fn player_visibility_update(
             Option<&Monster>,
             Option<&Drop>,
             Option<&NPC>,
+            Option<&GuildMember>,
+            Option<&Buffed>,
+            Option<&Stall>,
+            Has<ReturnScrollCast>,
+            Has<TeleportBuilding>,
+            Option<&PvpFlag>,
+            Option<&PlayerKillRecord>,
         ),
         Without<Invisible>,
     >,
+    guilds: Res<Guilds>,
 ) {
     for (client, player, mut visibility) in query.iter_mut() {
         let mut spawns = Vec::new();
         for reference in visibility.added_entities.iter() {
             let added = reference.0;
             let entity = reference.1;
-            if let Ok((pos, inventory_opt, agent_opt, player_opt, monster_opt, item_opt, npc_opt)) = lookup.get(added) {
+            if let Ok((
+                pos,
+                inventory_opt,
+                agent_opt,
+                player_opt,
+                monster_opt,
+                item_opt,
+                npc_opt,
+                guild_opt,
+                buffed_opt,
+                stall_opt,
+                returning,
+                teleport,
+                pvp_flag_opt,
+                player_kills_opt,
+            )) = lookup.get(added)
+            {
                 if let Some(player) = player_opt {
                     let agent = agent_opt.unwrap();
                     let items = inventory_opt
@@ -124,7 +155,7 @@ pub(crate) fn player_visibility_update(
                             unique_id: entity.unique_id,
                             scale: player.character.scale,
                             berserk_level: 0,
-                            pvp_cape: PvpCape::None,
+                            pvp_cape: pvp_flag_opt.map(|flag| flag.0).unwrap_or(PvpCape::None),
                             beginner: player.character.beginner_mark,
                             title: 0,
                             inventory_size: inventory_opt.map(|inv| inv.size() as u8).unwrap_or(0),
@@ -134,25 +165,31 @@ pub(crate) fn player_visibility_update(
                             mask: None,
                             position: pos.as_protocol(),
                             movement: pos.as_standing(),
-                            entity_state: entity_state_from_agent(agent),
+                            entity_state: entity_state_from_agent(agent, buffed_opt),
                             name: player.character.name.clone(),
-                            job_type: JobType::None,
-                            pk_state: PlayerKillState::None,
+                            job_type: inventory_opt.map(|inv| active_job(inv)).unwrap_or(JobType::None),
+                            pk_state: player_kills_opt
+                                .map(|record| record.state())
+                                .unwrap_or(PlayerKillState::None),
                             mounted: false,
                             in_combat: false,
-                            active_scroll: ActiveScroll::None,
-                            unknown2: 0,
-                            guild: GuildInformation {
-                                name: "".to_string(),
-                                id: 0,
-                                member: "".to_string(),
-                                last_icon_rev: 0,
-                                union_id: 0,
-                                last_union_icon_rev: 0,
-                                is_friendly: 0,
-                                siege_unknown: 0,
+                            active_scroll: if returning {
+                                ActiveScroll::ReturnScroll
+                            } else {
+                                ActiveScroll::None
                             },
+                            interact_mode: if stall_opt.is_some() {
+                                InteractMode::Stall
+                            } else {
+                                InteractMode::None
+                            },
+                            guild: guild_opt
+                                .and_then(|membership| guilds.get(membership.0))
+                                .map(|guild| guild.spawn_information())
+                                .unwrap_or_else(GuildInformation::none),
                             unknown3: [0; 9],
+                            stall: stall_opt
+                                .map(|stall| StallSpawnInformation::new(stall.name.clone(), DEFAULT_DECORATION)),
                             equipment_cooldown: false,
                             unknown4: 0,
                         },
@@ -165,7 +202,7 @@ pub(crate) fn player_visibility_update(
                             unique_id: entity.unique_id,
                             position: pos.as_protocol(),
                             movement: pos.as_movement(),
-                            entity_state: entity_state_from_agent(agent),
+                            entity_state: entity_state_from_agent(agent, buffed_opt),
                             interaction_options: InteractOptions::talk(vec![5]),
                             rarity: monster.rarity,
                             unknown: 0,
@@ -185,10 +222,15 @@ pub(crate) fn player_visibility_update(
                             unique_id: entity.unique_id,
                             position: pos.as_protocol(),
                             movement: pos.as_standing(),
-                            entity_state: entity_state_from_agent(agent),
+                            entity_state: entity_state_from_agent(agent, buffed_opt),
                             interaction_options: InteractOptions::None,
                         },
                     ));
+                } else if teleport {
+                    spawns.push(GroupSpawnDataContent::spawn(
+                        entity.ref_id,
+                        EntityTypeSpawnData::teleport(entity.unique_id, pos.as_protocol()),
+                    ));
                 }
             }
         }
@@ -207,17 +249,17 @@ pub(crate) fn player_visibility_update(
     }
 }
 
-fn entity_state_from_agent(agent: &Agent) -> EntityState {
+fn entity_state_from_agent(agent: &Agent, buffed: Option<&Buffed>) -> EntityState {
     EntityState {
         alive: AliveState::Alive,
         unknown1: 0,
         action_state: ActionState::None,
         body_state: BodyState::None,
         unknown2: 0,
-        walk_speed: agent.walking_speed,
-        run_speed: agent.running_speed,
-        berserk_speed: agent.berserk_speed,
-        active_buffs: vec![],
+        walk_speed: agent.get_speed_value(MovementSpeed::Walking),
+        run_speed: agent.get_speed_value(MovementSpeed::Running),
+        berserk_speed: agent.get_speed_value(MovementSpeed::Berserk),
+        active_buffs: buffed.map(Buffed::as_active_buff_data).unwrap_or_default(),
     }
 }
 
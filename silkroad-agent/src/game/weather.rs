@@ -0,0 +1,179 @@
+use crate::comp::net::Client;
+use crate::comp::player::Player;
+use crate::comp::pos::Position;
+use crate::config::WeatherConfig;
+use bevy_ecs::prelude::*;
+use bevy_time::Time;
+use rand::{thread_rng, Rng};
+use silkroad_definitions::Region;
+use silkroad_game_base::SpawningState;
+use silkroad_protocol::world::{WeatherType, WeatherUpdate};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const MAX_WEATHER_SPEED: u8 = 100;
+
+#[derive(Copy, Clone)]
+struct AreaWeather {
+    kind: WeatherType,
+    speed: u8,
+    remaining: Duration,
+}
+
+impl AreaWeather {
+    fn roll<R: Rng>(config: &WeatherConfig, rng: &mut R) -> Self {
+        let clear = config.clear_chance.max(0.0);
+        let rain = config.rain_chance.max(0.0);
+        let snow = config.snow_chance.max(0.0);
+        let total = clear + rain + snow;
+        let kind = if total <= 0.0 {
+            WeatherType::Clear
+        } else {
+            let roll = rng.gen_range(0.0..total);
+            if roll < clear {
+                WeatherType::Clear
+            } else if roll < clear + rain {
+                WeatherType::Rain
+            } else {
+                WeatherType::Snow
+            }
+        };
+        let speed = match kind {
+            WeatherType::Clear => 0,
+            WeatherType::Rain | WeatherType::Snow => rng.gen_range(1..=MAX_WEATHER_SPEED),
+        };
+        let duration = rng.gen_range(config.min_duration..=config.max_duration.max(config.min_duration));
+        AreaWeather {
+            kind,
+            speed,
+            remaining: Duration::from_secs(duration),
+        }
+    }
+}
+
+/// The weather across the world, which is split into square areas of regions that each have their own weather. The
+/// weather of an area is only rolled once someone enters it and gets rolled again whenever its duration runs out.
+#[derive(Resource)]
+pub(crate) struct WeatherCycle {
+    config: WeatherConfig,
+    areas: HashMap<(u8, u8), AreaWeather>,
+}
+
+impl WeatherCycle {
+    pub(crate) fn new(config: WeatherConfig) -> Self {
+        Self {
+            config,
+            areas: HashMap::new(),
+        }
+    }
+
+    fn area_of(&self, region: Region) -> (u8, u8) {
+        let size = self.config.area_size.max(1);
+        (region.x() / size, region.y() / size)
+    }
+
+    /// Provides the weather and its speed in the given region. Dungeons are always clear.
+    pub(crate) fn weather_in(&mut self, region: Region) -> (WeatherType, u8) {
+        if region.is_dungeon() {
+            return (WeatherType::Clear, 0);
+        }
+
+        let area = self.area_of(region);
+        let config = &self.config;
+        let weather = self
+            .areas
+            .entry(area)
+            .or_insert_with(|| AreaWeather::roll(config, &mut thread_rng()));
+        (weather.kind, weather.speed)
+    }
+
+    pub(crate) fn advance(&mut self, amount: Duration) {
+        let config = &self.config;
+        let mut rng = thread_rng();
+        for weather in self.areas.values_mut() {
+            weather.remaining = weather.remaining.saturating_sub(amount);
+            if weather.remaining.is_zero() {
+                *weather = AreaWeather::roll(config, &mut rng);
+            }
+        }
+    }
+}
+
+/// The weather the player has last been informed about.
+#[derive(Component, Default)]
+pub(crate) struct KnownWeather(Option<(WeatherType, u8)>);
+
+pub(crate) fn advance_weather(mut cycle: ResMut<WeatherCycle>, time: Res<Time>) {
+    cycle.advance(time.delta());
+}
+
+pub(crate) fn update_player_weather(
+    mut query: Query<(&Client, &Player, &Position, &mut KnownWeather)>,
+    mut cycle: ResMut<WeatherCycle>,
+) {
+    for (client, player, position, mut known) in query.iter_mut() {
+        // Loading into the world, e.g. after a teleport, resets the weather on the client.
+        if player.character.state != SpawningState::Finished {
+            known.0 = None;
+            continue;
+        }
+
+        let weather = cycle.weather_in(position.position().region());
+        if known.0 != Some(weather) {
+            let (kind, speed) = weather;
+            client.send(WeatherUpdate::new(kind, speed));
+            known.0 = Some(weather);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config(clear_chance: f32, rain_chance: f32, snow_chance: f32) -> WeatherConfig {
+        WeatherConfig {
+            area_size: 4,
+            clear_chance,
+            rain_chance,
+            snow_chance,
+            min_duration: 10,
+            max_duration: 10,
+        }
+    }
+
+    #[test]
+    pub fn test_only_rolls_possible_weather() {
+        let mut cycle = WeatherCycle::new(config(0.0, 0.0, 1.0));
+        let (kind, speed) = cycle.weather_in(Region::from_xy(100, 90));
+        assert_eq!(kind, WeatherType::Snow);
+        assert!(speed > 0);
+
+        assert_eq!(cycle.weather_in(Region::new(0x8001)), (WeatherType::Clear, 0));
+
+        let mut cycle = WeatherCycle::new(config(0.0, 0.0, 0.0));
+        assert_eq!(cycle.weather_in(Region::from_xy(100, 90)).0, WeatherType::Clear);
+    }
+
+    #[test]
+    pub fn test_areas_share_weather_until_rerolled() {
+        let mut cycle = WeatherCycle::new(config(0.0, 1.0, 0.0));
+        let weather = cycle.weather_in(Region::from_xy(100, 90));
+        assert_eq!(cycle.weather_in(Region::from_xy(103, 91)), weather);
+        assert_eq!(cycle.areas.len(), 1);
+
+        cycle.weather_in(Region::from_xy(104, 91));
+        assert_eq!(cycle.areas.len(), 2);
+
+        cycle.advance(Duration::from_secs(6));
+        assert!(cycle
+            .areas
+            .values()
+            .all(|area| area.remaining == Duration::from_secs(4)));
+        cycle.advance(Duration::from_secs(4));
+        assert!(cycle
+            .areas
+            .values()
+            .all(|area| area.remaining == Duration::from_secs(10)));
+    }
+}
@@ -0,0 +1,284 @@
+use crate::guild::db::DbGuild;
+use bevy_ecs::prelude::*;
+use bevy_time::{Timer, TimerMode};
+use derive_more::{Deref, DerefMut};
+use silkroad_protocol::community::GuildInformation;
+use silkroad_protocol::guild::{GuildInfo, GuildMemberInfo, GuildRank};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::oneshot::Receiver;
+
+const MIN_NAME_LENGTH: usize = 3;
+const MAX_NAME_LENGTH: usize = 12;
+
+pub(crate) fn is_valid_guild_name(name: &str) -> bool {
+    let length = name.chars().count();
+    (MIN_NAME_LENGTH..=MAX_NAME_LENGTH).contains(&length) && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+pub(crate) struct GuildMemberEntry {
+    pub(crate) character_id: u32,
+    pub(crate) name: String,
+    pub(crate) ref_id: u32,
+    pub(crate) level: u8,
+    pub(crate) rank: GuildRank,
+    /// The entity of the member while they are logged in.
+    pub(crate) entity: Option<Entity>,
+}
+
+impl GuildMemberEntry {
+    pub(crate) fn as_info(&self) -> GuildMemberInfo {
+        GuildMemberInfo::new(
+            self.character_id,
+            self.name.clone(),
+            self.rank,
+            self.level,
+            self.ref_id,
+            self.entity.is_some(),
+        )
+    }
+}
+
+pub(crate) struct Guild {
+    id: u32,
+    name: String,
+    level: u8,
+    notice_title: String,
+    notice_body: String,
+    members: Vec<GuildMemberEntry>,
+}
+
+impl Guild {
+    pub(crate) fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub(crate) fn members(&self) -> &[GuildMemberEntry] {
+        &self.members
+    }
+
+    pub(crate) fn find_member(&self, character_id: u32) -> Option<&GuildMemberEntry> {
+        self.members.iter().find(|member| member.character_id == character_id)
+    }
+
+    pub(crate) fn find_member_mut(&mut self, character_id: u32) -> Option<&mut GuildMemberEntry> {
+        self.members
+            .iter_mut()
+            .find(|member| member.character_id == character_id)
+    }
+
+    pub(crate) fn find_member_by_name(&self, name: &str) -> Option<&GuildMemberEntry> {
+        self.members
+            .iter()
+            .find(|member| member.name.eq_ignore_ascii_case(name))
+    }
+
+    pub(crate) fn rank_of(&self, character_id: u32) -> Option<GuildRank> {
+        self.find_member(character_id).map(|member| member.rank)
+    }
+
+    pub(crate) fn add_member(&mut self, member: GuildMemberEntry) {
+        if self.find_member(member.character_id).is_none() {
+            self.members.push(member);
+        }
+    }
+
+    pub(crate) fn remove_member(&mut self, character_id: u32) -> Option<GuildMemberEntry> {
+        let index = self
+            .members
+            .iter()
+            .position(|member| member.character_id == character_id)?;
+        Some(self.members.remove(index))
+    }
+
+    pub(crate) fn set_rank(&mut self, character_id: u32, rank: GuildRank) -> bool {
+        match self.find_member_mut(character_id) {
+            Some(member) => {
+                member.rank = rank;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Marks the member as online with the given entity, or offline if no entity is given.
+    pub(crate) fn set_online(&mut self, character_id: u32, entity: Option<Entity>) {
+        if let Some(member) = self.find_member_mut(character_id) {
+            member.entity = entity;
+        }
+    }
+
+    pub(crate) fn online_members(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.members.iter().filter_map(|member| member.entity)
+    }
+
+    pub(crate) fn set_notice(&mut self, title: String, body: String) {
+        self.notice_title = title;
+        self.notice_body = body;
+    }
+
+    pub(crate) fn as_info(&self) -> GuildInfo {
+        GuildInfo::new(
+            self.id,
+            self.name.clone(),
+            self.level,
+            self.notice_title.clone(),
+            self.notice_body.clone(),
+            self.members.iter().map(GuildMemberEntry::as_info).collect(),
+        )
+    }
+
+    /// The guild information that is shown to other players for members of this guild.
+    pub(crate) fn spawn_information(&self) -> GuildInformation {
+        GuildInformation::new(self.name.clone(), self.id, String::new(), 0, 0, 0, 0)
+    }
+}
+
+impl From<DbGuild> for Guild {
+    fn from(value: DbGuild) -> Self {
+        Guild {
+            id: value.data.id as u32,
+            name: value.data.name,
+            level: value.data.level as u8,
+            notice_title: value.data.notice_title,
+            notice_body: value.data.notice_body,
+            members: value
+                .members
+                .into_iter()
+                .map(|member| GuildMemberEntry {
+                    character_id: member.character_id as u32,
+                    name: member.charname,
+                    ref_id: member.character_type as u32,
+                    level: member.level as u8,
+                    rank: member.rank.into(),
+                    entity: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Contains all guilds that currently have at least one member online.
+#[derive(Resource, Default)]
+pub(crate) struct Guilds {
+    guilds: HashMap<u32, Guild>,
+}
+
+impl Guilds {
+    pub(crate) fn get(&self, id: u32) -> Option<&Guild> {
+        self.guilds.get(&id)
+    }
+
+    pub(crate) fn get_mut(&mut self, id: u32) -> Option<&mut Guild> {
+        self.guilds.get_mut(&id)
+    }
+
+    /// Returns the already loaded guild, if present, as it might have changed since the given data was loaded.
+    pub(crate) fn get_or_insert(&mut self, guild: DbGuild) -> &mut Guild {
+        self.guilds
+            .entry(guild.data.id as u32)
+            .or_insert_with(|| Guild::from(guild))
+    }
+
+    pub(crate) fn remove(&mut self, id: u32) -> Option<Guild> {
+        self.guilds.remove(&id)
+    }
+}
+
+/// Marks an entity as being a member of the guild with the given id.
+#[derive(Component, Copy, Clone)]
+#[component(storage = "SparseSet")]
+pub(crate) struct GuildMember(pub u32);
+
+/// An open invitation for the entity to join the guild of the inviter.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct GuildInvitation {
+    pub(crate) inviter: Entity,
+    pub(crate) guild: u32,
+    pub(crate) timeout: Timer,
+}
+
+impl GuildInvitation {
+    pub(crate) fn new(inviter: Entity, guild: u32, timeout: Duration) -> Self {
+        GuildInvitation {
+            inviter,
+            guild,
+            timeout: Timer::new(timeout, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Component, Deref, DerefMut)]
+#[component(storage = "SparseSet")]
+pub(crate) struct GuildLoading(pub(crate) Receiver<Result<Option<DbGuild>, sqlx::Error>>);
+
+/// A guild creation that is still being persisted. If it fails, the creation cost is refunded.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct GuildCreation {
+    pub(crate) task: Receiver<Result<DbGuild, sqlx::Error>>,
+    pub(crate) cost: u64,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn member(character_id: u32, rank: GuildRank) -> GuildMemberEntry {
+        GuildMemberEntry {
+            character_id,
+            name: format!("Member{}", character_id),
+            ref_id: 1907,
+            level: 20,
+            rank,
+            entity: None,
+        }
+    }
+
+    fn guild() -> Guild {
+        Guild {
+            id: 1,
+            name: "Test".to_string(),
+            level: 1,
+            notice_title: String::new(),
+            notice_body: String::new(),
+            members: vec![member(1, GuildRank::Master)],
+        }
+    }
+
+    #[test]
+    pub fn test_guild_names() {
+        assert!(is_valid_guild_name("Skrillax"));
+        assert!(!is_valid_guild_name("Ab"));
+        assert!(!is_valid_guild_name("ThisNameIsTooLong"));
+        assert!(!is_valid_guild_name("No Spaces"));
+    }
+
+    #[test]
+    pub fn test_member_management() {
+        let mut guild = guild();
+        guild.add_member(member(2, GuildRank::Member));
+        guild.add_member(member(2, GuildRank::Member));
+        assert_eq!(guild.members().len(), 2);
+        assert!(guild.find_member_by_name("member2").is_some());
+
+        assert!(guild.set_rank(2, GuildRank::Officer));
+        assert_eq!(guild.rank_of(2), Some(GuildRank::Officer));
+
+        guild.set_online(2, Some(Entity::from_raw(5)));
+        assert_eq!(guild.online_members().collect::<Vec<_>>(), vec![Entity::from_raw(5)]);
+
+        assert!(guild.remove_member(2).is_some());
+        assert!(guild.rank_of(2).is_none());
+        assert_eq!(guild.online_members().count(), 0);
+    }
+
+    #[test]
+    pub fn test_rank_hierarchy() {
+        assert!(GuildRank::Master.outranks(GuildRank::Officer));
+        assert!(GuildRank::Officer.outranks(GuildRank::Member));
+        assert!(!GuildRank::Officer.outranks(GuildRank::Officer));
+        assert!(!GuildRank::Member.can_manage());
+    }
+}
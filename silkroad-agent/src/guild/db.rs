@@ -0,0 +1,181 @@
+use silkroad_protocol::guild::GuildRank;
+use sqlx::{Error, PgPool};
+use std::borrow::Borrow;
+
+#[derive(sqlx::Type, Copy, Clone, Debug)]
+#[sqlx(type_name = "guild_rank")]
+#[sqlx(rename_all = "lowercase")]
+pub(crate) enum DbGuildRank {
+    Master,
+    Officer,
+    Member,
+}
+
+impl From<GuildRank> for DbGuildRank {
+    fn from(value: GuildRank) -> Self {
+        match value {
+            GuildRank::Master => Self::Master,
+            GuildRank::Officer => Self::Officer,
+            GuildRank::Member => Self::Member,
+        }
+    }
+}
+
+impl From<DbGuildRank> for GuildRank {
+    fn from(value: DbGuildRank) -> Self {
+        match value {
+            DbGuildRank::Master => GuildRank::Master,
+            DbGuildRank::Officer => GuildRank::Officer,
+            DbGuildRank::Member => GuildRank::Member,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Clone)]
+pub(crate) struct DbGuildData {
+    pub(crate) id: i32,
+    pub(crate) name: String,
+    pub(crate) level: i16,
+    pub(crate) notice_title: String,
+    pub(crate) notice_body: String,
+}
+
+#[derive(sqlx::FromRow, Clone)]
+pub(crate) struct DbGuildMember {
+    pub(crate) character_id: i32,
+    pub(crate) charname: String,
+    pub(crate) character_type: i32,
+    pub(crate) level: i16,
+    pub(crate) rank: DbGuildRank,
+}
+
+pub(crate) struct DbGuild {
+    pub(crate) data: DbGuildData,
+    pub(crate) members: Vec<DbGuildMember>,
+}
+
+async fn load_members<T: Borrow<PgPool>>(guild_id: i32, pool: T) -> Result<Vec<DbGuildMember>, Error> {
+    sqlx::query_as!(
+        DbGuildMember,
+        "SELECT guild_members.character_id, characters.charname, characters.character_type, characters.level, guild_members.rank as \"rank!: DbGuildRank\" FROM guild_members JOIN characters ON characters.id = guild_members.character_id WHERE guild_members.guild_id = $1 AND characters.deletion_end IS NULL ORDER BY guild_members.joined_at ASC",
+        guild_id
+    )
+    .fetch_all(pool.borrow())
+    .await
+}
+
+/// Loads the guild the given character is a member of, if any, including all of its members.
+pub(crate) async fn load_guild_of<T: Borrow<PgPool>>(character_id: u32, pool: T) -> Result<Option<DbGuild>, Error> {
+    let data = sqlx::query_as!(
+        DbGuildData,
+        "SELECT guilds.id, guilds.name, guilds.level, guilds.notice_title, guilds.notice_body FROM guilds JOIN guild_members ON guild_members.guild_id = guilds.id WHERE guild_members.character_id = $1",
+        character_id as i32
+    )
+    .fetch_optional(pool.borrow())
+    .await?;
+
+    let Some(data) = data else {
+        return Ok(None);
+    };
+
+    let members = load_members(data.id, pool).await?;
+    Ok(Some(DbGuild { data, members }))
+}
+
+/// Creates a new guild with the given character as its master.
+pub(crate) async fn create_guild<T: Borrow<PgPool>>(
+    character_id: u32,
+    name: String,
+    pool: T,
+) -> Result<DbGuild, Error> {
+    let mut transaction = pool.borrow().begin().await?;
+    let data = sqlx::query_as!(
+        DbGuildData,
+        "INSERT INTO guilds(name) VALUES($1) RETURNING id, name, level, notice_title, notice_body",
+        name
+    )
+    .fetch_one(&mut *transaction)
+    .await?;
+    sqlx::query!(
+        "INSERT INTO guild_members(guild_id, character_id, rank) VALUES($1, $2, 'master')",
+        data.id,
+        character_id as i32
+    )
+    .execute(&mut *transaction)
+    .await?;
+    transaction.commit().await?;
+
+    let members = load_members(data.id, pool).await?;
+    Ok(DbGuild { data, members })
+}
+
+pub(crate) async fn delete_guild<T: Borrow<PgPool>>(guild_id: u32, pool: T) -> Result<(), Error> {
+    sqlx::query!("DELETE FROM guilds WHERE id = $1", guild_id as i32)
+        .execute(pool.borrow())
+        .await?;
+    Ok(())
+}
+
+pub(crate) async fn add_guild_member<T: Borrow<PgPool>>(
+    guild_id: u32,
+    character_id: u32,
+    pool: T,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "INSERT INTO guild_members(guild_id, character_id) VALUES($1, $2)",
+        guild_id as i32,
+        character_id as i32
+    )
+    .execute(pool.borrow())
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn remove_guild_member<T: Borrow<PgPool>>(
+    guild_id: u32,
+    character_id: u32,
+    pool: T,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "DELETE FROM guild_members WHERE guild_id = $1 AND character_id = $2",
+        guild_id as i32,
+        character_id as i32
+    )
+    .execute(pool.borrow())
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn update_guild_member_rank<T: Borrow<PgPool>>(
+    guild_id: u32,
+    character_id: u32,
+    rank: GuildRank,
+    pool: T,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE guild_members SET rank = $3 WHERE guild_id = $1 AND character_id = $2",
+        guild_id as i32,
+        character_id as i32,
+        DbGuildRank::from(rank) as DbGuildRank
+    )
+    .execute(pool.borrow())
+    .await?;
+    Ok(())
+}
+
+pub(crate) async fn update_guild_notice<T: Borrow<PgPool>>(
+    guild_id: u32,
+    title: String,
+    body: String,
+    pool: T,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "UPDATE guilds SET notice_title = $2, notice_body = $3 WHERE id = $1",
+        guild_id as i32,
+        title,
+        body
+    )
+    .execute(pool.borrow())
+    .await?;
+    Ok(())
+}
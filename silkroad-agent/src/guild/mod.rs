@@ -0,0 +1,33 @@
+use crate::guild::system::{
+    guild_member_offline, handle_guild_creation, handle_guild_invitation_responses, handle_guild_invites,
+    handle_guild_management, handle_guild_membership, load_guild, receive_created_guild, receive_guild,
+};
+use bevy_app::{App, Plugin, PostUpdate, PreUpdate, Update};
+use bevy_ecs::prelude::*;
+pub(crate) use component::*;
+
+mod component;
+mod db;
+mod system;
+
+pub(crate) struct GuildPlugin;
+
+impl Plugin for GuildPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Guilds::default())
+            .add_systems(PreUpdate, guild_member_offline)
+            .add_systems(
+                Update,
+                (
+                    receive_guild,
+                    handle_guild_creation,
+                    receive_created_guild.after(handle_guild_creation),
+                    handle_guild_invites.after(receive_guild),
+                    handle_guild_invitation_responses.after(handle_guild_invites),
+                    handle_guild_membership.after(handle_guild_invitation_responses),
+                    handle_guild_management.after(handle_guild_membership),
+                ),
+            )
+            .add_systems(PostUpdate, load_guild);
+    }
+}
@@ -0,0 +1,673 @@
+use crate::comp::exp::Leveled;
+use crate::comp::gold::GoldPouch;
+use crate::comp::net::Client;
+use crate::comp::player::Player;
+use crate::comp::visibility::Visibility;
+use crate::comp::GameEntity;
+use crate::config::GameConfig;
+use crate::event::{ClientDisconnectedEvent, LoadingFinishedEvent};
+use crate::exchange::ExchangeInvitation;
+use crate::ext::DbPool;
+use crate::guild::db::{
+    add_guild_member, create_guild, delete_guild, load_guild_of, remove_guild_member, update_guild_member_rank,
+    update_guild_notice,
+};
+use crate::guild::{
+    is_valid_guild_name, Guild, GuildCreation, GuildInvitation, GuildLoading, GuildMember, GuildMemberEntry, Guilds,
+};
+use crate::input::PlayerInput;
+use crate::party::PartyInvitation;
+use crate::tasks::TaskCreator;
+use crate::world::EntityLookup;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::Has;
+use bevy_time::Time;
+use silkroad_protocol::community::GuildInformation;
+use silkroad_protocol::guild::{
+    CreateGuildResponse, DisbandGuildResponse, GuildEntityUpdate, GuildErrorCode, GuildLeaveReason, GuildRank,
+    GuildResult, GuildUpdate, InviteToGuildResponse, KickFromGuildResponse, LeaveGuildResponse,
+    SetGuildMemberRankResponse, UpdateGuildNoticeResponse,
+};
+use silkroad_protocol::party::PlayerInvitationRequest;
+use silkroad_protocol::ServerPacket;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::oneshot::error::TryRecvError;
+use tracing::{error, warn};
+
+type InvitationTargets<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Client,
+        Has<GuildMember>,
+        Has<GuildInvitation>,
+        Has<PartyInvitation>,
+        Has<ExchangeInvitation>,
+    ),
+    With<Player>,
+>;
+
+type MemberData<'a> = (&'a Client, &'a GameEntity, &'a Visibility);
+
+fn persist<F>(task_creator: &TaskCreator, guild_id: u32, task: F)
+where
+    F: Future<Output = Result<(), sqlx::Error>> + Send + 'static,
+{
+    task_creator.spawn(async move {
+        if let Err(e) = task.await {
+            error!(error = %e, guild_id = guild_id, "Could not update guild");
+        }
+    });
+}
+
+fn send_to_guild<T: Into<ServerPacket> + Clone>(guild: &Guild, packet: T, clients: &Query<&Client>) {
+    for member in guild.online_members() {
+        if let Ok(client) = clients.get(member) {
+            client.send(packet.clone());
+        }
+    }
+}
+
+/// Shows the new guild of the entity to all players that can currently see it.
+fn announce_guild(
+    visibility: &Visibility,
+    game_entity: &GameEntity,
+    guild: GuildInformation,
+    clients: &Query<&Client>,
+) {
+    let update = GuildEntityUpdate::new(game_entity.unique_id, guild);
+    for other in visibility.entities_in_radius.iter() {
+        if let Ok(client) = clients.get(other.0) {
+            client.send(update.clone());
+        }
+    }
+}
+
+/// Marks the member as being online, notifying the other members and the surrounding players.
+/// Returns `false` if the character is no longer a member of the guild.
+fn bring_online(
+    guild: &mut Guild,
+    entity: Entity,
+    (client, game_entity, visibility): MemberData,
+    player: &Player,
+    level: &Leveled,
+    clients: &Query<&Client>,
+) -> bool {
+    let character_id = player.character.id;
+    if guild.find_member(character_id).is_none() {
+        return false;
+    }
+
+    send_to_guild(guild, GuildUpdate::MemberOnline { character_id }, clients);
+    if let Some(member) = guild.find_member_mut(character_id) {
+        member.entity = Some(entity);
+        member.level = level.current_level();
+    }
+
+    client.send(guild.as_info());
+    announce_guild(visibility, game_entity, guild.spawn_information(), clients);
+    true
+}
+
+fn remove_from_guild(
+    guild: &mut Guild,
+    character_id: u32,
+    reason: GuildLeaveReason,
+    members: &Query<MemberData>,
+    clients: &Query<&Client>,
+    cmd: &mut Commands,
+) {
+    let Some(removed) = guild.remove_member(character_id) else {
+        return;
+    };
+
+    let update = GuildUpdate::member_left(character_id, reason);
+    send_to_guild(guild, update.clone(), clients);
+
+    let Some(entity) = removed.entity else {
+        return;
+    };
+
+    if let Some(mut entity_commands) = cmd.get_entity(entity) {
+        entity_commands.remove::<GuildMember>();
+    }
+
+    if let Ok((client, game_entity, visibility)) = members.get(entity) {
+        client.send(update);
+        announce_guild(visibility, game_entity, GuildInformation::none(), clients);
+    }
+}
+
+fn find_invitable<'a>(
+    target_id: u32,
+    inviter: Entity,
+    lookup: &EntityLookup,
+    targets: &'a InvitationTargets,
+    invited: &HashSet<Entity>,
+) -> Result<(Entity, &'a Client), GuildErrorCode> {
+    let target = lookup
+        .get_entity_for_id(target_id)
+        .filter(|target| *target != inviter)
+        .ok_or(GuildErrorCode::InvalidTarget)?;
+    let (client, in_guild, has_guild_invitation, has_party_invitation, has_exchange_invitation) =
+        targets.get(target).map_err(|_| GuildErrorCode::InvalidTarget)?;
+    if in_guild {
+        return Err(GuildErrorCode::AlreadyInGuild);
+    }
+
+    if has_guild_invitation || has_party_invitation || has_exchange_invitation || invited.contains(&target) {
+        return Err(GuildErrorCode::TargetBusy);
+    }
+
+    Ok((target, client))
+}
+
+pub(crate) fn load_guild(
+    mut events: EventReader<LoadingFinishedEvent>,
+    query: Query<&Player>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+    mut cmd: Commands,
+) {
+    for event in events.read() {
+        let Ok(player) = query.get(event.0) else {
+            continue;
+        };
+
+        let task = task_creator.create_task(load_guild_of(player.character.id, PgPool::clone(&pool)));
+        cmd.entity(event.0).insert(GuildLoading(task));
+    }
+}
+
+pub(crate) fn receive_guild(
+    mut query: Query<(
+        Entity,
+        &Client,
+        &GameEntity,
+        &Visibility,
+        &Player,
+        &Leveled,
+        &mut GuildLoading,
+    )>,
+    clients: Query<&Client>,
+    mut guilds: ResMut<Guilds>,
+    mut cmd: Commands,
+) {
+    for (entity, client, game_entity, visibility, player, level, mut loading) in query.iter_mut() {
+        let guild = match loading.try_recv() {
+            Ok(Ok(guild)) => guild,
+            Ok(Err(e)) => {
+                error!(error = %e, character_id = player.character.id, "Could not load guild");
+                None
+            },
+            Err(TryRecvError::Empty) => continue,
+            Err(e) => {
+                warn!(character_id = player.character.id, "Error when loading guild. {:?}", e);
+                None
+            },
+        };
+
+        cmd.entity(entity).remove::<GuildLoading>();
+        let Some(guild) = guild else {
+            continue;
+        };
+
+        let id = guild.data.id as u32;
+        let guild = guilds.get_or_insert(guild);
+        if bring_online(
+            guild,
+            entity,
+            (client, game_entity, visibility),
+            player,
+            level,
+            &clients,
+        ) {
+            cmd.entity(entity).insert(GuildMember(id));
+        } else if guild.online_members().next().is_none() {
+            // The character got removed from the guild while we were loading it.
+            guilds.remove(id);
+        }
+    }
+}
+
+pub(crate) fn handle_guild_creation(
+    mut query: Query<(
+        Entity,
+        &Client,
+        &Player,
+        &Leveled,
+        &PlayerInput,
+        &mut GoldPouch,
+        Has<GuildMember>,
+        Has<GuildCreation>,
+    )>,
+    settings: Res<GameConfig>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+    mut cmd: Commands,
+) {
+    let config = &settings.guild;
+    for (entity, client, player, level, input, mut gold, in_guild, creating) in query.iter_mut() {
+        let Some(ref create) = input.guild_create else {
+            continue;
+        };
+
+        let error = if in_guild || creating {
+            Some(GuildErrorCode::AlreadyInGuild)
+        } else if level.current_level() < config.min_level {
+            Some(GuildErrorCode::LevelTooLow)
+        } else if !is_valid_guild_name(&create.name) {
+            Some(GuildErrorCode::InvalidName)
+        } else if gold.amount() < config.creation_cost {
+            Some(GuildErrorCode::NotEnoughGold)
+        } else {
+            None
+        };
+
+        if let Some(code) = error {
+            client.send(CreateGuildResponse::new(GuildResult::error(code)));
+            continue;
+        }
+
+        gold.spend(config.creation_cost);
+        let task = task_creator.create_task(create_guild(
+            player.character.id,
+            create.name.clone(),
+            PgPool::clone(&pool),
+        ));
+        cmd.entity(entity).insert(GuildCreation {
+            task,
+            cost: config.creation_cost,
+        });
+    }
+}
+
+pub(crate) fn receive_created_guild(
+    mut query: Query<(
+        Entity,
+        &Client,
+        &GameEntity,
+        &Visibility,
+        &Player,
+        &Leveled,
+        &mut GoldPouch,
+        &mut GuildCreation,
+    )>,
+    clients: Query<&Client>,
+    mut guilds: ResMut<Guilds>,
+    mut cmd: Commands,
+) {
+    for (entity, client, game_entity, visibility, player, level, mut gold, mut creation) in query.iter_mut() {
+        let result = match creation.task.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => continue,
+            Err(TryRecvError::Closed) => Err(sqlx::Error::WorkerCrashed),
+        };
+
+        cmd.entity(entity).remove::<GuildCreation>();
+        match result {
+            Ok(created) => {
+                let id = created.data.id as u32;
+                let guild = guilds.get_or_insert(created);
+                client.send(CreateGuildResponse::new(GuildResult::Success));
+                bring_online(
+                    guild,
+                    entity,
+                    (client, game_entity, visibility),
+                    player,
+                    level,
+                    &clients,
+                );
+                cmd.entity(entity).insert(GuildMember(id));
+            },
+            Err(e) => {
+                gold.gain(creation.cost);
+                let code = match e {
+                    sqlx::Error::Database(ref db_error) if db_error.is_unique_violation() => GuildErrorCode::NameTaken,
+                    e => {
+                        error!(error = %e, character_id = player.character.id, "Could not create guild");
+                        GuildErrorCode::NotAllowed
+                    },
+                };
+                client.send(CreateGuildResponse::new(GuildResult::error(code)));
+            },
+        }
+    }
+}
+
+pub(crate) fn handle_guild_invites(
+    query: Query<(
+        Entity,
+        &Client,
+        &GameEntity,
+        &Player,
+        &PlayerInput,
+        Option<&GuildMember>,
+    )>,
+    targets: InvitationTargets,
+    lookup: Res<EntityLookup>,
+    guilds: Res<Guilds>,
+    settings: Res<GameConfig>,
+    mut cmd: Commands,
+) {
+    let timeout = Duration::from_secs(settings.guild.invitation_timeout);
+    let mut invited = HashSet::new();
+    for (entity, client, game_entity, player, input, membership) in query.iter() {
+        let Some(ref invite) = input.guild_invite else {
+            continue;
+        };
+
+        let target = match membership.and_then(|membership| guilds.get(membership.0)) {
+            None => Err(GuildErrorCode::NotInGuild),
+            Some(guild) if !guild.rank_of(player.character.id).is_some_and(|rank| rank.can_manage()) => {
+                Err(GuildErrorCode::NotAllowed)
+            },
+            Some(guild) if guild.members().len() >= settings.guild.max_members => Err(GuildErrorCode::GuildFull),
+            Some(guild) => find_invitable(invite.target, entity, &lookup, &targets, &invited)
+                .map(|(target, target_client)| (guild.id(), target, target_client)),
+        };
+
+        match target {
+            Ok((guild, target, target_client)) => {
+                invited.insert(target);
+                target_client.send(PlayerInvitationRequest::guild_invitation(game_entity.unique_id));
+                cmd.entity(target).insert(GuildInvitation::new(entity, guild, timeout));
+            },
+            Err(code) => client.send(InviteToGuildResponse::new(GuildResult::error(code))),
+        }
+    }
+}
+
+pub(crate) fn handle_guild_invitation_responses(
+    mut query: Query<(
+        Entity,
+        &Client,
+        &GameEntity,
+        &Visibility,
+        &Player,
+        &Leveled,
+        &PlayerInput,
+        Has<GuildMember>,
+        &mut GuildInvitation,
+    )>,
+    clients: Query<&Client>,
+    mut guilds: ResMut<Guilds>,
+    settings: Res<GameConfig>,
+    (task_creator, pool): (Res<TaskCreator>, Res<DbPool>),
+    time: Res<Time>,
+    mut cmd: Commands,
+) {
+    let delta = time.delta();
+    for (entity, client, game_entity, visibility, player, level, input, in_guild, mut invitation) in query.iter_mut() {
+        let accepted = match input.invitation_response {
+            Some(response) => response.accepted(),
+            None if invitation.timeout.tick(delta).finished() => false,
+            None => continue,
+        };
+
+        cmd.entity(entity).remove::<GuildInvitation>();
+        let result = if !accepted {
+            Err(GuildErrorCode::Declined)
+        } else if in_guild {
+            Err(GuildErrorCode::AlreadyInGuild)
+        } else {
+            match guilds.get_mut(invitation.guild) {
+                Some(guild) if !guild.online_members().any(|member| member == invitation.inviter) => {
+                    Err(GuildErrorCode::NotInGuild)
+                },
+                Some(guild) if guild.members().len() >= settings.guild.max_members => Err(GuildErrorCode::GuildFull),
+                Some(guild) => {
+                    let member = GuildMemberEntry {
+                        character_id: player.character.id,
+                        name: player.character.name.clone(),
+                        ref_id: game_entity.ref_id,
+                        level: level.current_level(),
+                        rank: GuildRank::Member,
+                        entity: Some(entity),
+                    };
+                    send_to_guild(guild, GuildUpdate::MemberJoined(member.as_info()), &clients);
+                    guild.add_member(member);
+                    client.send(guild.as_info());
+                    announce_guild(visibility, game_entity, guild.spawn_information(), &clients);
+                    cmd.entity(entity).insert(GuildMember(guild.id()));
+                    persist(
+                        &task_creator,
+                        guild.id(),
+                        add_guild_member(guild.id(), player.character.id, PgPool::clone(&pool)),
+                    );
+                    Ok(())
+                },
+                None => Err(GuildErrorCode::NotInGuild),
+            }
+        };
+
+        if let Ok(inviter_client) = clients.get(invitation.inviter) {
+            let result = match result {
+                Ok(_) => GuildResult::Success,
+                Err(code) => GuildResult::error(code),
+            };
+            inviter_client.send(InviteToGuildResponse::new(result));
+        }
+    }
+}
+
+pub(crate) fn handle_guild_membership(
+    query: Query<(&Client, &Player, &PlayerInput, Option<&GuildMember>)>,
+    members: Query<MemberData>,
+    clients: Query<&Client>,
+    mut guilds: ResMut<Guilds>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+    mut cmd: Commands,
+) {
+    for (client, player, input, membership) in query.iter() {
+        let character_id = player.character.id;
+
+        if input.guild_leave.is_some() {
+            let result = match membership.and_then(|membership| guilds.get_mut(membership.0)) {
+                None => Err(GuildErrorCode::NotInGuild),
+                Some(guild) if guild.rank_of(character_id) == Some(GuildRank::Master) => {
+                    Err(GuildErrorCode::MasterCannotLeave)
+                },
+                Some(guild) => {
+                    remove_from_guild(
+                        guild,
+                        character_id,
+                        GuildLeaveReason::Left,
+                        &members,
+                        &clients,
+                        &mut cmd,
+                    );
+                    persist(
+                        &task_creator,
+                        guild.id(),
+                        remove_guild_member(guild.id(), character_id, PgPool::clone(&pool)),
+                    );
+                    Ok(())
+                },
+            };
+
+            match result {
+                Ok(_) => client.send(LeaveGuildResponse::new(GuildResult::Success)),
+                Err(code) => client.send(LeaveGuildResponse::new(GuildResult::error(code))),
+            }
+        }
+
+        if let Some(ref kick) = input.guild_kick {
+            let result = match membership.and_then(|membership| guilds.get_mut(membership.0)) {
+                None => Err(GuildErrorCode::NotInGuild),
+                Some(guild) => match (guild.rank_of(character_id), guild.find_member_by_name(&kick.name)) {
+                    (_, None) => Err(GuildErrorCode::InvalidTarget),
+                    (Some(rank), Some(target)) if rank.outranks(target.rank) => {
+                        let target = target.character_id;
+                        remove_from_guild(guild, target, GuildLeaveReason::Kicked, &members, &clients, &mut cmd);
+                        persist(
+                            &task_creator,
+                            guild.id(),
+                            remove_guild_member(guild.id(), target, PgPool::clone(&pool)),
+                        );
+                        Ok(())
+                    },
+                    _ => Err(GuildErrorCode::NotAllowed),
+                },
+            };
+
+            match result {
+                Ok(_) => client.send(KickFromGuildResponse::new(GuildResult::Success)),
+                Err(code) => client.send(KickFromGuildResponse::new(GuildResult::error(code))),
+            }
+        }
+
+        if input.guild_disband.is_some() {
+            let result = match membership.and_then(|membership| guilds.get(membership.0)) {
+                None => Err(GuildErrorCode::NotInGuild),
+                Some(guild) if guild.rank_of(character_id) != Some(GuildRank::Master) => {
+                    Err(GuildErrorCode::NotAllowed)
+                },
+                Some(guild) => Ok(guild.id()),
+            };
+
+            let id = match result {
+                Ok(id) => id,
+                Err(code) => {
+                    client.send(DisbandGuildResponse::new(GuildResult::error(code)));
+                    continue;
+                },
+            };
+
+            if let Some(guild) = guilds.remove(id) {
+                for member in guild.online_members() {
+                    if let Some(mut entity_commands) = cmd.get_entity(member) {
+                        entity_commands.remove::<GuildMember>();
+                    }
+
+                    if let Ok((member_client, game_entity, visibility)) = members.get(member) {
+                        member_client.send(GuildUpdate::Disbanded);
+                        announce_guild(visibility, game_entity, GuildInformation::none(), &clients);
+                    }
+                }
+            }
+
+            persist(&task_creator, id, delete_guild(id, PgPool::clone(&pool)));
+            client.send(DisbandGuildResponse::new(GuildResult::Success));
+        }
+    }
+}
+
+pub(crate) fn handle_guild_management(
+    query: Query<(&Client, &Player, &PlayerInput, Option<&GuildMember>)>,
+    clients: Query<&Client>,
+    mut guilds: ResMut<Guilds>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+) {
+    for (client, player, input, membership) in query.iter() {
+        let character_id = player.character.id;
+
+        if let Some(ref notice) = input.guild_notice {
+            let result = match membership.and_then(|membership| guilds.get_mut(membership.0)) {
+                None => Err(GuildErrorCode::NotInGuild),
+                Some(guild) if !guild.rank_of(character_id).is_some_and(|rank| rank.can_manage()) => {
+                    Err(GuildErrorCode::NotAllowed)
+                },
+                Some(guild) => {
+                    guild.set_notice(notice.title.clone(), notice.body.clone());
+                    send_to_guild(
+                        guild,
+                        GuildUpdate::NoticeChanged {
+                            title: notice.title.clone(),
+                            body: notice.body.clone(),
+                        },
+                        &clients,
+                    );
+                    persist(
+                        &task_creator,
+                        guild.id(),
+                        update_guild_notice(
+                            guild.id(),
+                            notice.title.clone(),
+                            notice.body.clone(),
+                            PgPool::clone(&pool),
+                        ),
+                    );
+                    Ok(())
+                },
+            };
+
+            match result {
+                Ok(_) => client.send(UpdateGuildNoticeResponse::new(GuildResult::Success)),
+                Err(code) => client.send(UpdateGuildNoticeResponse::new(GuildResult::error(code))),
+            }
+        }
+
+        if let Some(ref change) = input.guild_rank {
+            let result = match membership.and_then(|membership| guilds.get_mut(membership.0)) {
+                None => Err(GuildErrorCode::NotInGuild),
+                Some(guild) if guild.rank_of(character_id) != Some(GuildRank::Master) => {
+                    Err(GuildErrorCode::NotAllowed)
+                },
+                // Passing on the leadership of the guild is not supported.
+                Some(_) if change.rank == GuildRank::Master || change.character_id == character_id => {
+                    Err(GuildErrorCode::NotAllowed)
+                },
+                Some(guild) if guild.find_member(change.character_id).is_none() => Err(GuildErrorCode::InvalidTarget),
+                Some(guild) => {
+                    guild.set_rank(change.character_id, change.rank);
+                    send_to_guild(
+                        guild,
+                        GuildUpdate::RankChanged {
+                            character_id: change.character_id,
+                            rank: change.rank,
+                        },
+                        &clients,
+                    );
+                    persist(
+                        &task_creator,
+                        guild.id(),
+                        update_guild_member_rank(guild.id(), change.character_id, change.rank, PgPool::clone(&pool)),
+                    );
+                    Ok(())
+                },
+            };
+
+            match result {
+                Ok(_) => client.send(SetGuildMemberRankResponse::new(GuildResult::Success)),
+                Err(code) => client.send(SetGuildMemberRankResponse::new(GuildResult::error(code))),
+            }
+        }
+    }
+}
+
+pub(crate) fn guild_member_offline(
+    mut events: EventReader<ClientDisconnectedEvent>,
+    query: Query<(&Player, &GuildMember)>,
+    clients: Query<&Client>,
+    mut guilds: ResMut<Guilds>,
+) {
+    for event in events.read() {
+        let Ok((player, membership)) = query.get(event.0) else {
+            continue;
+        };
+
+        let Some(guild) = guilds.get_mut(membership.0) else {
+            continue;
+        };
+
+        guild.set_online(player.character.id, None);
+        if guild.online_members().next().is_none() {
+            guilds.remove(membership.0);
+            continue;
+        }
+
+        send_to_guild(
+            guild,
+            GuildUpdate::MemberOffline {
+                character_id: player.character.id,
+            },
+            &clients,
+        );
+    }
+}
@@ -1,14 +1,30 @@
 use bevy_ecs::prelude::*;
 use silkroad_game_base::StatType;
+use silkroad_protocol::alchemy::AlchemyRequest;
 use silkroad_protocol::auth::{AuthRequest, LogoutRequest};
 use silkroad_protocol::character::{CharacterJoinRequest, CharacterListRequestAction};
 use silkroad_protocol::chat::ChatMessage;
 use silkroad_protocol::combat::PerformAction;
+use silkroad_protocol::community::{
+    AddFriend, CreateFriendGroup, DeleteFriend, DeleteFriendGroup, FriendRequestAnswer, MoveFriendToGroup,
+};
+use silkroad_protocol::exchange::{ApproveExchange, CancelExchange, ConfirmExchange, StartExchange};
 use silkroad_protocol::gm::GmCommand;
-use silkroad_protocol::inventory::InventoryOperation;
-use silkroad_protocol::movement::{MovementTarget, Rotation};
+use silkroad_protocol::guild::{
+    CreateGuild, DisbandGuild, InviteToGuild, KickFromGuild, LeaveGuild, SetGuildMemberRank, UpdateGuildNotice,
+};
+use silkroad_protocol::inventory::{
+    ConsignmentAbort, ConsignmentBuy, ConsignmentList, ConsignmentRegister, ConsignmentSearch, ConsignmentSettle,
+    InventoryOperation, OpenStorage, UseItem,
+};
+use silkroad_protocol::movement::{CharacterAction, MovementTarget, Rotation};
+use silkroad_protocol::party::{CreateParty, InviteToParty, KickFromParty, LeaveParty, PlayerInvitationResponse};
+use silkroad_protocol::pvp::PvpCapeRequest;
 use silkroad_protocol::skill::{LearnSkill, LevelUpMastery};
-use silkroad_protocol::world::{TargetEntity, UnTargetEntity};
+use silkroad_protocol::stall::{BuyFromStall, CloseStall, LeaveStall, OpenStall, UpdateStall, VisitStall};
+use silkroad_protocol::world::{
+    DesignateReturnPoint, Resurrect, TalkToNpc, TargetEntity, TeleportConfirm, TeleportRequest, UnTargetEntity,
+};
 use std::mem;
 
 #[derive(Component, Default)]
@@ -16,15 +32,59 @@ pub(crate) struct PlayerInput {
     pub logout: Option<LogoutRequest>,
     pub target: Option<TargetEntity>,
     pub untarget: Option<UnTargetEntity>,
+    pub talk: Option<TalkToNpc>,
     pub chat: Vec<ChatMessage>,
     pub action: Option<PerformAction>,
     pub movement: Option<MovementTarget>,
     pub rotation: Option<Rotation>,
+    pub character_action: Option<CharacterAction>,
+    pub resurrect: Option<Resurrect>,
+    pub teleport_confirm: Option<TeleportConfirm>,
+    pub designate_return: Option<DesignateReturnPoint>,
+    pub teleport_request: Option<TeleportRequest>,
     pub inventory: Option<InventoryOperation>,
+    pub use_item: Option<UseItem>,
+    pub consignment_list: Option<ConsignmentList>,
+    pub consignment_register: Option<ConsignmentRegister>,
+    pub consignment_abort: Option<ConsignmentAbort>,
+    pub consignment_settle: Option<ConsignmentSettle>,
+    pub consignment_search: Option<ConsignmentSearch>,
+    pub consignment_buy: Option<ConsignmentBuy>,
     pub gm: Option<GmCommand>,
     pub mastery: Option<LevelUpMastery>,
     pub skill_add: Option<LearnSkill>,
     pub increase_stats: Vec<StatType>,
+    pub party_create: Option<CreateParty>,
+    pub party_invite: Option<InviteToParty>,
+    pub party_leave: Option<LeaveParty>,
+    pub party_kick: Option<KickFromParty>,
+    pub invitation_response: Option<PlayerInvitationResponse>,
+    pub friend_add: Option<AddFriend>,
+    pub friend_answer: Option<FriendRequestAnswer>,
+    pub friend_delete: Option<DeleteFriend>,
+    pub friend_group_create: Option<CreateFriendGroup>,
+    pub friend_group_delete: Option<DeleteFriendGroup>,
+    pub friend_move: Option<MoveFriendToGroup>,
+    pub guild_create: Option<CreateGuild>,
+    pub guild_disband: Option<DisbandGuild>,
+    pub guild_invite: Option<InviteToGuild>,
+    pub guild_leave: Option<LeaveGuild>,
+    pub guild_kick: Option<KickFromGuild>,
+    pub guild_notice: Option<UpdateGuildNotice>,
+    pub guild_rank: Option<SetGuildMemberRank>,
+    pub stall_open: Option<OpenStall>,
+    pub stall_close: Option<CloseStall>,
+    pub stall_update: Vec<UpdateStall>,
+    pub stall_visit: Option<VisitStall>,
+    pub stall_leave: Option<LeaveStall>,
+    pub stall_buy: Option<BuyFromStall>,
+    pub storage_open: Option<OpenStorage>,
+    pub alchemy: Option<AlchemyRequest>,
+    pub exchange_start: Option<StartExchange>,
+    pub exchange_confirm: Option<ConfirmExchange>,
+    pub exchange_approve: Option<ApproveExchange>,
+    pub exchange_cancel: Option<CancelExchange>,
+    pub pvp_cape: Option<PvpCapeRequest>,
 }
 
 impl PlayerInput {
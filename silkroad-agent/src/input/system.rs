@@ -9,7 +9,6 @@ use silkroad_game_base::StatType;
 use silkroad_network::stream::{SendResult, Stream, StreamError};
 use silkroad_protocol::character::GameGuideResponse;
 use silkroad_protocol::general::IdentityInformation;
-use silkroad_protocol::inventory::ConsignmentResponse;
 use silkroad_protocol::ClientPacket;
 use std::time::Instant;
 use tracing::warn;
@@ -45,6 +44,21 @@ pub(crate) fn receive_game_inputs(
                         ClientPacket::Rotation(rotate) => {
                             input.rotation = Some(*rotate);
                         },
+                        ClientPacket::CharacterAction(action) => {
+                            input.character_action = Some(*action);
+                        },
+                        ClientPacket::Resurrect(resurrect) => {
+                            input.resurrect = Some(*resurrect);
+                        },
+                        ClientPacket::TeleportConfirm(confirm) => {
+                            input.teleport_confirm = Some(*confirm);
+                        },
+                        ClientPacket::DesignateReturnPoint(designate) => {
+                            input.designate_return = Some(*designate);
+                        },
+                        ClientPacket::TeleportRequest(request) => {
+                            input.teleport_request = Some(*request);
+                        },
                         ClientPacket::PlayerMovementRequest(request) => {
                             input.movement = Some(request.kind);
                         },
@@ -57,6 +71,9 @@ pub(crate) fn receive_game_inputs(
                         ClientPacket::UnTargetEntity(untarget) => {
                             input.untarget = Some(*untarget);
                         },
+                        ClientPacket::TalkToNpc(talk) => {
+                            input.talk = Some(*talk);
+                        },
                         ClientPacket::PerformAction(action) => {
                             input.action = Some(*action);
                         },
@@ -69,12 +86,21 @@ pub(crate) fn receive_game_inputs(
                         ClientPacket::InventoryOperation(inventory) => {
                             input.inventory = Some(*inventory);
                         },
-                        ClientPacket::ConsignmentList(_) => {
-                            client.send(ConsignmentResponse::success_empty());
-                        },
-                        ClientPacket::AddFriend(_) => {},
-                        ClientPacket::CreateFriendGroup(_) => {},
-                        ClientPacket::DeleteFriend(_) => {},
+                        ClientPacket::UseItem(use_item) => {
+                            input.use_item = Some(*use_item);
+                        },
+                        ClientPacket::ConsignmentList(list) => input.consignment_list = Some(*list),
+                        ClientPacket::ConsignmentRegister(register) => input.consignment_register = Some(*register),
+                        ClientPacket::ConsignmentAbort(abort) => input.consignment_abort = Some(*abort),
+                        ClientPacket::ConsignmentSettle(settle) => input.consignment_settle = Some(*settle),
+                        ClientPacket::ConsignmentSearch(search) => input.consignment_search = Some(*search),
+                        ClientPacket::ConsignmentBuy(buy) => input.consignment_buy = Some(*buy),
+                        ClientPacket::AddFriend(add) => input.friend_add = Some(*add),
+                        ClientPacket::FriendRequestAnswer(answer) => input.friend_answer = Some(*answer),
+                        ClientPacket::CreateFriendGroup(group) => input.friend_group_create = Some(*group),
+                        ClientPacket::DeleteFriend(delete) => input.friend_delete = Some(*delete),
+                        ClientPacket::DeleteFriendGroup(group) => input.friend_group_delete = Some(*group),
+                        ClientPacket::MoveFriendToGroup(move_friend) => input.friend_move = Some(*move_friend),
                         ClientPacket::UpdateGameGuide(guide) => {
                             client.send(GameGuideResponse::Success(guide.0));
                         },
@@ -87,6 +113,33 @@ pub(crate) fn receive_game_inputs(
                         ClientPacket::LearnSkill(skill) => input.skill_add = Some(*skill),
                         ClientPacket::IncreaseStr(_) => input.increase_stats.push(StatType::STR),
                         ClientPacket::IncreaseInt(_) => input.increase_stats.push(StatType::INT),
+                        ClientPacket::CreateParty(party) => input.party_create = Some(*party),
+                        ClientPacket::InviteToParty(invite) => input.party_invite = Some(*invite),
+                        ClientPacket::LeaveParty(leave) => input.party_leave = Some(*leave),
+                        ClientPacket::KickFromParty(kick) => input.party_kick = Some(*kick),
+                        ClientPacket::PlayerInvitationResponse(response) => {
+                            input.invitation_response = Some(*response);
+                        },
+                        ClientPacket::CreateGuild(create) => input.guild_create = Some(*create),
+                        ClientPacket::DisbandGuild(disband) => input.guild_disband = Some(*disband),
+                        ClientPacket::InviteToGuild(invite) => input.guild_invite = Some(*invite),
+                        ClientPacket::LeaveGuild(leave) => input.guild_leave = Some(*leave),
+                        ClientPacket::KickFromGuild(kick) => input.guild_kick = Some(*kick),
+                        ClientPacket::UpdateGuildNotice(notice) => input.guild_notice = Some(*notice),
+                        ClientPacket::SetGuildMemberRank(rank) => input.guild_rank = Some(*rank),
+                        ClientPacket::OpenStall(open) => input.stall_open = Some(*open),
+                        ClientPacket::CloseStall(close) => input.stall_close = Some(*close),
+                        ClientPacket::UpdateStall(update) => input.stall_update.push(*update),
+                        ClientPacket::VisitStall(visit) => input.stall_visit = Some(*visit),
+                        ClientPacket::LeaveStall(leave) => input.stall_leave = Some(*leave),
+                        ClientPacket::BuyFromStall(buy) => input.stall_buy = Some(*buy),
+                        ClientPacket::OpenStorage(open) => input.storage_open = Some(*open),
+                        ClientPacket::AlchemyRequest(request) => input.alchemy = Some(*request),
+                        ClientPacket::StartExchange(start) => input.exchange_start = Some(*start),
+                        ClientPacket::ConfirmExchange(confirm) => input.exchange_confirm = Some(*confirm),
+                        ClientPacket::ApproveExchange(approve) => input.exchange_approve = Some(*approve),
+                        ClientPacket::CancelExchange(cancel) => input.exchange_cancel = Some(*cancel),
+                        ClientPacket::PvpCapeRequest(request) => input.pvp_cape = Some(*request),
                         _ => {},
                     }
                 },
@@ -0,0 +1,193 @@
+use crate::persistence::ApplyToDatabase;
+use axum::async_trait;
+use bevy_ecs::prelude::*;
+use silkroad_data::level::LevelMap;
+use silkroad_definitions::inventory::EquipmentSlot;
+use silkroad_definitions::type_id::{ObjectEquippable, ObjectFlagType, ObjectItem, ObjectType};
+use silkroad_game_base::{ChangeProvided, Inventory, Item};
+use silkroad_protocol::world::JobType;
+use sqlx::PgPool;
+
+/// The side an account chose in the conflict between hunters and thieves, which is shared by all of its characters
+/// on the server. Traders are on the side of the hunters.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) enum JobAlignment {
+    None,
+    Thief,
+    Hunter,
+}
+
+impl JobAlignment {
+    /// Provides the alignment from the job as it is stored for the user on a server.
+    pub(crate) fn from_id(id: i16) -> Self {
+        match id {
+            1 => JobAlignment::Thief,
+            2 => JobAlignment::Hunter,
+            _ => JobAlignment::None,
+        }
+    }
+
+    pub(crate) fn id(&self) -> i16 {
+        match self {
+            JobAlignment::None => 0,
+            JobAlignment::Thief => 1,
+            JobAlignment::Hunter => 2,
+        }
+    }
+
+    pub(crate) fn allows(&self, job: JobType) -> bool {
+        match job {
+            JobType::None => true,
+            JobType::Thief => *self == JobAlignment::Thief,
+            JobType::Trader | JobType::Hunter => *self == JobAlignment::Hunter,
+        }
+    }
+}
+
+/// Provides the job the item is the suit of, if it is a job suit at all.
+pub(crate) fn job_of_suit(item: &Item) -> Option<JobType> {
+    let ObjectType::Item(ObjectItem::Equippable(ObjectEquippable::Flag(flag))) =
+        ObjectType::from_type_id(&item.reference.common.type_id)?
+    else {
+        return None;
+    };
+
+    match flag {
+        ObjectFlagType::OldTrader | ObjectFlagType::NewTrader => Some(JobType::Trader),
+        ObjectFlagType::OldThief => Some(JobType::Thief),
+        ObjectFlagType::OldHunter | ObjectFlagType::NewHunter => Some(JobType::Hunter),
+        ObjectFlagType::PvpSuit => None,
+    }
+}
+
+/// Provides the job a player is currently practicing, which is given by the job suit they're wearing.
+pub(crate) fn active_job(inventory: &Inventory) -> JobType {
+    inventory
+        .get_equipment_item(EquipmentSlot::Special)
+        .and_then(job_of_suit)
+        .unwrap_or(JobType::None)
+}
+
+/// Thieves prey on traders and hunters, while those fight back against the thieves.
+pub(crate) fn are_hostile(job: JobType, other: JobType) -> bool {
+    matches!(
+        (job, other),
+        (JobType::Thief, JobType::Trader | JobType::Hunter) | (JobType::Trader | JobType::Hunter, JobType::Thief)
+    )
+}
+
+/// Provides the job experience required to advance from the given level, if there is a level to advance to.
+pub(crate) fn required_job_experience(levels: &LevelMap, job: JobType, level: u8) -> Option<u64> {
+    let level = levels.get(&level)?;
+    let required = match job {
+        JobType::None => return None,
+        JobType::Trader => level.job_exp_trader,
+        JobType::Thief => level.job_exp_thief,
+        JobType::Hunter => level.job_exp_hunter,
+    };
+    u64::try_from(required).ok().filter(|required| *required > 0)
+}
+
+#[derive(Component)]
+pub(crate) struct JobExperience {
+    level: u8,
+    experience: u64,
+}
+
+impl JobExperience {
+    pub(crate) fn new(level: u8, experience: u64) -> Self {
+        JobExperience { level, experience }
+    }
+
+    pub(crate) fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub(crate) fn experience(&self) -> u64 {
+        self.experience
+    }
+
+    /// Adds the experience and advances as many levels as it covers. Once there is no further level, the experience
+    /// stops accumulating. Returns `true` if the level changed.
+    pub(crate) fn receive(&mut self, amount: u64, required: impl Fn(u8) -> Option<u64>) -> bool {
+        let previous_level = self.level;
+        let mut experience = self.experience + amount;
+        let mut next_required = required(self.level);
+        while let Some(needed) = next_required.filter(|needed| experience >= *needed) {
+            experience -= needed;
+            self.level += 1;
+            next_required = required(self.level);
+        }
+
+        self.experience = if next_required.is_some() { experience } else { 0 };
+        self.level != previous_level
+    }
+}
+
+pub(crate) struct JobExperienceChange {
+    level: u8,
+    experience: u64,
+}
+
+impl ChangeProvided for JobExperience {
+    type Change = JobExperienceChange;
+
+    fn as_change(&self) -> Self::Change {
+        JobExperienceChange {
+            level: self.level,
+            experience: self.experience,
+        }
+    }
+}
+
+#[async_trait]
+impl ApplyToDatabase for JobExperienceChange {
+    async fn apply(&self, character_id: u32, pool: &PgPool) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE characters SET job_level = $1, job_exp = $2 WHERE id = $3",
+            self.level as i16,
+            self.experience as i64,
+            character_id as i32
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_alignment_decides_suits() {
+        assert!(JobAlignment::Hunter.allows(JobType::Trader));
+        assert!(JobAlignment::Hunter.allows(JobType::Hunter));
+        assert!(!JobAlignment::Hunter.allows(JobType::Thief));
+        assert!(JobAlignment::Thief.allows(JobType::Thief));
+        assert!(!JobAlignment::Thief.allows(JobType::Trader));
+        assert!(!JobAlignment::None.allows(JobType::Hunter));
+        assert_eq!(JobAlignment::from_id(JobAlignment::Thief.id()), JobAlignment::Thief);
+
+        assert!(are_hostile(JobType::Thief, JobType::Trader));
+        assert!(are_hostile(JobType::Hunter, JobType::Thief));
+        assert!(!are_hostile(JobType::Hunter, JobType::Trader));
+        assert!(!are_hostile(JobType::Thief, JobType::None));
+    }
+
+    #[test]
+    pub fn test_experience_advances_levels() {
+        let required = |level: u8| if level < 4 { Some(100) } else { None };
+        let mut job = JobExperience::new(1, 50);
+        assert!(!job.receive(20, required));
+        assert_eq!(job.experience(), 70);
+
+        assert!(job.receive(140, required));
+        assert_eq!(job.level(), 3);
+        assert_eq!(job.experience(), 10);
+
+        assert!(job.receive(500, required));
+        assert_eq!(job.level(), 4);
+        assert_eq!(job.experience(), 0);
+    }
+}
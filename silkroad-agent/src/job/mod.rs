@@ -0,0 +1,16 @@
+use crate::job::system::reward_job_kills;
+use crate::persistence::AppPersistanceExt;
+use bevy_app::{App, Plugin, Update};
+pub(crate) use component::*;
+
+mod component;
+mod system;
+
+pub(crate) struct JobPlugin;
+
+impl Plugin for JobPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, reward_job_kills)
+            .track_change_component::<JobExperience>();
+    }
+}
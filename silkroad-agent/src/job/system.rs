@@ -0,0 +1,37 @@
+use crate::comp::inventory::PlayerInventory;
+use crate::comp::net::Client;
+use crate::config::GameConfig;
+use crate::event::EntityDeath;
+use crate::job::component::{active_job, are_hostile, required_job_experience, JobExperience};
+use crate::world::WorldData;
+use bevy_ecs::prelude::*;
+use silkroad_protocol::world::JobExperienceUpdate;
+
+pub(crate) fn reward_job_kills(
+    mut death_events: EventReader<EntityDeath>,
+    mut killers: Query<(&Client, &PlayerInventory, &mut JobExperience)>,
+    victims: Query<&PlayerInventory>,
+    settings: Res<GameConfig>,
+) {
+    for event in death_events.read() {
+        let Some(killer) = event.killer else {
+            continue;
+        };
+        let Ok(victim_inventory) = victims.get(event.died.0) else {
+            continue;
+        };
+        let Ok((client, killer_inventory, mut job)) = killers.get_mut(killer.0) else {
+            continue;
+        };
+
+        let killer_job = active_job(killer_inventory);
+        if !are_hostile(killer_job, active_job(victim_inventory)) {
+            continue;
+        }
+
+        job.receive(settings.job.kill_experience, |level| {
+            required_job_experience(WorldData::levels(), killer_job, level)
+        });
+        client.send(JobExperienceUpdate::new(job.level(), job.experience() as u32));
+    }
+}
@@ -8,16 +8,20 @@ use crate::comp::visibility::Visibility;
 use crate::comp::{GameEntity, Playing};
 use crate::config::GameConfig;
 use crate::db::character::{CharacterData, CharacterItem, DbRace};
+use crate::db::user::ServerUser;
 use crate::ext::{DbPool, EntityIdPool};
 use crate::input::LoginInput;
+use crate::job::{active_job, JobAlignment, JobExperience};
 use crate::login::character_loader::DbCharacter;
 use crate::login::job_distribution::JobDistribution;
 use crate::login::{
     CharacterCheckName, CharacterCreate, CharacterDelete, CharacterRestore, CharacterSelect, CharactersLoading,
 };
 use crate::population::{LoginQueue, ReservationError};
+use crate::pvp::{PlayerKillRecord, PvpFlag};
 use crate::server_plugin::ServerId;
 use crate::tasks::TaskCreator;
+use crate::teleport::ReturnPoint;
 use crate::world::WorldData;
 use bevy_ecs::prelude::*;
 use cgmath::Vector3;
@@ -32,20 +36,20 @@ use silkroad_protocol::character::{
 use silkroad_protocol::inventory::{InventoryItemBindingData, InventoryItemContentData, InventoryItemData, RentInfo};
 use silkroad_protocol::skill::{MasteryData, SkillData};
 use silkroad_protocol::spawn::{CharacterSpawn, CharacterSpawnEnd, CharacterSpawnStart};
-use silkroad_protocol::world::{ActionState, AliveState, BodyState, EntityState, JobType};
+use silkroad_protocol::world::{ActionState, AliveState, BodyState, EntityState, PlayerKillState};
 use silkroad_protocol::SilkroadTime;
-use tracing::debug;
+use tracing::{debug, error, warn};
 
 pub(crate) fn handle_list_request(
-    mut query: Query<(Entity, &Client, &Playing, &LoginInput, &mut CharacterSelect)>,
+    mut query: Query<(Entity, &Client, &mut Playing, &LoginInput, &mut CharacterSelect)>,
     task_creator: Res<TaskCreator>,
     pool: Res<DbPool>,
     mut cmd: Commands,
-    job_distribution: Res<JobDistribution>,
+    mut job_distribution: ResMut<JobDistribution>,
     server_id: Res<ServerId>,
     settings: Res<GameConfig>,
 ) {
-    for (entity, client, playing, input, mut character_list) in query.iter_mut() {
+    for (entity, client, mut playing, input, mut character_list) in query.iter_mut() {
         for action in input.list.iter() {
             match action {
                 CharacterListRequestAction::Create {
@@ -136,7 +140,34 @@ pub(crate) fn handle_list_request(
                     let (hunter_perc, thief_perc) = job_distribution.spread();
                     send_job_spread(client, hunter_perc, thief_perc);
                 },
-                CharacterListRequestAction::AssignJob { .. } => {},
+                CharacterListRequestAction::AssignJob { job } => {
+                    // The side can only be chosen once and applies to all characters of the user on this server.
+                    let alignment = JobAlignment::from_id(*job as i16);
+                    if playing.0.job != JobAlignment::None.id() || alignment == JobAlignment::None {
+                        client.send(CharacterListResponse::new(
+                            CharacterListAction::AssignJob,
+                            CharacterListResult::error(CharacterListError::InvalidCharacterData),
+                        ));
+                        continue;
+                    }
+
+                    playing.0.job = alignment.id();
+                    job_distribution.add(alignment);
+                    let user_id = playing.0.id;
+                    let server_id = server_id.0;
+                    let pool = pool.clone();
+                    task_creator.spawn(async move {
+                        match ServerUser::assign_job(user_id, server_id, alignment.id(), pool).await {
+                            Ok(true) => {},
+                            Ok(false) => warn!(user = user_id, "User already had a job when assigning one."),
+                            Err(e) => error!(user = user_id, error = %e, "Could not assign job."),
+                        }
+                    });
+                    client.send(CharacterListResponse::new(
+                        CharacterListAction::AssignJob,
+                        CharacterListResult::ok(CharacterListContent::Empty),
+                    ));
+                },
             }
         }
     }
@@ -190,6 +221,23 @@ pub(crate) fn handle_join(
                     let pos =
                         LocalPosition((data.region as u16).into(), Vector3::new(data.x, data.y, data.z)).to_global();
                     let position = Position::new(pos, Heading::from(data.rotation as u16));
+                    let return_point = match (data.return_region, data.return_x, data.return_y, data.return_z) {
+                        (Some(region), Some(x), Some(y), Some(z)) => {
+                            Some(LocalPosition((region as u16).into(), Vector3::new(x, y, z)).to_global())
+                        },
+                        _ => None,
+                    };
+                    let player_kill_state = match data.pk_state {
+                        1 => PlayerKillState::Purple,
+                        2 => PlayerKillState::Red,
+                        _ => PlayerKillState::None,
+                    };
+                    let player_kills = PlayerKillRecord::new(
+                        player_kill_state,
+                        data.pk_state_until,
+                        data.pk_kills as u16,
+                        data.pk_penalty as u32,
+                    );
 
                     let agent = Agent::from_character_data(character_data);
 
@@ -200,7 +248,15 @@ pub(crate) fn handle_join(
 
                     client.send(CharacterJoinResponse::success());
 
-                    send_spawn(client, &game_entity, &player, &inventory, &position, settings.max_level);
+                    send_spawn(
+                        client,
+                        &game_entity,
+                        &player,
+                        &inventory,
+                        &position,
+                        &player_kills,
+                        settings.max_level,
+                    );
 
                     client.send(MacroStatus::Possible(MACRO_POTION | MACRO_HUNT | MACRO_SKILL, 0));
 
@@ -214,6 +270,9 @@ pub(crate) fn handle_join(
                             position.clone(),
                             Visibility::with_radius(500.),
                         ))
+                        .insert(ReturnPoint::new(return_point))
+                        .insert((PvpFlag::default(), player_kills))
+                        .insert(JobExperience::new(data.job_level as u8, data.job_exp as u64))
                         .remove::<CharacterSelect>()
                         .remove::<LoginInput>();
                 },
@@ -289,12 +348,13 @@ fn send_job_spread(client: &Client, hunters: u8, thieves: u8) {
     ));
 }
 
-fn send_spawn(
+pub(crate) fn send_spawn(
     client: &Client,
     entity: &GameEntity,
     player: &Player,
     inventory: &PlayerInventory,
     position: &Position,
+    player_kills: &PlayerKillRecord,
     max_level: u8,
 ) {
     client.send(CharacterSpawnStart);
@@ -354,8 +414,8 @@ fn send_spawn(
         character_data.current_mp,
         character_data.beginner_mark,
         0,
-        0,
-        0,
+        player_kills.kills(),
+        player_kills.penalty(),
         0,
         0,
         0x4,
@@ -399,12 +459,12 @@ fn send_spawn(
         entity_state,
         character_data.name.clone(),
         String::new(),
-        JobType::None, // TODO
-        1,
-        0,
-        0,
+        active_job(inventory),
+        character_data.job_level,
+        character_data.job_exp as u32,
         0,
         0,
+        player_kills.state().level(),
         false,
         0,
         0xFF,
@@ -460,6 +520,16 @@ pub(crate) fn create_character_from(
         beginner_mark: true,
         gm: false,
         last_logout: None,
+        return_region: None,
+        return_x: None,
+        return_y: None,
+        return_z: None,
+        pk_state: 0,
+        pk_state_until: None,
+        pk_kills: 0,
+        pk_penalty: 0,
+        job_level: 1,
+        job_exp: 0,
         race: if ref_id > 2000 {
             DbRace::European
         } else {
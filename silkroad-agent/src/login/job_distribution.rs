@@ -1,5 +1,6 @@
 use crate::db::user::ServerUser;
 use crate::ext::DbPool;
+use crate::job::JobAlignment;
 use crate::server_plugin::ServerId;
 use crate::tasks::TaskCreator;
 use bevy_ecs::prelude::*;
@@ -37,11 +38,20 @@ impl JobDistribution {
         } else if self.hunters == 0 {
             (0, 100)
         } else {
-            let hunter_percentage = (self.hunters / total) as u8;
+            let hunter_percentage = (self.hunters * 100 / total) as u8;
             let thieves_percentage = 100 - hunter_percentage;
             (hunter_percentage, thieves_percentage)
         }
     }
+
+    /// Counts a user that just chose their side, until the next refresh provides the real numbers.
+    pub fn add(&mut self, alignment: JobAlignment) {
+        match alignment {
+            JobAlignment::None => {},
+            JobAlignment::Thief => self.thieves += 1,
+            JobAlignment::Hunter => self.hunters += 1,
+        }
+    }
 }
 
 impl Default for JobDistribution {
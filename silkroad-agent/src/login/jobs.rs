@@ -22,7 +22,7 @@ pub(crate) fn handle_character_list_received(
     for (entity, client, playing, mut character_list, mut loading) in query.iter_mut() {
         match loading.try_recv() {
             Ok(characters) => {
-                send_character_list(client, &characters);
+                send_character_list(client, &characters, playing.0.job as u8);
                 character_list.characters = Some(characters);
             },
             Err(TryRecvError::Empty) => continue,
@@ -34,11 +34,11 @@ pub(crate) fn handle_character_list_received(
     }
 }
 
-fn send_character_list(client: &Client, character_list: &[DbCharacter]) {
+fn send_character_list(client: &Client, character_list: &[DbCharacter], job: u8) {
     let characters = character_list.iter().map(from_character).collect();
     let response = CharacterListResponse::new(
         CharacterListAction::List,
-        CharacterListResult::ok(CharacterListContent::characters(characters, 0)),
+        CharacterListResult::ok(CharacterListContent::characters(characters, job)),
     );
     client.send(response);
 }
@@ -14,6 +14,7 @@ pub mod job_distribution;
 mod jobs;
 pub mod web;
 
+pub(crate) use charselect::send_spawn;
 pub(crate) use components::*;
 
 pub(crate) struct LoginPlugin {
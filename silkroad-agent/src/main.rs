@@ -1,38 +1,66 @@
 #![allow(clippy::type_complexity)]
 
 mod agent;
+mod alchemy;
+mod buff;
 mod chat;
 mod comp;
 mod config;
+mod consignment;
 mod db;
 mod event;
+mod exchange;
 mod ext;
+mod friends;
 mod game;
+mod guild;
 mod input;
+mod job;
 mod login;
 mod mall;
 mod net;
+mod party;
 mod persistence;
 mod population;
+mod pvp;
 mod server_plugin;
+mod shop;
+mod stall;
+mod storage;
 mod sync;
 mod tasks;
+mod teleport;
+#[cfg(test)]
+mod test_util;
 mod world;
 
 use crate::agent::AgentPlugin;
+use crate::alchemy::AlchemyPlugin;
+use crate::buff::BuffPlugin;
 use crate::config::get_config;
+use crate::consignment::ConsignmentPlugin;
 use crate::db::server::ServerRegistration;
+use crate::exchange::ExchangePlugin;
 use crate::ext::DbPool;
+use crate::friends::FriendsPlugin;
 use crate::game::GamePlugin;
+use crate::guild::GuildPlugin;
 use crate::input::ReceivePlugin;
+use crate::job::JobPlugin;
 use crate::login::LoginPlugin;
 use crate::mall::MallPlugin;
 use crate::net::NetworkPlugin;
+use crate::party::PartyPlugin;
 use crate::persistence::PersistencePlugin;
 use crate::population::{CapacityController, LoginQueue};
+use crate::pvp::PvpPlugin;
 use crate::server_plugin::ServerPlugin;
+use crate::shop::ShopPlugin;
+use crate::stall::StallPlugin;
+use crate::storage::StoragePlugin;
 use crate::sync::SynchronizationPlugin;
 use crate::tasks::TaskCreator;
+use crate::teleport::TeleportPlugin;
 use crate::world::WorldPlugin;
 use bevy_app::App;
 use bevy_core::TaskPoolPlugin;
@@ -125,5 +153,18 @@ fn main() {
         .add_plugins(LoginPlugin::new(queue))
         .add_plugins(GamePlugin)
         .add_plugins(MallPlugin)
+        .add_plugins(PartyPlugin)
+        .add_plugins(FriendsPlugin)
+        .add_plugins(GuildPlugin)
+        .add_plugins(BuffPlugin)
+        .add_plugins(ShopPlugin)
+        .add_plugins(TeleportPlugin)
+        .add_plugins(ConsignmentPlugin)
+        .add_plugins(StallPlugin)
+        .add_plugins(StoragePlugin)
+        .add_plugins(AlchemyPlugin)
+        .add_plugins(ExchangePlugin)
+        .add_plugins(PvpPlugin)
+        .add_plugins(JobPlugin)
         .run();
 }
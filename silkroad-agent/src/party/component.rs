@@ -0,0 +1,190 @@
+use crate::comp::EntityReference;
+use bevy_ecs::prelude::*;
+use bevy_time::{Timer, TimerMode};
+use silkroad_protocol::party::PartySettings;
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub(crate) struct Party {
+    id: u32,
+    leader: EntityReference,
+    members: Vec<EntityReference>,
+    settings: PartySettings,
+}
+
+impl Party {
+    fn new(id: u32, leader: EntityReference, member: EntityReference, settings: PartySettings) -> Self {
+        Party {
+            id,
+            leader,
+            members: vec![leader, member],
+            settings,
+        }
+    }
+
+    pub(crate) fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub(crate) fn leader(&self) -> EntityReference {
+        self.leader
+    }
+
+    pub(crate) fn is_leader(&self, entity: Entity) -> bool {
+        self.leader.0 == entity
+    }
+
+    pub(crate) fn members(&self) -> &[EntityReference] {
+        &self.members
+    }
+
+    pub(crate) fn settings(&self) -> PartySettings {
+        self.settings
+    }
+
+    pub(crate) fn contains(&self, entity: Entity) -> bool {
+        self.members.iter().any(|member| member.0 == entity)
+    }
+
+    pub(crate) fn find_member(&self, unique_id: u32) -> Option<EntityReference> {
+        self.members
+            .iter()
+            .find(|member| member.1.unique_id == unique_id)
+            .copied()
+    }
+
+    pub(crate) fn can_invite(&self, entity: Entity) -> bool {
+        self.settings.anyone_can_invite || self.is_leader(entity)
+    }
+
+    pub(crate) fn add_member(&mut self, member: EntityReference) {
+        if !self.contains(member.0) {
+            self.members.push(member);
+        }
+    }
+
+    /// Removes the given entity from the party, returning the removed member if it was part of this party.
+    /// If the leader leaves the party, the next member in line will become the new leader.
+    pub(crate) fn remove_member(&mut self, entity: Entity) -> Option<EntityReference> {
+        let index = self.members.iter().position(|member| member.0 == entity)?;
+        let removed = self.members.remove(index);
+        if self.leader.0 == entity {
+            if let Some(next) = self.members.first() {
+                self.leader = *next;
+            }
+        }
+        Some(removed)
+    }
+
+    /// A party only makes sense as long as there are at least two members in it.
+    pub(crate) fn should_disband(&self) -> bool {
+        self.members.len() < 2
+    }
+}
+
+#[derive(Resource, Default)]
+pub(crate) struct Parties {
+    next_id: u32,
+    parties: HashMap<u32, Party>,
+}
+
+impl Parties {
+    pub(crate) fn create(&mut self, leader: EntityReference, member: EntityReference, settings: PartySettings) -> u32 {
+        self.next_id = self.next_id.wrapping_add(1);
+        let id = self.next_id;
+        self.parties.insert(id, Party::new(id, leader, member, settings));
+        id
+    }
+
+    pub(crate) fn get(&self, id: u32) -> Option<&Party> {
+        self.parties.get(&id)
+    }
+
+    pub(crate) fn get_mut(&mut self, id: u32) -> Option<&mut Party> {
+        self.parties.get_mut(&id)
+    }
+
+    pub(crate) fn remove(&mut self, id: u32) -> Option<Party> {
+        self.parties.remove(&id)
+    }
+
+    pub(crate) fn find_party_of(&self, entity: Entity) -> Option<u32> {
+        self.parties
+            .values()
+            .find(|party| party.contains(entity))
+            .map(|party| party.id)
+    }
+}
+
+/// Marks an entity as being a member of the party with the given id.
+#[derive(Component, Copy, Clone)]
+#[component(storage = "SparseSet")]
+pub(crate) struct PartyMember(pub u32);
+
+/// An open invitation for the entity to either form a new party with the inviter or to join the
+/// party of the inviter.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct PartyInvitation {
+    pub(crate) inviter: EntityReference,
+    pub(crate) party: Option<u32>,
+    pub(crate) settings: PartySettings,
+    pub(crate) timeout: Timer,
+}
+
+impl PartyInvitation {
+    pub(crate) fn new(
+        inviter: EntityReference,
+        party: Option<u32>,
+        settings: PartySettings,
+        timeout: Duration,
+    ) -> Self {
+        PartyInvitation {
+            inviter,
+            party,
+            settings,
+            timeout: Timer::new(timeout, TimerMode::Once),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::comp::GameEntity;
+
+    fn reference(index: u32) -> EntityReference {
+        EntityReference(
+            Entity::from_raw(index),
+            GameEntity {
+                unique_id: index,
+                ref_id: 1907,
+            },
+        )
+    }
+
+    #[test]
+    pub fn test_leader_passes_on() {
+        let mut parties = Parties::default();
+        let id = parties.create(reference(1), reference(2), PartySettings::default());
+        let party = parties.get_mut(id).unwrap();
+        party.add_member(reference(3));
+
+        let removed = party.remove_member(Entity::from_raw(1));
+        assert_eq!(removed.map(|member| member.1.unique_id), Some(1));
+        assert!(party.is_leader(Entity::from_raw(2)));
+        assert!(!party.should_disband());
+    }
+
+    #[test]
+    pub fn test_disband_with_single_member() {
+        let mut parties = Parties::default();
+        let id = parties.create(reference(1), reference(2), PartySettings::default());
+        let party = parties.get_mut(id).unwrap();
+
+        assert!(party.remove_member(Entity::from_raw(5)).is_none());
+        party.remove_member(Entity::from_raw(2));
+        assert!(party.should_disband());
+        assert_eq!(parties.find_party_of(Entity::from_raw(1)), Some(id));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::party::system::{
+    handle_invitation_responses, handle_party_invites, handle_party_leave, leave_party_on_disconnect,
+    sync_party_members, sync_party_positions,
+};
+use bevy_app::{App, Plugin, PostUpdate, Update};
+use bevy_ecs::prelude::*;
+use bevy_time::common_conditions::on_timer;
+pub(crate) use component::*;
+use std::time::Duration;
+
+mod component;
+mod system;
+
+pub(crate) struct PartyPlugin;
+
+impl Plugin for PartyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Parties::default())
+            .add_systems(
+                Update,
+                (
+                    handle_party_invites,
+                    handle_invitation_responses.after(handle_party_invites),
+                    handle_party_leave.after(handle_invitation_responses),
+                    leave_party_on_disconnect.after(handle_party_leave),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    sync_party_members,
+                    sync_party_positions.run_if(on_timer(Duration::from_secs(1))),
+                ),
+            );
+    }
+}
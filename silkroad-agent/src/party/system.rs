@@ -0,0 +1,459 @@
+use crate::comp::exp::Leveled;
+use crate::comp::net::Client;
+use crate::comp::player::Player;
+use crate::comp::pos::Position;
+use crate::comp::{EntityReference, GameEntity, Health, Mana};
+use crate::config::GameConfig;
+use crate::event::ClientDisconnectedEvent;
+use crate::exchange::ExchangeInvitation;
+use crate::guild::GuildInvitation;
+use crate::input::PlayerInput;
+use crate::party::{Parties, Party, PartyInvitation, PartyMember};
+use crate::world::EntityLookup;
+use bevy_ecs::prelude::*;
+use bevy_ecs::query::Has;
+use bevy_time::Time;
+use silkroad_protocol::party::{
+    CreatePartyResponse, InviteToPartyResponse, KickFromPartyResponse, LeavePartyResponse, PartyErrorCode, PartyInfo,
+    PartyLeaveReason, PartyMemberInfo, PartyMemberPosition, PartyMemberUpdate, PartyResult, PartyUpdate,
+    PlayerInvitationRequest,
+};
+use silkroad_protocol::ServerPacket;
+use std::collections::HashSet;
+use std::time::Duration;
+
+type InvitationTargets<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Client,
+        Has<PartyMember>,
+        Has<PartyInvitation>,
+        Has<GuildInvitation>,
+        Has<ExchangeInvitation>,
+    ),
+    With<Player>,
+>;
+
+type MemberData<'a> = (
+    &'a Client,
+    &'a GameEntity,
+    &'a Player,
+    &'a Leveled,
+    &'a Health,
+    &'a Mana,
+    &'a Position,
+);
+
+fn scale_to_ten(current: u32, max: u32) -> u8 {
+    if max == 0 {
+        return 0;
+    }
+    ((u64::from(current.min(max)) * 10) / u64::from(max)) as u8
+}
+
+fn hp_mp_indicator(health: &Health, mana: &Mana) -> u8 {
+    let hp = scale_to_ten(health.current_health, health.max_health);
+    let mp = scale_to_ten(mana.current_mana, mana.max_mana);
+    (hp << 4) | mp
+}
+
+fn member_position(position: &Position) -> PartyMemberPosition {
+    let local = position.position().to_local();
+    PartyMemberPosition::new(local.0.id(), local.1.x as u16, local.1.y as u16, local.1.z as u16)
+}
+
+fn member_info((_, game_entity, player, level, health, mana, position): MemberData) -> PartyMemberInfo {
+    PartyMemberInfo::new(
+        game_entity.unique_id,
+        player.character.name.clone(),
+        game_entity.ref_id,
+        level.current_level(),
+        hp_mp_indicator(health, mana),
+        member_position(position),
+    )
+}
+
+fn send_to_party<T: Into<ServerPacket> + Clone>(party: &Party, packet: T, clients: &Query<&Client>) {
+    for member in party.members() {
+        if let Ok(client) = clients.get(member.0) {
+            client.send(packet.clone());
+        }
+    }
+}
+
+fn find_invitable<'a>(
+    target_id: u32,
+    inviter: Entity,
+    lookup: &EntityLookup,
+    targets: &'a InvitationTargets,
+    invited: &HashSet<Entity>,
+) -> Result<(Entity, &'a Client), PartyErrorCode> {
+    let target = lookup
+        .get_entity_for_id(target_id)
+        .filter(|target| *target != inviter)
+        .ok_or(PartyErrorCode::InvalidTarget)?;
+    let (client, in_party, has_party_invitation, has_guild_invitation, has_exchange_invitation) =
+        targets.get(target).map_err(|_| PartyErrorCode::InvalidTarget)?;
+    if in_party {
+        return Err(PartyErrorCode::AlreadyInParty);
+    }
+
+    if has_party_invitation || has_guild_invitation || has_exchange_invitation || invited.contains(&target) {
+        return Err(PartyErrorCode::TargetBusy);
+    }
+
+    Ok((target, client))
+}
+
+pub(crate) fn handle_party_invites(
+    query: Query<(Entity, &GameEntity, &Client, &PlayerInput, Option<&PartyMember>)>,
+    targets: InvitationTargets,
+    lookup: Res<EntityLookup>,
+    parties: Res<Parties>,
+    settings: Res<GameConfig>,
+    mut cmd: Commands,
+) {
+    let timeout = Duration::from_secs(settings.party.invitation_timeout);
+    let mut invited = HashSet::new();
+    for (entity, game_entity, client, input, membership) in query.iter() {
+        let inviter = EntityReference(entity, *game_entity);
+
+        if let Some(ref create) = input.party_create {
+            let target = if membership.is_some() {
+                Err(PartyErrorCode::AlreadyInParty)
+            } else {
+                find_invitable(create.target, entity, &lookup, &targets, &invited)
+            };
+
+            match target {
+                Ok((target, target_client)) => {
+                    invited.insert(target);
+                    target_client.send(PlayerInvitationRequest::party_creation(
+                        game_entity.unique_id,
+                        create.settings,
+                    ));
+                    cmd.entity(target)
+                        .insert(PartyInvitation::new(inviter, None, create.settings, timeout));
+                },
+                Err(code) => client.send(CreatePartyResponse::new(PartyResult::error(code))),
+            }
+        }
+
+        if let Some(ref invite) = input.party_invite {
+            let target = match membership.and_then(|membership| parties.get(membership.0)) {
+                None => Err(PartyErrorCode::NotInParty),
+                Some(party) if !party.can_invite(entity) => Err(PartyErrorCode::NotAllowed),
+                Some(party) if party.members().len() >= settings.party.max_members => Err(PartyErrorCode::PartyFull),
+                Some(party) => find_invitable(invite.target, entity, &lookup, &targets, &invited)
+                    .map(|(target, target_client)| (party, target, target_client)),
+            };
+
+            match target {
+                Ok((party, target, target_client)) => {
+                    invited.insert(target);
+                    target_client.send(PlayerInvitationRequest::party_invitation(
+                        game_entity.unique_id,
+                        party.settings(),
+                    ));
+                    cmd.entity(target).insert(PartyInvitation::new(
+                        inviter,
+                        Some(party.id()),
+                        party.settings(),
+                        timeout,
+                    ));
+                },
+                Err(code) => client.send(InviteToPartyResponse::new(PartyResult::error(code))),
+            }
+        }
+    }
+}
+
+pub(crate) fn handle_invitation_responses(
+    mut query: Query<(Entity, &GameEntity, &PlayerInput, &mut PartyInvitation)>,
+    members: Query<MemberData>,
+    mut parties: ResMut<Parties>,
+    settings: Res<GameConfig>,
+    time: Res<Time>,
+    mut cmd: Commands,
+) {
+    let delta = time.delta();
+    for (entity, game_entity, input, mut invitation) in query.iter_mut() {
+        let accepted = match input.invitation_response {
+            Some(response) => response.accepted(),
+            None if invitation.timeout.tick(delta).finished() => false,
+            None => continue,
+        };
+
+        cmd.entity(entity).remove::<PartyInvitation>();
+        let Ok((inviter_client, ..)) = members.get(invitation.inviter.0) else {
+            continue;
+        };
+
+        let member = EntityReference(entity, *game_entity);
+        let result = if !accepted {
+            Err(PartyErrorCode::Declined)
+        } else if parties.find_party_of(member.0).is_some() {
+            Err(PartyErrorCode::AlreadyInParty)
+        } else {
+            match invitation.party {
+                None => create_party(
+                    &mut parties,
+                    invitation.inviter,
+                    member,
+                    &invitation,
+                    &members,
+                    &mut cmd,
+                ),
+                Some(id) => join_party(
+                    &mut parties,
+                    id,
+                    invitation.inviter,
+                    member,
+                    settings.party.max_members,
+                    &members,
+                    &mut cmd,
+                ),
+            }
+        };
+
+        let result = match result {
+            Ok(_) => PartyResult::Success,
+            Err(code) => PartyResult::error(code),
+        };
+
+        match invitation.party {
+            None => inviter_client.send(CreatePartyResponse::new(result)),
+            Some(_) => inviter_client.send(InviteToPartyResponse::new(result)),
+        }
+    }
+}
+
+fn create_party(
+    parties: &mut Parties,
+    leader: EntityReference,
+    member: EntityReference,
+    invitation: &PartyInvitation,
+    members: &Query<MemberData>,
+    cmd: &mut Commands,
+) -> Result<(), PartyErrorCode> {
+    if parties.find_party_of(leader.0).is_some() {
+        return Err(PartyErrorCode::AlreadyInParty);
+    }
+
+    let id = parties.create(leader, member, invitation.settings);
+    let party = parties.get(id).expect("Party should have just been created");
+    let infos = party
+        .members()
+        .iter()
+        .filter_map(|member| members.get(member.0).ok())
+        .map(member_info)
+        .collect::<Vec<_>>();
+    let info = PartyInfo::new(id, leader.1.unique_id, party.settings(), infos);
+
+    for member in party.members() {
+        cmd.entity(member.0).insert(PartyMember(id));
+        if let Ok((client, ..)) = members.get(member.0) {
+            client.send(info.clone());
+        }
+    }
+
+    Ok(())
+}
+
+fn join_party(
+    parties: &mut Parties,
+    id: u32,
+    inviter: EntityReference,
+    member: EntityReference,
+    max_members: usize,
+    members: &Query<MemberData>,
+    cmd: &mut Commands,
+) -> Result<(), PartyErrorCode> {
+    let party = parties
+        .get_mut(id)
+        .filter(|party| party.contains(inviter.0))
+        .ok_or(PartyErrorCode::NotInParty)?;
+    if party.members().len() >= max_members {
+        return Err(PartyErrorCode::PartyFull);
+    }
+    let joined = members.get(member.0).map_err(|_| PartyErrorCode::InvalidTarget)?;
+    let joined_client = joined.0;
+    let joined_info = member_info(joined);
+
+    for other in party.members() {
+        if let Ok((client, ..)) = members.get(other.0) {
+            client.send(PartyUpdate::MemberJoined(joined_info.clone()));
+        }
+    }
+
+    party.add_member(member);
+    cmd.entity(member.0).insert(PartyMember(id));
+
+    let infos = party
+        .members()
+        .iter()
+        .filter_map(|member| members.get(member.0).ok())
+        .map(member_info)
+        .collect::<Vec<_>>();
+    joined_client.send(PartyInfo::new(id, party.leader().1.unique_id, party.settings(), infos));
+    Ok(())
+}
+
+fn leave_party(
+    parties: &mut Parties,
+    id: u32,
+    entity: Entity,
+    reason: PartyLeaveReason,
+    clients: &Query<&Client>,
+    cmd: &mut Commands,
+) {
+    let Some(party) = parties.get_mut(id) else {
+        return;
+    };
+    let previous_leader = party.leader();
+    let Some(removed) = party.remove_member(entity) else {
+        return;
+    };
+
+    if let Some(mut entity_commands) = cmd.get_entity(entity) {
+        entity_commands.remove::<PartyMember>();
+    }
+
+    if let Ok(client) = clients.get(entity) {
+        client.send(PartyUpdate::dismissed());
+    }
+
+    if party.should_disband() {
+        for member in party.members() {
+            if let Some(mut entity_commands) = cmd.get_entity(member.0) {
+                entity_commands.remove::<PartyMember>();
+            }
+        }
+        send_to_party(party, PartyUpdate::dismissed(), clients);
+        parties.remove(id);
+        return;
+    }
+
+    send_to_party(party, PartyUpdate::member_left(removed.1.unique_id, reason), clients);
+    if party.leader() != previous_leader {
+        send_to_party(
+            party,
+            PartyUpdate::LeaderChanged {
+                id: party.leader().1.unique_id,
+            },
+            clients,
+        );
+    }
+}
+
+pub(crate) fn handle_party_leave(
+    query: Query<(Entity, &Client, &PlayerInput, Option<&PartyMember>)>,
+    clients: Query<&Client>,
+    mut parties: ResMut<Parties>,
+    mut cmd: Commands,
+) {
+    for (entity, client, input, membership) in query.iter() {
+        if input.party_leave.is_some() {
+            match membership {
+                Some(membership) => {
+                    leave_party(
+                        &mut parties,
+                        membership.0,
+                        entity,
+                        PartyLeaveReason::Left,
+                        &clients,
+                        &mut cmd,
+                    );
+                    client.send(LeavePartyResponse::new(PartyResult::Success));
+                },
+                None => client.send(LeavePartyResponse::new(PartyResult::error(PartyErrorCode::NotInParty))),
+            }
+        }
+
+        if let Some(ref kick) = input.party_kick {
+            let kicked = match membership.and_then(|membership| parties.get(membership.0)) {
+                None => Err(PartyErrorCode::NotInParty),
+                Some(party) if !party.is_leader(entity) => Err(PartyErrorCode::NotAllowed),
+                Some(party) => party
+                    .find_member(kick.member)
+                    .filter(|member| member.0 != entity)
+                    .map(|member| (party.id(), member.0))
+                    .ok_or(PartyErrorCode::InvalidTarget),
+            };
+
+            match kicked {
+                Ok((id, kicked)) => {
+                    leave_party(&mut parties, id, kicked, PartyLeaveReason::Kicked, &clients, &mut cmd);
+                    client.send(KickFromPartyResponse::new(PartyResult::Success));
+                },
+                Err(code) => client.send(KickFromPartyResponse::new(PartyResult::error(code))),
+            }
+        }
+    }
+}
+
+pub(crate) fn leave_party_on_disconnect(
+    mut events: EventReader<ClientDisconnectedEvent>,
+    clients: Query<&Client>,
+    mut parties: ResMut<Parties>,
+    mut cmd: Commands,
+) {
+    for event in events.read() {
+        if let Some(id) = parties.find_party_of(event.0) {
+            leave_party(&mut parties, id, event.0, PartyLeaveReason::Left, &clients, &mut cmd);
+        }
+    }
+}
+
+pub(crate) fn sync_party_members(
+    query: Query<
+        (&GameEntity, &PartyMember, Ref<Health>, Ref<Mana>, Ref<Leveled>),
+        Or<(Changed<Health>, Changed<Mana>, Changed<Leveled>)>,
+    >,
+    clients: Query<&Client>,
+    parties: Res<Parties>,
+) {
+    for (game_entity, membership, health, mana, level) in query.iter() {
+        let Some(party) = parties.get(membership.0) else {
+            continue;
+        };
+
+        if health.is_changed() || mana.is_changed() {
+            let update = PartyMemberUpdate::HpMp(hp_mp_indicator(&health, &mana));
+            send_to_party(
+                party,
+                PartyUpdate::member_update(game_entity.unique_id, update),
+                &clients,
+            );
+        }
+
+        if level.is_changed() {
+            let update = PartyMemberUpdate::Level(level.current_level());
+            send_to_party(
+                party,
+                PartyUpdate::member_update(game_entity.unique_id, update),
+                &clients,
+            );
+        }
+    }
+}
+
+pub(crate) fn sync_party_positions(
+    query: Query<(&GameEntity, &PartyMember, &Position), Changed<Position>>,
+    clients: Query<&Client>,
+    parties: Res<Parties>,
+) {
+    for (game_entity, membership, position) in query.iter() {
+        let Some(party) = parties.get(membership.0) else {
+            continue;
+        };
+
+        let update = PartyMemberUpdate::Position(member_position(position));
+        send_to_party(
+            party,
+            PartyUpdate::member_update(game_entity.unique_id, update),
+            &clients,
+        );
+    }
+}
@@ -119,6 +119,30 @@ impl AppPersistanceExt for App {
     }
 }
 
+/// Takes the changes of the component on the given entity that have been collected, but not persisted yet. This is
+/// only useful when the changes get written to the database by other means, like together with the changes of other
+/// entities in a single transaction.
+pub(crate) fn take_changes<T: ChangeTracked + Component>(world: &mut World, entity: Entity) -> Vec<T::ChangeItem> {
+    world
+        .get_mut::<PersistenceCollection<T>>(entity)
+        .map(|mut collection| mem::take(&mut collection.changes))
+        .unwrap_or_default()
+}
+
+/// Puts changes that have been taken from the component on the given entity back in front of the ones collected in
+/// the meantime, such that they still get persisted in their original order.
+pub(crate) fn restore_changes<T: ChangeTracked + Component>(
+    entity: Entity,
+    mut changes: Vec<T::ChangeItem>,
+) -> impl FnOnce(&mut World) + Send {
+    move |world: &mut World| {
+        if let Some(mut collection) = world.get_mut::<PersistenceCollection<T>>(entity) {
+            changes.append(&mut collection.changes);
+            collection.changes = changes;
+        }
+    }
+}
+
 fn add_change_tracker<T: ChangeTracked + Component>(mut cmd: Commands, query: Query<Entity, Added<T>>) {
     for entity in query.iter() {
         let comp: PersistenceCollection<T> = PersistenceCollection::default();
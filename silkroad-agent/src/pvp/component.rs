@@ -0,0 +1,280 @@
+use crate::job::{active_job, are_hostile};
+use crate::persistence::ApplyToDatabase;
+use axum::async_trait;
+use bevy_ecs::prelude::*;
+use bevy_time::{Timer, TimerMode};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use derive_more::Constructor;
+use silkroad_game_base::{ChangeProvided, Inventory};
+use silkroad_protocol::world::{JobType, PlayerKillState, PvpCape};
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// The PvP cape a player is currently wearing, which decides whom they may fight.
+#[derive(Component, Copy, Clone)]
+pub(crate) struct PvpFlag(pub(crate) PvpCape);
+
+impl Default for PvpFlag {
+    fn default() -> Self {
+        PvpFlag(PvpCape::None)
+    }
+}
+
+/// A cape the player is about to put on, or [PvpCape::None] if they are about to take theirs off. The change only
+/// happens once the timer ran out.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct CapeChange {
+    pub(crate) cape: PvpCape,
+    pub(crate) timer: Timer,
+}
+
+impl CapeChange {
+    pub(crate) fn new(cape: PvpCape, delay: Duration) -> Self {
+        CapeChange {
+            cape,
+            timer: Timer::new(delay, TimerMode::Once),
+        }
+    }
+}
+
+/// How an attack of one player against another player would be judged.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) enum AttackPermission {
+    /// The target may be attacked without any consequences.
+    Allowed,
+    /// The target may be attacked, but the attacker becomes an aggressor for doing so.
+    Aggression,
+    /// The target may not be attacked at all.
+    Denied,
+}
+
+/// Everything about a player that decides whether they may attack, or be attacked by, another player.
+#[derive(Copy, Clone, Constructor)]
+pub(crate) struct PvpStanding {
+    pub(crate) cape: PvpCape,
+    pub(crate) state: PlayerKillState,
+    pub(crate) job: JobType,
+}
+
+impl PvpStanding {
+    pub(crate) fn of(flag: &PvpFlag, record: &PlayerKillRecord, inventory: &Inventory) -> Self {
+        PvpStanding::new(flag.0, record.state(), active_job(inventory))
+    }
+
+    /// Players wearing a job suit only fight players of a hostile job and cannot be attacked by anyone else.
+    /// Aggressors and murderers may be attacked by anyone. Otherwise, players wearing a cape may only fight other
+    /// players wearing a cape of a different color, unless either of them wears the red cape, which fights
+    /// everyone. Players without a cape may attack innocent players without a cape, but become aggressors.
+    pub(crate) fn permission(&self, target: &PvpStanding) -> AttackPermission {
+        if self.job != JobType::None || target.job != JobType::None {
+            return if are_hostile(self.job, target.job) {
+                AttackPermission::Allowed
+            } else {
+                AttackPermission::Denied
+            };
+        }
+
+        if target.state != PlayerKillState::None {
+            return AttackPermission::Allowed;
+        }
+
+        match (self.cape, target.cape) {
+            (PvpCape::None, PvpCape::None) => AttackPermission::Aggression,
+            (PvpCape::None, _) | (_, PvpCape::None) => AttackPermission::Denied,
+            (PvpCape::Red, _) | (_, PvpCape::Red) => AttackPermission::Allowed,
+            (own, other) if own != other => AttackPermission::Allowed,
+            _ => AttackPermission::Denied,
+        }
+    }
+}
+
+/// The record of a player attacking and killing innocent players. Aggressors and murderers keep their state until
+/// the given point in time, even across logouts.
+#[derive(Component, Clone)]
+pub(crate) struct PlayerKillRecord {
+    state: PlayerKillState,
+    until: Option<DateTime<Utc>>,
+    kills: u16,
+    penalty: u32,
+}
+
+impl PlayerKillRecord {
+    pub(crate) fn new(state: PlayerKillState, until: Option<DateTime<Utc>>, kills: u16, penalty: u32) -> Self {
+        PlayerKillRecord {
+            state,
+            until,
+            kills,
+            penalty,
+        }
+    }
+
+    pub(crate) fn state(&self) -> PlayerKillState {
+        self.state
+    }
+
+    pub(crate) fn kills(&self) -> u16 {
+        self.kills
+    }
+
+    pub(crate) fn penalty(&self) -> u32 {
+        self.penalty
+    }
+
+    pub(crate) fn is_murderer(&self) -> bool {
+        self.state == PlayerKillState::Red
+    }
+
+    /// Marks the player as an aggressor until the given time. Murderers stay murderers and existing aggressors only
+    /// have their time extended.
+    pub(crate) fn mark_aggressor(&mut self, until: DateTime<Utc>) {
+        match self.state {
+            PlayerKillState::Red => {},
+            PlayerKillState::Purple | PlayerKillState::None => {
+                self.state = PlayerKillState::Purple;
+                self.until = Some(until);
+            },
+        }
+    }
+
+    /// Turns the player into a murderer for the given duration, which gets added on top of the remaining time if
+    /// they were a murderer already.
+    pub(crate) fn record_murder(&mut self, now: DateTime<Utc>, duration: ChronoDuration, penalty: u32) {
+        let start = match (self.state, self.until) {
+            (PlayerKillState::Red, Some(until)) if until > now => until,
+            _ => now,
+        };
+        self.state = PlayerKillState::Red;
+        self.until = Some(start + duration);
+        self.kills = self.kills.saturating_add(1);
+        self.penalty = self.penalty.saturating_add(penalty);
+    }
+
+    pub(crate) fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.state != PlayerKillState::None && self.until.is_none_or(|until| until <= now)
+    }
+
+    /// Makes the player innocent again. Murderers also have their penalty lifted, as they've served their time.
+    pub(crate) fn expire(&mut self) {
+        if self.is_murderer() {
+            self.penalty = 0;
+        }
+        self.state = PlayerKillState::None;
+        self.until = None;
+    }
+}
+
+pub(crate) struct PlayerKillChange {
+    state: PlayerKillState,
+    until: Option<DateTime<Utc>>,
+    kills: u16,
+    penalty: u32,
+}
+
+impl ChangeProvided for PlayerKillRecord {
+    type Change = PlayerKillChange;
+
+    fn as_change(&self) -> Self::Change {
+        PlayerKillChange {
+            state: self.state,
+            until: self.until,
+            kills: self.kills,
+            penalty: self.penalty,
+        }
+    }
+}
+
+#[async_trait]
+impl ApplyToDatabase for PlayerKillChange {
+    async fn apply(&self, character_id: u32, pool: &PgPool) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE characters SET pk_state = $1, pk_state_until = $2, pk_kills = $3, pk_penalty = $4 WHERE id = $5",
+            self.state.level() as i16,
+            self.until,
+            self.kills as i32,
+            self.penalty as i32,
+            character_id as i32
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn standing(cape: PvpCape, state: PlayerKillState) -> PvpStanding {
+        PvpStanding::new(cape, state, JobType::None)
+    }
+
+    #[test]
+    pub fn test_capes_decide_permission() {
+        let innocent = standing(PvpCape::None, PlayerKillState::None);
+        let red = standing(PvpCape::Red, PlayerKillState::None);
+        let blue = standing(PvpCape::Blue, PlayerKillState::None);
+        let yellow = standing(PvpCape::Yellow, PlayerKillState::None);
+
+        assert_eq!(innocent.permission(&innocent), AttackPermission::Aggression);
+        assert_eq!(innocent.permission(&blue), AttackPermission::Denied);
+        assert_eq!(blue.permission(&innocent), AttackPermission::Denied);
+        assert_eq!(blue.permission(&blue), AttackPermission::Denied);
+        assert_eq!(blue.permission(&yellow), AttackPermission::Allowed);
+        assert_eq!(red.permission(&red), AttackPermission::Allowed);
+        assert_eq!(blue.permission(&red), AttackPermission::Allowed);
+    }
+
+    #[test]
+    pub fn test_aggressors_and_murderers_can_be_attacked() {
+        let aggressor = standing(PvpCape::None, PlayerKillState::Purple);
+        let murderer = standing(PvpCape::None, PlayerKillState::Red);
+        let innocent = standing(PvpCape::None, PlayerKillState::None);
+        let caped = standing(PvpCape::White, PlayerKillState::None);
+
+        assert_eq!(innocent.permission(&aggressor), AttackPermission::Allowed);
+        assert_eq!(caped.permission(&murderer), AttackPermission::Allowed);
+        assert_eq!(murderer.permission(&innocent), AttackPermission::Aggression);
+    }
+
+    #[test]
+    pub fn test_jobs_only_fight_hostile_jobs() {
+        let thief = PvpStanding::new(PvpCape::None, PlayerKillState::None, JobType::Thief);
+        let hunter = PvpStanding::new(PvpCape::None, PlayerKillState::None, JobType::Hunter);
+        let trader = PvpStanding::new(PvpCape::None, PlayerKillState::None, JobType::Trader);
+        let murderer = standing(PvpCape::Red, PlayerKillState::Red);
+
+        assert_eq!(thief.permission(&trader), AttackPermission::Allowed);
+        assert_eq!(hunter.permission(&thief), AttackPermission::Allowed);
+        assert_eq!(hunter.permission(&trader), AttackPermission::Denied);
+        assert_eq!(thief.permission(&thief), AttackPermission::Denied);
+        assert_eq!(murderer.permission(&trader), AttackPermission::Denied);
+        assert_eq!(trader.permission(&murderer), AttackPermission::Denied);
+    }
+
+    #[test]
+    pub fn test_murders_extend_the_record() {
+        let now = Utc::now();
+        let mut record = PlayerKillRecord::new(PlayerKillState::None, None, 0, 0);
+        assert!(!record.is_expired(now));
+
+        record.mark_aggressor(now + ChronoDuration::seconds(10));
+        assert_eq!(record.state(), PlayerKillState::Purple);
+
+        record.record_murder(now, ChronoDuration::seconds(60), 2);
+        record.record_murder(now, ChronoDuration::seconds(60), 2);
+        assert!(record.is_murderer());
+        assert_eq!(record.kills(), 2);
+        assert_eq!(record.penalty(), 4);
+
+        record.mark_aggressor(now + ChronoDuration::seconds(10));
+        assert!(record.is_murderer());
+        assert!(!record.is_expired(now + ChronoDuration::seconds(100)));
+        assert!(record.is_expired(now + ChronoDuration::seconds(120)));
+
+        record.expire();
+        assert_eq!(record.state(), PlayerKillState::None);
+        assert_eq!(record.kills(), 2);
+        assert_eq!(record.penalty(), 0);
+    }
+}
@@ -0,0 +1,30 @@
+use crate::persistence::AppPersistanceExt;
+use crate::pvp::system::{
+    change_capes, expire_player_kill_states, handle_cape_requests, mark_aggressors, record_murders,
+};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use bevy_time::common_conditions::on_timer;
+pub(crate) use component::*;
+use std::time::Duration;
+
+mod component;
+mod system;
+
+pub(crate) struct PvpPlugin;
+
+impl Plugin for PvpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                handle_cape_requests,
+                change_capes.after(handle_cape_requests),
+                mark_aggressors,
+                record_murders.after(mark_aggressors),
+                expire_player_kill_states.run_if(on_timer(Duration::from_secs(1))),
+            ),
+        )
+        .track_change_component::<PlayerKillRecord>();
+    }
+}
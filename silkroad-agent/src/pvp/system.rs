@@ -0,0 +1,143 @@
+use crate::agent::states::Dead;
+use crate::comp::inventory::PlayerInventory;
+use crate::comp::net::Client;
+use crate::config::GameConfig;
+use crate::event::{DamageReceiveEvent, EntityDeath};
+use crate::input::PlayerInput;
+use crate::job::active_job;
+use crate::pvp::component::{AttackPermission, CapeChange, PlayerKillRecord, PvpFlag, PvpStanding};
+use bevy_ecs::prelude::*;
+use bevy_time::Time;
+use chrono::Utc;
+use silkroad_protocol::pvp::{PvpCapeResponse, PvpErrorCode};
+use silkroad_protocol::world::{JobType, PlayerKillState, PvpCape};
+use std::time::Duration;
+
+/// Provides how the attacker would be judged for attacking the target, if both of them are players.
+fn permission_between(
+    query: &Query<(&PvpFlag, &mut PlayerKillRecord, &PlayerInventory)>,
+    attacker: Entity,
+    target: Entity,
+) -> Option<AttackPermission> {
+    let standing_of = |entity: Entity| {
+        query
+            .get(entity)
+            .ok()
+            .map(|(flag, record, inventory)| PvpStanding::of(flag, record, inventory))
+    };
+    let attacker_standing = standing_of(attacker)?;
+    let target_standing = standing_of(target)?;
+    Some(attacker_standing.permission(&target_standing))
+}
+
+pub(crate) fn handle_cape_requests(
+    query: Query<(
+        Entity,
+        &Client,
+        &PlayerInput,
+        &PvpFlag,
+        &PlayerKillRecord,
+        &PlayerInventory,
+        Has<CapeChange>,
+        Has<Dead>,
+    )>,
+    settings: Res<GameConfig>,
+    mut cmd: Commands,
+) {
+    for (entity, client, input, flag, record, inventory, changing, dead) in query.iter() {
+        let Some(ref request) = input.pvp_cape else {
+            continue;
+        };
+
+        // Players practicing a job already fight on their job's side.
+        if dead || request.cape == flag.0 || (request.cape != PvpCape::None && active_job(inventory) != JobType::None) {
+            client.send(PvpCapeResponse::Error(PvpErrorCode::NotAllowed));
+            continue;
+        }
+
+        if changing {
+            client.send(PvpCapeResponse::Error(PvpErrorCode::AlreadyChanging));
+            continue;
+        }
+
+        // Aggressors and murderers should not be able to hide behind a cape.
+        if request.cape != PvpCape::None && record.state() != PlayerKillState::None {
+            client.send(PvpCapeResponse::Error(PvpErrorCode::Murderer));
+            continue;
+        }
+
+        cmd.entity(entity).insert(CapeChange::new(
+            request.cape,
+            Duration::from_secs(settings.pvp.cape_delay),
+        ));
+        client.send(PvpCapeResponse::Success);
+    }
+}
+
+pub(crate) fn change_capes(
+    mut query: Query<(Entity, &mut PvpFlag, &mut CapeChange)>,
+    time: Res<Time>,
+    mut cmd: Commands,
+) {
+    let delta = time.delta();
+    for (entity, mut flag, mut change) in query.iter_mut() {
+        if change.timer.tick(delta).finished() {
+            flag.0 = change.cape;
+            cmd.entity(entity).remove::<CapeChange>();
+        }
+    }
+}
+
+pub(crate) fn mark_aggressors(
+    mut damage_events: EventReader<DamageReceiveEvent>,
+    mut query: Query<(&PvpFlag, &mut PlayerKillRecord, &PlayerInventory)>,
+    settings: Res<GameConfig>,
+) {
+    for event in damage_events.read() {
+        let (attacker, target) = (event.source.0, event.target.0);
+        if attacker == target || permission_between(&query, attacker, target) != Some(AttackPermission::Aggression) {
+            continue;
+        }
+
+        let Ok((_, mut record, _)) = query.get_mut(attacker) else {
+            continue;
+        };
+        let until = Utc::now() + chrono::Duration::seconds(settings.pvp.aggressor_duration as i64);
+        record.mark_aggressor(until);
+    }
+}
+
+pub(crate) fn record_murders(
+    mut death_events: EventReader<EntityDeath>,
+    mut query: Query<(&PvpFlag, &mut PlayerKillRecord, &PlayerInventory)>,
+    settings: Res<GameConfig>,
+) {
+    for event in death_events.read() {
+        let Some(killer) = event.killer else {
+            continue;
+        };
+        let (killer, victim) = (killer.0, event.died.0);
+        // Killing someone that could be attacked freely isn't a murder, only killing the innocent is.
+        if killer == victim || permission_between(&query, killer, victim) != Some(AttackPermission::Aggression) {
+            continue;
+        }
+
+        let Ok((_, mut record, _)) = query.get_mut(killer) else {
+            continue;
+        };
+        record.record_murder(
+            Utc::now(),
+            chrono::Duration::seconds(settings.pvp.murderer_duration as i64),
+            settings.pvp.murder_penalty,
+        );
+    }
+}
+
+pub(crate) fn expire_player_kill_states(mut query: Query<&mut PlayerKillRecord>) {
+    let now = Utc::now();
+    for mut record in query.iter_mut() {
+        if record.is_expired(now) {
+            record.expire();
+        }
+    }
+}
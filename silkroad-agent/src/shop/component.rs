@@ -0,0 +1,81 @@
+use bevy_ecs::prelude::*;
+use silkroad_data::shopdata::Shop;
+use silkroad_game_base::Item;
+use std::collections::VecDeque;
+
+/// The amount of sold items a player can buy back from a shop.
+const BUY_BACK_SIZE: usize = 5;
+
+/// The shop of the NPC the player is currently talking to.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct OpenShop {
+    pub npc: Entity,
+    pub unique_id: u32,
+    pub shop: &'static Shop,
+}
+
+pub(crate) struct SoldItem {
+    pub item: Item,
+    pub price: u64,
+}
+
+/// The items a player has recently sold, which they may buy back for the same price. Only the last few items are
+/// kept, with the oldest ones being dropped first.
+#[derive(Component, Default)]
+pub(crate) struct BuyBackList {
+    items: VecDeque<SoldItem>,
+}
+
+impl BuyBackList {
+    pub fn push(&mut self, item: Item, price: u64) -> u8 {
+        if self.items.len() >= BUY_BACK_SIZE {
+            self.items.pop_front();
+        }
+        self.items.push_back(SoldItem { item, price });
+        (self.items.len() - 1) as u8
+    }
+
+    pub fn get(&self, slot: u8) -> Option<&SoldItem> {
+        self.items.get(slot as usize)
+    }
+
+    pub fn take(&mut self, slot: u8) -> Option<SoldItem> {
+        self.items.remove(slot as usize)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::item_reference;
+    use once_cell::sync::Lazy;
+    use silkroad_data::itemdata::RefItemData;
+    use silkroad_definitions::type_id::TypeId;
+    use silkroad_game_base::ItemTypeData;
+
+    static REFERENCE: Lazy<RefItemData> = Lazy::new(|| item_reference("ITEM_ETC_HP_POTION_01", TypeId(3, 3, 1, 1), 50));
+
+    fn item(amount: u16) -> Item {
+        Item {
+            reference: &REFERENCE,
+            variance: None,
+            type_data: ItemTypeData::Consumable { amount },
+        }
+    }
+
+    #[test]
+    pub fn test_drops_oldest() {
+        let mut list = BuyBackList::default();
+        for amount in 1..=6 {
+            list.push(item(amount), amount as u64 * 10);
+        }
+        assert_eq!(list.get(0).map(|sold| sold.price), Some(20));
+        assert_eq!(list.get(4).map(|sold| sold.price), Some(60));
+        assert!(list.get(5).is_none());
+
+        let taken = list.take(0).unwrap();
+        assert_eq!(taken.item.stack_size(), 2);
+        assert_eq!(list.get(0).map(|sold| sold.price), Some(30));
+    }
+}
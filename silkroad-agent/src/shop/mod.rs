@@ -0,0 +1,23 @@
+use crate::shop::system::{close_shop, handle_shop_operations, talk_to_npc};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+pub(crate) use component::*;
+pub(crate) use system::{talk_options_of, MAX_TALK_DISTANCE};
+
+mod component;
+mod system;
+
+pub(crate) struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                close_shop,
+                talk_to_npc.after(close_shop),
+                handle_shop_operations.after(talk_to_npc),
+            ),
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use crate::comp::gold::GoldPouch;
+use crate::comp::inventory::{new_item, PlayerInventory};
+use crate::comp::net::Client;
+use crate::comp::npc::NPC;
+use crate::comp::pos::Position;
+use crate::comp::GameEntity;
+use crate::exchange::Exchange;
+use crate::input::PlayerInput;
+use crate::shop::component::{BuyBackList, OpenShop};
+use crate::storage::{is_storage_keeper, StorageKeeper};
+use crate::world::{EntityLookup, WorldData};
+use bevy_ecs::prelude::*;
+use cgmath::MetricSpace;
+use silkroad_data::shopdata::Shop;
+use silkroad_game_base::Inventory;
+use silkroad_protocol::inventory::{
+    InventoryOperationError, InventoryOperationRequest, InventoryOperationResponseData, InventoryOperationResult,
+};
+use silkroad_protocol::world::{TalkOption, TalkToNpcError, TalkToNpcResponse};
+use tracing::debug;
+
+pub(crate) const MAX_TALK_DISTANCE: f32 = 500. * 500.;
+
+fn shop_of(npc_ref_id: u32) -> Option<&'static Shop> {
+    let npc = WorldData::characters().find_id(npc_ref_id)?;
+    WorldData::shops().shop_of(&npc.common.id)
+}
+
+/// Provides the options a player has when talking to the NPC with the given ref id.
+pub(crate) fn talk_options_of(npc_ref_id: u32) -> Vec<u8> {
+    let mut options = Vec::new();
+    if shop_of(npc_ref_id).is_some() {
+        options.push(TalkOption::Store.into());
+    }
+    if is_storage_keeper(npc_ref_id) {
+        options.push(TalkOption::Storage.into());
+    }
+    options
+}
+
+pub(crate) fn talk_to_npc(
+    query: Query<(Entity, &Client, &PlayerInput, &Position)>,
+    npcs: Query<(&GameEntity, &Position), With<NPC>>,
+    lookup: Res<EntityLookup>,
+    mut cmd: Commands,
+) {
+    for (entity, client, input, position) in query.iter() {
+        let Some(ref talk) = input.talk else {
+            continue;
+        };
+
+        let Some((npc, npc_entity, npc_position)) = lookup.get_entity_for_id(talk.unique_id).and_then(|npc| {
+            npcs.get(npc)
+                .ok()
+                .map(|(game_entity, position)| (npc, game_entity, position))
+        }) else {
+            client.send(TalkToNpcResponse::Error(TalkToNpcError::NoSuchOption));
+            continue;
+        };
+
+        if npc_position.position().distance2(position.position().0) >= MAX_TALK_DISTANCE {
+            client.send(TalkToNpcResponse::Error(TalkToNpcError::TooFarAway));
+            continue;
+        }
+
+        match talk.option {
+            TalkOption::Store => {
+                let Some(shop) = shop_of(npc_entity.ref_id) else {
+                    client.send(TalkToNpcResponse::Error(TalkToNpcError::NoSuchOption));
+                    continue;
+                };
+                cmd.entity(entity).insert(OpenShop {
+                    npc,
+                    unique_id: talk.unique_id,
+                    shop,
+                });
+                client.send(TalkToNpcResponse::Success(TalkOption::Store));
+            },
+            TalkOption::Storage => {
+                if !is_storage_keeper(npc_entity.ref_id) {
+                    client.send(TalkToNpcResponse::Error(TalkToNpcError::NoSuchOption));
+                    continue;
+                }
+                cmd.entity(entity).insert(StorageKeeper {
+                    npc,
+                    unique_id: talk.unique_id,
+                });
+                client.send(TalkToNpcResponse::Success(TalkOption::Storage));
+            },
+        }
+    }
+}
+
+pub(crate) fn close_shop(query: Query<(Entity, &PlayerInput, &OpenShop)>, npcs: Query<()>, mut cmd: Commands) {
+    for (entity, input, shop) in query.iter() {
+        let closed = input
+            .untarget
+            .as_ref()
+            .is_some_and(|untarget| untarget.unique_id == shop.unique_id);
+        if closed || npcs.get(shop.npc).is_err() {
+            cmd.entity(entity).remove::<OpenShop>();
+        }
+    }
+}
+
+pub(crate) fn handle_shop_operations(
+    mut query: Query<(
+        &Client,
+        &PlayerInput,
+        Option<&OpenShop>,
+        &mut PlayerInventory,
+        &mut GoldPouch,
+        &mut BuyBackList,
+        Has<Exchange>,
+    )>,
+) {
+    for (client, input, shop, mut inventory, mut gold, mut buy_back, in_exchange) in query.iter_mut() {
+        let Some(ref operation) = input.inventory else {
+            continue;
+        };
+
+        let result = match operation.data {
+            InventoryOperationRequest::BuyItem { .. }
+            | InventoryOperationRequest::SellItem { .. }
+            | InventoryOperationRequest::BuyBackItem { .. }
+                if in_exchange =>
+            {
+                Err(InventoryOperationError::Busy)
+            },
+            InventoryOperationRequest::BuyItem { tab, slot, amount, npc } => {
+                open_shop(shop, npc).and_then(|shop| buy_item(shop, tab, slot, amount, &mut inventory, &mut gold))
+            },
+            InventoryOperationRequest::SellItem { slot, amount, npc } => open_shop(shop, npc)
+                .and_then(|_| sell_item(slot, amount, npc, &mut inventory, &mut gold, &mut buy_back)),
+            InventoryOperationRequest::BuyBackItem { npc, slot, amount } => {
+                open_shop(shop, npc).and_then(|_| buy_back_item(slot, amount, &mut inventory, &mut gold, &mut buy_back))
+            },
+            _ => continue,
+        };
+
+        match result {
+            Ok(response) => client.send(InventoryOperationResult::Success(response)),
+            Err(error) => client.send(InventoryOperationResult::Error(error)),
+        }
+    }
+}
+
+/// Ensures the player is currently talking to the NPC they're trying to trade with.
+fn open_shop(shop: Option<&OpenShop>, npc: u32) -> Result<&'static Shop, InventoryOperationError> {
+    match shop {
+        Some(shop) if shop.unique_id == npc => Ok(shop.shop),
+        _ => Err(InventoryOperationError::InvalidTarget),
+    }
+}
+
+fn buy_item(
+    shop: &Shop,
+    tab: u8,
+    slot: u8,
+    amount: u16,
+    inventory: &mut PlayerInventory,
+    gold: &mut Mut<GoldPouch>,
+) -> Result<InventoryOperationResponseData, InventoryOperationError> {
+    let shop_item = shop.item(tab, slot).ok_or(InventoryOperationError::InvalidTarget)?;
+    let reference = WorldData::items()
+        .find_code(&shop_item.item)
+        .ok_or(InventoryOperationError::InvalidTarget)?;
+    let amount = amount.max(1);
+
+    // Items which cannot be stacked, like equipment, each need a slot of their own.
+    let stack_size = reference.max_stack_size.max(1);
+    let stacks = (0..amount)
+        .step_by(stack_size as usize)
+        .map(|bought| {
+            new_item(
+                reference,
+                shop_item.upgrade_level,
+                Some(shop_item.variance).filter(|variance| *variance != 0),
+                (amount - bought).min(stack_size),
+            )
+        })
+        .collect::<Vec<_>>();
+    if stacks.len() > inventory.free_slots() {
+        return Err(InventoryOperationError::InventoryFull);
+    }
+
+    let price = shop_item.price * amount as u64;
+    if price > gold.amount() {
+        return Err(InventoryOperationError::NotEnoughGold);
+    }
+
+    let mut target_slots = Vec::with_capacity(stacks.len());
+    for item in stacks {
+        let target = inventory.add_item(item).ok_or(InventoryOperationError::InventoryFull)?;
+        if !target_slots.contains(&target) {
+            target_slots.push(target);
+        }
+    }
+    gold.spend(price);
+    debug!("Bought {} of {} for {} gold.", amount, shop_item.item, price);
+    Ok(InventoryOperationResponseData::buy_item(
+        tab,
+        slot,
+        target_slots,
+        amount,
+    ))
+}
+
+fn sell_item(
+    slot: u8,
+    amount: u16,
+    npc: u32,
+    inventory: &mut PlayerInventory,
+    gold: &mut Mut<GoldPouch>,
+    buy_back: &mut BuyBackList,
+) -> Result<InventoryOperationResponseData, InventoryOperationError> {
+    if Inventory::is_equipment_slot(slot) {
+        return Err(InventoryOperationError::Indisposable);
+    }
+
+    let item = inventory
+        .take_from_slot(slot, amount.max(1))
+        .map_err(|_| InventoryOperationError::InvalidTarget)?;
+    let amount = item.stack_size();
+    let price = item.reference.sell_price * amount as u64;
+    gold.gain(price);
+    let buy_back_slot = buy_back.push(item, price);
+    Ok(InventoryOperationResponseData::sell_item(
+        slot,
+        amount,
+        npc,
+        buy_back_slot,
+    ))
+}
+
+fn buy_back_item(
+    slot: u8,
+    amount: u16,
+    inventory: &mut PlayerInventory,
+    gold: &mut Mut<GoldPouch>,
+    buy_back: &mut BuyBackList,
+) -> Result<InventoryOperationResponseData, InventoryOperationError> {
+    let sold = buy_back.get(slot).ok_or(InventoryOperationError::InvalidTarget)?;
+    if amount > sold.item.stack_size() {
+        return Err(InventoryOperationError::InvalidTarget);
+    }
+
+    if sold.price > gold.amount() {
+        return Err(InventoryOperationError::NotEnoughGold);
+    }
+
+    if inventory.free_slots() == 0 {
+        return Err(InventoryOperationError::InventoryFull);
+    }
+
+    let sold = buy_back
+        .take(slot)
+        .expect("Sold item should still exist just after checking");
+    let target = inventory
+        .add_item(sold.item)
+        .ok_or(InventoryOperationError::InventoryFull)?;
+    gold.spend(sold.price);
+    Ok(InventoryOperationResponseData::buy_back_item(
+        target,
+        slot,
+        sold.item.stack_size(),
+    ))
+}
@@ -0,0 +1,312 @@
+use crate::comp::inventory::item_content;
+use bevy_ecs::prelude::*;
+use silkroad_data::DataEntry;
+use silkroad_game_base::{Inventory, Item};
+use silkroad_protocol::stall::{StallErrorCode, StallItemData};
+
+/// The amount of different items a stall can offer at once.
+pub(crate) const STALL_SLOTS: usize = 10;
+
+/// The stall decoration shown for stalls that were opened without a special avatar.
+pub(crate) const DEFAULT_DECORATION: u32 = 0;
+
+/// The maximum length of a stall name as well as the stall message.
+const MAX_TEXT_LENGTH: usize = 60;
+
+/// Items from the inventory of the stall owner that are on sale. The items stay in the inventory until they're
+/// bought, so the listing only points at the slot they're in. It also remembers which item was in that slot, such
+/// that a different item put into the slot in the meantime doesn't get sold in its place.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) struct StallListing {
+    pub inventory_slot: u8,
+    pub ref_id: u32,
+    pub upgrade_level: u8,
+    pub amount: u16,
+    pub price: u64,
+}
+
+impl StallListing {
+    /// Creates a listing for the item currently in the inventory slot, if there is one.
+    pub fn new(inventory_slot: u8, amount: u16, price: u64, inventory: &Inventory) -> Option<Self> {
+        let item = inventory.get_item_at(inventory_slot)?;
+        Some(StallListing {
+            inventory_slot,
+            ref_id: item.reference.ref_id(),
+            upgrade_level: item.upgrade_level(),
+            amount,
+            price,
+        })
+    }
+
+    /// Checks if the item is still the one that was put up for sale and there's enough of it left.
+    pub fn matches(&self, item: &Item) -> bool {
+        item.reference.ref_id() == self.ref_id
+            && item.upgrade_level() == self.upgrade_level
+            && item.stack_size() >= self.amount
+    }
+}
+
+/// A stall opened by a player, in which other players can buy the items put up for sale. Items can only be changed
+/// while the stall isn't open for business.
+#[derive(Component)]
+pub(crate) struct Stall {
+    pub name: String,
+    pub message: String,
+    pub open: bool,
+    listings: [Option<StallListing>; STALL_SLOTS],
+    visitors: Vec<Entity>,
+}
+
+impl Stall {
+    pub fn new(name: String) -> Result<Self, StallErrorCode> {
+        Ok(Stall {
+            name: check_name(name)?,
+            message: String::new(),
+            open: false,
+            listings: [None; STALL_SLOTS],
+            visitors: Vec::new(),
+        })
+    }
+
+    pub fn rename(&mut self, name: String) -> Result<(), StallErrorCode> {
+        self.name = check_name(name)?;
+        Ok(())
+    }
+
+    pub fn set_message(&mut self, message: String) -> Result<(), StallErrorCode> {
+        self.message = check_text(message).ok_or(StallErrorCode::InvalidName)?;
+        Ok(())
+    }
+
+    pub fn listing(&self, slot: u8) -> Option<StallListing> {
+        self.listings.get(slot as usize).copied().flatten()
+    }
+
+    /// Puts the given amount of the item in the inventory slot up for sale.
+    pub fn add_listing(
+        &mut self,
+        slot: u8,
+        listing: StallListing,
+        inventory: &Inventory,
+    ) -> Result<(), StallErrorCode> {
+        if self.open {
+            return Err(StallErrorCode::NotAllowed);
+        }
+
+        let entry = self
+            .listings
+            .get_mut(slot as usize)
+            .ok_or(StallErrorCode::InvalidTarget)?;
+        if entry.is_some() {
+            return Err(StallErrorCode::InvalidTarget);
+        }
+
+        let listed_elsewhere = self
+            .listings
+            .iter()
+            .flatten()
+            .any(|other| other.inventory_slot == listing.inventory_slot);
+        if listed_elsewhere || !Self::is_listable(&listing, inventory) {
+            return Err(StallErrorCode::InvalidItem);
+        }
+
+        self.listings[slot as usize] = Some(listing);
+        Ok(())
+    }
+
+    pub fn update_listing(
+        &mut self,
+        slot: u8,
+        amount: u16,
+        price: u64,
+        inventory: &Inventory,
+    ) -> Result<(), StallErrorCode> {
+        if self.open {
+            return Err(StallErrorCode::NotAllowed);
+        }
+
+        let entry = self
+            .listings
+            .get_mut(slot as usize)
+            .and_then(|listing| listing.as_mut())
+            .ok_or(StallErrorCode::InvalidTarget)?;
+        let updated = StallListing {
+            amount,
+            price,
+            ..*entry
+        };
+        if !Self::is_listable(&updated, inventory) {
+            return Err(StallErrorCode::InvalidItem);
+        }
+        *entry = updated;
+        Ok(())
+    }
+
+    pub fn remove_listing(&mut self, slot: u8) -> Result<StallListing, StallErrorCode> {
+        if self.open {
+            return Err(StallErrorCode::NotAllowed);
+        }
+
+        self.listings
+            .get_mut(slot as usize)
+            .and_then(|listing| listing.take())
+            .ok_or(StallErrorCode::InvalidTarget)
+    }
+
+    /// Removes the listing after it has been bought, which is possible while the stall is open.
+    pub fn sell(&mut self, slot: u8) -> Option<StallListing> {
+        self.listings.get_mut(slot as usize).and_then(|listing| listing.take())
+    }
+
+    fn is_listable(listing: &StallListing, inventory: &Inventory) -> bool {
+        if Inventory::is_equipment_slot(listing.inventory_slot) || listing.amount == 0 || listing.price == 0 {
+            return false;
+        }
+
+        inventory
+            .get_item_at(listing.inventory_slot)
+            .is_some_and(|item| listing.matches(item))
+    }
+
+    pub fn visitors(&self) -> &[Entity] {
+        &self.visitors
+    }
+
+    pub fn add_visitor(&mut self, visitor: Entity) {
+        if !self.visitors.contains(&visitor) {
+            self.visitors.push(visitor);
+        }
+    }
+
+    pub fn remove_visitor(&mut self, visitor: Entity) {
+        self.visitors.retain(|other| *other != visitor);
+    }
+
+    /// Provides the listings in a form that can be shown to the owner and the visitors. Listings whose item is no
+    /// longer present in the inventory of the owner, or has been replaced by a different one, are left out.
+    pub fn items(&self, inventory: &Inventory) -> Vec<StallItemData> {
+        self.listings
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, listing)| listing.map(|listing| (slot, listing)))
+            .filter_map(|(slot, listing)| {
+                let item = inventory
+                    .get_item_at(listing.inventory_slot)
+                    .filter(|item| listing.matches(item))?;
+                Some(StallItemData::new(
+                    slot as u8,
+                    item.reference.ref_id(),
+                    item_content(item),
+                    listing.inventory_slot,
+                    listing.amount,
+                    listing.price,
+                ))
+            })
+            .collect()
+    }
+}
+
+fn check_name(name: String) -> Result<String, StallErrorCode> {
+    check_text(name)
+        .filter(|name| !name.is_empty())
+        .ok_or(StallErrorCode::InvalidName)
+}
+
+fn check_text(text: String) -> Option<String> {
+    let trimmed = text.trim();
+    if trimmed.chars().count() > MAX_TEXT_LENGTH {
+        return None;
+    }
+    Some(trimmed.to_string())
+}
+
+/// The stall the player is currently browsing.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct StallVisitor {
+    pub stall: Entity,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::item_reference;
+    use once_cell::sync::Lazy;
+    use silkroad_data::itemdata::RefItemData;
+    use silkroad_definitions::type_id::TypeId;
+    use silkroad_game_base::ItemTypeData;
+
+    static REFERENCE: Lazy<RefItemData> = Lazy::new(|| item_reference("ITEM_ETC_HP_POTION_01", TypeId(3, 3, 1, 1), 50));
+    static SWORD: Lazy<RefItemData> = Lazy::new(|| {
+        let mut reference = item_reference("ITEM_CH_SWORD_01_A", TypeId(3, 1, 6, 2), 1);
+        reference.common.ref_id = REFERENCE.common.ref_id + 1;
+        reference
+    });
+
+    fn potions(amount: u16) -> Item {
+        Item {
+            reference: &REFERENCE,
+            variance: None,
+            type_data: ItemTypeData::Consumable { amount },
+        }
+    }
+
+    fn inventory() -> Inventory {
+        let mut inventory = Inventory::new(45);
+        inventory.set_item(13, potions(20));
+        inventory
+    }
+
+    fn listing(amount: u16) -> StallListing {
+        StallListing::new(13, amount, 100, &inventory()).unwrap()
+    }
+
+    #[test]
+    pub fn test_listings_must_exist_in_inventory() {
+        let inventory = inventory();
+        let mut stall = Stall::new("My Stall".to_string()).unwrap();
+
+        assert_eq!(
+            stall.add_listing(0, listing(21), &inventory),
+            Err(StallErrorCode::InvalidItem)
+        );
+        assert_eq!(stall.add_listing(0, listing(20), &inventory), Ok(()));
+        assert_eq!(
+            stall.add_listing(1, listing(5), &inventory),
+            Err(StallErrorCode::InvalidItem)
+        );
+        assert_eq!(
+            stall.add_listing(STALL_SLOTS as u8, listing(5), &inventory),
+            Err(StallErrorCode::InvalidTarget)
+        );
+        assert_eq!(stall.update_listing(0, 10, 500, &inventory), Ok(()));
+        assert_eq!(stall.listing(0).map(|listing| listing.price), Some(500));
+        assert_eq!(stall.items(&inventory).len(), 1);
+    }
+
+    #[test]
+    pub fn test_open_stall_cannot_be_changed() {
+        let inventory = inventory();
+        let mut stall = Stall::new("My Stall".to_string()).unwrap();
+        stall.add_listing(3, listing(20), &inventory).unwrap();
+        stall.open = true;
+
+        assert_eq!(stall.remove_listing(3), Err(StallErrorCode::NotAllowed));
+        assert_eq!(stall.sell(3), Some(listing(20)));
+        assert_eq!(stall.sell(3), None);
+    }
+
+    #[test]
+    pub fn test_listing_must_match_item() {
+        let listing = listing(10);
+        assert!(listing.matches(&potions(10)));
+        assert!(!listing.matches(&potions(9)));
+
+        let sword = Item {
+            reference: &SWORD,
+            variance: None,
+            type_data: ItemTypeData::Equipment { upgrade_level: 0 },
+        };
+        assert!(!listing.matches(&sword));
+    }
+}
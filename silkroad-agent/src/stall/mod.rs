@@ -0,0 +1,25 @@
+use crate::stall::system::{buy_from_stall, close_stall, leave_stall, open_stall, update_stall, visit_stall};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+pub(crate) use component::*;
+
+mod component;
+mod system;
+
+pub(crate) struct StallPlugin;
+
+impl Plugin for StallPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                close_stall,
+                open_stall.after(close_stall),
+                update_stall.after(open_stall),
+                visit_stall.after(update_stall),
+                leave_stall.after(visit_stall),
+                buy_from_stall.after(leave_stall),
+            ),
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use crate::agent::states::{Action, Dead, Idle, Moving, Sitting};
+use crate::comp::gold::GoldPouch;
+use crate::comp::inventory::{give_item, PlayerInventory};
+use crate::comp::net::Client;
+use crate::comp::player::Player;
+use crate::comp::pos::Position;
+use crate::comp::visibility::Visibility;
+use crate::comp::GameEntity;
+use crate::exchange::Exchange;
+use crate::input::PlayerInput;
+use crate::shop::MAX_TALK_DISTANCE;
+use crate::stall::component::{Stall, StallListing, StallVisitor, DEFAULT_DECORATION};
+use crate::teleport::Teleporting;
+use crate::world::EntityLookup;
+use bevy_ecs::prelude::*;
+use silkroad_protocol::stall::{
+    BuyFromStallResponse, CloseStallResponse, LeaveStallResponse, OpenStallResponse, StallEntityClosed,
+    StallEntityOpened, StallEntityRenamed, StallErrorCode, StallResult, StallUpdate, UpdateStallKind,
+    UpdateStallResponse, VisitStallResponse,
+};
+use silkroad_protocol::ServerPacket;
+use tracing::debug;
+
+/// The amount of players that can browse a stall at the same time.
+const MAX_VISITORS: usize = 10;
+
+/// Sends the packet to all players that can currently see the stall owner.
+fn send_to_surrounding<T: Into<ServerPacket> + Clone>(visibility: &Visibility, packet: T, clients: &Query<&Client>) {
+    for other in visibility.entities_in_radius.iter() {
+        if let Ok(client) = clients.get(other.0) {
+            client.send(packet.clone());
+        }
+    }
+}
+
+fn send_to_visitors<T: Into<ServerPacket> + Clone>(stall: &Stall, packet: T, clients: &Query<&Client>) {
+    for visitor in stall.visitors() {
+        if let Ok(client) = clients.get(*visitor) {
+            client.send(packet.clone());
+        }
+    }
+}
+
+fn stall_content(stall: &Stall, inventory: &PlayerInventory) -> StallUpdate {
+    StallUpdate::Content {
+        open: stall.open,
+        message: stall.message.clone(),
+        items: stall.items(inventory),
+    }
+}
+
+pub(crate) fn close_stall(
+    query: Query<(
+        Entity,
+        &Client,
+        &PlayerInput,
+        &Visibility,
+        &GameEntity,
+        &Stall,
+        Has<Moving>,
+        Has<Action>,
+        Has<Dead>,
+        Has<Teleporting>,
+    )>,
+    clients: Query<&Client>,
+    mut cmd: Commands,
+) {
+    for (entity, client, input, visibility, game_entity, stall, moving, acting, dead, teleporting) in query.iter() {
+        let requested = input.stall_close.is_some();
+        if !requested && !moving && !acting && !dead && !teleporting {
+            continue;
+        }
+
+        send_to_visitors(stall, StallUpdate::Closed, &clients);
+        for visitor in stall.visitors() {
+            if let Some(mut visitor) = cmd.get_entity(*visitor) {
+                visitor.remove::<StallVisitor>();
+            }
+        }
+        send_to_surrounding(visibility, StallEntityClosed::new(game_entity.unique_id), &clients);
+        cmd.entity(entity).remove::<Stall>();
+        if requested {
+            client.send(CloseStallResponse::new(StallResult::Success));
+        }
+    }
+}
+
+pub(crate) fn open_stall(
+    query: Query<(
+        Entity,
+        &Client,
+        &PlayerInput,
+        &Visibility,
+        &GameEntity,
+        Has<Stall>,
+        Has<StallVisitor>,
+        Has<Idle>,
+        Has<Sitting>,
+    )>,
+    clients: Query<&Client>,
+    mut cmd: Commands,
+) {
+    for (entity, client, input, visibility, game_entity, has_stall, visiting, idle, sitting) in query.iter() {
+        if input.stall_close.is_some() && !has_stall {
+            client.send(CloseStallResponse::new(StallResult::error(StallErrorCode::NotAllowed)));
+        }
+
+        let Some(ref open) = input.stall_open else {
+            continue;
+        };
+
+        if has_stall || visiting || !(idle || sitting) {
+            client.send(OpenStallResponse::new(StallResult::error(StallErrorCode::NotAllowed)));
+            continue;
+        }
+
+        let stall = match Stall::new(open.name.clone()) {
+            Ok(stall) => stall,
+            Err(code) => {
+                client.send(OpenStallResponse::new(StallResult::error(code)));
+                continue;
+            },
+        };
+
+        debug!(id = ?client.id(), "Opened stall '{}'", stall.name);
+        send_to_surrounding(
+            visibility,
+            StallEntityOpened::new(game_entity.unique_id, stall.name.clone(), DEFAULT_DECORATION),
+            &clients,
+        );
+        cmd.entity(entity).insert(stall);
+        client.send(OpenStallResponse::new(StallResult::Success));
+    }
+}
+
+pub(crate) fn update_stall(
+    mut query: Query<(
+        &Client,
+        &PlayerInput,
+        &Visibility,
+        &GameEntity,
+        &PlayerInventory,
+        &mut Stall,
+    )>,
+    clients: Query<&Client>,
+) {
+    for (client, input, visibility, game_entity, inventory, mut stall) in query.iter_mut() {
+        for update in input.stall_update.iter() {
+            let result = match update.kind {
+                UpdateStallKind::AddItem {
+                    slot,
+                    inventory_slot,
+                    amount,
+                    price,
+                } => StallListing::new(inventory_slot, amount, price, inventory)
+                    .ok_or(StallErrorCode::InvalidItem)
+                    .and_then(|listing| stall.add_listing(slot, listing, inventory)),
+                UpdateStallKind::UpdateItem { slot, amount, price } => {
+                    stall.update_listing(slot, amount, price, inventory)
+                },
+                UpdateStallKind::RemoveItem { slot } => stall.remove_listing(slot).map(|_| ()),
+                UpdateStallKind::State { open } => {
+                    stall.open = open;
+                    Ok(())
+                },
+                UpdateStallKind::Message { ref message } => stall.set_message(message.clone()),
+                UpdateStallKind::Name { ref name } => {
+                    let renamed = stall.rename(name.clone());
+                    if renamed.is_ok() {
+                        send_to_surrounding(
+                            visibility,
+                            StallEntityRenamed::new(game_entity.unique_id, stall.name.clone()),
+                            &clients,
+                        );
+                    }
+                    renamed
+                },
+            };
+
+            if result.is_ok() {
+                send_to_visitors(&stall, stall_content(&stall, inventory), &clients);
+            }
+            client.send(UpdateStallResponse::new(result.into()));
+        }
+    }
+}
+
+pub(crate) fn visit_stall(
+    query: Query<(
+        Entity,
+        &Client,
+        &PlayerInput,
+        &GameEntity,
+        &Position,
+        Has<Stall>,
+        Has<StallVisitor>,
+    )>,
+    mut stalls: Query<(&Position, &PlayerInventory, &mut Stall)>,
+    clients: Query<&Client>,
+    lookup: Res<EntityLookup>,
+    mut cmd: Commands,
+) {
+    for (entity, client, input, game_entity, position, has_stall, visiting) in query.iter() {
+        let Some(visit) = input.stall_visit else {
+            continue;
+        };
+
+        if has_stall || visiting {
+            client.send(VisitStallResponse::Error(StallErrorCode::NotAllowed));
+            continue;
+        }
+
+        let Some((owner, (stall_position, inventory, mut stall))) = lookup
+            .get_entity_for_id(visit.unique_id)
+            .filter(|owner| *owner != entity)
+            .and_then(|owner| stalls.get_mut(owner).ok().map(|stall| (owner, stall)))
+        else {
+            client.send(VisitStallResponse::Error(StallErrorCode::InvalidTarget));
+            continue;
+        };
+
+        if position.distance_to(stall_position) >= MAX_TALK_DISTANCE {
+            client.send(VisitStallResponse::Error(StallErrorCode::TooFarAway));
+            continue;
+        }
+
+        if stall.visitors().len() >= MAX_VISITORS {
+            client.send(VisitStallResponse::Error(StallErrorCode::StallFull));
+            continue;
+        }
+
+        let entered = StallUpdate::Enter {
+            unique_id: game_entity.unique_id,
+        };
+        if let Ok(owner_client) = clients.get(owner) {
+            owner_client.send(entered.clone());
+        }
+        send_to_visitors(&stall, entered, &clients);
+
+        stall.add_visitor(entity);
+        cmd.entity(entity).insert(StallVisitor { stall: owner });
+        client.send(VisitStallResponse::success(
+            visit.unique_id,
+            stall.message.clone(),
+            stall.open,
+            stall.items(inventory),
+        ));
+    }
+}
+
+pub(crate) fn leave_stall(
+    query: Query<(
+        Entity,
+        &Client,
+        &PlayerInput,
+        &GameEntity,
+        &StallVisitor,
+        Has<Moving>,
+        Has<Dead>,
+        Has<Teleporting>,
+    )>,
+    mut stalls: Query<(Entity, &mut Stall)>,
+    visitors: Query<&StallVisitor>,
+    clients: Query<&Client>,
+    mut cmd: Commands,
+) {
+    for (entity, client, input, game_entity, visitor, moving, dead, teleporting) in query.iter() {
+        let requested = input.stall_leave.is_some();
+        let stall = stalls.get_mut(visitor.stall).ok();
+        if !requested && !moving && !dead && !teleporting && stall.is_some() {
+            continue;
+        }
+
+        match stall {
+            Some((owner, mut stall)) => {
+                stall.remove_visitor(entity);
+                let left = StallUpdate::Leave {
+                    unique_id: game_entity.unique_id,
+                };
+                if let Ok(owner_client) = clients.get(owner) {
+                    owner_client.send(left.clone());
+                }
+                send_to_visitors(&stall, left, &clients);
+            },
+            None => client.send(StallUpdate::Closed),
+        }
+
+        cmd.entity(entity).remove::<StallVisitor>();
+        if requested {
+            client.send(LeaveStallResponse::new(StallResult::Success));
+        }
+    }
+
+    // Visitors that disconnected while browsing are never going to leave on their own.
+    for (owner, mut stall) in stalls.iter_mut() {
+        let disconnected = stall
+            .visitors()
+            .iter()
+            .copied()
+            .filter(|visitor| !visitors.get(*visitor).is_ok_and(|visitor| visitor.stall == owner))
+            .collect::<Vec<_>>();
+        for visitor in disconnected {
+            stall.remove_visitor(visitor);
+        }
+    }
+}
+
+/// Hands the listed item over to the buyer and the price over to the stall owner. Everything is checked before
+/// anything is changed, such that either both sides receive their part of the trade or nothing happens at all.
+fn trade(
+    buyer: Entity,
+    owner: Entity,
+    slot: u8,
+    stall: &mut Stall,
+    traders: &mut Query<(&Client, &mut PlayerInventory, &mut GoldPouch)>,
+) -> Result<(), StallErrorCode> {
+    if !stall.open {
+        return Err(StallErrorCode::NotOpen);
+    }
+
+    let listing = stall.listing(slot).ok_or(StallErrorCode::ItemUnavailable)?;
+    let [(buyer_client, mut buyer_inventory, mut buyer_gold), (_, mut owner_inventory, mut owner_gold)] = traders
+        .get_many_mut([buyer, owner])
+        .map_err(|_| StallErrorCode::InvalidTarget)?;
+
+    let available = owner_inventory
+        .get_item_at(listing.inventory_slot)
+        .is_some_and(|item| listing.matches(item));
+    if !available {
+        return Err(StallErrorCode::ItemUnavailable);
+    }
+
+    if buyer_gold.amount() < listing.price {
+        return Err(StallErrorCode::NotEnoughGold);
+    }
+
+    if buyer_inventory.free_slots() == 0 {
+        return Err(StallErrorCode::InventoryFull);
+    }
+
+    let item = owner_inventory
+        .take_from_slot(listing.inventory_slot, listing.amount)
+        .map_err(|_| StallErrorCode::ItemUnavailable)?;
+    stall.sell(slot);
+    buyer_gold.spend(listing.price);
+    owner_gold.gain(listing.price);
+    // The owner's client removes the item from its inventory on its own once it learns about the sale.
+    give_item(buyer_client, &mut buyer_inventory, item);
+    Ok(())
+}
+
+pub(crate) fn buy_from_stall(
+    buyers: Query<(Entity, &Client, &PlayerInput, &Player, &StallVisitor, Has<Exchange>)>,
+    mut stalls: Query<&mut Stall>,
+    mut traders: Query<(&Client, &mut PlayerInventory, &mut GoldPouch)>,
+    clients: Query<&Client>,
+) {
+    for (buyer, client, input, player, visitor, in_exchange) in buyers.iter() {
+        let Some(buy) = input.stall_buy else {
+            continue;
+        };
+
+        // The bought item could end up on a stack that is offered in an exchange.
+        if in_exchange {
+            client.send(BuyFromStallResponse::new(StallResult::error(
+                StallErrorCode::NotAllowed,
+            )));
+            continue;
+        }
+
+        let Ok(mut stall) = stalls.get_mut(visitor.stall) else {
+            client.send(BuyFromStallResponse::new(StallResult::error(
+                StallErrorCode::InvalidTarget,
+            )));
+            continue;
+        };
+
+        let result = trade(buyer, visitor.stall, buy.slot, &mut stall, &mut traders);
+        if result.is_ok() {
+            debug!(id = ?client.id(), "Bought item in slot {} of stall '{}'", buy.slot, stall.name);
+            let sold = StallUpdate::Buy {
+                slot: buy.slot,
+                buyer: player.character.name.clone(),
+            };
+            if let Ok(owner_client) = clients.get(visitor.stall) {
+                owner_client.send(sold.clone());
+            }
+            send_to_visitors(&stall, sold, &clients);
+            if let Ok((_, inventory, _)) = traders.get(visitor.stall) {
+                send_to_visitors(&stall, stall_content(&stall, inventory), &clients);
+            }
+        }
+        client.send(BuyFromStallResponse::new(result.into()));
+    }
+}
@@ -0,0 +1,159 @@
+use crate::comp::inventory::new_item;
+use crate::storage::db::DbStorage;
+use crate::world::WorldData;
+use bevy_ecs::prelude::*;
+use silkroad_game_base::{Change, ChangeProvided, ChangeTracked, Inventory, InventoryChange, MergeResult};
+use sqlx::Error;
+use std::ops::{Deref, DerefMut};
+use tokio::sync::oneshot::Receiver;
+
+/// The storage NPC the player is currently talking to.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct StorageKeeper {
+    pub npc: Entity,
+    pub unique_id: u32,
+}
+
+/// The storage of the account of the player, which is still being loaded from the database.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct StorageTask(pub Receiver<Result<DbStorage, Error>>);
+
+/// The items and gold the account of the player keeps in its storage on this server. The storage is shared by all
+/// characters of the account and is only loaded once the player first opens it.
+#[derive(Component)]
+pub(crate) struct PlayerStorage {
+    items: Inventory,
+    gold: u64,
+}
+
+impl PlayerStorage {
+    pub fn from_db(storage: &DbStorage, size: usize) -> Self {
+        let mut items = Inventory::without_equipment(size);
+        for item in storage.items.iter() {
+            let Some(reference) = WorldData::items().find_id(item.item_obj_id as u32) else {
+                continue;
+            };
+            items.set_item(
+                item.slot as u8,
+                new_item(
+                    reference,
+                    item.upgrade_level as u8,
+                    item.variance.map(|variance| variance as u64),
+                    item.amount as u16,
+                ),
+            );
+        }
+
+        PlayerStorage {
+            items,
+            gold: storage.gold.max(0) as u64,
+        }
+    }
+
+    pub fn gold(&self) -> u64 {
+        self.gold
+    }
+
+    pub fn deposit_gold(&mut self, amount: u64) {
+        self.gold = self.gold.saturating_add(amount);
+    }
+
+    pub fn withdraw_gold(&mut self, amount: u64) -> bool {
+        if amount > self.gold {
+            return false;
+        }
+        self.gold -= amount;
+        true
+    }
+}
+
+impl Deref for PlayerStorage {
+    type Target = Inventory;
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl DerefMut for PlayerStorage {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.items
+    }
+}
+
+/// A change to the items inside the storage, which only differs from changes to the inventory by where it ends up
+/// being stored.
+pub(crate) struct StorageChange(pub InventoryChange);
+
+impl Change for StorageChange {
+    fn merge(self, other: Self) -> MergeResult<Self> {
+        match self.0.merge(other.0) {
+            MergeResult::Unchanged(first, second) => {
+                MergeResult::Unchanged(StorageChange(first), StorageChange(second))
+            },
+            MergeResult::Incompatible(first, second) => {
+                MergeResult::Incompatible(StorageChange(first), StorageChange(second))
+            },
+            MergeResult::Merged(merged) => MergeResult::Merged(StorageChange(merged)),
+            MergeResult::Cancelled => MergeResult::Cancelled,
+        }
+    }
+}
+
+impl ChangeTracked for PlayerStorage {
+    type ChangeItem = StorageChange;
+
+    fn changes(&mut self) -> Vec<Self::ChangeItem> {
+        self.items.changes().into_iter().map(StorageChange).collect()
+    }
+}
+
+pub(crate) struct StorageGoldChange(pub u64);
+
+impl ChangeProvided for PlayerStorage {
+    type Change = StorageGoldChange;
+
+    fn as_change(&self) -> Self::Change {
+        StorageGoldChange(self.gold)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::item_reference;
+    use once_cell::sync::Lazy;
+    use silkroad_data::itemdata::RefItemData;
+    use silkroad_definitions::type_id::TypeId;
+    use silkroad_game_base::{Item, ItemTypeData};
+
+    static REFERENCE: Lazy<RefItemData> = Lazy::new(|| item_reference("ITEM_ETC_HP_POTION_01", TypeId(3, 3, 1, 1), 50));
+
+    #[test]
+    pub fn test_storage_has_no_equipment_slots() {
+        let mut storage = PlayerStorage {
+            items: Inventory::without_equipment(5),
+            gold: 100,
+        };
+        assert_eq!(storage.free_slots(), 5);
+
+        let item = Item {
+            reference: &REFERENCE,
+            variance: None,
+            type_data: ItemTypeData::Consumable { amount: 1 },
+        };
+        assert!(storage.place_item(0, item).is_ok());
+        assert!(storage.place_item(0, item).is_err());
+        assert!(storage.place_item(5, item).is_err());
+        assert!(matches!(
+            storage.changes().as_slice(),
+            [StorageChange(InventoryChange::AddItem { slot: 0, .. })]
+        ));
+
+        assert!(!storage.withdraw_gold(101));
+        assert!(storage.withdraw_gold(60));
+        assert_eq!(storage.gold(), 40);
+    }
+}
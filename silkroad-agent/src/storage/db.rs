@@ -0,0 +1,140 @@
+use crate::persistence::ApplyToDatabase;
+use crate::storage::component::{StorageChange, StorageGoldChange};
+use axum::async_trait;
+use silkroad_game_base::InventoryChange;
+use sqlx::{Error, PgPool};
+use std::borrow::Borrow;
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct DbStorageItem {
+    pub(crate) slot: i16,
+    pub(crate) item_obj_id: i32,
+    pub(crate) upgrade_level: i16,
+    pub(crate) variance: Option<i64>,
+    pub(crate) amount: i16,
+}
+
+pub(crate) struct DbStorage {
+    pub(crate) gold: i64,
+    pub(crate) items: Vec<DbStorageItem>,
+}
+
+pub(crate) async fn load_storage<T: Borrow<PgPool>>(user_id: i32, server_id: u16, pool: T) -> Result<DbStorage, Error> {
+    let gold = sqlx::query_scalar!(
+        "SELECT storage_gold FROM user_servers WHERE user_id = $1 AND server_id = $2",
+        user_id,
+        server_id as i32
+    )
+    .fetch_optional(pool.borrow())
+    .await?;
+
+    let items = sqlx::query_as!(
+        DbStorageItem,
+        "SELECT slot, item_obj_id, upgrade_level, variance, amount FROM user_storage_items WHERE user_id = $1 AND server_id = $2",
+        user_id,
+        server_id as i32
+    )
+    .fetch_all(pool.borrow())
+    .await?;
+
+    Ok(DbStorage {
+        gold: gold.unwrap_or(0),
+        items,
+    })
+}
+
+// The storage belongs to the account of the character on the server of the character, so we go through the
+// character to find the storage it belongs to.
+
+#[async_trait]
+impl ApplyToDatabase for StorageChange {
+    async fn apply(&self, character_id: u32, pool: &PgPool) -> Result<(), Error> {
+        match &self.0 {
+            InventoryChange::AddItem { slot, item } => {
+                sqlx::query!(
+                    "INSERT INTO user_storage_items(user_id, server_id, slot, item_obj_id, upgrade_level, variance, amount) SELECT user_id, server_id, $2, $3, $4, $5, $6 FROM characters WHERE id = $1 ON CONFLICT(user_id, server_id, slot) DO UPDATE SET item_obj_id = EXCLUDED.item_obj_id, upgrade_level = EXCLUDED.upgrade_level, variance = EXCLUDED.variance, amount = EXCLUDED.amount",
+                    character_id as i32,
+                    *slot as i16,
+                    item.reference.common.ref_id as i32,
+                    item.type_data.upgrade_level().map(|a| a as i16).unwrap_or(0),
+                    item.variance.map(|a| a as i64),
+                    item.type_data.amount() as i16
+                )
+                .execute(pool)
+                .await?;
+            },
+            InventoryChange::ChangeTypeData { slot, new_item, .. } => {
+                sqlx::query!(
+                    "UPDATE user_storage_items SET upgrade_level = $1, amount = $2 WHERE (user_id, server_id) = (SELECT user_id, server_id FROM characters WHERE id = $3) AND slot = $4",
+                    new_item.upgrade_level().map(|a| a as i16).unwrap_or(0),
+                    new_item.amount() as i16,
+                    character_id as i32,
+                    *slot as i16,
+                )
+                .execute(pool)
+                .await?;
+            },
+            InventoryChange::MoveItem {
+                source_slot,
+                target_slot,
+            } => {
+                sqlx::query!(
+                    "UPDATE user_storage_items SET slot = $1 WHERE (user_id, server_id) = (SELECT user_id, server_id FROM characters WHERE id = $2) AND slot = $3",
+                    *target_slot as i16,
+                    character_id as i32,
+                    *source_slot as i16,
+                )
+                .execute(pool)
+                .await?;
+            },
+            InventoryChange::RemoveItem { slot } => {
+                sqlx::query!(
+                    "DELETE FROM user_storage_items WHERE (user_id, server_id) = (SELECT user_id, server_id FROM characters WHERE id = $1) AND slot = $2",
+                    character_id as i32,
+                    *slot as i16,
+                )
+                .execute(pool)
+                .await?;
+            },
+            InventoryChange::Swap {
+                first_slot,
+                second_slot,
+            } => {
+                // Same as for the inventory, we need to move one of the items out of the way to not violate the
+                // unique slot constraint.
+                let mut transaction = pool.begin().await?;
+                sqlx::query!(
+                    "UPDATE user_storage_items SET slot = CASE slot WHEN $2 THEN -1 ELSE $2 END WHERE (user_id, server_id) = (SELECT user_id, server_id FROM characters WHERE id = $1) AND slot IN ($2, $3)",
+                    character_id as i32,
+                    *first_slot as i16,
+                    *second_slot as i16,
+                )
+                .execute(&mut *transaction)
+                .await?;
+                sqlx::query!(
+                    "UPDATE user_storage_items SET slot = $2 WHERE (user_id, server_id) = (SELECT user_id, server_id FROM characters WHERE id = $1) AND slot = -1",
+                    character_id as i32,
+                    *second_slot as i16,
+                )
+                .execute(&mut *transaction)
+                .await?;
+                transaction.commit().await?;
+            },
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ApplyToDatabase for StorageGoldChange {
+    async fn apply(&self, character_id: u32, pool: &PgPool) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE user_servers SET storage_gold = $1 WHERE (user_id, server_id) = (SELECT user_id, server_id FROM characters WHERE id = $2)",
+            self.0 as i64,
+            character_id as i32
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use crate::persistence::AppPersistanceExt;
+use crate::storage::system::{close_storage, handle_storage_operations, open_storage, receive_storage};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+pub(crate) use component::*;
+pub(crate) use system::is_storage_keeper;
+
+mod component;
+mod db;
+mod system;
+
+pub(crate) struct StoragePlugin;
+
+impl Plugin for StoragePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                close_storage,
+                open_storage.after(close_storage),
+                receive_storage.after(open_storage),
+                handle_storage_operations.after(receive_storage),
+            ),
+        )
+        .track_component::<PlayerStorage>()
+        .track_change_component::<PlayerStorage>();
+    }
+}
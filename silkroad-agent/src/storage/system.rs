@@ -0,0 +1,286 @@
+use crate::comp::gold::GoldPouch;
+use crate::comp::inventory::{item_content, PlayerInventory};
+use crate::comp::net::Client;
+use crate::comp::player::Player;
+use crate::config::GameConfig;
+use crate::exchange::Exchange;
+use crate::ext::DbPool;
+use crate::input::PlayerInput;
+use crate::server_plugin::ServerId;
+use crate::storage::component::{PlayerStorage, StorageKeeper, StorageTask};
+use crate::storage::db::load_storage;
+use crate::tasks::TaskCreator;
+use crate::world::WorldData;
+use bevy_ecs::prelude::*;
+use silkroad_data::DataEntry;
+use silkroad_game_base::Inventory;
+use silkroad_protocol::inventory::{
+    InventoryItemData, InventoryOperationError, InventoryOperationRequest, InventoryOperationResponseData,
+    InventoryOperationResult, OpenStorageResponse, RentInfo, StorageData, StorageEnd, StorageGold, StorageStart,
+};
+use sqlx::PgPool;
+use std::cmp::max;
+use tokio::sync::oneshot::error::TryRecvError;
+use tracing::{debug, error};
+
+/// Checks if the NPC with the given ref id is a storage keeper, which are the NPCs that provide access to the
+/// storage of the account.
+pub(crate) fn is_storage_keeper(npc_ref_id: u32) -> bool {
+    WorldData::characters()
+        .find_id(npc_ref_id)
+        .is_some_and(|npc| npc.common.id.contains("WAREHOUSE"))
+}
+
+fn send_storage(client: &Client, storage: &PlayerStorage) {
+    let items = storage
+        .items()
+        .map(|(slot, item)| InventoryItemData::new(*slot, RentInfo::Empty, item.reference.ref_id(), item_content(item)))
+        .collect();
+    client.send(StorageGold::new(storage.gold()));
+    client.send(StorageStart);
+    client.send(StorageData::new(storage.size() as u8, items));
+    client.send(StorageEnd);
+    client.send(OpenStorageResponse::Success);
+}
+
+pub(crate) fn close_storage(query: Query<(Entity, &PlayerInput, &StorageKeeper)>, npcs: Query<()>, mut cmd: Commands) {
+    for (entity, input, keeper) in query.iter() {
+        let closed = input
+            .untarget
+            .as_ref()
+            .is_some_and(|untarget| untarget.unique_id == keeper.unique_id);
+        if closed || npcs.get(keeper.npc).is_err() {
+            cmd.entity(entity).remove::<StorageKeeper>();
+        }
+    }
+}
+
+pub(crate) fn open_storage(
+    query: Query<(
+        Entity,
+        &Client,
+        &Player,
+        &PlayerInput,
+        Option<&StorageKeeper>,
+        Option<&PlayerStorage>,
+        Has<StorageTask>,
+    )>,
+    server_id: Res<ServerId>,
+    task_creator: Res<TaskCreator>,
+    pool: Res<DbPool>,
+    mut cmd: Commands,
+) {
+    for (entity, client, player, input, keeper, storage, loading) in query.iter() {
+        let Some(ref open) = input.storage_open else {
+            continue;
+        };
+
+        if keeper.is_none_or(|keeper| keeper.unique_id != open.npc) {
+            client.send(OpenStorageResponse::Error(InventoryOperationError::InvalidTarget));
+            continue;
+        }
+
+        if let Some(storage) = storage {
+            send_storage(client, storage);
+        } else if !loading {
+            let task = task_creator.create_task(load_storage(player.user.id, server_id.0, PgPool::clone(&pool)));
+            cmd.entity(entity).insert(StorageTask(task));
+        }
+    }
+}
+
+pub(crate) fn receive_storage(
+    mut query: Query<(Entity, &Client, &Player, &mut StorageTask)>,
+    settings: Res<GameConfig>,
+    mut cmd: Commands,
+) {
+    for (entity, client, player, mut task) in query.iter_mut() {
+        let result = match task.0.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => continue,
+            Err(TryRecvError::Closed) => Err(sqlx::Error::WorkerCrashed),
+        };
+
+        let mut entity_commands = cmd.entity(entity);
+        entity_commands.remove::<StorageTask>();
+        match result {
+            Ok(storage) => {
+                let storage = PlayerStorage::from_db(&storage, settings.storage_size);
+                send_storage(client, &storage);
+                entity_commands.insert(storage);
+            },
+            Err(e) => {
+                error!(error = %e, user_id = player.user.id, "Could not load storage");
+                client.send(OpenStorageResponse::Error(InventoryOperationError::Busy));
+            },
+        }
+    }
+}
+
+pub(crate) fn handle_storage_operations(
+    mut query: Query<(
+        &Client,
+        &PlayerInput,
+        Option<&StorageKeeper>,
+        Option<&mut PlayerStorage>,
+        &mut PlayerInventory,
+        &mut GoldPouch,
+        Has<Exchange>,
+    )>,
+) {
+    for (client, input, keeper, storage, mut inventory, mut gold, in_exchange) in query.iter_mut() {
+        let Some(ref operation) = input.inventory else {
+            continue;
+        };
+
+        let npc = match operation.data {
+            InventoryOperationRequest::MoveInStorage { npc, .. }
+            | InventoryOperationRequest::DepositItem { npc, .. }
+            | InventoryOperationRequest::WithdrawItem { npc, .. }
+            | InventoryOperationRequest::DepositGold { npc, .. }
+            | InventoryOperationRequest::WithdrawGold { npc, .. } => npc,
+            _ => continue,
+        };
+
+        let result = match storage {
+            _ if in_exchange => Err(InventoryOperationError::Busy),
+            Some(mut storage) if keeper.is_some_and(|keeper| keeper.unique_id == npc) => match operation.data {
+                InventoryOperationRequest::MoveInStorage {
+                    source, target, amount, ..
+                } => move_in_storage(source, target, amount, &mut storage),
+                InventoryOperationRequest::DepositItem {
+                    inventory_slot,
+                    storage_slot,
+                    ..
+                } => deposit_item(inventory_slot, storage_slot, &mut inventory, &mut storage),
+                InventoryOperationRequest::WithdrawItem {
+                    storage_slot,
+                    inventory_slot,
+                    ..
+                } => withdraw_item(storage_slot, inventory_slot, &mut inventory, &mut storage),
+                InventoryOperationRequest::DepositGold { amount, .. } => deposit_gold(amount, &mut gold, &mut storage),
+                InventoryOperationRequest::WithdrawGold { amount, .. } => {
+                    withdraw_gold(amount, &mut gold, &mut storage)
+                },
+                _ => continue,
+            },
+            _ => Err(InventoryOperationError::InvalidTarget),
+        };
+
+        match result {
+            Ok(response) => client.send(InventoryOperationResult::Success(response)),
+            Err(error) => client.send(InventoryOperationResult::Error(error)),
+        }
+    }
+}
+
+fn move_in_storage(
+    source: u8,
+    target: u8,
+    amount: u16,
+    storage: &mut PlayerStorage,
+) -> Result<InventoryOperationResponseData, InventoryOperationError> {
+    if target as usize >= storage.size() || source == target {
+        return Err(InventoryOperationError::InvalidTarget);
+    }
+
+    let moved = storage
+        .move_item(source, target, max(1, amount))
+        .map_err(|_| InventoryOperationError::InvalidTarget)?;
+    Ok(InventoryOperationResponseData::MoveInStorage {
+        source,
+        target,
+        amount: moved,
+    })
+}
+
+/// Puts the whole stack in the given inventory slot into the given storage slot.
+fn deposit_item(
+    inventory_slot: u8,
+    storage_slot: u8,
+    inventory: &mut PlayerInventory,
+    storage: &mut PlayerStorage,
+) -> Result<InventoryOperationResponseData, InventoryOperationError> {
+    if Inventory::is_equipment_slot(inventory_slot) {
+        return Err(InventoryOperationError::CannotBeStored);
+    }
+
+    if storage_slot as usize >= storage.size() || storage.get_item_at(storage_slot).is_some() {
+        return Err(InventoryOperationError::InvalidTarget);
+    }
+
+    let amount = inventory
+        .get_item_at(inventory_slot)
+        .map(|item| item.stack_size())
+        .ok_or(InventoryOperationError::InvalidTarget)?;
+    let item = inventory
+        .take_from_slot(inventory_slot, amount)
+        .map_err(|_| InventoryOperationError::InvalidTarget)?;
+    storage
+        .place_item(storage_slot, item)
+        .expect("Storage slot should be free after checking");
+    debug!("Deposited {} of {} into the storage.", amount, item.reference.common.id);
+    Ok(InventoryOperationResponseData::DepositItem {
+        inventory_slot,
+        storage_slot,
+    })
+}
+
+/// Takes the whole stack in the given storage slot out and puts it into the given inventory slot.
+fn withdraw_item(
+    storage_slot: u8,
+    inventory_slot: u8,
+    inventory: &mut PlayerInventory,
+    storage: &mut PlayerStorage,
+) -> Result<InventoryOperationResponseData, InventoryOperationError> {
+    if Inventory::is_equipment_slot(inventory_slot) {
+        return Err(InventoryOperationError::InvalidTarget);
+    }
+
+    if inventory_slot as usize >= inventory.size() || inventory.get_item_at(inventory_slot).is_some() {
+        return Err(InventoryOperationError::InventoryFull);
+    }
+
+    let amount = storage
+        .get_item_at(storage_slot)
+        .map(|item| item.stack_size())
+        .ok_or(InventoryOperationError::InvalidTarget)?;
+    let item = storage
+        .take_from_slot(storage_slot, amount)
+        .map_err(|_| InventoryOperationError::InvalidTarget)?;
+    inventory
+        .place_item(inventory_slot, item)
+        .expect("Inventory slot should be free after checking");
+    debug!("Withdrew {} of {} from the storage.", amount, item.reference.common.id);
+    Ok(InventoryOperationResponseData::WithdrawItem {
+        storage_slot,
+        inventory_slot,
+    })
+}
+
+fn deposit_gold(
+    amount: u64,
+    gold: &mut Mut<GoldPouch>,
+    storage: &mut PlayerStorage,
+) -> Result<InventoryOperationResponseData, InventoryOperationError> {
+    if amount == 0 || amount > gold.amount() {
+        return Err(InventoryOperationError::NotEnoughGold);
+    }
+
+    gold.spend(amount);
+    storage.deposit_gold(amount);
+    Ok(InventoryOperationResponseData::DepositGold { amount })
+}
+
+fn withdraw_gold(
+    amount: u64,
+    gold: &mut Mut<GoldPouch>,
+    storage: &mut PlayerStorage,
+) -> Result<InventoryOperationResponseData, InventoryOperationError> {
+    if amount == 0 || !storage.withdraw_gold(amount) {
+        return Err(InventoryOperationError::NotEnoughGold);
+    }
+
+    gold.gain(amount);
+    Ok(InventoryOperationResponseData::WithdrawGold { amount })
+}
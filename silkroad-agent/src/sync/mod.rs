@@ -5,8 +5,9 @@ use crate::comp::pos::Position;
 use crate::comp::{Health, Mana};
 use crate::sync::reset::AppResetExt;
 use crate::sync::system::{
-    collect_alives, collect_body_states, collect_deaths, collect_gold_changes, collect_mastery_changes,
-    collect_movement_speed_change, collect_movement_update, collect_pickup_animation, collect_stat_changes,
+    collect_alives, collect_body_states, collect_cape_changes, collect_deaths, collect_gold_changes,
+    collect_mastery_changes, collect_movement_speed_change, collect_movement_update, collect_pickup_animation,
+    collect_player_kill_changes, collect_return_scroll_changes, collect_sitting_changes, collect_stat_changes,
     debug_queries, synchronize_updates, system_collect_bars_update, system_collect_exp_update, system_collect_level_up,
     system_collect_sp_update,
 };
@@ -81,6 +82,8 @@ impl Plugin for SynchronizationPlugin {
                     collect_movement_update,
                     collect_movement_speed_change,
                     collect_pickup_animation,
+                    collect_sitting_changes,
+                    collect_return_scroll_changes,
                     debug_queries,
                     collect_deaths,
                     collect_alives,
@@ -88,6 +91,8 @@ impl Plugin for SynchronizationPlugin {
                     collect_stat_changes,
                     collect_gold_changes,
                     collect_mastery_changes,
+                    collect_player_kill_changes,
+                    collect_cape_changes,
                 )
                     .in_set(SynchronizationStage::Collection),
             )
@@ -1,4 +1,4 @@
-use crate::agent::states::{Dead, Idle, MovementGoal, Moving, Pickup};
+use crate::agent::states::{Dead, Idle, MovementGoal, Moving, Pickup, Sitting};
 use crate::agent::MovementState;
 use crate::comp::damage::Invincible;
 use crate::comp::exp::{Experienced, Leveled, SP};
@@ -11,7 +11,9 @@ use crate::comp::pos::Position;
 use crate::comp::visibility::{Invisible, Visibility};
 use crate::comp::{GameEntity, Health, Mana};
 use crate::event::LoadingFinishedEvent;
+use crate::pvp::{PlayerKillRecord, PvpFlag};
 use crate::sync::{SynchronizationCollector, Update};
+use crate::teleport::ReturnScrollCast;
 use bevy_ecs::prelude::*;
 use log::debug;
 use silkroad_definitions::type_id::ObjectWeaponType;
@@ -21,10 +23,11 @@ use silkroad_protocol::combat::ReceiveExperience;
 use silkroad_protocol::movement::{
     EntityMovementInterrupt, MovementDestination, MovementSource, MovementType, PlayerMovementResponse,
 };
+use silkroad_protocol::pvp::PvpCapeUpdate;
 use silkroad_protocol::skill::LevelUpMasteryResponse;
 use silkroad_protocol::world::{
-    AliveState, BodyState, CharacterPointsUpdate, EntityBarUpdateSource, EntityBarUpdates, EntityBarsUpdate,
-    EntityUpdateState, LevelUpEffect, PlayerPickupAnimation, UpdatedState,
+    ActiveScroll, AliveState, BodyState, CharacterPointsUpdate, EntityBarUpdateSource, EntityBarUpdates,
+    EntityBarsUpdate, EntityUpdateState, LevelUpEffect, PlayerPickupAnimation, UpdatedState,
 };
 use std::ops::Deref;
 
@@ -150,7 +153,8 @@ pub(crate) fn system_collect_exp_update(
                 change_self: Some(
                     ReceiveExperience {
                         exp_origin: event.from.map(|source| source.1.unique_id).unwrap_or(0),
-                        experience: event.exp,
+                        // Lost experience is sent as a negative amount.
+                        experience: event.exp as u64,
                         sp: event.sp_exp,
                         unknown: 0,
                         new_level: event.trigged_level_up.then(|| level.current_level() as u16),
@@ -226,6 +230,10 @@ pub(crate) fn collect_movement_update(
                     MovementGoal::Direction(direction) => {
                         MovementUpdate::StartMoveTowards(pos.position().to_local(), direction)
                     },
+                    MovementGoal::Path(ref path) => match path.front() {
+                        Some(next) => MovementUpdate::StartMove(pos.position().to_local(), next.to_local()),
+                        None => continue,
+                    },
                 }
             },
             (None, Some(idle)) if idle.is_added() => {
@@ -338,6 +346,96 @@ pub(crate) fn collect_movement_speed_change(
     }
 }
 
+pub(crate) fn collect_sitting_changes(
+    collector: Res<SynchronizationCollector>,
+    sat_down: Query<(Entity, &GameEntity), Added<Sitting>>,
+    mut stood_up: RemovedComponents<Sitting>,
+    query: Query<(&GameEntity, &MovementState), Without<Dead>>,
+) {
+    let sitting = sat_down
+        .iter()
+        .map(|(entity, game_entity)| (entity, game_entity, MovementType::Sitting));
+    let standing = stood_up.read().filter_map(|entity| {
+        let (game_entity, state) = query.get(entity).ok()?;
+        let movement = match state.deref() {
+            MovementSpeed::Running | MovementSpeed::Berserk => MovementType::Running,
+            MovementSpeed::Walking => MovementType::Walking,
+        };
+        Some((entity, game_entity, movement))
+    });
+
+    for (entity, game_entity, movement) in sitting.chain(standing) {
+        let update = EntityUpdateState {
+            unique_id: game_entity.unique_id,
+            update: UpdatedState::Movement(movement),
+        };
+        collector.send_update(Update {
+            source: entity,
+            change_self: Some(update.into()),
+            change_others: Some(update.into()),
+        });
+    }
+}
+
+pub(crate) fn collect_return_scroll_changes(
+    collector: Res<SynchronizationCollector>,
+    started: Query<(Entity, &GameEntity), Added<ReturnScrollCast>>,
+    mut stopped: RemovedComponents<ReturnScrollCast>,
+    query: Query<&GameEntity>,
+) {
+    let casting = started
+        .iter()
+        .map(|(entity, game_entity)| (entity, game_entity, ActiveScroll::ReturnScroll));
+    let cancelled = stopped
+        .read()
+        .filter_map(|entity| Some((entity, query.get(entity).ok()?, ActiveScroll::None)));
+
+    for (entity, game_entity, scroll) in casting.chain(cancelled) {
+        let update = EntityUpdateState::scroll(game_entity.unique_id, scroll);
+        collector.send_update(Update {
+            source: entity,
+            change_self: Some(update.into()),
+            change_others: Some(update.into()),
+        });
+    }
+}
+
+pub(crate) fn collect_player_kill_changes(
+    collector: Res<SynchronizationCollector>,
+    query: Query<(Entity, &GameEntity, Ref<PlayerKillRecord>), Changed<PlayerKillRecord>>,
+) {
+    for (entity, game_entity, record) in query.iter() {
+        if record.is_added() {
+            continue;
+        }
+
+        let update = EntityUpdateState::player_kill(game_entity.unique_id, record.state());
+        collector.send_update(Update {
+            source: entity,
+            change_self: Some(update.into()),
+            change_others: Some(update.into()),
+        });
+    }
+}
+
+pub(crate) fn collect_cape_changes(
+    collector: Res<SynchronizationCollector>,
+    query: Query<(Entity, &GameEntity, Ref<PvpFlag>), Changed<PvpFlag>>,
+) {
+    for (entity, game_entity, flag) in query.iter() {
+        if flag.is_added() {
+            continue;
+        }
+
+        let update = PvpCapeUpdate::new(game_entity.unique_id, flag.0);
+        collector.send_update(Update {
+            source: entity,
+            change_self: Some(update.into()),
+            change_others: Some(update.into()),
+        });
+    }
+}
+
 pub(crate) fn collect_pickup_animation(
     collector: Res<SynchronizationCollector>,
     query: Query<(Entity, &GameEntity, &Position, &Pickup), Or<(Added<Pickup>, Changed<Pickup>)>>,
@@ -416,15 +514,17 @@ pub(crate) fn collect_deaths(
 pub(crate) fn collect_alives(
     collector: Res<SynchronizationCollector>,
     mut reader: EventReader<LoadingFinishedEvent>,
+    mut resurrected: RemovedComponents<Dead>,
     query: Query<&GameEntity>,
 ) {
-    for event in reader.read() {
-        let Ok(game_entity) = query.get(event.0) else {
+    let loaded = reader.read().map(|event| event.0);
+    for entity in loaded.chain(resurrected.read()) {
+        let Ok(game_entity) = query.get(entity) else {
             continue;
         };
         let update = EntityUpdateState::life(game_entity.unique_id, AliveState::Alive);
         collector.send_update(Update {
-            source: event.0,
+            source: entity,
             change_self: Some(update.into()),
             change_others: Some(update.into()),
         });
@@ -0,0 +1,97 @@
+use crate::persistence::ApplyToDatabase;
+use axum::async_trait;
+use bevy_ecs::prelude::*;
+use bevy_time::{Timer, TimerMode};
+use silkroad_game_base::{ChangeProvided, GlobalPosition};
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// The location a player gets brought back to when using a return scroll or when choosing to be resurrected in
+/// town. If the player never designated a point, the default town is used instead.
+#[derive(Component, Copy, Clone, Default)]
+pub(crate) struct ReturnPoint(Option<GlobalPosition>);
+
+impl ReturnPoint {
+    pub(crate) fn new(position: Option<GlobalPosition>) -> Self {
+        ReturnPoint(position)
+    }
+
+    pub(crate) fn designate(&mut self, position: GlobalPosition) {
+        self.0 = Some(position);
+    }
+
+    pub(crate) fn position_or(&self, default: GlobalPosition) -> GlobalPosition {
+        self.0.unwrap_or(default)
+    }
+}
+
+pub(crate) struct ReturnPointChange(Option<GlobalPosition>);
+
+impl ChangeProvided for ReturnPoint {
+    type Change = ReturnPointChange;
+
+    fn as_change(&self) -> Self::Change {
+        ReturnPointChange(self.0)
+    }
+}
+
+#[async_trait]
+impl ApplyToDatabase for ReturnPointChange {
+    async fn apply(&self, character_id: u32, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let location = self.0.map(|position| position.to_local());
+        sqlx::query!(
+            "UPDATE characters SET return_region = $1, return_x = $2, return_y = $3, return_z = $4 WHERE id = $5",
+            location.map(|location| location.0.id() as i16),
+            location.map(|location| location.1.x),
+            location.map(|location| location.1.y),
+            location.map(|location| location.1.z),
+            character_id as i32
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// A return scroll that is currently being cast. Once the time runs out, the player gets teleported to their
+/// [ReturnPoint], unless they move or get hit before that.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct ReturnScrollCast(Timer);
+
+impl ReturnScrollCast {
+    pub(crate) fn new(duration: Duration) -> Self {
+        ReturnScrollCast(Timer::new(duration, TimerMode::Once))
+    }
+
+    pub(crate) fn tick(&mut self, delta: Duration) -> bool {
+        self.0.tick(delta).finished()
+    }
+}
+
+/// The player got moved to a new location and is currently looking at the loading screen. They will only receive
+/// their surroundings again once the client confirms it's ready.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(crate) struct Teleporting;
+
+/// A building, like a town gate, which players can use to teleport to other places.
+#[derive(Component)]
+pub(crate) struct TeleportBuilding;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cgmath::Vector3;
+
+    #[test]
+    pub fn test_return_point_falls_back() {
+        let town = GlobalPosition(Vector3::new(1.0, 2.0, 3.0));
+        let mut point = ReturnPoint::default();
+        assert_eq!(point.position_or(town).0, town.0);
+
+        let designated = GlobalPosition(Vector3::new(4.0, 5.0, 6.0));
+        point.designate(designated);
+        assert_eq!(point.position_or(town).0, designated.0);
+    }
+}
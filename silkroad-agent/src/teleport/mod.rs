@@ -0,0 +1,33 @@
+use crate::event::PlayerTeleportEvent;
+use crate::persistence::AppPersistanceExt;
+use crate::teleport::system::{
+    designate_return_point, finish_teleport, handle_teleport_request, interrupt_return_scrolls,
+    spawn_teleport_buildings, start_teleport, tick_return_scrolls,
+};
+use bevy_app::{App, Plugin, Startup, Update};
+use bevy_ecs::prelude::*;
+pub(crate) use component::*;
+
+mod component;
+mod system;
+
+pub(crate) struct TeleportPlugin;
+
+impl Plugin for TeleportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlayerTeleportEvent>()
+            .add_systems(Startup, spawn_teleport_buildings)
+            .add_systems(
+                Update,
+                (
+                    designate_return_point,
+                    handle_teleport_request,
+                    interrupt_return_scrolls,
+                    tick_return_scrolls.after(interrupt_return_scrolls),
+                    start_teleport.after(tick_return_scrolls).after(handle_teleport_request),
+                    finish_teleport,
+                ),
+            )
+            .track_change_component::<ReturnPoint>();
+    }
+}
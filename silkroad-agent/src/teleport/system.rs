@@ -0,0 +1,290 @@
+use crate::agent::states::{Dead, Idle, Moving};
+use crate::comp::exp::{Experienced, Leveled, SP};
+use crate::comp::gold::GoldPouch;
+use crate::comp::inventory::PlayerInventory;
+use crate::comp::mastery::MasteryKnowledge;
+use crate::comp::net::Client;
+use crate::comp::npc::NPC;
+use crate::comp::player::{Player, StatPoints};
+use crate::comp::pos::Position;
+use crate::comp::skill::SkillBook;
+use crate::comp::visibility::Visibility;
+use crate::comp::{GameEntity, Health, Mana};
+use crate::config::GameConfig;
+use crate::event::{DamageReceiveEvent, PlayerTeleportEvent};
+use crate::ext::EntityIdPool;
+use crate::input::PlayerInput;
+use crate::job::JobExperience;
+use crate::login::send_spawn;
+use crate::pvp::PlayerKillRecord;
+use crate::shop::MAX_TALK_DISTANCE;
+use crate::teleport::component::{ReturnPoint, ReturnScrollCast, TeleportBuilding, Teleporting};
+use crate::world::{EntityLookup, WorldData};
+use bevy_ecs::prelude::*;
+use bevy_time::Time;
+use cgmath::{MetricSpace, Vector3};
+use silkroad_data::teleportdata::RefTeleport;
+use silkroad_data::DataEntry;
+use silkroad_game_base::{GlobalPosition, Heading, LocalPosition, SpawningState};
+use silkroad_protocol::world::{
+    DesignateReturnPointError, DesignateReturnPointResponse, TeleportError, TeleportKind, TeleportResponse,
+    TeleportStart,
+};
+use tracing::debug;
+
+/// The location players arrive at when teleporting to the given teleport.
+fn arrival_position(teleport: &RefTeleport) -> GlobalPosition {
+    LocalPosition(teleport.region.into(), Vector3::new(teleport.x, teleport.y, teleport.z)).to_global()
+}
+
+pub(crate) fn spawn_teleport_buildings(mut cmd: Commands, mut id_pool: ResMut<EntityIdPool>) {
+    for building in WorldData::teleports().buildings() {
+        let position = LocalPosition(building.region.into(), Vector3::new(building.x, building.y, building.z));
+        cmd.spawn((
+            GameEntity {
+                unique_id: id_pool
+                    .request_id()
+                    .expect("Should have ID available for teleport building"),
+                ref_id: building.ref_id(),
+            },
+            Position::new(position.to_global(), Heading(0.0)),
+            TeleportBuilding,
+        ));
+    }
+}
+
+pub(crate) fn designate_return_point(
+    mut query: Query<(&Client, &PlayerInput, &Position, &mut ReturnPoint)>,
+    teleporters: Query<(&GameEntity, &Position), Or<(With<NPC>, With<TeleportBuilding>)>>,
+    lookup: Res<EntityLookup>,
+) {
+    for (client, input, position, mut return_point) in query.iter_mut() {
+        let Some(ref designate) = input.designate_return else {
+            continue;
+        };
+
+        let Some((teleport, teleporter_position)) = lookup
+            .get_entity_for_id(designate.unique_id)
+            .and_then(|teleporter| teleporters.get(teleporter).ok())
+            .and_then(|(teleporter, position)| {
+                WorldData::teleports()
+                    .teleport_of(teleporter.ref_id)
+                    .map(|teleport| (teleport, position))
+            })
+            .filter(|(teleport, _)| teleport.can_designate)
+        else {
+            client.send(DesignateReturnPointResponse::Error(
+                DesignateReturnPointError::InvalidTarget,
+            ));
+            continue;
+        };
+
+        if teleporter_position.position().distance2(position.position().0) >= MAX_TALK_DISTANCE {
+            client.send(DesignateReturnPointResponse::Error(
+                DesignateReturnPointError::TooFarAway,
+            ));
+            continue;
+        }
+
+        return_point.designate(arrival_position(teleport));
+        client.send(DesignateReturnPointResponse::Success);
+    }
+}
+
+pub(crate) fn handle_teleport_request(
+    mut query: Query<
+        (Entity, &Client, &PlayerInput, &Position, &Leveled, &mut GoldPouch),
+        (Without<Dead>, Without<Teleporting>),
+    >,
+    teleporters: Query<(&GameEntity, &Position), Or<(With<NPC>, With<TeleportBuilding>)>>,
+    lookup: Res<EntityLookup>,
+    mut teleport_events: EventWriter<PlayerTeleportEvent>,
+) {
+    for (entity, client, input, position, level, mut gold) in query.iter_mut() {
+        let Some(ref request) = input.teleport_request else {
+            continue;
+        };
+
+        let TeleportKind::Teleport { destination } = request.kind;
+
+        let Some((teleport, teleporter_position)) = lookup
+            .get_entity_for_id(request.unique_id)
+            .and_then(|teleporter| teleporters.get(teleporter).ok())
+            .and_then(|(teleporter, position)| {
+                WorldData::teleports()
+                    .teleport_of(teleporter.ref_id)
+                    .map(|teleport| (teleport, position))
+            })
+        else {
+            client.send(TeleportResponse::Error(TeleportError::InvalidTarget));
+            continue;
+        };
+
+        if teleporter_position.position().distance2(position.position().0) >= MAX_TALK_DISTANCE {
+            client.send(TeleportResponse::Error(TeleportError::TooFarAway));
+            continue;
+        }
+
+        let Some((link, target)) = teleport
+            .links
+            .iter()
+            .find(|link| link.target == destination)
+            .and_then(|link| WorldData::teleports().find_id(link.target).map(|target| (link, target)))
+        else {
+            client.send(TeleportResponse::Error(TeleportError::InvalidTarget));
+            continue;
+        };
+
+        let current_level = level.current_level();
+        if link.min_level.is_some_and(|min| current_level < min) {
+            client.send(TeleportResponse::Error(TeleportError::LevelTooLow));
+            continue;
+        }
+
+        if link.max_level.is_some_and(|max| current_level > max) {
+            client.send(TeleportResponse::Error(TeleportError::LevelTooHigh));
+            continue;
+        }
+
+        if gold.amount() < link.fee {
+            client.send(TeleportResponse::Error(TeleportError::NotEnoughGold));
+            continue;
+        }
+
+        debug!(
+            "Teleporting {:?} from {} to {} for {} gold.",
+            entity, teleport.code, target.code, link.fee
+        );
+        gold.spend(link.fee);
+        client.send(TeleportResponse::Success);
+        teleport_events.send(PlayerTeleportEvent(entity, arrival_position(target)));
+    }
+}
+
+pub(crate) fn interrupt_return_scrolls(
+    moving: Query<Entity, (With<ReturnScrollCast>, With<Moving>)>,
+    casting: Query<(), With<ReturnScrollCast>>,
+    mut damage_events: EventReader<DamageReceiveEvent>,
+    mut cmd: Commands,
+) {
+    let damaged = damage_events
+        .read()
+        .map(|event| event.target.0)
+        .filter(|target| casting.contains(*target));
+    for entity in moving.iter().chain(damaged) {
+        debug!("Interrupted return scroll of {:?}.", entity);
+        cmd.entity(entity).remove::<ReturnScrollCast>();
+    }
+}
+
+pub(crate) fn tick_return_scrolls(
+    mut query: Query<(Entity, &mut ReturnScrollCast, &ReturnPoint)>,
+    time: Res<Time>,
+    settings: Res<GameConfig>,
+    mut teleport_events: EventWriter<PlayerTeleportEvent>,
+    mut cmd: Commands,
+) {
+    for (entity, mut cast, return_point) in query.iter_mut() {
+        if !cast.tick(time.delta()) {
+            continue;
+        }
+
+        let destination = return_point.position_or(settings.teleport.default_return.position());
+        teleport_events.send(PlayerTeleportEvent(entity, destination));
+        cmd.entity(entity).remove::<ReturnScrollCast>();
+    }
+}
+
+pub(crate) fn start_teleport(
+    mut teleport_events: EventReader<PlayerTeleportEvent>,
+    mut query: Query<(&Client, &mut Player, &mut Position, &mut Visibility, Has<Moving>)>,
+    mut cmd: Commands,
+) {
+    for event in teleport_events.read() {
+        let Ok((client, mut player, mut position, mut visibility, moving)) = query.get_mut(event.0) else {
+            continue;
+        };
+
+        position.move_to(event.1);
+        if moving {
+            cmd.entity(event.0).remove::<Moving>().insert(Idle);
+        }
+        cmd.entity(event.0).remove::<ReturnScrollCast>();
+
+        // The client throws away everything it knew about its surroundings when loading, so we need to send all
+        // entities around the new location once it's done.
+        visibility.entities_in_radius.clear();
+        visibility.added_entities.clear();
+        visibility.removed_entities.clear();
+
+        player.character.state = SpawningState::Spawning;
+        cmd.entity(event.0).insert(Teleporting);
+        client.send(TeleportStart);
+    }
+}
+
+pub(crate) fn finish_teleport(
+    mut query: Query<
+        (
+            Entity,
+            &Client,
+            &PlayerInput,
+            &GameEntity,
+            &mut Player,
+            &PlayerInventory,
+            &Position,
+            &PlayerKillRecord,
+            (
+                &Leveled,
+                &Experienced,
+                &SP,
+                &GoldPouch,
+                &StatPoints,
+                &Health,
+                &Mana,
+                &MasteryKnowledge,
+                &SkillBook,
+                &JobExperience,
+            ),
+        ),
+        With<Teleporting>,
+    >,
+    settings: Res<GameConfig>,
+    mut cmd: Commands,
+) {
+    for (entity, client, input, game_entity, mut player, inventory, position, player_kills, state) in query.iter_mut() {
+        if input.teleport_confirm.is_none() {
+            continue;
+        }
+
+        // The character data only reflects the state at the time of joining, so we need to bring it up to date
+        // before sending it out again.
+        let (level, experience, sp, gold, stat_points, health, mana, masteries, skills, job) = state;
+        let character = &mut player.character;
+        character.level = level.current_level();
+        character.max_level = level.max_level_reached();
+        character.exp = experience.experience();
+        character.sp_exp = experience.sp_experience() as u32;
+        character.sp = sp.current();
+        character.gold = gold.amount();
+        character.stats = stat_points.stats();
+        character.stat_points = stat_points.remaining_points();
+        character.current_hp = health.current_health;
+        character.current_mp = mana.current_mana;
+        character.masteries = masteries.iter().collect();
+        character.skills = skills.iter().collect();
+        character.job_level = job.level();
+        character.job_exp = job.experience();
+
+        send_spawn(
+            client,
+            game_entity,
+            &player,
+            inventory,
+            position,
+            player_kills,
+            settings.max_level,
+        );
+        cmd.entity(entity).remove::<Teleporting>();
+    }
+}
@@ -0,0 +1,15 @@
+use silkroad_data::itemdata::RefItemData;
+use silkroad_definitions::type_id::TypeId;
+
+/// Creates the reference data of an item that only has its code, type and stack size set, leaving every other column
+/// empty.
+pub(crate) fn item_reference(code: &str, type_id: TypeId, max_stack_size: u16) -> RefItemData {
+    let mut reference: RefItemData = ["0"; 125]
+        .join("\t")
+        .parse()
+        .expect("Empty item data should be parseable");
+    reference.common.id = code.to_string();
+    reference.common.type_id = type_id;
+    reference.max_stack_size = max_stack_size;
+    reference
+}
@@ -2,12 +2,17 @@ use once_cell::sync::OnceCell;
 use pk2::Pk2;
 use silkroad_data::characterdata::{load_character_map, RefCharacterData};
 use silkroad_data::datamap::DataMap;
+use silkroad_data::dropdata::{load_drop_map, DropMap};
 use silkroad_data::gold::{load_gold_map, GoldMap};
 use silkroad_data::itemdata::{load_item_map, RefItemData};
 use silkroad_data::level::{load_level_map, LevelMap};
 use silkroad_data::masterydata::{load_mastery_map, RefMasteryData};
+use silkroad_data::shopdata::{load_shop_map, ShopMap};
 use silkroad_data::skilldata::{load_skill_map, RefSkillData};
+use silkroad_data::teleportdata::{load_teleport_map, TeleportMap};
 use silkroad_data::FileError;
+use std::io;
+use tracing::warn;
 
 static ITEMS: OnceCell<DataMap<RefItemData>> = OnceCell::new();
 static CHARACTERS: OnceCell<DataMap<RefCharacterData>> = OnceCell::new();
@@ -15,6 +20,9 @@ static SKILLS: OnceCell<DataMap<RefSkillData>> = OnceCell::new();
 static LEVELS: OnceCell<LevelMap> = OnceCell::new();
 static GOLD: OnceCell<GoldMap> = OnceCell::new();
 static MASTERIES: OnceCell<DataMap<RefMasteryData>> = OnceCell::new();
+static SHOPS: OnceCell<ShopMap> = OnceCell::new();
+static DROPS: OnceCell<DropMap> = OnceCell::new();
+static TELEPORTS: OnceCell<TeleportMap> = OnceCell::new();
 
 pub struct WorldData;
 
@@ -26,12 +34,26 @@ impl WorldData {
         let items = load_item_map(media_pk2)?;
         let skills = load_skill_map(media_pk2)?;
         let masteries = load_mastery_map(media_pk2)?;
+        let shops = load_shop_map(media_pk2)?;
+        // The drop tables are only part of the server files, so they might not be available.
+        let drops = match load_drop_map(media_pk2) {
+            Ok(drops) => drops,
+            Err(FileError::IoError(e)) if e.kind() == io::ErrorKind::NotFound => {
+                warn!("Could not find drop tables, monsters won't drop any items: {}", e);
+                DropMap::default()
+            },
+            Err(e) => return Err(e),
+        };
+        let teleports = load_teleport_map(media_pk2)?;
         let _ = LEVELS.set(levels);
         let _ = GOLD.set(gold);
         let _ = CHARACTERS.set(characters);
         let _ = ITEMS.set(items);
         let _ = SKILLS.set(skills);
         let _ = MASTERIES.set(masteries);
+        let _ = SHOPS.set(shops);
+        let _ = DROPS.set(drops);
+        let _ = TELEPORTS.set(teleports);
         Ok(())
     }
 
@@ -58,4 +80,16 @@ impl WorldData {
     pub fn masteries() -> &'static DataMap<RefMasteryData> {
         MASTERIES.get().expect("Masteries should have been set")
     }
+
+    pub fn shops() -> &'static ShopMap {
+        SHOPS.get().expect("Shops should have been set")
+    }
+
+    pub fn drops() -> &'static DropMap {
+        DROPS.get().expect("Drops should have been set")
+    }
+
+    pub fn teleports() -> &'static TeleportMap {
+        TELEPORTS.get().expect("Teleports should have been set")
+    }
 }
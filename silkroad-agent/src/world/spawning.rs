@@ -1,5 +1,6 @@
 use crate::agent::states::{Dead, StateTransitionQueue};
 use crate::agent::{Agent, MovementState};
+use crate::buff::Buffed;
 use crate::comp::damage::DamageReceiver;
 use crate::comp::monster::{Monster, MonsterAiBundle, MonsterBundle, RandomStroll, SpawnedBy};
 use crate::comp::npc::NpcBundle;
@@ -183,8 +184,7 @@ fn to_position(location: GlobalLocation, navmesh: &GlobalNavmesh) -> Option<Posi
     let local = location.to_local();
     let navmesh = navmesh.mesh_for(local.0)?;
     let height = navmesh
-        .heightmap()
-        .height_at_position(local.1.x, local.1.y)
+        .height_at(local.1.x, local.1.y, None)
         .expect("Location should be inside region.");
     let pos = location.with_y(height);
     let heading = Heading(rand::thread_rng().gen_range(0..360) as f32);
@@ -216,6 +216,7 @@ fn spawn_monster(
         state_queue: StateTransitionQueue::default(),
         movement_state: MovementState::default_monster(),
         damage_receiver: DamageReceiver::default(),
+        buffed: Buffed::default(),
     };
 
     let ai_bundle = MonsterAiBundle {
@@ -23,6 +23,10 @@ pub struct RefCharacterData {
     pub pickup_range: Option<NonZeroU16>, // column 61
     pub aggressive: bool,                 // column 93
     pub skills: Vec<u32>,                 // column 83-92
+    pub physical_defense: f32,            // column 72
+    pub magical_defense: f32,             // column 73
+    pub block_ratio: u16,                 // column 76
+    pub critical_resist: u16,             // column 78
 }
 
 impl DataEntry for RefCharacterData {
@@ -64,6 +68,39 @@ impl FromStr for RefCharacterData {
             pickup_range: NonZeroU16::new(pickup_range),
             aggressive: aggressive == 1,
             skills,
+            physical_defense: elements.get(72).ok_or(ParseError::MissingColumn(72))?.parse()?,
+            magical_defense: elements.get(73).ok_or(ParseError::MissingColumn(73))?.parse()?,
+            block_ratio: elements.get(76).ok_or(ParseError::MissingColumn(76))?.parse()?,
+            critical_resist: elements.get(78).ok_or(ParseError::MissingColumn(78))?.parse()?,
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use silkroad_definitions::rarity::EntityRarityType;
+
+    // A monster entry laid out like the ones in CharacterData.txt.
+    const MANGNYANG: &str =
+        "1\t1933\tMOB_CH_MANGNYANG\txxx\txxx\tSN_MOB_CH_MANGNYANG\txxx\t0\t1\t1\t2\t1\t1\t0\t0\t0\t0\t0\t0\t0\t0\t0\t\
+        0\t0\t0\t0\t0\t0\t0\t0\t0\t0\t0\t1\t0\t0\t0\t0\t0\t0\t0\t0\t0\t0\t0\t0\t16\t50\t100\t0\t12\t0\t\
+        res\\mob\\china\\mangnyang.bsr\txxx\ticon\\mob\\china\\mangnyang.ddj\txxx\txxx\t1\t0\t55\t0\t0\t0\t0\t0\t0\t\
+        0\t0\t0\t0\t0\t1\t6\t4\t0\t8\t5\t11\t2\t10\t0\t0\t0\t1407\t0\t0\t0\t0\t0\t0\t0\t0\t0\t0";
+
+    #[test]
+    pub fn test_parse_monster() {
+        let monster: RefCharacterData = MANGNYANG.parse().expect("Monster should be parseable");
+        assert_eq!(monster.common.ref_id, 1933);
+        assert_eq!(monster.common.id, "MOB_CH_MANGNYANG");
+        assert!(monster.rarity == EntityRarityType::Normal);
+        assert_eq!(monster.level, 1);
+        assert_eq!(monster.hp, 55);
+        assert_eq!(monster.exp, 10);
+        assert_eq!(monster.skills, vec![1407]);
+        assert_eq!(monster.physical_defense, 6.0);
+        assert_eq!(monster.magical_defense, 4.0);
+        assert_eq!(monster.block_ratio, 5);
+        assert_eq!(monster.critical_resist, 2);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{parse_file, FileError, ParseError};
+use pk2::Pk2;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+pub fn load_drop_map(pk2: &Pk2) -> Result<DropMap, FileError> {
+    let mut file = pk2.open_file("/server_dep/silkroad/textdata/refmonster_assigneditemdrop.txt")?;
+    let item_drops: Vec<RefAssignedItemDrop> = parse_file(&mut file)?;
+    let mut file = pk2.open_file("/server_dep/silkroad/textdata/refmonster_assigneditemrnddrop.txt")?;
+    let group_drops: Vec<RefAssignedGroupDrop> = parse_file(&mut file)?;
+    let mut file = pk2.open_file("/server_dep/silkroad/textdata/refdropitemgroup.txt")?;
+    let group_items: Vec<RefDropGroupItem> = parse_file(&mut file)?;
+
+    let mut groups: HashMap<u32, Vec<DropGroupItem>> = HashMap::new();
+    for item in group_items.into_iter().filter(|item| item.service) {
+        groups.entry(item.group).or_default().push(DropGroupItem {
+            item: item.item,
+            weight: item.weight,
+        });
+    }
+
+    let mut monsters: HashMap<u32, MonsterDrops> = HashMap::new();
+    for drop in item_drops {
+        monsters.entry(drop.monster).or_default().items.push(ItemDrop {
+            item: drop.item,
+            upgrade_level: drop.upgrade_level,
+            amount: drop.amount_min..=drop.amount_max.max(drop.amount_min),
+            chance: drop.chance,
+        });
+    }
+
+    for drop in group_drops {
+        let Some(items) = groups.get(&drop.group) else {
+            continue;
+        };
+        monsters.entry(drop.monster).or_default().groups.push(GroupDrop {
+            items: items.clone(),
+            amount: drop.amount_min..=drop.amount_max.max(drop.amount_min),
+            chance: drop.chance,
+        });
+    }
+
+    Ok(DropMap(monsters))
+}
+
+/// The items monsters may drop when they die, keyed by the ref id of the monster.
+#[derive(Default)]
+pub struct DropMap(HashMap<u32, MonsterDrops>);
+
+impl DropMap {
+    pub fn drops_of(&self, monster: u32) -> Option<&MonsterDrops> {
+        self.0.get(&monster)
+    }
+}
+
+#[derive(Default)]
+pub struct MonsterDrops {
+    /// Specific items that are rolled for individually.
+    pub items: Vec<ItemDrop>,
+    /// Groups of items of which at most one item is picked when the group gets dropped.
+    pub groups: Vec<GroupDrop>,
+}
+
+pub struct ItemDrop {
+    pub item: u32,
+    pub upgrade_level: u8,
+    pub amount: RangeInclusive<u16>,
+    /// The chance for the item to drop, between `0.0` and `1.0`.
+    pub chance: f32,
+}
+
+pub struct GroupDrop {
+    pub items: Vec<DropGroupItem>,
+    pub amount: RangeInclusive<u16>,
+    /// The chance for an item of the group to drop, between `0.0` and `1.0`.
+    pub chance: f32,
+}
+
+#[derive(Clone)]
+pub struct DropGroupItem {
+    pub item: u32,
+    /// The relative weight with which this item gets picked among the others in the same group.
+    pub weight: f32,
+}
+
+struct RefAssignedItemDrop {
+    monster: u32,
+    item: u32,
+    upgrade_level: u8,
+    amount_min: u16,
+    amount_max: u16,
+    chance: f32,
+}
+
+impl FromStr for RefAssignedItemDrop {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        Ok(Self {
+            monster: elements.get(0).ok_or(ParseError::MissingColumn(0))?.parse()?,
+            item: elements.get(1).ok_or(ParseError::MissingColumn(1))?.parse()?,
+            upgrade_level: elements.get(3).ok_or(ParseError::MissingColumn(3))?.parse()?,
+            amount_min: elements.get(4).ok_or(ParseError::MissingColumn(4))?.parse()?,
+            amount_max: elements.get(5).ok_or(ParseError::MissingColumn(5))?.parse()?,
+            chance: elements.get(6).ok_or(ParseError::MissingColumn(6))?.parse()?,
+        })
+    }
+}
+
+struct RefAssignedGroupDrop {
+    monster: u32,
+    group: u32,
+    amount_min: u16,
+    amount_max: u16,
+    chance: f32,
+}
+
+impl FromStr for RefAssignedGroupDrop {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        Ok(Self {
+            monster: elements.get(0).ok_or(ParseError::MissingColumn(0))?.parse()?,
+            group: elements.get(1).ok_or(ParseError::MissingColumn(1))?.parse()?,
+            amount_min: elements.get(4).ok_or(ParseError::MissingColumn(4))?.parse()?,
+            amount_max: elements.get(5).ok_or(ParseError::MissingColumn(5))?.parse()?,
+            chance: elements.get(6).ok_or(ParseError::MissingColumn(6))?.parse()?,
+        })
+    }
+}
+
+struct RefDropGroupItem {
+    service: bool,
+    group: u32,
+    item: u32,
+    weight: f32,
+}
+
+impl FromStr for RefDropGroupItem {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        let service: u8 = elements.get(0).ok_or(ParseError::MissingColumn(0))?.parse()?;
+        Ok(Self {
+            service: service == 1,
+            group: elements.get(1).ok_or(ParseError::MissingColumn(1))?.parse()?,
+            item: elements.get(3).ok_or(ParseError::MissingColumn(3))?.parse()?,
+            weight: elements.get(4).ok_or(ParseError::MissingColumn(4))?.parse()?,
+        })
+    }
+}
@@ -44,6 +44,7 @@ impl FromStr for RefBiologicalType {
 pub struct RefItemData {
     pub common: RefCommon,
     pub price: u64,
+    pub sell_price: u64,
     pub max_stack_size: u16,
     pub range: Option<NonZeroU16>,
     pub required_level: Option<NonZeroU8>,
@@ -54,11 +55,16 @@ pub struct RefItemData {
     pub magical_attack_power_lower: f32,  // column 100
     pub magical_attack_power_upper: f32,  // column 102
     pub critical: f32,
-    pub physical_reinforce_lower: f32, // column 105
-    pub physical_reinforce_upper: f32, // column 107
-    pub magical_reinforce_upper: f32,  // column 109
-    pub magical_reinforce_lower: f32,  // column 111
-    pub attack_rate: f32,              // column 113
+    pub physical_reinforce_lower: f32,   // column 105
+    pub physical_reinforce_upper: f32,   // column 107
+    pub magical_reinforce_upper: f32,    // column 109
+    pub magical_reinforce_lower: f32,    // column 111
+    pub attack_rate: f32,                // column 113
+    pub physical_defense: f32,           // column 65
+    pub magical_defense: f32,            // column 76
+    pub block_ratio: f32,                // column 74
+    pub physical_defense_reinforce: f32, // column 82
+    pub magical_defense_reinforce: f32,  // column 84
 }
 
 impl PartialEq for RefItemData {
@@ -89,6 +95,7 @@ impl FromStr for RefItemData {
         Ok(Self {
             common,
             price: elements.get(26).ok_or(ParseError::MissingColumn(26))?.parse()?,
+            sell_price: elements.get(31).ok_or(ParseError::MissingColumn(31))?.parse()?,
             params: [
                 elements.get(118).ok_or(ParseError::MissingColumn(118))?.parse()?,
                 elements.get(120).ok_or(ParseError::MissingColumn(120))?.parse()?,
@@ -129,6 +136,39 @@ impl FromStr for RefItemData {
 
             attack_rate: elements.get(113).ok_or(ParseError::MissingColumn(113))?.parse()?,
             critical: elements.get(116).ok_or(ParseError::MissingColumn(116))?.parse()?,
+            physical_defense: elements.get(65).ok_or(ParseError::MissingColumn(65))?.parse()?,
+            magical_defense: elements.get(76).ok_or(ParseError::MissingColumn(76))?.parse()?,
+            block_ratio: elements.get(74).ok_or(ParseError::MissingColumn(74))?.parse()?,
+            physical_defense_reinforce: elements.get(82).ok_or(ParseError::MissingColumn(82))?.parse::<f32>()? / 100.0,
+            magical_defense_reinforce: elements.get(84).ok_or(ParseError::MissingColumn(84))?.parse::<f32>()? / 100.0,
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A shield entry laid out like the ones in ItemData.txt.
+    const SHIELD: &str =
+        "1\t3720\tITEM_CH_SHIELD_01_A_DEF\txxx\txxx\tSN_ITEM_CH_SHIELD_01_A\tSN_ITEM_CH_SHIELD_01_A_TT_DESC\t0\t1\t3\t\
+        1\t4\t1\t0\t0\t0\t1\t1\t1\t0\t1\t1\t1\t0\t1\t0\t110\t0\t0\t0\t0\t27\t1\t1\t0\t0\t0\t0\t0\t0\t0\t0\t0\t0\t0\t\
+        0\t0\t0\t0\t0\t0\t0\titem\\china\\shield\\ch_shield_01_a.bsr\txxx\titem\\china\\shield\\ch_shield_01_a.ddj\t\
+        xxx\txxx\t1\t2\t0\t0\t1\t0\t0.0\t0.0\t11.0\t14.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t6.0\t9.0\t9.0\t12.0\t\
+        0.0\t0.0\t0.0\t0.0\t30.0\t40.0\t20.0\t30.0\t0\t0\t0\t0\t0\t0\t0\t0\t0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t\
+        0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t0.0\t-1\t0\t-1\t0\t-1\t0\t-1";
+
+    #[test]
+    pub fn test_parse_defense() {
+        let shield: RefItemData = SHIELD.parse().expect("Shield should be parseable");
+        assert_eq!(shield.common.ref_id, 3720);
+        assert_eq!(shield.max_stack_size, 1);
+        assert_eq!(shield.required_level.map(NonZeroU8::get), Some(1));
+        assert_eq!(shield.physical_defense, 11.0);
+        assert_eq!(shield.block_ratio, 6.0);
+        assert_eq!(shield.magical_defense, 9.0);
+        assert_eq!(shield.physical_defense_reinforce, 0.3);
+        assert_eq!(shield.magical_defense_reinforce, 0.2);
+        assert_eq!(shield.physical_attack_power_lower, 0.0);
+    }
+}
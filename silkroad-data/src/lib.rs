@@ -5,12 +5,15 @@
 pub mod characterdata;
 pub mod common;
 pub mod datamap;
+pub mod dropdata;
 pub mod gold;
 pub mod itemdata;
 pub mod level;
 pub mod masterydata;
 pub mod npc_pos;
+pub mod shopdata;
 pub mod skilldata;
+pub mod teleportdata;
 
 pub use datamap::*;
 use encoding_rs::WINDOWS_1252;
@@ -42,6 +45,12 @@ pub(crate) fn parse_file<T: FromStr<Err = ParseError>, S: Read>(file: &mut S) ->
     Ok(load_lines(full_string.as_ref())?)
 }
 
+/// Checks the first column of a line, which denotes whether the entry is currently in service.
+pub(crate) fn parse_service(elements: &[&str]) -> Result<bool, ParseError> {
+    let service: u8 = elements.get(0).ok_or(ParseError::MissingColumn(0))?.parse()?;
+    Ok(service == 1)
+}
+
 pub(crate) fn list_files<S: Read>(file: &mut S) -> Result<Vec<String>, FileError> {
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer)?;
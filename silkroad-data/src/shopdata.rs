@@ -0,0 +1,282 @@
+use crate::{parse_file, parse_service, FileError, ParseError};
+use pk2::Pk2;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The payment device used by shops selling for gold. Other devices, like silk or guild points, are not supported.
+const PAYMENT_GOLD: u8 = 1;
+
+fn load<T: FromStr<Err = ParseError> + ShopLine>(pk2: &Pk2, file: &str) -> Result<Vec<T>, FileError> {
+    let mut file = pk2.open_file(format!("/server_dep/silkroad/textdata/{}", file))?;
+    let lines: Vec<T> = parse_file(&mut file)?;
+    Ok(lines.into_iter().filter(|line| line.in_service()).collect())
+}
+
+pub fn load_shop_map(pk2: &Pk2) -> Result<ShopMap, FileError> {
+    let groups: Vec<RefShopGroup> = load(pk2, "refshopgroup.txt")?;
+    let group_mappings: Vec<RefShopGroupMapping> = load(pk2, "refmappingshopgroup.txt")?;
+    let tab_mappings: Vec<RefShopTabMapping> = load(pk2, "refmappingshopwithtab.txt")?;
+    let tabs: Vec<RefShopTab> = load(pk2, "refshoptab.txt")?;
+    let goods: Vec<RefShopGoods> = load(pk2, "refshopgoods.txt")?;
+    let packages: Vec<RefPackageItem> = load(pk2, "refscrapofpackageitem.txt")?;
+    let prices: Vec<RefPackagePrice> = load(pk2, "refpricepolicyofitem.txt")?;
+
+    let packages: HashMap<&str, &RefPackageItem> = packages
+        .iter()
+        .map(|package| (package.package.as_str(), package))
+        .collect();
+    let prices: HashMap<&str, u64> = prices
+        .iter()
+        .filter(|price| price.payment_device == PAYMENT_GOLD)
+        .map(|price| (price.package.as_str(), price.cost))
+        .collect();
+
+    let mut tab_items: HashMap<&str, HashMap<u8, ShopItem>> = HashMap::new();
+    for good in goods.iter() {
+        let (Some(package), Some(price)) = (packages.get(good.package.as_str()), prices.get(good.package.as_str()))
+        else {
+            continue;
+        };
+        tab_items.entry(good.tab.as_str()).or_default().insert(
+            good.slot,
+            ShopItem {
+                item: package.item.clone(),
+                upgrade_level: package.upgrade_level,
+                variance: package.variance,
+                data: package.data,
+                price: *price,
+            },
+        );
+    }
+
+    let mut shops = HashMap::new();
+    for group in groups.iter() {
+        let mut npc_tabs = group_mappings
+            .iter()
+            .filter(|mapping| mapping.group == group.code)
+            .flat_map(|mapping| tab_mappings.iter().filter(move |tab| tab.shop == mapping.shop))
+            .flat_map(|mapping| tabs.iter().filter(move |tab| tab.tab_group == mapping.tab_group))
+            .collect::<Vec<_>>();
+        // The client orders the tabs of a shop by their id, which is also how it refers to them when buying. A tab can
+        // be part of several shops, so each shop gets its own copy of the items.
+        npc_tabs.sort_by_key(|tab| tab.id);
+        npc_tabs.dedup_by_key(|tab| tab.id);
+
+        let tabs = npc_tabs
+            .into_iter()
+            .map(|tab| ShopTab {
+                code: tab.code.clone(),
+                items: tab_items.get(tab.code.as_str()).cloned().unwrap_or_default(),
+            })
+            .collect();
+        shops.insert(group.npc.clone(), Shop { tabs });
+    }
+
+    Ok(ShopMap(shops))
+}
+
+/// All shops, keyed by the code of the NPC selling them.
+pub struct ShopMap(HashMap<String, Shop>);
+
+impl ShopMap {
+    pub fn shop_of(&self, npc: &str) -> Option<&Shop> {
+        self.0.get(npc)
+    }
+}
+
+pub struct Shop {
+    pub tabs: Vec<ShopTab>,
+}
+
+impl Shop {
+    pub fn item(&self, tab: u8, slot: u8) -> Option<&ShopItem> {
+        self.tabs.get(tab as usize).and_then(|tab| tab.items.get(&slot))
+    }
+}
+
+pub struct ShopTab {
+    pub code: String,
+    pub items: HashMap<u8, ShopItem>,
+}
+
+#[derive(Clone)]
+pub struct ShopItem {
+    /// The code of the item being sold.
+    pub item: String,
+    pub upgrade_level: u8,
+    pub variance: u64,
+    pub data: u32,
+    /// The price in gold for a single item.
+    pub price: u64,
+}
+
+trait ShopLine {
+    fn in_service(&self) -> bool;
+}
+
+macro_rules! in_service {
+    ($($name:ident),*) => {
+        $(
+            impl ShopLine for $name {
+                fn in_service(&self) -> bool {
+                    self.service
+                }
+            }
+        )*
+    };
+}
+
+in_service!(
+    RefShopGroup,
+    RefShopGroupMapping,
+    RefShopTabMapping,
+    RefShopTab,
+    RefShopGoods,
+    RefPackageItem,
+    RefPackagePrice
+);
+
+struct RefShopGroup {
+    service: bool,
+    code: String,
+    npc: String,
+}
+
+impl FromStr for RefShopGroup {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        Ok(Self {
+            service: parse_service(&elements)?,
+            code: elements.get(3).ok_or(ParseError::MissingColumn(3))?.to_string(),
+            npc: elements.get(4).ok_or(ParseError::MissingColumn(4))?.to_string(),
+        })
+    }
+}
+
+struct RefShopGroupMapping {
+    service: bool,
+    group: String,
+    shop: String,
+}
+
+impl FromStr for RefShopGroupMapping {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        Ok(Self {
+            service: parse_service(&elements)?,
+            group: elements.get(2).ok_or(ParseError::MissingColumn(2))?.to_string(),
+            shop: elements.get(3).ok_or(ParseError::MissingColumn(3))?.to_string(),
+        })
+    }
+}
+
+struct RefShopTabMapping {
+    service: bool,
+    shop: String,
+    tab_group: String,
+}
+
+impl FromStr for RefShopTabMapping {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        Ok(Self {
+            service: parse_service(&elements)?,
+            shop: elements.get(2).ok_or(ParseError::MissingColumn(2))?.to_string(),
+            tab_group: elements.get(3).ok_or(ParseError::MissingColumn(3))?.to_string(),
+        })
+    }
+}
+
+struct RefShopTab {
+    service: bool,
+    id: u32,
+    code: String,
+    tab_group: String,
+}
+
+impl FromStr for RefShopTab {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        Ok(Self {
+            service: parse_service(&elements)?,
+            id: elements.get(2).ok_or(ParseError::MissingColumn(2))?.parse()?,
+            code: elements.get(3).ok_or(ParseError::MissingColumn(3))?.to_string(),
+            tab_group: elements.get(4).ok_or(ParseError::MissingColumn(4))?.to_string(),
+        })
+    }
+}
+
+struct RefShopGoods {
+    service: bool,
+    tab: String,
+    package: String,
+    slot: u8,
+}
+
+impl FromStr for RefShopGoods {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        Ok(Self {
+            service: parse_service(&elements)?,
+            tab: elements.get(2).ok_or(ParseError::MissingColumn(2))?.to_string(),
+            package: elements.get(3).ok_or(ParseError::MissingColumn(3))?.to_string(),
+            slot: elements.get(4).ok_or(ParseError::MissingColumn(4))?.parse()?,
+        })
+    }
+}
+
+struct RefPackageItem {
+    service: bool,
+    package: String,
+    item: String,
+    upgrade_level: u8,
+    variance: u64,
+    data: u32,
+}
+
+impl FromStr for RefPackageItem {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        Ok(Self {
+            service: parse_service(&elements)?,
+            package: elements.get(2).ok_or(ParseError::MissingColumn(2))?.to_string(),
+            item: elements.get(3).ok_or(ParseError::MissingColumn(3))?.to_string(),
+            upgrade_level: elements.get(4).ok_or(ParseError::MissingColumn(4))?.parse()?,
+            variance: elements.get(5).ok_or(ParseError::MissingColumn(5))?.parse()?,
+            data: elements.get(6).ok_or(ParseError::MissingColumn(6))?.parse()?,
+        })
+    }
+}
+
+struct RefPackagePrice {
+    service: bool,
+    package: String,
+    payment_device: u8,
+    cost: u64,
+}
+
+impl FromStr for RefPackagePrice {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        Ok(Self {
+            service: parse_service(&elements)?,
+            package: elements.get(2).ok_or(ParseError::MissingColumn(2))?.to_string(),
+            payment_device: elements.get(3).ok_or(ParseError::MissingColumn(3))?.parse()?,
+            cost: elements.get(5).ok_or(ParseError::MissingColumn(5))?.parse()?,
+        })
+    }
+}
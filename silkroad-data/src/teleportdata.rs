@@ -0,0 +1,186 @@
+use crate::common::RefCommon;
+use crate::{parse_file, parse_service, DataEntry, FileError, ParseError};
+use pk2::Pk2;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The restriction type of a link which limits the levels that may use it.
+const RESTRICTION_LEVEL: u8 = 1;
+
+fn load<T: FromStr<Err = ParseError>>(pk2: &Pk2, file: &str) -> Result<Vec<T>, FileError> {
+    let mut file = pk2.open_file(format!("/server_dep/silkroad/textdata/{}", file))?;
+    parse_file(&mut file)
+}
+
+pub fn load_teleport_map(pk2: &Pk2) -> Result<TeleportMap, FileError> {
+    let buildings: Vec<RefTeleportBuilding> = load(pk2, "TeleportBuilding.txt")?;
+    let teleports: Vec<RefTeleport> = load(pk2, "TeleportData.txt")?;
+    let links: Vec<RefTeleportLinkLine> = load(pk2, "TeleportLink.txt")?;
+
+    let mut teleports: HashMap<u32, RefTeleport> = teleports
+        .into_iter()
+        .filter(|teleport| teleport.service)
+        .map(|teleport| (teleport.id, teleport))
+        .collect();
+    for line in links.into_iter().filter(|line| line.service) {
+        if let Some(teleport) = teleports.get_mut(&line.source) {
+            teleport.links.push(line.link);
+        }
+    }
+
+    Ok(TeleportMap {
+        buildings: buildings.into_iter().filter(|building| building.service).collect(),
+        teleports,
+    })
+}
+
+/// All places players can teleport from and to, together with the buildings that need to be spawned for them.
+pub struct TeleportMap {
+    buildings: Vec<RefTeleportBuilding>,
+    teleports: HashMap<u32, RefTeleport>,
+}
+
+impl TeleportMap {
+    pub fn buildings(&self) -> &[RefTeleportBuilding] {
+        &self.buildings
+    }
+
+    pub fn find_id(&self, id: u32) -> Option<&RefTeleport> {
+        self.teleports.get(&id)
+    }
+
+    /// Finds the teleport that can be used through the object with the given ref id, which may either be a
+    /// teleport building or an NPC.
+    pub fn teleport_of(&self, object_ref_id: u32) -> Option<&RefTeleport> {
+        self.teleports
+            .values()
+            .find(|teleport| teleport.object_ref_id == object_ref_id)
+    }
+}
+
+/// A building, like a town gate, that players can interact with to teleport.
+pub struct RefTeleportBuilding {
+    service: bool,
+    pub common: RefCommon,
+    pub region: u16, // column 41
+    pub x: f32,      // column 43
+    pub y: f32,      // column 44
+    pub z: f32,      // column 45
+}
+
+impl DataEntry for RefTeleportBuilding {
+    fn ref_id(&self) -> u32 {
+        self.common.ref_id
+    }
+
+    fn code(&self) -> &str {
+        &self.common.id
+    }
+}
+
+impl FromStr for RefTeleportBuilding {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        let region: i16 = elements.get(41).ok_or(ParseError::MissingColumn(41))?.parse()?;
+        Ok(Self {
+            service: parse_service(&elements)?,
+            common: RefCommon::from_columns(&elements)?,
+            region: region as u16,
+            x: elements.get(43).ok_or(ParseError::MissingColumn(43))?.parse()?,
+            y: elements.get(44).ok_or(ParseError::MissingColumn(44))?.parse()?,
+            z: elements.get(45).ok_or(ParseError::MissingColumn(45))?.parse()?,
+        })
+    }
+}
+
+pub struct RefTeleport {
+    service: bool,
+    pub id: u32,
+    pub code: String,
+    /// The ref id of the building or NPC through which this teleport is used.
+    pub object_ref_id: u32,
+    /// The location players arrive at when teleporting here.
+    pub region: u16,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    /// Whether players may designate this teleport as their return point.
+    pub can_designate: bool,
+    pub links: Vec<RefTeleportLink>,
+}
+
+impl FromStr for RefTeleport {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        let region: i16 = elements.get(6).ok_or(ParseError::MissingColumn(6))?.parse()?;
+        let can_designate: u8 = elements.get(11).ok_or(ParseError::MissingColumn(11))?.parse()?;
+        Ok(Self {
+            service: parse_service(&elements)?,
+            id: elements.get(1).ok_or(ParseError::MissingColumn(1))?.parse()?,
+            code: elements.get(2).ok_or(ParseError::MissingColumn(2))?.to_string(),
+            object_ref_id: elements.get(4).ok_or(ParseError::MissingColumn(4))?.parse()?,
+            region: region as u16,
+            x: elements.get(7).ok_or(ParseError::MissingColumn(7))?.parse()?,
+            y: elements.get(8).ok_or(ParseError::MissingColumn(8))?.parse()?,
+            z: elements.get(9).ok_or(ParseError::MissingColumn(9))?.parse()?,
+            can_designate: can_designate == 1,
+            links: Vec::new(),
+        })
+    }
+}
+
+/// A connection from one teleport to another, which players can use for a fee.
+pub struct RefTeleportLink {
+    pub target: u32,
+    pub fee: u64,
+    pub min_level: Option<u8>,
+    pub max_level: Option<u8>,
+}
+
+struct RefTeleportLinkLine {
+    service: bool,
+    source: u32,
+    link: RefTeleportLink,
+}
+
+impl FromStr for RefTeleportLinkLine {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let elements = s.split('\t').collect::<Vec<&str>>();
+        let mut min_level = None;
+        let mut max_level = None;
+        // There are up to five restrictions, each followed by two values describing it.
+        for i in (7..=19).step_by(3) {
+            let kind: u8 = elements.get(i).ok_or(ParseError::MissingColumn(i as u8))?.parse()?;
+            if kind != RESTRICTION_LEVEL {
+                continue;
+            }
+            let min: u8 = elements
+                .get(i + 1)
+                .ok_or(ParseError::MissingColumn(i as u8 + 1))?
+                .parse()?;
+            let max: u8 = elements
+                .get(i + 2)
+                .ok_or(ParseError::MissingColumn(i as u8 + 2))?
+                .parse()?;
+            min_level = Some(min).filter(|min| *min > 0);
+            max_level = Some(max).filter(|max| *max > 0);
+        }
+
+        Ok(Self {
+            service: parse_service(&elements)?,
+            source: elements.get(1).ok_or(ParseError::MissingColumn(1))?.parse()?,
+            link: RefTeleportLink {
+                target: elements.get(2).ok_or(ParseError::MissingColumn(2))?.parse()?,
+                fee: elements.get(3).ok_or(ParseError::MissingColumn(3))?.parse()?,
+                min_level,
+                max_level,
+            },
+        })
+    }
+}
@@ -19,6 +19,8 @@ pub struct Character {
     pub gold: u64,
     pub beginner_mark: bool,
     pub gm: bool,
+    pub job_level: u8,
+    pub job_exp: u64,
     pub state: SpawningState,
     pub masteries: Vec<(u32, u8)>,
     pub skills: Vec<(u32, u8)>,
@@ -84,7 +84,7 @@ impl ItemTypeData {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum InventoryChange {
     AddItem {
         slot: u8,
@@ -292,9 +292,11 @@ impl Change for InventoryChange {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Inventory {
     size: usize,
+    /// Whether the first slots are reserved for equipment, which is not the case for containers like the storage.
+    equipment: bool,
     // TODO: wouldn't this make more sense as an array of N size?
     items: HashMap<u8, Item>,
     changes: Vec<InventoryChange>,
@@ -305,6 +307,17 @@ impl Inventory {
         assert!(size > 0xC, "Minimum Inventory size is 12");
         Inventory {
             size,
+            equipment: true,
+            items: HashMap::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Creates an inventory without any equipment slots, where every slot can hold any item.
+    pub fn without_equipment(size: usize) -> Self {
+        Inventory {
+            size,
+            equipment: false,
             items: HashMap::new(),
             changes: Vec::new(),
         }
@@ -331,13 +344,50 @@ impl Inventory {
     }
 
     fn non_equipment_slots(&self) -> impl Iterator<Item = u8> {
-        (0u8..(self.size as u8)).filter(|index| !Self::is_equipment_slot(*index))
+        let equipment = self.equipment;
+        (0u8..(self.size as u8)).filter(move |index| !equipment || !Self::is_equipment_slot(*index))
     }
 
     fn empty_slot(&self) -> Option<u8> {
         self.non_equipment_slots().find(|slot| !self.items.contains_key(slot))
     }
 
+    pub fn free_slots(&self) -> usize {
+        self.non_equipment_slots()
+            .filter(|slot| !self.items.contains_key(slot))
+            .count()
+    }
+
+    /// Takes `amount` items out of the stack in the given slot, removing the item from the slot entirely if nothing
+    /// remains. Provides the taken items as a new item.
+    pub fn take_from_slot(&mut self, slot: u8, amount: u16) -> Result<Item, MoveError> {
+        let existing = self.items.get_mut(&slot).ok_or(MoveError::ItemDoesNotExist)?;
+        if amount == 0 || amount > existing.stack_size() {
+            return Err(MoveError::Impossible);
+        }
+
+        if amount == existing.stack_size() {
+            let item = self
+                .items
+                .remove(&slot)
+                .expect("Item should still exist just after checking");
+            self.changes.push(InventoryChange::RemoveItem { slot });
+            return Ok(item);
+        }
+
+        let old_data = existing.type_data;
+        existing.change_stack_size(-(amount as i16))?;
+        let new_data = existing.type_data;
+        self.changes.push(InventoryChange::ChangeTypeData {
+            slot,
+            old_item: old_data,
+            new_item: new_data,
+        });
+        let mut taken = *existing;
+        taken.type_data = ItemTypeData::Consumable { amount };
+        Ok(taken)
+    }
+
     pub fn move_item(&mut self, source: u8, target: u8, amount: u16) -> Result<u16, MoveError> {
         if let Some(mut source_item) = self.items.remove(&source) {
             if let Some(mut target_item) = self.items.remove(&target) {
@@ -420,6 +470,34 @@ impl Inventory {
         self.items.insert(slot, item);
     }
 
+    /// Puts the item into the given slot, as long as that slot exists and is still empty.
+    pub fn place_item(&mut self, slot: u8, item: Item) -> Result<(), MoveError> {
+        if slot as usize >= self.size || self.items.contains_key(&slot) {
+            return Err(MoveError::Impossible);
+        }
+
+        self.items.insert(slot, item);
+        self.changes.push(InventoryChange::AddItem { slot, item });
+        Ok(())
+    }
+
+    /// Changes the upgrade level of the equipment in the given slot.
+    pub fn set_upgrade_level(&mut self, slot: u8, upgrade_level: u8) -> Result<(), MoveError> {
+        let item = self.items.get_mut(&slot).ok_or(MoveError::ItemDoesNotExist)?;
+        let ItemTypeData::Equipment { .. } = item.type_data else {
+            return Err(MoveError::Impossible);
+        };
+
+        let old_item = item.type_data;
+        item.type_data = ItemTypeData::Equipment { upgrade_level };
+        self.changes.push(InventoryChange::ChangeTypeData {
+            slot,
+            old_item,
+            new_item: item.type_data,
+        });
+        Ok(())
+    }
+
     fn find_slots_matching(&self, item: Item) -> impl Iterator<Item = u8> + '_ {
         self.items
             .iter()
@@ -566,6 +644,7 @@ mod test {
             despawn_time: Default::default(),
         },
         price: 100,
+        sell_price: 25,
         max_stack_size: 50,
         range: None,
         required_level: None,
@@ -585,6 +664,7 @@ mod test {
             despawn_time: Default::default(),
         },
         price: 100,
+        sell_price: 25,
         max_stack_size: 50,
         range: None,
         required_level: None,
@@ -671,4 +751,28 @@ mod test {
         assert_eq!(1, changes.len());
         assert!(matches!(changes.pop().unwrap(), InventoryChange::RemoveItem { slot }));
     }
+
+    #[test]
+    pub fn test_take_from_slot() {
+        let mut inv = Inventory::default();
+
+        let item = Item {
+            variance: None,
+            reference: FIRST_ITEM_DATA.deref(),
+            type_data: ItemTypeData::Consumable { amount: 5 },
+        };
+        let slot = inv.add_item(item).unwrap();
+        let _ = inv.changes();
+
+        let taken = inv.take_from_slot(slot, 2).unwrap();
+        assert_eq!(2, taken.stack_size());
+        assert_eq!(3, inv.get_item_at(slot).unwrap().stack_size());
+        assert!(inv.take_from_slot(slot, 4).is_err());
+
+        inv.take_from_slot(slot, 3).unwrap();
+        assert!(inv.get_item_at(slot).is_none());
+        let changes = inv.changes();
+        assert_eq!(2, changes.len());
+        assert!(matches!(changes[1], InventoryChange::RemoveItem { .. }));
+    }
 }
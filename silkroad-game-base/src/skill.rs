@@ -64,4 +64,8 @@ pub enum AttackSkillError {
     SkillNotFound,
     #[error("The type of weapon was not known")]
     UnknownWeapon,
+    #[error("There is no path to reach the target")]
+    Unreachable,
+    #[error("The target may not be attacked")]
+    NotAllowed,
 }
@@ -17,11 +17,20 @@ pub(crate) enum Commands {
         character_name: String,
         gm: Option<bool>,
     },
+    /// Bans the user from logging in, either for the given amount of hours or forever.
+    Ban {
+        username: String,
+        reason: String,
+        #[arg(long)]
+        hours: Option<u32>,
+    },
+    /// Lifts all active bans of the user.
+    Unban {
+        username: String,
+    },
     // Some other command ideas:
     // - Add news entry
     // - list news
     // - remove news
-    // - ban user
-    // - unban user
     // - set/unset gm
 }
@@ -80,10 +80,12 @@ impl Client {
                         files,
                         target_version,
                         host,
+                        download_address,
+                        download_port,
                     } => {
                         let response = PatchResponse::error(PatchError::Update {
-                            server_ip: "localhost".to_string(),
-                            server_port: 80,
+                            server_ip: download_address,
+                            server_port: download_port,
                             current_version: target_version,
                             patch_files: files,
                             http_server: host,
@@ -142,12 +144,11 @@ impl Client {
                                 .await?;
                             debug!("invalid credentials {:?}, {:?}", &login.username, &login.password);
                         },
-                        LoginResult::Blocked => {
+                        LoginResult::Blocked { reason, until } => {
+                            // Permanent bans still need an end, so we pick one far enough in the future.
+                            let end = until.unwrap_or_else(|| Utc.with_ymd_and_hms(2099, 12, 31, 23, 59, 59).unwrap());
                             let response = LoginResponse::error(SecurityError::Blocked {
-                                reason: BlockReason::Punishment {
-                                    reason: "You have been blocked.".to_string(),
-                                    end: Utc.with_ymd_and_hms(2099, 12, 31, 23, 59, 59).unwrap(),
-                                },
+                                reason: BlockReason::Punishment { reason, end },
                             });
                             writer.send(response).await?;
                         },
@@ -182,7 +183,7 @@ impl Client {
                             LoginResult::InvalidCredentials => {
                                 writer.send(PasscodeRequiredResponse::passcode_invalid()).await?;
                             },
-                            LoginResult::Blocked => {
+                            LoginResult::Blocked { .. } => {
                                 writer.send(PasscodeRequiredResponse::passcode_blocked()).await?;
                             },
                         }
@@ -15,6 +15,10 @@ pub(crate) struct PatchConfig {
     pub(crate) dir: String,
     pub(crate) expected_client_version: u32,
     pub(crate) minimum_client_version: u32,
+    /// Address clients download the patch files from, if it isn't this server.
+    pub(crate) download_address: Option<String>,
+    /// Port to serve the patch files on. The download server is only started if this is set.
+    pub(crate) download_port: Option<u16>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -0,0 +1,58 @@
+use crate::patch::PatchIndex;
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+#[derive(Clone)]
+struct Downloads {
+    index: Arc<PatchIndex>,
+    version: u32,
+}
+
+/// Serves the files of the patches over HTTP, where each file can be downloaded by the path the client received
+/// for it. Only files that are part of a patch can be downloaded.
+pub(crate) async fn serve_patches(
+    socket: SocketAddr,
+    index: Arc<PatchIndex>,
+    version: u32,
+    cancellation: CancellationToken,
+) -> Result<(), io::Error> {
+    let router = Router::new()
+        .route("/*path", get(download))
+        .with_state(Downloads { index, version });
+    let listener = TcpListener::bind(socket).await?;
+    info!(?socket, "Serving patch downloads.");
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move { cancellation.cancelled().await })
+        .await
+}
+
+async fn download(State(downloads): State<Downloads>, Path(path): Path<String>) -> Response {
+    let Some(entry) = downloads.index.find(&path, downloads.version) else {
+        debug!(path, "Requested file is not part of any patch");
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match tokio::fs::read(&entry.location).await {
+        Ok(content) => (
+            [
+                (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+                (header::ETAG, format!("\"{:08x}\"", entry.crc)),
+            ],
+            content,
+        )
+            .into_response(),
+        Err(e) => {
+            warn!(error = %e, path, "Could not read patch file");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        },
+    }
+}
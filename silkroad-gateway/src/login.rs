@@ -1,4 +1,5 @@
 use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::{DateTime, Utc};
 use log::debug;
 use sqlx::PgPool;
 
@@ -16,7 +17,11 @@ pub(crate) enum LoginResult {
     Success(i32),
     MissingPasscode,
     InvalidCredentials,
-    Blocked,
+    /// The user is banned for the given reason, until the given time or forever if there is none.
+    Blocked {
+        reason: String,
+        until: Option<DateTime<Utc>>,
+    },
 }
 
 pub(crate) enum RegistrationResult {
@@ -31,6 +36,13 @@ pub(crate) enum SetGmResult {
     UserNotFound,
 }
 
+pub(crate) enum BanResult {
+    Success,
+    DatabaseError,
+    UserNotFound,
+    NotBanned,
+}
+
 pub(crate) struct LoginProvider {
     pool: PgPool,
 }
@@ -56,6 +68,10 @@ impl LoginProvider {
                     return LoginResult::InvalidCredentials;
                 }
 
+                if let Some(blocked) = self.check_ban(result.id).await {
+                    return blocked;
+                }
+
                 if result.passcode.is_some() {
                     LoginResult::MissingPasscode
                 } else {
@@ -79,7 +95,7 @@ impl LoginProvider {
         match result {
             Some(r) => {
                 if verify(password, &r.password).ok().unwrap_or(false) {
-                    LoginResult::Success(r.id)
+                    self.check_ban(r.id).await.unwrap_or(LoginResult::Success(r.id))
                 } else {
                     LoginResult::InvalidCredentials
                 }
@@ -88,6 +104,73 @@ impl LoginProvider {
         }
     }
 
+    /// Provides the blocked result if the user is currently banned. If the user has multiple bans, the one lasting
+    /// the longest is used.
+    async fn check_ban(&self, user_id: i32) -> Option<LoginResult> {
+        let ban = sqlx::query!(
+            "SELECT reason, banned_until FROM bans WHERE user_id = $1 AND lifted_at IS NULL AND (banned_until IS NULL OR banned_until > now()) ORDER BY banned_until DESC NULLS FIRST LIMIT 1",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .unwrap()?;
+
+        Some(LoginResult::Blocked {
+            reason: ban.reason,
+            until: ban.banned_until,
+        })
+    }
+
+    pub async fn ban(&self, username: &str, reason: &str, until: Option<DateTime<Utc>>) -> BanResult {
+        let user = sqlx::query!("SELECT id FROM users WHERE username = $1", username)
+            .fetch_optional(&self.pool)
+            .await
+            .expect("should be able to query existing usernames");
+
+        let Some(user) = user else {
+            return BanResult::UserNotFound;
+        };
+
+        let result = sqlx::query!(
+            "INSERT INTO bans(user_id, reason, banned_until) VALUES($1, $2, $3)",
+            user.id,
+            reason,
+            until
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(_) => BanResult::Success,
+            Err(_) => BanResult::DatabaseError,
+        }
+    }
+
+    /// Lifts all bans of the user that are still active.
+    pub async fn unban(&self, username: &str) -> BanResult {
+        let user = sqlx::query!("SELECT id FROM users WHERE username = $1", username)
+            .fetch_optional(&self.pool)
+            .await
+            .expect("should be able to query existing usernames");
+
+        let Some(user) = user else {
+            return BanResult::UserNotFound;
+        };
+
+        let result = sqlx::query!(
+            "UPDATE bans SET lifted_at = now() WHERE user_id = $1 AND lifted_at IS NULL AND (banned_until IS NULL OR banned_until > now())",
+            user.id
+        )
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(result) if result.rows_affected() == 0 => BanResult::NotBanned,
+            Ok(_) => BanResult::Success,
+            Err(_) => BanResult::DatabaseError,
+        }
+    }
+
     pub async fn register(&self, username: &str, password: &str, passcode: Option<&str>) -> RegistrationResult {
         let exists = sqlx::query!("SELECT ID FROM users WHERE username = $1", username)
             .fetch_optional(&self.pool)
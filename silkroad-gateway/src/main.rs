@@ -2,6 +2,7 @@ mod agentserver;
 mod cli;
 mod client;
 mod config;
+mod download;
 mod login;
 mod news;
 mod patch;
@@ -10,11 +11,13 @@ mod server;
 use crate::agentserver::AgentServerManager;
 use crate::cli::{Cli, Commands};
 use crate::config::{get_config, DbOptions, GatewayServerConfig};
-use crate::login::{LoginProvider, RegistrationResult, SetGmResult};
+use crate::download::serve_patches;
+use crate::login::{BanResult, LoginProvider, RegistrationResult, SetGmResult};
 use crate::news::NewsCacheAsync;
 use crate::patch::Patcher;
 use crate::server::GatewayServer;
 use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
 use clap::Parser;
 use silkroad_protocol::login::Farm;
 use sqlx::PgPool;
@@ -89,6 +92,17 @@ async fn main() -> Result<()> {
         .unwrap_or_else(Patcher::allow_all);
 
     let cancellation = CancellationToken::new();
+    let download_port = configuration.patch.as_ref().and_then(|patch| patch.download_port);
+    if let (Some(port), Some((index, version))) = (download_port, patcher.downloads()) {
+        let socket = SocketAddr::new(listen_addr.ip(), port);
+        let cancellation = cancellation.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve_patches(socket, index, version, cancellation).await {
+                error!(error = %e, "Could not start patch download server")
+            }
+        });
+    }
+
     let server = GatewayServer::new(
         listen_addr,
         cancellation.clone(),
@@ -154,6 +168,47 @@ async fn run_command(command: &Commands, configuration: &GatewayServerConfig) ->
                 },
             }
         },
+        Commands::Ban {
+            username,
+            reason,
+            hours,
+        } => {
+            let db = create_db(&configuration.database).await?;
+            let login_handler = LoginProvider::new(db);
+            let until = hours.map(|hours| Utc::now() + chrono::Duration::hours(hours as i64));
+            match login_handler.ban(username, reason, until).await {
+                BanResult::Success => match until {
+                    Some(until) => info!("Banned user {} until {}.", username, until),
+                    None => info!("Banned user {} permanently.", username),
+                },
+                BanResult::UserNotFound => {
+                    return Err(anyhow!("Could not ban user, because no user with that name was found."));
+                },
+                _ => {
+                    return Err(anyhow!("Could not ban user."));
+                },
+            }
+        },
+        Commands::Unban { username } => {
+            let db = create_db(&configuration.database).await?;
+            let login_handler = LoginProvider::new(db);
+            match login_handler.unban(username).await {
+                BanResult::Success => {
+                    info!("Lifted the bans of user {}.", username);
+                },
+                BanResult::UserNotFound => {
+                    return Err(anyhow!(
+                        "Could not unban user, because no user with that name was found."
+                    ));
+                },
+                BanResult::NotBanned => {
+                    return Err(anyhow!("Could not unban user, because the user is not banned."));
+                },
+                BanResult::DatabaseError => {
+                    return Err(anyhow!("Could not unban user."));
+                },
+            }
+        },
     }
     Ok(())
 }
@@ -1,5 +1,15 @@
 use crate::config::PatchConfig;
+use crc::{Crc, CRC_32_ISO_HDLC};
 use silkroad_protocol::login::PatchFile;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fs, io};
+use tracing::{debug, warn};
+
+const CHECKSUM: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+const DEFAULT_DOWNLOAD_ADDRESS: &str = "localhost";
+const DEFAULT_DOWNLOAD_PORT: u16 = 80;
 
 pub(crate) enum PatchInformation {
     UpToDate,
@@ -7,27 +17,151 @@ pub(crate) enum PatchInformation {
         files: Vec<PatchFile>,
         target_version: u32,
         host: String,
+        download_address: String,
+        download_port: u16,
     },
     Outdated,
 }
 
+/// A file of a patch, identified by its path relative to the folder of the patch.
+pub(crate) struct PatchEntry {
+    pub(crate) path: String,
+    pub(crate) location: PathBuf,
+    pub(crate) size: u32,
+    pub(crate) crc: u32,
+}
+
+impl PatchEntry {
+    fn to_patch_file(&self, file_id: u32) -> PatchFile {
+        let (directory, name) = self.path.rsplit_once('/').unwrap_or(("", &self.path));
+        // Files inside a top level folder named after an archive, like `Media.pk2`, get packed into that archive.
+        let in_pk2 = directory
+            .split('/')
+            .next()
+            .is_some_and(|folder| folder.to_lowercase().ends_with(".pk2"));
+        PatchFile::new(
+            file_id,
+            name.to_string(),
+            directory.replace('/', "\\"),
+            self.size,
+            in_pk2,
+        )
+    }
+}
+
+/// The files of all patches found in the patch directory, where each patch is a folder named after the version it
+/// updates the client to.
+#[derive(Default)]
+pub(crate) struct PatchIndex {
+    versions: BTreeMap<u32, Vec<PatchEntry>>,
+}
+
+impl PatchIndex {
+    pub(crate) fn scan(dir: &Path) -> io::Result<Self> {
+        let mut versions = BTreeMap::new();
+        for folder in fs::read_dir(dir)? {
+            let folder = folder?;
+            let Some(version) = folder.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+                continue;
+            };
+            if !folder.file_type()?.is_dir() {
+                continue;
+            }
+
+            let mut entries = Vec::new();
+            collect_files(&folder.path(), "", &mut entries)?;
+            entries.sort_by(|a, b| a.path.cmp(&b.path));
+            debug!(version, files = entries.len(), "Indexed patch");
+            versions.insert(version, entries);
+        }
+        Ok(PatchIndex { versions })
+    }
+
+    /// Provides the newest state of every file that got changed by the patches after the given version, up to and
+    /// including the target version.
+    fn changes_between(&self, version: u32, target: u32) -> Vec<&PatchEntry> {
+        if version >= target {
+            return Vec::new();
+        }
+
+        let mut latest = BTreeMap::new();
+        for (_, entries) in self.versions.range(version.saturating_add(1)..=target) {
+            for entry in entries {
+                latest.insert(entry.path.as_str(), entry);
+            }
+        }
+        latest.into_values().collect()
+    }
+
+    /// Finds the newest state of the file at the given path, considering only patches up to the given version. The
+    /// path may use either `/` or `\\` as separators.
+    pub(crate) fn find(&self, path: &str, up_to: u32) -> Option<&PatchEntry> {
+        let path = path.replace('\\', "/");
+        let path = path.trim_start_matches('/');
+        self.versions.range(..=up_to).rev().find_map(|(_, entries)| {
+            entries
+                .binary_search_by(|entry| entry.path.as_str().cmp(path))
+                .ok()
+                .map(|index| &entries[index])
+        })
+    }
+}
+
+fn collect_files(dir: &Path, prefix: &str, entries: &mut Vec<PatchEntry>) -> io::Result<()> {
+    for file in fs::read_dir(dir)? {
+        let file = file?;
+        let Ok(name) = file.file_name().into_string() else {
+            warn!(path = ?file.path(), "Skipping patch file with a name that is not valid UTF-8");
+            continue;
+        };
+        let path = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}/{name}")
+        };
+
+        if file.file_type()?.is_dir() {
+            collect_files(&file.path(), &path, entries)?;
+        } else {
+            let content = fs::read(file.path())?;
+            entries.push(PatchEntry {
+                path,
+                location: file.path(),
+                size: content.len() as u32,
+                crc: CHECKSUM.checksum(&content),
+            });
+        }
+    }
+    Ok(())
+}
+
 pub(crate) enum Patcher {
     AcceptAll,
     AcceptMatching {
         min: u32,
         current: u32,
-        dir: String,
+        index: Arc<PatchIndex>,
         remote: String,
+        download_address: String,
+        download_port: u16,
     },
 }
 
 impl Patcher {
     pub(crate) fn new(config: PatchConfig) -> Self {
+        let index = PatchIndex::scan(Path::new(&config.dir)).unwrap_or_else(|e| {
+            warn!(error = %e, dir = config.dir, "Could not read patches, clients will not receive any files");
+            PatchIndex::default()
+        });
         Patcher::AcceptMatching {
             min: config.minimum_client_version,
             current: config.expected_client_version,
-            dir: config.dir,
+            index: Arc::new(index),
             remote: config.remote_url,
+            download_address: config
+                .download_address
+                .unwrap_or_else(|| DEFAULT_DOWNLOAD_ADDRESS.to_string()),
+            download_port: config.download_port.unwrap_or(DEFAULT_DOWNLOAD_PORT),
         }
     }
 
@@ -35,11 +169,24 @@ impl Patcher {
         Patcher::AcceptAll
     }
 
+    /// Provides the patch files that can be downloaded, together with the version they update the client to.
+    pub(crate) fn downloads(&self) -> Option<(Arc<PatchIndex>, u32)> {
+        match &self {
+            Patcher::AcceptAll => None,
+            Patcher::AcceptMatching { index, current, .. } => Some((index.clone(), *current)),
+        }
+    }
+
     pub fn get_patch_information(&self, version: u32) -> PatchInformation {
         match &self {
             Patcher::AcceptAll => PatchInformation::UpToDate,
             Patcher::AcceptMatching {
-                min, current, remote, ..
+                min,
+                current,
+                remote,
+                download_address,
+                download_port,
+                ..
             } => {
                 if version == *current {
                     PatchInformation::UpToDate
@@ -48,6 +195,8 @@ impl Patcher {
                         files: self.get_patches_for(version),
                         target_version: *current,
                         host: remote.clone(),
+                        download_address: download_address.clone(),
+                        download_port: *download_port,
                     }
                 } else {
                     PatchInformation::Outdated
@@ -57,6 +206,130 @@ impl Patcher {
     }
 
     fn get_patches_for(&self, version: u32) -> Vec<PatchFile> {
-        todo!("Load patches from dir and check them")
+        match &self {
+            Patcher::AcceptAll => Vec::new(),
+            Patcher::AcceptMatching { index, current, .. } => index
+                .changes_between(version, *current)
+                .into_iter()
+                .zip(1..)
+                .map(|(entry, file_id)| entry.to_patch_file(file_id))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct PatchDir(PathBuf);
+
+    impl PatchDir {
+        fn new(name: &str, files: &[&str]) -> Self {
+            let dir = std::env::temp_dir().join(format!("skrillax-patches-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&dir);
+            for file in files {
+                let path = dir.join(file);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, file.as_bytes()).unwrap();
+            }
+            PatchDir(dir)
+        }
+    }
+
+    impl Drop for PatchDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    pub fn test_scan_only_indexes_version_folders() {
+        let dir = PatchDir::new(
+            "scan",
+            &[
+                "189/sro_client.exe",
+                "189/Media.pk2/icon/skill.ddj",
+                "notes/readme.txt",
+                "190.txt",
+            ],
+        );
+        let index = PatchIndex::scan(&dir.0).unwrap();
+
+        assert_eq!(index.versions.len(), 1);
+        let paths: Vec<&str> = index.versions[&189].iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(paths, vec!["Media.pk2/icon/skill.ddj", "sro_client.exe"]);
+
+        let client = &index.versions[&189][1];
+        assert_eq!(client.size, "189/sro_client.exe".len() as u32);
+        assert_eq!(client.crc, CHECKSUM.checksum(b"189/sro_client.exe"));
+    }
+
+    #[test]
+    pub fn test_changes_only_contain_newest_files_after_version() {
+        let dir = PatchDir::new(
+            "changes",
+            &[
+                "188/sro_client.exe",
+                "189/sro_client.exe",
+                "189/Media.pk2/icon/skill.ddj",
+                "190/sro_client.exe",
+                "191/server.txt",
+            ],
+        );
+        let index = PatchIndex::scan(&dir.0).unwrap();
+
+        let changes = index.changes_between(188, 190);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].path, "Media.pk2/icon/skill.ddj");
+        assert_eq!(changes[1].path, "sro_client.exe");
+        assert!(changes[1].location.starts_with(dir.0.join("190")));
+
+        assert!(index.changes_between(190, 190).is_empty());
+    }
+
+    #[test]
+    pub fn test_find_respects_version_and_separators() {
+        let dir = PatchDir::new(
+            "find",
+            &[
+                "189/sro_client.exe",
+                "190/sro_client.exe",
+                "190/Media.pk2/icon/skill.ddj",
+            ],
+        );
+        let index = PatchIndex::scan(&dir.0).unwrap();
+
+        let client = index.find("sro_client.exe", 189).unwrap();
+        assert!(client.location.starts_with(dir.0.join("189")));
+        let client = index.find("/sro_client.exe", 200).unwrap();
+        assert!(client.location.starts_with(dir.0.join("190")));
+
+        assert!(index.find("Media.pk2\\icon\\skill.ddj", 190).is_some());
+        assert!(index.find("Media.pk2/icon/skill.ddj", 190).is_some());
+        assert!(index.find("Media.pk2/icon/skill.ddj", 189).is_none());
+        assert!(index.find("icon/skill.ddj", 190).is_none());
+    }
+
+    #[test]
+    pub fn test_files_in_archive_folders_are_packed() {
+        let entry = |path: &str| PatchEntry {
+            path: path.to_string(),
+            location: PathBuf::new(),
+            size: 10,
+            crc: 0,
+        };
+
+        let packed = entry("Media.pk2/icon/skill.ddj").to_patch_file(1);
+        assert_eq!(packed.filename, "skill.ddj");
+        assert_eq!(packed.file_path, "Media.pk2\\icon");
+        assert!(packed.in_pk2);
+
+        let client = entry("sro_client.exe").to_patch_file(2);
+        assert_eq!(client.filename, "sro_client.exe");
+        assert_eq!(client.file_path, "");
+        assert!(!client.in_pk2);
+
+        assert!(!entry("data/icon/skill.ddj").to_patch_file(3).in_pk2);
     }
 }
@@ -15,6 +15,10 @@ pub struct NavmeshBuilder;
 impl NavmeshBuilder {
     pub fn build_from(loader: &dyn FileLoader) -> io::Result<GlobalNavmesh> {
         let objects = ObjectLoader::load_objects(loader)?;
+        let collisions = objects
+            .iter()
+            .filter_map(|(id, object)| Some((*id, Arc::new(object.collision(loader)?))))
+            .collect::<HashMap<_, _>>();
         let (_, region_info) = JmxMapInfo::parse(&loader.load_file(MAP_INFO_FILE)?)
             .map_err(|_| io::Error::new(ErrorKind::InvalidData, "Could not parse map info file."))?;
         let regions = region_info
@@ -25,7 +29,7 @@ impl NavmeshBuilder {
                     Err(_) => return None,
                 };
 
-                let container = NavmeshContainer::new(region, new_mesh);
+                let container = NavmeshContainer::new(region, new_mesh, &collisions);
                 let new_mesh = Arc::new(container);
                 Some((region, new_mesh))
             })
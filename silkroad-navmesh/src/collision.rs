@@ -0,0 +1,200 @@
+use cgmath::{Vector2, Vector3};
+
+/// The navigation mesh of an object, like a building or a bridge, in the coordinates of the object itself.
+///
+/// The mesh consists of walkable triangles, which may be on top of or inside the object, and the edges that cannot
+/// be walked through, like the walls of a building.
+pub struct ObjectCollision {
+    vertices: Vec<Vector3<f32>>,
+    cells: Vec<[usize; 3]>,
+    blocking_edges: Vec<[usize; 2]>,
+}
+
+impl ObjectCollision {
+    pub fn new(vertices: Vec<Vector3<f32>>, cells: Vec<[usize; 3]>, blocking_edges: Vec<[usize; 2]>) -> Self {
+        // Indices outside the vertex list would only make us panic later on, so get rid of them right away.
+        let cells = cells
+            .into_iter()
+            .filter(|cell| cell.iter().all(|index| *index < vertices.len()))
+            .collect();
+        let blocking_edges = blocking_edges
+            .into_iter()
+            .filter(|edge| edge.iter().all(|index| *index < vertices.len()))
+            .collect();
+        ObjectCollision {
+            vertices,
+            cells,
+            blocking_edges,
+        }
+    }
+}
+
+/// An object placed inside a region, with its collision mesh already moved to its position.
+pub struct PlacedObject {
+    surfaces: Vec<[Vector3<f32>; 3]>,
+    walls: Vec<(Vector2<f32>, Vector2<f32>)>,
+    min: Vector2<f32>,
+    max: Vector2<f32>,
+}
+
+impl PlacedObject {
+    /// Places the object at the given position inside the region, rotated by `yaw` radians around the vertical axis.
+    pub fn new(collision: &ObjectCollision, position: Vector3<f32>, yaw: f32) -> Self {
+        let (sin, cos) = yaw.sin_cos();
+        let vertices = collision
+            .vertices
+            .iter()
+            .map(|vertex| {
+                Vector3::new(
+                    vertex.x * cos - vertex.z * sin + position.x,
+                    vertex.y + position.y,
+                    vertex.x * sin + vertex.z * cos + position.z,
+                )
+            })
+            .collect::<Vec<_>>();
+        let surfaces = collision
+            .cells
+            .iter()
+            .map(|cell| [vertices[cell[0]], vertices[cell[1]], vertices[cell[2]]])
+            .collect();
+        let walls = collision
+            .blocking_edges
+            .iter()
+            .map(|edge| (flat(vertices[edge[0]]), flat(vertices[edge[1]])))
+            .collect();
+        let min = vertices.iter().fold(Vector2::new(f32::MAX, f32::MAX), |min, vertex| {
+            Vector2::new(min.x.min(vertex.x), min.y.min(vertex.z))
+        });
+        let max = vertices.iter().fold(Vector2::new(f32::MIN, f32::MIN), |max, vertex| {
+            Vector2::new(max.x.max(vertex.x), max.y.max(vertex.z))
+        });
+        PlacedObject {
+            surfaces,
+            walls,
+            min,
+            max,
+        }
+    }
+
+    fn overlaps(&self, min: Vector2<f32>, max: Vector2<f32>) -> bool {
+        self.min.x <= max.x && self.max.x >= min.x && self.min.y <= max.y && self.max.y >= min.y
+    }
+
+    /// Provides the heights of all walkable surfaces of this object at the given location.
+    pub fn heights_at(&self, x: f32, z: f32) -> impl Iterator<Item = f32> + '_ {
+        let point = Vector2::new(x, z);
+        let inside = self.overlaps(point, point);
+        self.surfaces
+            .iter()
+            .filter(move |_| inside)
+            .filter_map(move |triangle| height_in_triangle(triangle, point))
+    }
+
+    /// Checks if walking in a straight line from `from` to `to` would walk through a wall of this object.
+    pub fn blocks(&self, from: Vector2<f32>, to: Vector2<f32>) -> bool {
+        let min = Vector2::new(from.x.min(to.x), from.y.min(to.y));
+        let max = Vector2::new(from.x.max(to.x), from.y.max(to.y));
+        self.overlaps(min, max) && self.walls.iter().any(|(start, end)| intersects(from, to, *start, *end))
+    }
+}
+
+fn flat(vertex: Vector3<f32>) -> Vector2<f32> {
+    Vector2::new(vertex.x, vertex.z)
+}
+
+fn cross(a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+/// Interpolates the height of the point on the triangle, if the point is inside the triangle when looking at it from
+/// above.
+fn height_in_triangle(triangle: &[Vector3<f32>; 3], point: Vector2<f32>) -> Option<f32> {
+    let [a, b, c] = triangle.map(flat);
+    let area = cross(b - a, c - a);
+    if area.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let u = cross(c - b, point - b) / area;
+    let v = cross(a - c, point - c) / area;
+    let w = 1.0 - u - v;
+    if u < 0.0 || v < 0.0 || w < 0.0 {
+        return None;
+    }
+
+    Some(u * triangle[0].y + v * triangle[1].y + w * triangle[2].y)
+}
+
+/// Checks if the two line segments cross each other. Segments that only touch at their ends are not considered to
+/// be crossing, as otherwise we'd get stuck right next to a wall.
+fn intersects(
+    first_start: Vector2<f32>,
+    first_end: Vector2<f32>,
+    second_start: Vector2<f32>,
+    second_end: Vector2<f32>,
+) -> bool {
+    let first = first_end - first_start;
+    let second = second_end - second_start;
+    let denominator = cross(first, second);
+    if denominator.abs() < f32::EPSILON {
+        return false;
+    }
+
+    let offset = second_start - first_start;
+    let along_first = cross(offset, second) / denominator;
+    let along_second = cross(offset, first) / denominator;
+    along_first > 0.0 && along_first < 1.0 && along_second > 0.0 && along_second < 1.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A platform of 10x10 at the height of 5, with a wall along its left side.
+    fn platform() -> ObjectCollision {
+        ObjectCollision::new(
+            vec![
+                Vector3::new(0.0, 5.0, 0.0),
+                Vector3::new(10.0, 5.0, 0.0),
+                Vector3::new(10.0, 5.0, 10.0),
+                Vector3::new(0.0, 5.0, 10.0),
+            ],
+            vec![[0, 1, 2], [0, 2, 3]],
+            vec![[0, 3]],
+        )
+    }
+
+    #[test]
+    pub fn test_heights() {
+        let placed = PlacedObject::new(&platform(), Vector3::new(100.0, 10.0, 100.0), 0.0);
+        assert_eq!(placed.heights_at(105.0, 105.0).next(), Some(15.0));
+        assert_eq!(placed.heights_at(95.0, 105.0).next(), None);
+    }
+
+    #[test]
+    pub fn test_rotation() {
+        let placed = PlacedObject::new(
+            &platform(),
+            Vector3::new(100.0, 0.0, 100.0),
+            std::f32::consts::FRAC_PI_2,
+        );
+        // Rotating by 90° moves the platform to the other side of its origin on the x axis.
+        assert_eq!(placed.heights_at(105.0, 105.0).next(), None);
+        assert!(placed.heights_at(95.0, 105.0).next().is_some());
+    }
+
+    #[test]
+    pub fn test_walls() {
+        let placed = PlacedObject::new(&platform(), Vector3::new(100.0, 0.0, 100.0), 0.0);
+        assert!(placed.blocks(Vector2::new(95.0, 105.0), Vector2::new(105.0, 105.0)));
+        assert!(!placed.blocks(Vector2::new(105.0, 105.0), Vector2::new(109.0, 105.0)));
+        assert!(!placed.blocks(Vector2::new(95.0, 95.0), Vector2::new(95.0, 115.0)));
+    }
+
+    #[test]
+    pub fn test_invalid_indices() {
+        let collision = ObjectCollision::new(vec![Vector3::new(0.0, 0.0, 0.0)], vec![[0, 1, 2]], vec![[0, 1]]);
+        let placed = PlacedObject::new(&collision, Vector3::new(0.0, 0.0, 0.0), 0.0);
+        assert_eq!(placed.heights_at(0.0, 0.0).count(), 0);
+    }
+}
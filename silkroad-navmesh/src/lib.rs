@@ -7,11 +7,13 @@ use std::sync::Arc;
 use std::{fs, io};
 
 pub mod builder;
+pub mod collision;
 pub mod heightmap;
 pub mod map_info_ext;
 pub mod navmesh;
 pub mod object;
 pub mod object_info;
+pub mod pathfinding;
 pub mod region;
 
 pub trait FileLoader {
@@ -39,7 +41,6 @@ const MAP_INFO_FILE: &str = "navmesh/mapinfo.mfo";
 
 pub struct GlobalNavmesh {
     loaded_meshes: HashMap<Region, Arc<NavmeshContainer>>,
-    #[allow(unused)] // We will eventually use objects for navigation
     loaded_objects: HashMap<u32, Arc<Object>>,
 }
 
@@ -51,4 +52,8 @@ impl GlobalNavmesh {
     pub fn mesh_ref_for(&self, region: Region) -> Option<&NavmeshContainer> {
         self.loaded_meshes.get(&region).map(|arc| arc.as_ref())
     }
+
+    pub fn object(&self, id: u32) -> Option<Arc<Object>> {
+        self.loaded_objects.get(&id).cloned()
+    }
 }
@@ -1,14 +1,52 @@
+use crate::collision::{ObjectCollision, PlacedObject};
 use crate::heightmap::Heightmap;
 use crate::Region;
+use cgmath::{Vector2, Vector3};
 use sr_formats::jmxvnvm::JmxNvm;
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
 
 const MESH_SIZE: usize = 96;
 const MESH_TILE_SIZE: usize = 20;
+const NO_CELL: u16 = 0xFFFF;
+
+/// A walkable, rectangular area inside a region.
+#[derive(Copy, Clone, Debug)]
+pub struct NavmeshCell {
+    pub min: Vector2<f32>,
+    pub max: Vector2<f32>,
+}
+
+impl NavmeshCell {
+    pub fn contains(&self, x: f32, z: f32) -> bool {
+        x >= self.min.x && x <= self.max.x && z >= self.min.y && z <= self.max.y
+    }
+}
+
+/// A connection from one cell into a neighbouring cell, which may also be inside a different region. The edge can be
+/// crossed anywhere along the line from `start` to `end`, given in the local coordinates of the region the edge
+/// originates from.
+#[derive(Copy, Clone, Debug)]
+pub struct CellEdge {
+    pub region: Region,
+    pub cell: u16,
+    pub start: Vector2<f32>,
+    pub end: Vector2<f32>,
+}
+
+impl CellEdge {
+    pub fn midpoint(&self) -> Vector2<f32> {
+        (self.start + self.end) / 2.0
+    }
+}
 
 pub struct NavmeshContainer {
     region: Region,
-    mesh: JmxNvm,
+    height_map: Box<[f32]>,
+    cells: Vec<NavmeshCell>,
+    edges: Vec<Vec<CellEdge>>,
+    objects: Vec<PlacedObject>,
 }
 
 impl Debug for NavmeshContainer {
@@ -18,11 +56,124 @@ impl Debug for NavmeshContainer {
 }
 
 impl NavmeshContainer {
-    pub fn new(region: Region, jmx: JmxNvm) -> Self {
-        Self { region, mesh: jmx }
+    pub fn new(region: Region, jmx: JmxNvm, collisions: &HashMap<u32, Arc<ObjectCollision>>) -> Self {
+        let cells = jmx
+            .cells
+            .iter()
+            .map(|cell| NavmeshCell {
+                min: Vector2::new(cell.rect.min.x, cell.rect.min.y),
+                max: Vector2::new(cell.rect.max.x, cell.rect.max.y),
+            })
+            .collect();
+        let mut container = Self::from_parts(region, jmx.height_map, cells);
+
+        // Links between cells are only stored once, but can be walked in both directions.
+        for link in jmx.cell_links.iter() {
+            let start = Vector2::new(link.line.start.x, link.line.start.y);
+            let end = Vector2::new(link.line.end.x, link.line.end.y);
+            container.add_edge(link.source_cell, region, link.destination_cell, start, end);
+            container.add_edge(link.destination_cell, region, link.source_cell, start, end);
+        }
+
+        // Links into other regions are stored in both regions' meshes, so we only need to care about our side.
+        for link in jmx.region_links.iter() {
+            let start = Vector2::new(link.line.start.x, link.line.start.y);
+            let end = Vector2::new(link.line.end.x, link.line.end.y);
+            container.add_edge(
+                link.source_cell,
+                link.destination_region.into(),
+                link.destination_cell,
+                start,
+                end,
+            );
+        }
+
+        for entry in jmx.entries.iter() {
+            if let Some(collision) = collisions.get(&entry.id) {
+                let position = Vector3::new(entry.position.x, entry.position.y, entry.position.z);
+                container.place(PlacedObject::new(collision, position, entry.yaw));
+            }
+        }
+
+        container
+    }
+
+    pub(crate) fn from_parts(region: Region, height_map: Box<[f32]>, cells: Vec<NavmeshCell>) -> Self {
+        let edges = vec![Vec::new(); cells.len()];
+        Self {
+            region,
+            height_map,
+            cells,
+            edges,
+            objects: Vec::new(),
+        }
+    }
+
+    pub(crate) fn place(&mut self, object: PlacedObject) {
+        self.objects.push(object);
+    }
+
+    /// Adds a one-directional connection from the `from` cell of this region into the `to` cell of the given region.
+    /// Edges that lead nowhere, i.e. those that mark the border of the mesh, are ignored.
+    pub(crate) fn add_edge(&mut self, from: u16, region: Region, to: u16, start: Vector2<f32>, end: Vector2<f32>) {
+        if from == NO_CELL || to == NO_CELL {
+            return;
+        }
+
+        if let Some(edges) = self.edges.get_mut(from as usize) {
+            edges.push(CellEdge {
+                region,
+                cell: to,
+                start,
+                end,
+            });
+        }
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
     }
 
     pub fn heightmap(&self) -> Heightmap {
-        Heightmap::new(&self.mesh.height_map, MESH_SIZE, MESH_TILE_SIZE)
+        Heightmap::new(&self.height_map, MESH_SIZE, MESH_TILE_SIZE)
+    }
+
+    /// Provides the height of the ground at the given location, which is either the terrain or the surface of an
+    /// object, like a bridge or the floor of a building. If there are multiple surfaces at this location, we pick the
+    /// one closest to the `reference` height, or the highest one if there is no reference.
+    pub fn height_at(&self, x: f32, z: f32, reference: Option<f32>) -> Option<f32> {
+        self.heightmap()
+            .height_at_position(x, z)
+            .into_iter()
+            .chain(self.objects.iter().flat_map(|object| object.heights_at(x, z)))
+            .min_by(|first, second| match reference {
+                Some(reference) => (first - reference).abs().total_cmp(&(second - reference).abs()),
+                None => second.total_cmp(first),
+            })
+    }
+
+    /// Checks if walking in a straight line between the two local locations would walk through the wall of an object.
+    pub fn is_blocked(&self, from: Vector2<f32>, to: Vector2<f32>) -> bool {
+        self.objects.iter().any(|object| object.blocks(from, to))
+    }
+
+    pub fn cell(&self, index: u16) -> Option<&NavmeshCell> {
+        self.cells.get(index as usize)
+    }
+
+    /// Finds the cell containing the given local location.
+    pub fn cell_at(&self, x: f32, z: f32) -> Option<u16> {
+        self.cells
+            .iter()
+            .position(|cell| cell.contains(x, z))
+            .map(|index| index as u16)
+    }
+
+    /// Provides all connections leading out of the given cell.
+    pub fn edges_of(&self, cell: u16) -> &[CellEdge] {
+        self.edges
+            .get(cell as usize)
+            .map(|edges| edges.as_slice())
+            .unwrap_or(&[])
     }
 }
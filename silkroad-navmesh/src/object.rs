@@ -1,5 +1,7 @@
+use crate::collision::ObjectCollision;
 use crate::object_info::ObjectInfo;
 use crate::FileLoader;
+use cgmath::Vector3;
 use log::debug;
 use sr_formats::jmxvbms::JmxBMesh;
 use sr_formats::jmxvbsr::JmxRes;
@@ -93,6 +95,21 @@ impl Object {
         Ok(res)
     }
 
+    /// Loads the navigation mesh of the object, if it has one. Objects without a navigation mesh, like trees, are only
+    /// decoration and can be walked through.
+    pub fn collision(&self, loader: &dyn FileLoader) -> Option<ObjectCollision> {
+        let resource = match self {
+            Object::Compound { collision_resource, .. } => collision_resource.as_ref()?,
+            Object::Resource(resource) => resource,
+            Object::Mesh(mesh) => return collision_of_mesh(mesh),
+        };
+
+        let path = resource.collision_mesh_path.to_str().filter(|path| !path.is_empty())?;
+        let data = loader.load_file(path).ok()?;
+        let (_, mesh) = JmxBMesh::parse(&data).ok()?;
+        collision_of_mesh(&mesh)
+    }
+
     pub fn name(&self) -> &str {
         match &self {
             Object::Compound { header, .. } => header.name.borrow(),
@@ -102,6 +119,31 @@ impl Object {
     }
 }
 
+/// Edges with either of these flags cannot be crossed in at least one direction, which makes them walls.
+const EDGE_BLOCKED: u8 = 0x03;
+
+fn collision_of_mesh(mesh: &JmxBMesh) -> Option<ObjectCollision> {
+    let nav_mesh = mesh.nav_mesh.as_ref()?;
+    let vertices = nav_mesh
+        .vertices
+        .iter()
+        .map(|vertex| Vector3::new(vertex.position.x, vertex.position.y, vertex.position.z))
+        .collect();
+    let cells = nav_mesh
+        .cells
+        .iter()
+        .map(|cell| cell.vertices.map(usize::from))
+        .collect();
+    let blocking_edges = nav_mesh
+        .outline_edges
+        .iter()
+        .chain(nav_mesh.inline_edges.iter())
+        .filter(|edge| edge.flag & EDGE_BLOCKED != 0)
+        .map(|edge| edge.vertices.map(usize::from))
+        .collect();
+    Some(ObjectCollision::new(vertices, cells, blocking_edges))
+}
+
 pub struct ObjectLoader;
 
 const OBJECT_INFO_FILE: &str = "navmesh/object.ifo";
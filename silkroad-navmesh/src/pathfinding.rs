@@ -0,0 +1,351 @@
+use crate::GlobalNavmesh;
+use cgmath::{MetricSpace, Vector2};
+use silkroad_definitions::Region;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use thiserror::Error;
+
+const REGION_SIZE: f32 = 1920.0;
+/// The amount of cells we look at before giving up. Paths across this many cells are not something an entity should
+/// be walking in one go anyway.
+const MAX_VISITED_CELLS: usize = 4096;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum PathError {
+    #[error("No navmesh has been loaded for region {0}")]
+    UnknownRegion(Region),
+    #[error("The location is not inside any walkable cell")]
+    OutsideMesh,
+    #[error("There is no walkable path to the target")]
+    Unreachable,
+}
+
+/// A location inside a specific region, given in the local coordinates of that region.
+pub type NavmeshLocation = (Region, Vector2<f32>);
+
+type Node = (Region, u16);
+
+struct OpenNode {
+    node: Node,
+    estimate: f32,
+}
+
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl Eq for OpenNode {}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, such that the binary heap returns the lowest estimate first.
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+struct Visit {
+    cost: f32,
+    /// The point at which we entered the cell, in global coordinates.
+    entry: Vector2<f32>,
+    previous: Option<(Node, NavmeshLocation)>,
+}
+
+fn region_offset(region: Region) -> Vector2<f32> {
+    Vector2::new(region.x() as f32 * REGION_SIZE, region.y() as f32 * REGION_SIZE)
+}
+
+fn to_global(region: Region, local: Vector2<f32>) -> Vector2<f32> {
+    local + region_offset(region)
+}
+
+impl GlobalNavmesh {
+    fn cell_for(&self, location: NavmeshLocation) -> Result<u16, PathError> {
+        let mesh = self
+            .mesh_ref_for(location.0)
+            .ok_or(PathError::UnknownRegion(location.0))?;
+        mesh.cell_at(location.1.x, location.1.y).ok_or(PathError::OutsideMesh)
+    }
+
+    /// Finds a walkable path from `start` to `goal` through the cells of the navmesh, which may span multiple regions.
+    ///
+    /// The path is searched using A*, where walking from one cell to another is done through the middle of the edge
+    /// connecting them. The result contains all the points to walk through in order, ending with the goal itself, but
+    /// excluding the start.
+    pub fn find_path(&self, start: NavmeshLocation, goal: NavmeshLocation) -> Result<Vec<NavmeshLocation>, PathError> {
+        let start_cell = self.cell_for(start)?;
+        let goal_cell = self.cell_for(goal)?;
+        let start_node = (start.0, start_cell);
+        let goal_node = (goal.0, goal_cell);
+        if start_node == goal_node {
+            // We don't know how to walk around objects inside a single cell, so we can only walk there directly.
+            let blocked = self
+                .mesh_ref_for(start.0)
+                .is_some_and(|mesh| mesh.is_blocked(start.1, goal.1));
+            return if blocked {
+                Err(PathError::Unreachable)
+            } else {
+                Ok(vec![goal])
+            };
+        }
+
+        let goal_global = to_global(goal.0, goal.1);
+        let start_global = to_global(start.0, start.1);
+        let mut visits: HashMap<Node, Visit> = HashMap::new();
+        let mut open = BinaryHeap::new();
+        visits.insert(
+            start_node,
+            Visit {
+                cost: 0.0,
+                entry: start_global,
+                previous: None,
+            },
+        );
+        open.push(OpenNode {
+            node: start_node,
+            estimate: start_global.distance(goal_global),
+        });
+
+        let mut visited = 0;
+        while let Some(OpenNode { node, estimate }) = open.pop() {
+            if node == goal_node {
+                return Ok(Self::collect_path(&visits, goal_node, goal));
+            }
+
+            let (cost, entry) = {
+                let visit = &visits[&node];
+                (visit.cost, visit.entry)
+            };
+            if estimate > cost + entry.distance(goal_global) {
+                // We've already found a cheaper way into this cell after queueing this one.
+                continue;
+            }
+
+            visited += 1;
+            if visited > MAX_VISITED_CELLS {
+                break;
+            }
+
+            let Some(mesh) = self.mesh_ref_for(node.0) else {
+                continue;
+            };
+            for edge in mesh.edges_of(node.1) {
+                let next = (edge.region, edge.cell);
+                if self
+                    .mesh_ref_for(edge.region)
+                    .and_then(|mesh| mesh.cell(edge.cell))
+                    .is_none()
+                {
+                    continue;
+                }
+
+                if mesh.is_blocked(entry - region_offset(node.0), edge.midpoint()) {
+                    continue;
+                }
+
+                let crossing = to_global(node.0, edge.midpoint());
+                let next_cost = cost + entry.distance(crossing);
+                if visits.get(&next).is_some_and(|visit| visit.cost <= next_cost) {
+                    continue;
+                }
+
+                visits.insert(
+                    next,
+                    Visit {
+                        cost: next_cost,
+                        entry: crossing,
+                        previous: Some((node, (node.0, edge.midpoint()))),
+                    },
+                );
+                open.push(OpenNode {
+                    node: next,
+                    estimate: next_cost + crossing.distance(goal_global),
+                });
+            }
+        }
+
+        Err(PathError::Unreachable)
+    }
+
+    fn collect_path(visits: &HashMap<Node, Visit>, goal_node: Node, goal: NavmeshLocation) -> Vec<NavmeshLocation> {
+        let mut path = vec![goal];
+        let mut current = goal_node;
+        while let Some((previous, crossing)) = visits.get(&current).and_then(|visit| visit.previous) {
+            path.push(crossing);
+            current = previous;
+        }
+        path.reverse();
+        path
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::collision::{ObjectCollision, PlacedObject};
+    use crate::navmesh::{NavmeshCell, NavmeshContainer};
+    use cgmath::Vector3;
+    use std::sync::Arc;
+
+    fn cell(min_x: f32, min_z: f32, max_x: f32, max_z: f32) -> NavmeshCell {
+        NavmeshCell {
+            min: Vector2::new(min_x, min_z),
+            max: Vector2::new(max_x, max_z),
+        }
+    }
+
+    fn link(container: &mut NavmeshContainer, first: u16, second: u16, start: Vector2<f32>, end: Vector2<f32>) {
+        let region = container.region();
+        container.add_edge(first, region, second, start, end);
+        container.add_edge(second, region, first, start, end);
+    }
+
+    fn navmesh(meshes: Vec<NavmeshContainer>) -> GlobalNavmesh {
+        GlobalNavmesh {
+            loaded_meshes: meshes.into_iter().map(|mesh| (mesh.region(), Arc::new(mesh))).collect(),
+            loaded_objects: HashMap::new(),
+        }
+    }
+
+    /// Builds a region with a wall in the middle, which has to be walked around at the top:
+    /// ```text
+    /// +---+---+
+    /// | 2 | 3 |
+    /// +---+---+
+    /// | 0 | 1 |
+    /// +---#---+
+    /// ```
+    fn walled_region(region: Region, open: bool) -> NavmeshContainer {
+        let mut container = NavmeshContainer::from_parts(
+            region,
+            Box::new([]),
+            vec![
+                cell(0.0, 0.0, 960.0, 960.0),
+                cell(960.0, 0.0, 1920.0, 960.0),
+                cell(0.0, 960.0, 960.0, 1920.0),
+                cell(960.0, 960.0, 1920.0, 1920.0),
+            ],
+        );
+        link(
+            &mut container,
+            0,
+            2,
+            Vector2::new(0.0, 960.0),
+            Vector2::new(960.0, 960.0),
+        );
+        link(
+            &mut container,
+            1,
+            3,
+            Vector2::new(960.0, 960.0),
+            Vector2::new(1920.0, 960.0),
+        );
+        if open {
+            link(
+                &mut container,
+                2,
+                3,
+                Vector2::new(960.0, 960.0),
+                Vector2::new(960.0, 1920.0),
+            );
+        }
+        container
+    }
+
+    #[test]
+    pub fn test_same_cell() {
+        let region = Region::from_xy(10, 10);
+        let mesh = navmesh(vec![walled_region(region, true)]);
+        let goal = (region, Vector2::new(500.0, 500.0));
+        assert_eq!(
+            mesh.find_path((region, Vector2::new(100.0, 100.0)), goal),
+            Ok(vec![goal])
+        );
+    }
+
+    #[test]
+    pub fn test_walk_around_wall() {
+        let region = Region::from_xy(10, 10);
+        let mesh = navmesh(vec![walled_region(region, true)]);
+        let goal = (region, Vector2::new(1500.0, 500.0));
+        let path = mesh.find_path((region, Vector2::new(500.0, 500.0)), goal).unwrap();
+        assert_eq!(
+            path,
+            vec![
+                (region, Vector2::new(480.0, 960.0)),
+                (region, Vector2::new(960.0, 1440.0)),
+                (region, Vector2::new(1440.0, 960.0)),
+                goal,
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_unreachable() {
+        let region = Region::from_xy(10, 10);
+        let mesh = navmesh(vec![walled_region(region, false)]);
+        assert_eq!(
+            mesh.find_path(
+                (region, Vector2::new(500.0, 500.0)),
+                (region, Vector2::new(1500.0, 500.0))
+            ),
+            Err(PathError::Unreachable)
+        );
+        assert_eq!(
+            mesh.find_path(
+                (region, Vector2::new(500.0, 500.0)),
+                (Region::from_xy(11, 10), Vector2::new(0.0, 0.0))
+            ),
+            Err(PathError::UnknownRegion(Region::from_xy(11, 10)))
+        );
+    }
+
+    #[test]
+    pub fn test_blocked_by_object() {
+        let region = Region::from_xy(10, 10);
+        let mut container = walled_region(region, true);
+        let wall = ObjectCollision::new(
+            vec![Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 900.0)],
+            vec![],
+            vec![[0, 1]],
+        );
+        container.place(PlacedObject::new(&wall, Vector3::new(700.0, 0.0, 1000.0), 0.0));
+        let mesh = navmesh(vec![container]);
+        assert_eq!(
+            mesh.find_path(
+                (region, Vector2::new(500.0, 500.0)),
+                (region, Vector2::new(1500.0, 500.0))
+            ),
+            Err(PathError::Unreachable)
+        );
+        assert_eq!(
+            mesh.find_path(
+                (region, Vector2::new(500.0, 1100.0)),
+                (region, Vector2::new(900.0, 1100.0))
+            ),
+            Err(PathError::Unreachable)
+        );
+    }
+
+    #[test]
+    pub fn test_cross_region() {
+        let left = Region::from_xy(10, 10);
+        let right = Region::from_xy(11, 10);
+        let mut left_mesh = NavmeshContainer::from_parts(left, Box::new([]), vec![cell(0.0, 0.0, 1920.0, 1920.0)]);
+        let mut right_mesh = NavmeshContainer::from_parts(right, Box::new([]), vec![cell(0.0, 0.0, 1920.0, 1920.0)]);
+        left_mesh.add_edge(0, right, 0, Vector2::new(1920.0, 0.0), Vector2::new(1920.0, 1920.0));
+        right_mesh.add_edge(0, left, 0, Vector2::new(0.0, 0.0), Vector2::new(0.0, 1920.0));
+        let mesh = navmesh(vec![left_mesh, right_mesh]);
+
+        let goal = (right, Vector2::new(100.0, 100.0));
+        let path = mesh.find_path((left, Vector2::new(100.0, 100.0)), goal).unwrap();
+        assert_eq!(path, vec![(left, Vector2::new(1920.0, 960.0)), goal]);
+    }
+}
@@ -0,0 +1,76 @@
+use crate::inventory::InventoryItemData;
+use silkroad_serde::*;
+
+// ??? TODO
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, ByteSize, Debug)]
+#[silkroad(size = 2)]
+pub enum AlchemyErrorCode {
+    #[silkroad(value = 0x5401)]
+    InvalidTarget,
+    #[silkroad(value = 0x5402)]
+    InvalidMaterial,
+    #[silkroad(value = 0x5403)]
+    MaxLevelReached,
+    #[silkroad(value = 0x5404)]
+    Busy,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Deserialize, ByteSize, Debug)]
+pub enum AlchemyKind {
+    #[silkroad(value = 2)]
+    Elixir,
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub enum AlchemyAction {
+    /// Fuses the item in the first slot with the materials in the remaining slots.
+    #[silkroad(value = 1)]
+    Fuse { kind: AlchemyKind, slots: Vec<u8> },
+    #[silkroad(value = 2)]
+    Cancel { kind: AlchemyKind },
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct AlchemyRequest {
+    pub action: AlchemyAction,
+}
+
+/// What remains of the item after fusing it.
+#[derive(Clone, Serialize, ByteSize)]
+pub enum ReinforcedItem {
+    #[silkroad(value = 0)]
+    Destroyed,
+    #[silkroad(value = 1)]
+    Remaining(InventoryItemData),
+}
+
+// ??? TODO
+#[derive(Clone, Serialize, ByteSize)]
+pub enum AlchemyOutcome {
+    #[silkroad(value = 1)]
+    Fused {
+        succeeded: bool,
+        slot: u8,
+        item: ReinforcedItem,
+    },
+    #[silkroad(value = 2)]
+    Cancelled,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum AlchemyResponse {
+    #[silkroad(value = 1)]
+    Success(AlchemyOutcome),
+    #[silkroad(value = 2)]
+    Error(AlchemyErrorCode),
+}
+
+impl AlchemyResponse {
+    pub fn fused(succeeded: bool, slot: u8, item: ReinforcedItem) -> Self {
+        AlchemyResponse::Success(AlchemyOutcome::Fused { succeeded, slot, item })
+    }
+
+    pub fn cancelled() -> Self {
+        AlchemyResponse::Success(AlchemyOutcome::Cancelled)
+    }
+}
@@ -115,6 +115,8 @@ pub enum SkillPartDamage {
     KillingBlow(DamageValue),
     #[silkroad(value = 0x08)]
     Abort,
+    #[silkroad(value = 0x02)]
+    Blocked,
 }
 
 #[derive(Serialize, ByteSize, Copy, Clone)]
@@ -189,6 +191,37 @@ impl PerformActionUpdate {
     }
 }
 
+/// Informs about a buff becoming active on the target entity. The token identifies this specific instance of the
+/// buff, for example to remove it again later on.
+#[derive(Serialize, ByteSize, Copy, Clone)]
+pub struct BuffAdded {
+    pub target: u32,
+    pub skill_id: u32,
+    pub token: u32,
+}
+
+impl BuffAdded {
+    pub fn new(target: u32, skill_id: u32, token: u32) -> Self {
+        BuffAdded {
+            target,
+            skill_id,
+            token,
+        }
+    }
+}
+
+#[derive(Serialize, ByteSize, Copy, Clone)]
+pub struct BuffRemoved {
+    pub unknown: u8, // always 1
+    pub token: u32,
+}
+
+impl BuffRemoved {
+    pub fn new(token: u32) -> Self {
+        BuffRemoved { unknown: 1, token }
+    }
+}
+
 #[derive(Serialize, ByteSize, Copy, Clone)]
 pub struct ReceiveExperience {
     /// Unique ID of the entity that provided the experience
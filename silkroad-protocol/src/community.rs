@@ -33,6 +33,11 @@ impl GuildInformation {
             siege_unknown: 0,
         }
     }
+
+    /// The information sent for entities that are not part of any guild.
+    pub fn none() -> Self {
+        Self::new(String::new(), 0, String::new(), 0, 0, 0, 0)
+    }
 }
 
 #[derive(Clone, Serialize, ByteSize)]
@@ -98,3 +103,96 @@ pub struct CreateFriendGroup {
 pub struct DeleteFriend {
     pub friend_character_id: u32,
 }
+
+#[derive(Clone, Copy, Serialize, ByteSize)]
+#[silkroad(size = 2)]
+pub enum FriendErrorCode {
+    #[silkroad(value = 0x6401)]
+    InvalidTarget,
+    #[silkroad(value = 0x6402)]
+    AlreadyFriends,
+    #[silkroad(value = 0x6403)]
+    Declined,
+    #[silkroad(value = 0x6404)]
+    TargetBusy,
+    #[silkroad(value = 0x6405)]
+    InvalidGroup,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum AddFriendResponse {
+    #[silkroad(value = 1)]
+    Success(FriendListEntry),
+    #[silkroad(value = 2)]
+    Error(FriendErrorCode),
+}
+
+/// Asks the receiving player if they want to accept the friend request of the requesting player.
+#[derive(Clone, Serialize, ByteSize)]
+pub struct FriendRequest {
+    pub requester: u32,
+    pub name: String,
+}
+
+impl FriendRequest {
+    pub fn new(requester: u32, name: String) -> Self {
+        FriendRequest { requester, name }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct FriendRequestAnswer {
+    pub requester: u32,
+    pub accepted: bool,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum DeleteFriendResponse {
+    #[silkroad(value = 1)]
+    Success { friend_character_id: u32 },
+    #[silkroad(value = 2)]
+    Error(FriendErrorCode),
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum CreateFriendGroupResponse {
+    #[silkroad(value = 1)]
+    Success(FriendListGroup),
+    #[silkroad(value = 2)]
+    Error(FriendErrorCode),
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct DeleteFriendGroup {
+    pub id: u16,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum DeleteFriendGroupResponse {
+    #[silkroad(value = 1)]
+    Success { id: u16 },
+    #[silkroad(value = 2)]
+    Error(FriendErrorCode),
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct MoveFriendToGroup {
+    pub friend_character_id: u32,
+    pub group_id: u16,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum MoveFriendToGroupResponse {
+    #[silkroad(value = 1)]
+    Success { friend_character_id: u32, group_id: u16 },
+    #[silkroad(value = 2)]
+    Error(FriendErrorCode),
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum FriendStatusUpdate {
+    #[silkroad(value = 9)]
+    Online { friend_character_id: u32 },
+    #[silkroad(value = 10)]
+    Offline { friend_character_id: u32 },
+}
@@ -0,0 +1,165 @@
+use crate::inventory::InventoryItemData;
+use silkroad_serde::*;
+
+// ??? TODO
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, ByteSize, Debug)]
+#[silkroad(size = 2)]
+pub enum ExchangeErrorCode {
+    #[silkroad(value = 0x3C11)]
+    InvalidTarget,
+    #[silkroad(value = 0x3C12)]
+    TargetBusy,
+    #[silkroad(value = 0x3C13)]
+    Declined,
+    #[silkroad(value = 0x3C14)]
+    TooFarAway,
+    #[silkroad(value = 0x3C15)]
+    NotExchanging,
+    #[silkroad(value = 0x3C16)]
+    NotAllowed,
+    #[silkroad(value = 0x3C17)]
+    InventoryFull,
+    #[silkroad(value = 0x3C18)]
+    Failed,
+}
+
+#[derive(Clone, Copy, Serialize, ByteSize)]
+pub enum ExchangeResult {
+    #[silkroad(value = 1)]
+    Success,
+    #[silkroad(value = 2)]
+    Error(ExchangeErrorCode),
+}
+
+impl ExchangeResult {
+    pub fn error(code: ExchangeErrorCode) -> Self {
+        ExchangeResult::Error(code)
+    }
+}
+
+impl From<Result<(), ExchangeErrorCode>> for ExchangeResult {
+    fn from(result: Result<(), ExchangeErrorCode>) -> Self {
+        match result {
+            Ok(()) => ExchangeResult::Success,
+            Err(code) => ExchangeResult::Error(code),
+        }
+    }
+}
+
+/// Asks the target player to open an exchange with the sender.
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct StartExchange {
+    pub target: u32,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct StartExchangeResponse {
+    pub result: ExchangeResult,
+}
+
+impl StartExchangeResponse {
+    pub fn new(result: ExchangeResult) -> Self {
+        StartExchangeResponse { result }
+    }
+}
+
+/// Locks the offer of the sender, such that it can no longer be changed.
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct ConfirmExchange;
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct ConfirmExchangeResponse {
+    pub result: ExchangeResult,
+}
+
+impl ConfirmExchangeResponse {
+    pub fn new(result: ExchangeResult) -> Self {
+        ConfirmExchangeResponse { result }
+    }
+}
+
+/// Agrees to the exchange once both sides have confirmed their offers.
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct ApproveExchange;
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct ApproveExchangeResponse {
+    pub result: ExchangeResult,
+}
+
+impl ApproveExchangeResponse {
+    pub fn new(result: ExchangeResult) -> Self {
+        ApproveExchangeResponse { result }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct CancelExchange;
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct CancelExchangeResponse {
+    pub result: ExchangeResult,
+}
+
+impl CancelExchangeResponse {
+    pub fn new(result: ExchangeResult) -> Self {
+        CancelExchangeResponse { result }
+    }
+}
+
+/// Opens the exchange window for both players.
+#[derive(Clone, Serialize, ByteSize)]
+pub struct ExchangeStarted {
+    pub partner: u32,
+}
+
+impl ExchangeStarted {
+    pub fn new(partner: u32) -> Self {
+        ExchangeStarted { partner }
+    }
+}
+
+/// Informs the player that their partner has confirmed their offer.
+#[derive(Clone, Serialize, ByteSize)]
+pub struct ExchangeConfirmed;
+
+/// Informs the player that their partner has approved the exchange.
+#[derive(Clone, Serialize, ByteSize)]
+pub struct ExchangeApproved;
+
+/// Closes the exchange window after the items have been exchanged, providing the slots the offered items have been
+/// taken out of as well as the items that have been received.
+// ??? TODO
+#[derive(Clone, Serialize, ByteSize)]
+pub struct ExchangeCompleted {
+    pub removed_slots: Vec<u8>,
+    pub received_items: Vec<InventoryItemData>,
+}
+
+impl ExchangeCompleted {
+    pub fn new(removed_slots: Vec<u8>, received_items: Vec<InventoryItemData>) -> Self {
+        ExchangeCompleted {
+            removed_slots,
+            received_items,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct ExchangeCancelled;
+
+/// The items and gold currently offered by one of the players, where the slot of each item is its position inside
+/// the exchange window.
+// ??? TODO
+#[derive(Clone, Serialize, ByteSize)]
+pub struct ExchangeOfferUpdate {
+    pub unique_id: u32,
+    pub gold: u64,
+    pub items: Vec<InventoryItemData>,
+}
+
+impl ExchangeOfferUpdate {
+    pub fn new(unique_id: u32, gold: u64, items: Vec<InventoryItemData>) -> Self {
+        ExchangeOfferUpdate { unique_id, gold, items }
+    }
+}
@@ -61,6 +61,12 @@ impl GmResponse {
         }
     }
 
+    pub fn error() -> Self {
+        GmResponse {
+            result: GmResponseResult::Error,
+        }
+    }
+
     pub fn print_entity_ids(player_id: u32, mob_id: u32, item_id: u32) -> Self {
         GmResponse {
             result: GmResponseResult::Success(GmSuccessResult::EntityIds {
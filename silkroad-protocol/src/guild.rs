@@ -0,0 +1,282 @@
+use crate::community::GuildInformation;
+use silkroad_serde::*;
+
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize, ByteSize, Debug)]
+pub enum GuildRank {
+    #[silkroad(value = 1)]
+    Master,
+    #[silkroad(value = 2)]
+    Officer,
+    #[silkroad(value = 3)]
+    Member,
+}
+
+impl GuildRank {
+    /// Checks if a member of this rank may invite others into the guild, kick members or change the notice.
+    pub fn can_manage(&self) -> bool {
+        matches!(self, GuildRank::Master | GuildRank::Officer)
+    }
+
+    /// Checks if a member of this rank stands above a member of the other rank.
+    pub fn outranks(&self, other: GuildRank) -> bool {
+        matches!(
+            (self, other),
+            (GuildRank::Master, GuildRank::Officer | GuildRank::Member) | (GuildRank::Officer, GuildRank::Member)
+        )
+    }
+}
+
+#[derive(Clone, Copy, Serialize, ByteSize)]
+#[silkroad(size = 2)]
+pub enum GuildErrorCode {
+    #[silkroad(value = 0x4C01)]
+    InvalidTarget,
+    #[silkroad(value = 0x4C02)]
+    AlreadyInGuild,
+    #[silkroad(value = 0x4C03)]
+    NotInGuild,
+    #[silkroad(value = 0x4C04)]
+    NotAllowed,
+    #[silkroad(value = 0x4C05)]
+    GuildFull,
+    #[silkroad(value = 0x4C06)]
+    NameTaken,
+    #[silkroad(value = 0x4C07)]
+    InvalidName,
+    #[silkroad(value = 0x4C08)]
+    NotEnoughGold,
+    #[silkroad(value = 0x4C09)]
+    LevelTooLow,
+    #[silkroad(value = 0x4C0A)]
+    TargetBusy,
+    #[silkroad(value = 0x4C0B)]
+    Declined,
+    #[silkroad(value = 0x4C0C)]
+    MasterCannotLeave,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum GuildResult {
+    #[silkroad(value = 1)]
+    Success,
+    #[silkroad(value = 2)]
+    Error(GuildErrorCode),
+}
+
+impl GuildResult {
+    pub fn error(code: GuildErrorCode) -> Self {
+        GuildResult::Error(code)
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct CreateGuild {
+    pub npc: u32,
+    pub name: String,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct CreateGuildResponse {
+    pub result: GuildResult,
+}
+
+impl CreateGuildResponse {
+    pub fn new(result: GuildResult) -> Self {
+        CreateGuildResponse { result }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct DisbandGuild;
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct DisbandGuildResponse {
+    pub result: GuildResult,
+}
+
+impl DisbandGuildResponse {
+    pub fn new(result: GuildResult) -> Self {
+        DisbandGuildResponse { result }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct InviteToGuild {
+    pub target: u32,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct InviteToGuildResponse {
+    pub result: GuildResult,
+}
+
+impl InviteToGuildResponse {
+    pub fn new(result: GuildResult) -> Self {
+        InviteToGuildResponse { result }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct LeaveGuild;
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct LeaveGuildResponse {
+    pub result: GuildResult,
+}
+
+impl LeaveGuildResponse {
+    pub fn new(result: GuildResult) -> Self {
+        LeaveGuildResponse { result }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct KickFromGuild {
+    pub name: String,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct KickFromGuildResponse {
+    pub result: GuildResult,
+}
+
+impl KickFromGuildResponse {
+    pub fn new(result: GuildResult) -> Self {
+        KickFromGuildResponse { result }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct UpdateGuildNotice {
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct UpdateGuildNoticeResponse {
+    pub result: GuildResult,
+}
+
+impl UpdateGuildNoticeResponse {
+    pub fn new(result: GuildResult) -> Self {
+        UpdateGuildNoticeResponse { result }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct SetGuildMemberRank {
+    pub character_id: u32,
+    pub rank: GuildRank,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct SetGuildMemberRankResponse {
+    pub result: GuildResult,
+}
+
+impl SetGuildMemberRankResponse {
+    pub fn new(result: GuildResult) -> Self {
+        SetGuildMemberRankResponse { result }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct GuildMemberInfo {
+    pub character_id: u32,
+    pub name: String,
+    pub rank: GuildRank,
+    pub level: u8,
+    pub ref_id: u32,
+    pub online: bool,
+}
+
+impl GuildMemberInfo {
+    pub fn new(character_id: u32, name: String, rank: GuildRank, level: u8, ref_id: u32, online: bool) -> Self {
+        GuildMemberInfo {
+            character_id,
+            name,
+            rank,
+            level,
+            ref_id,
+            online,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct GuildInfo {
+    pub id: u32,
+    pub name: String,
+    pub level: u8,
+    pub notice_title: String,
+    pub notice_body: String,
+    pub members: Vec<GuildMemberInfo>,
+}
+
+impl GuildInfo {
+    pub fn new(
+        id: u32,
+        name: String,
+        level: u8,
+        notice_title: String,
+        notice_body: String,
+        members: Vec<GuildMemberInfo>,
+    ) -> Self {
+        GuildInfo {
+            id,
+            name,
+            level,
+            notice_title,
+            notice_body,
+            members,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, ByteSize)]
+pub enum GuildLeaveReason {
+    #[silkroad(value = 1)]
+    Left,
+    #[silkroad(value = 2)]
+    Kicked,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum GuildUpdate {
+    #[silkroad(value = 1)]
+    Disbanded,
+    #[silkroad(value = 2)]
+    MemberJoined(GuildMemberInfo),
+    #[silkroad(value = 3)]
+    MemberLeft {
+        character_id: u32,
+        reason: GuildLeaveReason,
+    },
+    #[silkroad(value = 4)]
+    RankChanged { character_id: u32, rank: GuildRank },
+    #[silkroad(value = 5)]
+    MemberOnline { character_id: u32 },
+    #[silkroad(value = 6)]
+    MemberOffline { character_id: u32 },
+    #[silkroad(value = 7)]
+    NoticeChanged { title: String, body: String },
+}
+
+impl GuildUpdate {
+    pub fn member_left(character_id: u32, reason: GuildLeaveReason) -> Self {
+        GuildUpdate::MemberLeft { character_id, reason }
+    }
+}
+
+/// Informs players in the surrounding area that the guild of the given entity has changed.
+#[derive(Clone, Serialize, ByteSize)]
+pub struct GuildEntityUpdate {
+    pub unique_id: u32,
+    pub guild: GuildInformation,
+}
+
+impl GuildEntityUpdate {
+    pub fn new(unique_id: u32, guild: GuildInformation) -> Self {
+        GuildEntityUpdate { unique_id, guild }
+    }
+}
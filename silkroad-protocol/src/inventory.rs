@@ -10,6 +10,44 @@ pub enum InventoryOperationRequest {
     PickupItem { unique_id: u32 },
     #[silkroad(value = 0x07)]
     DropItem { slot: u8 },
+    #[silkroad(value = 0x08)]
+    BuyItem { tab: u8, slot: u8, amount: u16, npc: u32 },
+    #[silkroad(value = 0x09)]
+    SellItem { slot: u8, amount: u16, npc: u32 },
+    #[silkroad(value = 0x22)]
+    BuyBackItem { npc: u32, slot: u8, amount: u16 },
+    #[silkroad(value = 0x01)]
+    MoveInStorage {
+        source: u8,
+        target: u8,
+        amount: u16,
+        npc: u32,
+    },
+    #[silkroad(value = 0x02)]
+    DepositItem {
+        inventory_slot: u8,
+        storage_slot: u8,
+        npc: u32,
+    },
+    #[silkroad(value = 0x03)]
+    WithdrawItem {
+        storage_slot: u8,
+        inventory_slot: u8,
+        npc: u32,
+    },
+    // ??? TODO
+    #[silkroad(value = 0x0C)]
+    DepositGold { amount: u64, npc: u32 },
+    // ??? TODO
+    #[silkroad(value = 0x0D)]
+    WithdrawGold { amount: u64, npc: u32 },
+    #[silkroad(value = 0x04)]
+    AddToExchange { inventory_slot: u8 },
+    #[silkroad(value = 0x05)]
+    RemoveFromExchange { exchange_slot: u8 },
+    // ??? TODO
+    #[silkroad(value = 0x13)]
+    SetExchangeGold { amount: u64 },
 }
 
 impl InventoryOperationRequest {
@@ -108,6 +146,41 @@ pub enum InventoryOperationResponseData {
         unknown: u8,
         data: ItemPickupData,
     },
+    #[silkroad(value = 0x08)]
+    BuyItem {
+        tab: u8,
+        slot: u8,
+        target_slots: Vec<u8>,
+        amount: u16,
+    },
+    #[silkroad(value = 0x09)]
+    SellItem {
+        slot: u8,
+        amount: u16,
+        npc: u32,
+        buy_back_slot: u8,
+    },
+    #[silkroad(value = 0x22)]
+    BuyBackItem { slot: u8, buy_back_slot: u8, amount: u16 },
+    #[silkroad(value = 0x01)]
+    MoveInStorage { source: u8, target: u8, amount: u16 },
+    #[silkroad(value = 0x02)]
+    DepositItem { inventory_slot: u8, storage_slot: u8 },
+    #[silkroad(value = 0x03)]
+    WithdrawItem { storage_slot: u8, inventory_slot: u8 },
+    // ??? TODO
+    #[silkroad(value = 0x0C)]
+    DepositGold { amount: u64 },
+    // ??? TODO
+    #[silkroad(value = 0x0D)]
+    WithdrawGold { amount: u64 },
+    #[silkroad(value = 0x04)]
+    AddToExchange { inventory_slot: u8 },
+    #[silkroad(value = 0x05)]
+    RemoveFromExchange { exchange_slot: u8 },
+    // ??? TODO
+    #[silkroad(value = 0x13)]
+    SetExchangeGold { amount: u64 },
 }
 
 impl InventoryOperationResponseData {
@@ -119,6 +192,32 @@ impl InventoryOperationResponseData {
         InventoryOperationResponseData::PickupItem { slot, item }
     }
 
+    pub fn buy_item(tab: u8, slot: u8, target_slots: Vec<u8>, amount: u16) -> Self {
+        InventoryOperationResponseData::BuyItem {
+            tab,
+            slot,
+            target_slots,
+            amount,
+        }
+    }
+
+    pub fn sell_item(slot: u8, amount: u16, npc: u32, buy_back_slot: u8) -> Self {
+        InventoryOperationResponseData::SellItem {
+            slot,
+            amount,
+            npc,
+            buy_back_slot,
+        }
+    }
+
+    pub fn buy_back_item(slot: u8, buy_back_slot: u8, amount: u16) -> Self {
+        InventoryOperationResponseData::BuyBackItem {
+            slot,
+            buy_back_slot,
+            amount,
+        }
+    }
+
     pub fn move_item(source: u8, dest: u8, amount: u16) -> Self {
         InventoryOperationResponseData::UpdateSlots {
             source_slot: source,
@@ -132,6 +231,24 @@ impl InventoryOperationResponseData {
 #[derive(Clone, Eq, PartialEq, Copy, Serialize, ByteSize)]
 #[silkroad(size = 2)]
 pub enum ConsignmentErrorCode {
+    // ??? TODO
+    #[silkroad(value = 0x7001)]
+    Failed,
+    // ??? TODO
+    #[silkroad(value = 0x7002)]
+    InvalidItem,
+    // ??? TODO
+    #[silkroad(value = 0x7003)]
+    TooManyListings,
+    // ??? TODO
+    #[silkroad(value = 0x7004)]
+    ListingUnavailable,
+    // ??? TODO
+    #[silkroad(value = 0x7005)]
+    InventoryFull,
+    // ??? TODO
+    #[silkroad(value = 0x7006)]
+    Busy,
     #[silkroad(value = 0x700D)]
     NotEnoughGold,
 }
@@ -339,10 +456,20 @@ impl CharacterSpawnItemData {
     }
 }
 
+#[derive(Clone, Eq, PartialEq, Copy, Serialize, ByteSize)]
+pub enum ConsignmentStatus {
+    #[silkroad(value = 0)]
+    OnSale,
+    #[silkroad(value = 1)]
+    Sold,
+    #[silkroad(value = 2)]
+    Expired,
+}
+
 #[derive(Clone, Serialize, ByteSize)]
 pub struct ConsignmentItem {
     pub personal_id: u32,
-    pub status: u8,
+    pub status: ConsignmentStatus,
     pub ref_item_id: u32,
     pub sell_count: u32,
     pub price: u64,
@@ -354,7 +481,7 @@ pub struct ConsignmentItem {
 impl ConsignmentItem {
     pub fn new(
         personal_id: u32,
-        status: u8,
+        status: ConsignmentStatus,
         ref_item_id: u32,
         sell_count: u32,
         price: u64,
@@ -375,9 +502,40 @@ impl ConsignmentItem {
     }
 }
 
-#[derive(Clone, Deserialize, ByteSize)]
+#[derive(Clone, Copy, Deserialize, ByteSize)]
 pub struct ConsignmentList;
 
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct ConsignmentRegister {
+    pub slot: u8,
+    pub amount: u16,
+    pub price: u64,
+}
+
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct ConsignmentAbort {
+    pub personal_id: u32,
+}
+
+/// Collects the gold of all sold items and takes back all items which did not sell in time.
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct ConsignmentSettle;
+
+/// Searches the items currently on sale. A value of `0` for any of the filters means it's not restricted.
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct ConsignmentSearch {
+    pub category: u8,
+    pub sub_category: u8,
+    pub min_level: u8,
+    pub max_level: u8,
+    pub page: u16,
+}
+
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct ConsignmentBuy {
+    pub listing_id: u32,
+}
+
 #[derive(Clone, Serialize, ByteSize)]
 pub struct ConsignmentResponse {
     pub result: ConsignmentResult,
@@ -395,11 +553,175 @@ impl ConsignmentResponse {
     }
 }
 
+#[derive(Clone, Serialize, ByteSize)]
+pub struct ConsignmentRegisterResponse {
+    pub result: ConsignmentResult,
+}
+
+impl ConsignmentRegisterResponse {
+    pub fn new(result: ConsignmentResult) -> Self {
+        ConsignmentRegisterResponse { result }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct ConsignmentAbortResponse {
+    pub result: ConsignmentResult,
+}
+
+impl ConsignmentAbortResponse {
+    pub fn new(result: ConsignmentResult) -> Self {
+        ConsignmentAbortResponse { result }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct ConsignmentSettleResponse {
+    pub result: ConsignmentResult,
+}
+
+impl ConsignmentSettleResponse {
+    pub fn new(result: ConsignmentResult) -> Self {
+        ConsignmentSettleResponse { result }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct ConsignmentListing {
+    pub listing_id: u32,
+    pub seller: String,
+    pub ref_item_id: u32,
+    pub upgrade_level: u8,
+    pub amount: u16,
+    pub price: u64,
+    pub end_date: u32,
+}
+
+impl ConsignmentListing {
+    pub fn new(
+        listing_id: u32,
+        seller: String,
+        ref_item_id: u32,
+        upgrade_level: u8,
+        amount: u16,
+        price: u64,
+        end_date: u32,
+    ) -> Self {
+        ConsignmentListing {
+            listing_id,
+            seller,
+            ref_item_id,
+            upgrade_level,
+            amount,
+            price,
+            end_date,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum ConsignmentSearchResponse {
+    #[silkroad(value = 1)]
+    Success {
+        page: u16,
+        total_pages: u16,
+        listings: Vec<ConsignmentListing>,
+    },
+    #[silkroad(value = 2)]
+    Error(ConsignmentErrorCode),
+}
+
+impl ConsignmentSearchResponse {
+    pub fn success(page: u16, total_pages: u16, listings: Vec<ConsignmentListing>) -> Self {
+        ConsignmentSearchResponse::Success {
+            page,
+            total_pages,
+            listings,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum ConsignmentBuyResponse {
+    #[silkroad(value = 1)]
+    Success { listing_id: u32 },
+    #[silkroad(value = 2)]
+    Error(ConsignmentErrorCode),
+}
+
 #[derive(Clone, Deserialize, ByteSize)]
 pub struct InventoryOperation {
     pub data: InventoryOperationRequest,
 }
 
+/// Uses the consumable in the given inventory slot. The client includes the type id of the item it thinks is in that
+/// slot, which may be followed by additional data depending on the kind of item.
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct UseItem {
+    pub slot: u8,
+    pub item_type: u16,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum UseItemResponse {
+    #[silkroad(value = 1)]
+    Success { slot: u8, remaining: u16, item_type: u16 },
+    #[silkroad(value = 2)]
+    Error(InventoryOperationError),
+}
+
+impl UseItemResponse {
+    pub fn success(slot: u8, remaining: u16, item_type: u16) -> Self {
+        UseItemResponse::Success {
+            slot,
+            remaining,
+            item_type,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct OpenStorage {
+    pub npc: u32,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum OpenStorageResponse {
+    #[silkroad(value = 1)]
+    Success,
+    #[silkroad(value = 2)]
+    Error(InventoryOperationError),
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct StorageGold {
+    pub amount: u64,
+}
+
+impl StorageGold {
+    pub fn new(amount: u64) -> Self {
+        StorageGold { amount }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct StorageStart;
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct StorageData {
+    pub size: u8,
+    pub items: Vec<InventoryItemData>,
+}
+
+impl StorageData {
+    pub fn new(size: u8, items: Vec<InventoryItemData>) -> Self {
+        StorageData { size, items }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct StorageEnd;
+
 #[derive(Clone, Deserialize, ByteSize)]
 pub struct OpenItemMall;
 
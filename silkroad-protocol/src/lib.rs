@@ -1,31 +1,43 @@
+use crate::alchemy::*;
 use crate::auth::*;
 use crate::character::*;
 use crate::chat::*;
 use crate::combat::*;
 use crate::community::*;
 use crate::error::ProtocolError;
+use crate::exchange::*;
 use crate::general::*;
 use crate::gm::*;
+use crate::guild::*;
 use crate::login::*;
 use crate::movement::*;
+use crate::party::*;
+use crate::pvp::*;
 use crate::skill::*;
 use crate::spawn::*;
+use crate::stall::*;
 use crate::world::*;
 use bytes::Bytes;
 
+pub mod alchemy;
 pub mod auth;
 pub mod character;
 pub mod chat;
 pub mod combat;
 pub mod community;
 pub mod error;
+pub mod exchange;
 pub mod general;
 pub mod gm;
+pub mod guild;
 pub mod inventory;
 pub mod login;
 pub mod movement;
+pub mod party;
+pub mod pvp;
 pub mod skill;
 pub mod spawn;
+pub mod stall;
 pub mod world;
 
 use crate::inventory::*;
@@ -61,14 +73,30 @@ client_packets! {
     0x7001 => CharacterJoinRequest,
     0x34c6 => FinishLoading,
     0x750E => ConsignmentList,
+    0x7508 => ConsignmentRegister,
+    0x7509 => ConsignmentAbort,
+    0x750A => ConsignmentSettle,
+    0x750B => ConsignmentSearch,
+    0x750C => ConsignmentBuy,
     0x7021 => PlayerMovementRequest,
     0x7302 => AddFriend,
     0x7310 => CreateFriendGroup,
     0x7304 => DeleteFriend,
+    0x7303 => FriendRequestAnswer,
+    0x7311 => DeleteFriendGroup,
+    0x7312 => MoveFriendToGroup,
     0x7024 => Rotation,
+    0x704F => CharacterAction,
+    0x3053 => Resurrect,
+    0x34B6 => TeleportConfirm,
+    0x7059 => DesignateReturnPoint,
+    0x705A => TeleportRequest,
     0x7045 => TargetEntity,
     0x704B => UnTargetEntity,
+    0x7046 => TalkToNpc,
     0x7034 => InventoryOperation,
+    0x704C => UseItem,
+    0x703C => OpenStorage,
     0x7025 => ChatMessage,
     0x6100 => PatchRequest,
     0x610A => LoginRequest,
@@ -89,7 +117,31 @@ client_packets! {
     0x70A2 => LevelUpMastery,
     0x70A1 => LearnSkill,
     0x7050 => IncreaseStr,
-    0x7051 => IncreaseInt
+    0x7051 => IncreaseInt,
+    0x7060 => CreateParty,
+    0x7061 => LeaveParty,
+    0x7062 => InviteToParty,
+    0x7063 => KickFromParty,
+    0x3080 => PlayerInvitationResponse,
+    0x70F0 => CreateGuild,
+    0x70F1 => DisbandGuild,
+    0x70F3 => InviteToGuild,
+    0x70F4 => LeaveGuild,
+    0x70F5 => KickFromGuild,
+    0x70F9 => UpdateGuildNotice,
+    0x70FB => SetGuildMemberRank,
+    0x70B1 => OpenStall,
+    0x70B2 => CloseStall,
+    0x70B3 => VisitStall,
+    0x70B4 => BuyFromStall,
+    0x70B5 => LeaveStall,
+    0x70BA => UpdateStall,
+    0x7150 => AlchemyRequest,
+    0x7081 => StartExchange,
+    0x7082 => ConfirmExchange,
+    0x7083 => ApproveExchange,
+    0x7084 => CancelExchange,
+    0x7516 => PvpCapeRequest
 }
 
 macro_rules! server_packets {
@@ -136,19 +188,35 @@ server_packets! {
     0x3013 => CharacterSpawn,
     0x34A6 => CharacterSpawnEnd,
     0x3077 => CharacterFinished,
+    0x34B5 => TeleportStart,
+    0xB059 => DesignateReturnPointResponse,
+    0xB05A => TeleportResponse,
     0x3016 => EntityDespawn,
     0x3015 => EntitySpawn,
     0x3017 => GroupEntitySpawnStart,
     0x3019 => GroupEntitySpawnData,
     0x3018 => GroupEntitySpawnEnd,
     0xB50E => ConsignmentResponse,
+    0xB508 => ConsignmentRegisterResponse,
+    0xB509 => ConsignmentAbortResponse,
+    0xB50A => ConsignmentSettleResponse,
+    0xB50B => ConsignmentSearchResponse,
+    0xB50C => ConsignmentBuyResponse,
     0x3809 => WeatherUpdate,
     0x3305 => FriendListInfo,
+    0x7302 => FriendRequest,
+    0xB302 => AddFriendResponse,
+    0xB304 => DeleteFriendResponse,
+    0xB310 => CreateFriendGroupResponse,
+    0xB311 => DeleteFriendGroupResponse,
+    0xB312 => MoveFriendToGroupResponse,
+    0x3303 => FriendStatusUpdate,
     0x300C => GameNotification,
     0xB021 => PlayerMovementResponse,
     0x30BF => EntityUpdateState,
     0xB045 => TargetEntityResponse,
     0xB04B => UnTargetEntityResponse,
+    0xB046 => TalkToNpcResponse,
     0x3535 => TextCharacterInitialization,
     0x3555 => MacroStatus,
     0x3026 => ChatUpdate,
@@ -170,6 +238,12 @@ server_packets! {
     0x2212 => Disconnect,
     0x3057 => EntityBarsUpdate,
     0xB034 => InventoryOperationResult,
+    0xB04C => UseItemResponse,
+    0xB03C => OpenStorageResponse,
+    0x3047 => StorageGold,
+    0x3048 => StorageStart,
+    0x3049 => StorageData,
+    0x304A => StorageEnd,
     0xB010 => GmResponse,
     0xB55D => OpenItemMallResponse,
     0xB074 => PerformActionResponse,
@@ -181,10 +255,53 @@ server_packets! {
     0x30D0 => ChangeSpeed,
     0x3054 => LevelUpEffect,
     0x3056 => ReceiveExperience,
+    0x30E2 => JobExperienceUpdate,
     0xB0A2 => LevelUpMasteryResponse,
     0xB0A1 => LearnSkillResponse,
     0xB050 => IncreaseStrResponse,
-    0xB051 => IncreaseIntResponse
+    0xB051 => IncreaseIntResponse,
+    0xB060 => CreatePartyResponse,
+    0xB061 => LeavePartyResponse,
+    0xB062 => InviteToPartyResponse,
+    0xB063 => KickFromPartyResponse,
+    0x3080 => PlayerInvitationRequest,
+    0x3065 => PartyInfo,
+    0x3864 => PartyUpdate,
+    0xB0F0 => CreateGuildResponse,
+    0xB0F1 => DisbandGuildResponse,
+    0xB0F3 => InviteToGuildResponse,
+    0xB0F4 => LeaveGuildResponse,
+    0xB0F5 => KickFromGuildResponse,
+    0xB0F9 => UpdateGuildNoticeResponse,
+    0xB0FB => SetGuildMemberRankResponse,
+    0x3101 => GuildInfo,
+    0x38F5 => GuildUpdate,
+    0x30FF => GuildEntityUpdate,
+    0xB0BD => BuffAdded,
+    0xB072 => BuffRemoved,
+    0xB0B1 => OpenStallResponse,
+    0xB0B2 => CloseStallResponse,
+    0xB0B3 => VisitStallResponse,
+    0xB0B4 => BuyFromStallResponse,
+    0xB0B5 => LeaveStallResponse,
+    0xB0BA => UpdateStallResponse,
+    0x30B7 => StallUpdate,
+    0x30B8 => StallEntityOpened,
+    0x30B9 => StallEntityClosed,
+    0x30BB => StallEntityRenamed,
+    0xB150 => AlchemyResponse,
+    0xB081 => StartExchangeResponse,
+    0xB082 => ConfirmExchangeResponse,
+    0xB083 => ApproveExchangeResponse,
+    0xB084 => CancelExchangeResponse,
+    0x3085 => ExchangeStarted,
+    0x3086 => ExchangeConfirmed,
+    0x3087 => ExchangeCompleted,
+    0x3088 => ExchangeCancelled,
+    0x3089 => ExchangeOfferUpdate,
+    0x308A => ExchangeApproved,
+    0xB516 => PvpCapeResponse,
+    0x3516 => PvpCapeUpdate
 }
 
 impl ServerPacket {
@@ -7,6 +7,8 @@ pub enum MovementType {
     Running,
     #[silkroad(value = 1)]
     Walking,
+    #[silkroad(value = 4)]
+    Sitting,
 }
 
 #[derive(Copy, Clone, Serialize, Deserialize, ByteSize)]
@@ -165,6 +167,17 @@ pub struct Rotation {
     pub heading: u16,
 }
 
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub enum CharacterActionKind {
+    #[silkroad(value = 4)]
+    ToggleSit,
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct CharacterAction {
+    pub kind: CharacterActionKind,
+}
+
 #[derive(Serialize, ByteSize, Copy, Clone)]
 pub struct ChangeSpeed {
     pub entity: u32,
@@ -0,0 +1,318 @@
+use byteorder::ReadBytesExt;
+use bytes::BytesMut;
+use silkroad_serde::*;
+use std::io::Read;
+
+/// The settings of a party, which are chosen by the leader when creating the party.
+/// On the wire, these are encoded as a set of flags inside a single byte.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct PartySettings {
+    pub exp_shared: bool,
+    pub item_shared: bool,
+    pub anyone_can_invite: bool,
+}
+
+impl PartySettings {
+    const EXP_SHARED: u8 = 1;
+    const ITEM_SHARED: u8 = 2;
+    const ANYONE_CAN_INVITE: u8 = 4;
+
+    pub fn new(exp_shared: bool, item_shared: bool, anyone_can_invite: bool) -> Self {
+        PartySettings {
+            exp_shared,
+            item_shared,
+            anyone_can_invite,
+        }
+    }
+
+    fn as_flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.exp_shared {
+            flags |= Self::EXP_SHARED;
+        }
+        if self.item_shared {
+            flags |= Self::ITEM_SHARED;
+        }
+        if self.anyone_can_invite {
+            flags |= Self::ANYONE_CAN_INVITE;
+        }
+        flags
+    }
+
+    fn from_flags(flags: u8) -> Self {
+        PartySettings {
+            exp_shared: flags & Self::EXP_SHARED != 0,
+            item_shared: flags & Self::ITEM_SHARED != 0,
+            anyone_can_invite: flags & Self::ANYONE_CAN_INVITE != 0,
+        }
+    }
+}
+
+impl Serialize for PartySettings {
+    fn write_to(&self, writer: &mut BytesMut) {
+        self.as_flags().write_to(writer);
+    }
+}
+
+impl ByteSize for PartySettings {
+    fn byte_size(&self) -> usize {
+        1
+    }
+}
+
+impl Deserialize for PartySettings {
+    fn read_from<T: Read + ReadBytesExt>(reader: &mut T) -> Result<Self, SerializationError> {
+        Ok(PartySettings::from_flags(u8::read_from(reader)?))
+    }
+}
+
+#[derive(Clone, Copy, Serialize, ByteSize)]
+#[silkroad(size = 2)]
+pub enum PartyErrorCode {
+    #[silkroad(value = 0x2C01)]
+    InvalidTarget,
+    #[silkroad(value = 0x2C02)]
+    AlreadyInParty,
+    #[silkroad(value = 0x2C03)]
+    PartyFull,
+    #[silkroad(value = 0x2C04)]
+    NotAllowed,
+    #[silkroad(value = 0x2C05)]
+    TargetBusy,
+    #[silkroad(value = 0x2C06)]
+    Declined,
+    #[silkroad(value = 0x2C07)]
+    NotInParty,
+}
+
+#[derive(Clone, Copy, Serialize, ByteSize)]
+pub enum PartyResult {
+    #[silkroad(value = 1)]
+    Success,
+    #[silkroad(value = 2)]
+    Error(PartyErrorCode),
+}
+
+impl PartyResult {
+    pub fn error(code: PartyErrorCode) -> Self {
+        PartyResult::Error(code)
+    }
+}
+
+/// Invites the target player to form a new party with the sender.
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct CreateParty {
+    pub target: u32,
+    pub settings: PartySettings,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct CreatePartyResponse {
+    pub result: PartyResult,
+}
+
+impl CreatePartyResponse {
+    pub fn new(result: PartyResult) -> Self {
+        CreatePartyResponse { result }
+    }
+}
+
+/// Invites the target player into the party the sender is already a member of.
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct InviteToParty {
+    pub target: u32,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct InviteToPartyResponse {
+    pub result: PartyResult,
+}
+
+impl InviteToPartyResponse {
+    pub fn new(result: PartyResult) -> Self {
+        InviteToPartyResponse { result }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct LeaveParty;
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct LeavePartyResponse {
+    pub result: PartyResult,
+}
+
+impl LeavePartyResponse {
+    pub fn new(result: PartyResult) -> Self {
+        LeavePartyResponse { result }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct KickFromParty {
+    pub member: u32,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct KickFromPartyResponse {
+    pub result: PartyResult,
+}
+
+impl KickFromPartyResponse {
+    pub fn new(result: PartyResult) -> Self {
+        KickFromPartyResponse { result }
+    }
+}
+
+/// Asks the receiving player if they want to accept the invitation of the requesting player.
+#[derive(Clone, Serialize, ByteSize)]
+pub enum PlayerInvitationRequest {
+    #[silkroad(value = 1)]
+    Exchange { requester: u32 },
+    #[silkroad(value = 2)]
+    PartyCreation { requester: u32, settings: PartySettings },
+    #[silkroad(value = 3)]
+    PartyInvitation { requester: u32, settings: PartySettings },
+    #[silkroad(value = 5)]
+    GuildInvitation { requester: u32 },
+}
+
+impl PlayerInvitationRequest {
+    pub fn party_creation(requester: u32, settings: PartySettings) -> Self {
+        PlayerInvitationRequest::PartyCreation { requester, settings }
+    }
+
+    pub fn party_invitation(requester: u32, settings: PartySettings) -> Self {
+        PlayerInvitationRequest::PartyInvitation { requester, settings }
+    }
+
+    pub fn guild_invitation(requester: u32) -> Self {
+        PlayerInvitationRequest::GuildInvitation { requester }
+    }
+
+    pub fn exchange(requester: u32) -> Self {
+        PlayerInvitationRequest::Exchange { requester }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub enum PlayerInvitationResponse {
+    #[silkroad(value = 1)]
+    Answered { accepted: bool },
+    #[silkroad(value = 2)]
+    Cancelled { code: u16 },
+}
+
+impl PlayerInvitationResponse {
+    pub fn accepted(&self) -> bool {
+        matches!(self, PlayerInvitationResponse::Answered { accepted: true })
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct PartyMemberPosition {
+    pub region: u16,
+    pub x: u16,
+    pub y: u16,
+    pub z: u16,
+}
+
+impl PartyMemberPosition {
+    pub fn new(region: u16, x: u16, y: u16, z: u16) -> Self {
+        PartyMemberPosition { region, x, y, z }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct PartyMemberInfo {
+    pub unknown: u8,
+    pub id: u32,
+    pub name: String,
+    pub ref_id: u32,
+    pub level: u8,
+    /// Health (upper 4 bits) and mana (lower 4 bits), each on a scale from 0 to 10.
+    pub hp_mp: u8,
+    pub position: PartyMemberPosition,
+}
+
+impl PartyMemberInfo {
+    pub fn new(id: u32, name: String, ref_id: u32, level: u8, hp_mp: u8, position: PartyMemberPosition) -> Self {
+        PartyMemberInfo {
+            unknown: 0xFF,
+            id,
+            name,
+            ref_id,
+            level,
+            hp_mp,
+            position,
+        }
+    }
+}
+
+/// Contains the complete state of a party and is sent to players when they join a party.
+#[derive(Clone, Serialize, ByteSize)]
+pub struct PartyInfo {
+    pub unknown: u8,
+    pub id: u32,
+    pub leader: u32,
+    pub settings: PartySettings,
+    pub members: Vec<PartyMemberInfo>,
+}
+
+impl PartyInfo {
+    pub fn new(id: u32, leader: u32, settings: PartySettings, members: Vec<PartyMemberInfo>) -> Self {
+        PartyInfo {
+            unknown: 0xFF,
+            id,
+            leader,
+            settings,
+            members,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, ByteSize)]
+pub enum PartyLeaveReason {
+    #[silkroad(value = 1)]
+    Left,
+    #[silkroad(value = 4)]
+    Kicked,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum PartyMemberUpdate {
+    #[silkroad(value = 2)]
+    Level(u8),
+    #[silkroad(value = 4)]
+    HpMp(u8),
+    #[silkroad(value = 0x20)]
+    Position(PartyMemberPosition),
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum PartyUpdate {
+    #[silkroad(value = 1)]
+    Dismissed { unknown: u16 },
+    #[silkroad(value = 2)]
+    MemberJoined(PartyMemberInfo),
+    #[silkroad(value = 3)]
+    MemberLeft { id: u32, reason: PartyLeaveReason },
+    #[silkroad(value = 6)]
+    MemberUpdate { id: u32, update: PartyMemberUpdate },
+    #[silkroad(value = 9)]
+    LeaderChanged { id: u32 },
+}
+
+impl PartyUpdate {
+    pub fn dismissed() -> Self {
+        PartyUpdate::Dismissed { unknown: 0x0B }
+    }
+
+    pub fn member_left(id: u32, reason: PartyLeaveReason) -> Self {
+        PartyUpdate::MemberLeft { id, reason }
+    }
+
+    pub fn member_update(id: u32, update: PartyMemberUpdate) -> Self {
+        PartyUpdate::MemberUpdate { id, update }
+    }
+}
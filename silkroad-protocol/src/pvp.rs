@@ -0,0 +1,41 @@
+use crate::world::PvpCape;
+use silkroad_serde::*;
+
+// ??? TODO
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, ByteSize, Debug)]
+#[silkroad(size = 2)]
+pub enum PvpErrorCode {
+    #[silkroad(value = 0x4401)]
+    NotAllowed,
+    #[silkroad(value = 0x4402)]
+    AlreadyChanging,
+    #[silkroad(value = 0x4403)]
+    Murderer,
+}
+
+/// Puts on the given cape, or takes the current one off when choosing [PvpCape::None].
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct PvpCapeRequest {
+    pub cape: PvpCape,
+}
+
+#[derive(Clone, Copy, Serialize, ByteSize)]
+pub enum PvpCapeResponse {
+    #[silkroad(value = 1)]
+    Success,
+    #[silkroad(value = 2)]
+    Error(PvpErrorCode),
+}
+
+/// Informs about an entity that changed their cape, including the player themselves.
+#[derive(Clone, Copy, Serialize, ByteSize)]
+pub struct PvpCapeUpdate {
+    pub unique_id: u32,
+    pub cape: PvpCape,
+}
+
+impl PvpCapeUpdate {
+    pub fn new(unique_id: u32, cape: PvpCape) -> Self {
+        PvpCapeUpdate { unique_id, cape }
+    }
+}
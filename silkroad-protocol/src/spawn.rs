@@ -2,7 +2,7 @@ use crate::community::GuildInformation;
 use crate::inventory::{CharacterSpawnItemData, InventoryAvatarItemData, InventoryItemData};
 use crate::movement::{EntityMovementState, Position};
 use crate::skill::{HotkeyData, MasteryData, SkillData};
-use crate::world::{ActiveScroll, EntityState, InteractOptions, JobType, PlayerKillState, PvpCape};
+use crate::world::{ActiveScroll, EntityState, InteractMode, InteractOptions, JobType, PlayerKillState, PvpCape};
 use chrono::{DateTime, Utc};
 use silkroad_definitions::rarity::EntityRarity;
 use silkroad_serde::*;
@@ -403,6 +403,19 @@ impl ActiveQuestObjectData {
     }
 }
 
+/// The stall a character currently has opened, which is only present if the interact mode is [InteractMode::Stall].
+#[derive(Clone, Serialize, ByteSize)]
+pub struct StallSpawnInformation {
+    pub name: String,
+    pub decoration: u32,
+}
+
+impl StallSpawnInformation {
+    pub fn new(name: String, decoration: u32) -> Self {
+        StallSpawnInformation { name, decoration }
+    }
+}
+
 #[derive(Clone, Serialize, ByteSize)]
 #[silkroad(size = 0)]
 pub enum EntityTypeSpawnData {
@@ -427,9 +440,11 @@ pub enum EntityTypeSpawnData {
         mounted: bool,
         in_combat: bool,
         active_scroll: ActiveScroll,
-        unknown2: u8,
+        interact_mode: InteractMode,
         guild: GuildInformation,
         unknown3: [u8; 9],
+        #[silkroad(size = 0)]
+        stall: Option<StallSpawnInformation>,
         equipment_cooldown: bool,
         pk_state: PlayerKillState,
         unknown4: u8,
@@ -450,6 +465,14 @@ pub enum EntityTypeSpawnData {
         rarity: EntityRarity,
         unknown: u32,
     },
+    Teleport {
+        unique_id: u32,
+        position: Position,
+        unknown: [u8; 2],
+        kind: u8,
+        unknown_1: u32,
+        unknown_2: u32,
+    },
 }
 
 impl EntityTypeSpawnData {
@@ -508,9 +531,10 @@ impl EntityTypeSpawnData {
             mounted,
             in_combat,
             active_scroll,
-            unknown2: 0,
+            interact_mode: InteractMode::None,
             guild,
             unknown3,
+            stall: None,
             equipment_cooldown,
             pk_state,
             unknown4: 0xFF,
@@ -536,4 +560,15 @@ impl EntityTypeSpawnData {
             unknown,
         }
     }
+
+    pub fn teleport(unique_id: u32, position: Position) -> Self {
+        EntityTypeSpawnData::Teleport {
+            unique_id,
+            position,
+            unknown: [0; 2],
+            kind: 1,
+            unknown_1: 0,
+            unknown_2: 0,
+        }
+    }
 }
@@ -0,0 +1,271 @@
+use crate::inventory::{InventoryItemContentData, RentInfo};
+use silkroad_serde::*;
+
+// ??? TODO
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, ByteSize, Debug)]
+#[silkroad(size = 2)]
+pub enum StallErrorCode {
+    #[silkroad(value = 0x3C01)]
+    InvalidTarget,
+    #[silkroad(value = 0x3C02)]
+    InvalidName,
+    #[silkroad(value = 0x3C03)]
+    NotAllowed,
+    #[silkroad(value = 0x3C04)]
+    TooFarAway,
+    #[silkroad(value = 0x3C05)]
+    NotOpen,
+    #[silkroad(value = 0x3C06)]
+    InvalidItem,
+    #[silkroad(value = 0x3C07)]
+    ItemUnavailable,
+    #[silkroad(value = 0x3C08)]
+    NotEnoughGold,
+    #[silkroad(value = 0x3C09)]
+    InventoryFull,
+    #[silkroad(value = 0x3C0A)]
+    StallFull,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum StallResult {
+    #[silkroad(value = 1)]
+    Success,
+    #[silkroad(value = 2)]
+    Error(StallErrorCode),
+}
+
+impl StallResult {
+    pub fn error(code: StallErrorCode) -> Self {
+        StallResult::Error(code)
+    }
+}
+
+impl From<Result<(), StallErrorCode>> for StallResult {
+    fn from(result: Result<(), StallErrorCode>) -> Self {
+        match result {
+            Ok(()) => StallResult::Success,
+            Err(code) => StallResult::Error(code),
+        }
+    }
+}
+
+/// An item on sale in a stall, which still resides in the inventory of the stall owner.
+#[derive(Clone, Serialize, ByteSize)]
+pub struct StallItemData {
+    pub slot: u8,
+    pub rent_data: RentInfo,
+    pub ref_id: u32,
+    pub content: InventoryItemContentData,
+    pub inventory_slot: u8,
+    pub amount: u16,
+    pub price: u64,
+}
+
+impl StallItemData {
+    pub fn new(
+        slot: u8,
+        ref_id: u32,
+        content: InventoryItemContentData,
+        inventory_slot: u8,
+        amount: u16,
+        price: u64,
+    ) -> Self {
+        StallItemData {
+            slot,
+            rent_data: RentInfo::Empty,
+            ref_id,
+            content,
+            inventory_slot,
+            amount,
+            price,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct OpenStall {
+    pub name: String,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct OpenStallResponse {
+    pub result: StallResult,
+}
+
+impl OpenStallResponse {
+    pub fn new(result: StallResult) -> Self {
+        OpenStallResponse { result }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct CloseStall;
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct CloseStallResponse {
+    pub result: StallResult,
+}
+
+impl CloseStallResponse {
+    pub fn new(result: StallResult) -> Self {
+        CloseStallResponse { result }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub enum UpdateStallKind {
+    #[silkroad(value = 1)]
+    UpdateItem { slot: u8, amount: u16, price: u64 },
+    #[silkroad(value = 2)]
+    AddItem {
+        slot: u8,
+        inventory_slot: u8,
+        amount: u16,
+        price: u64,
+    },
+    #[silkroad(value = 3)]
+    RemoveItem { slot: u8 },
+    #[silkroad(value = 6)]
+    State { open: bool },
+    #[silkroad(value = 7)]
+    Message { message: String },
+    #[silkroad(value = 8)]
+    Name { name: String },
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct UpdateStall {
+    pub kind: UpdateStallKind,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct UpdateStallResponse {
+    pub result: StallResult,
+}
+
+impl UpdateStallResponse {
+    pub fn new(result: StallResult) -> Self {
+        UpdateStallResponse { result }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct VisitStall {
+    pub unique_id: u32,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum VisitStallResponse {
+    #[silkroad(value = 1)]
+    Success {
+        unique_id: u32,
+        message: String,
+        open: bool,
+        items: Vec<StallItemData>,
+    },
+    #[silkroad(value = 2)]
+    Error(StallErrorCode),
+}
+
+impl VisitStallResponse {
+    pub fn success(unique_id: u32, message: String, open: bool, items: Vec<StallItemData>) -> Self {
+        VisitStallResponse::Success {
+            unique_id,
+            message,
+            open,
+            items,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct LeaveStall;
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct LeaveStallResponse {
+    pub result: StallResult,
+}
+
+impl LeaveStallResponse {
+    pub fn new(result: StallResult) -> Self {
+        LeaveStallResponse { result }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct BuyFromStall {
+    pub slot: u8,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct BuyFromStallResponse {
+    pub result: StallResult,
+}
+
+impl BuyFromStallResponse {
+    pub fn new(result: StallResult) -> Self {
+        BuyFromStallResponse { result }
+    }
+}
+
+/// Informs the owner and the visitors of a stall about things happening inside it.
+// ??? TODO
+#[derive(Clone, Serialize, ByteSize)]
+pub enum StallUpdate {
+    #[silkroad(value = 1)]
+    Leave { unique_id: u32 },
+    #[silkroad(value = 2)]
+    Enter { unique_id: u32 },
+    #[silkroad(value = 3)]
+    Buy { slot: u8, buyer: String },
+    #[silkroad(value = 4)]
+    Content {
+        open: bool,
+        message: String,
+        items: Vec<StallItemData>,
+    },
+    #[silkroad(value = 5)]
+    Closed,
+}
+
+/// Shows a newly opened stall to the players around its owner.
+#[derive(Clone, Serialize, ByteSize)]
+pub struct StallEntityOpened {
+    pub unique_id: u32,
+    pub name: String,
+    pub decoration: u32,
+}
+
+impl StallEntityOpened {
+    pub fn new(unique_id: u32, name: String, decoration: u32) -> Self {
+        StallEntityOpened {
+            unique_id,
+            name,
+            decoration,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct StallEntityClosed {
+    pub unique_id: u32,
+}
+
+impl StallEntityClosed {
+    pub fn new(unique_id: u32) -> Self {
+        StallEntityClosed { unique_id }
+    }
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub struct StallEntityRenamed {
+    pub unique_id: u32,
+    pub name: String,
+}
+
+impl StallEntityRenamed {
+    pub fn new(unique_id: u32, name: String) -> Self {
+        StallEntityRenamed { unique_id, name }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::movement::MovementType;
 use silkroad_serde::*;
 
-#[derive(Clone, Eq, PartialEq, Copy, Serialize, Deserialize, ByteSize)]
+#[derive(Clone, Eq, PartialEq, Copy, Serialize, Deserialize, ByteSize, Debug)]
 pub enum PvpCape {
     #[silkroad(value = 0)]
     None,
@@ -27,7 +27,89 @@ pub enum AliveState {
     Dead,
 }
 
-#[derive(Clone, Eq, PartialEq, Copy, Serialize, ByteSize)]
+/// The choice a player makes after dying.
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub enum ResurrectionKind {
+    #[silkroad(value = 1)]
+    ReturnToTown,
+    #[silkroad(value = 2)]
+    AtPresentPoint,
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct Resurrect {
+    pub kind: ResurrectionKind,
+}
+
+/// Tells the client to show the loading screen, after which it is ready to receive the spawn at the new location.
+#[derive(Clone, Serialize, ByteSize)]
+pub struct TeleportStart;
+
+#[derive(Deserialize, Copy, Clone)]
+pub struct TeleportConfirm;
+
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct DesignateReturnPoint {
+    pub unique_id: u32,
+}
+
+#[derive(Clone, Copy, Serialize, ByteSize)]
+#[silkroad(size = 2)]
+pub enum DesignateReturnPointError {
+    #[silkroad(value = 0x01)]
+    InvalidTarget,
+    #[silkroad(value = 0x1801)]
+    TooFarAway,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum DesignateReturnPointResponse {
+    #[silkroad(value = 1)]
+    Success,
+    #[silkroad(value = 2)]
+    Error(DesignateReturnPointError),
+}
+
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub enum TeleportKind {
+    #[silkroad(value = 2)]
+    Teleport { destination: u32 },
+}
+
+/// A request to use the teleporter with the given unique id, which can either be a building or an NPC.
+#[derive(Clone, Copy, Deserialize, ByteSize)]
+pub struct TeleportRequest {
+    pub unique_id: u32,
+    pub kind: TeleportKind,
+}
+
+#[derive(Clone, Copy, Serialize, ByteSize)]
+#[silkroad(size = 2)]
+pub enum TeleportError {
+    #[silkroad(value = 0x01)]
+    InvalidTarget,
+    #[silkroad(value = 0x1801)]
+    TooFarAway,
+    // ??? TODO
+    #[silkroad(value = 0x180F)]
+    NotEnoughGold,
+    // ??? TODO
+    #[silkroad(value = 0x1C15)]
+    LevelTooLow,
+    // ??? TODO
+    #[silkroad(value = 0x1C16)]
+    LevelTooHigh,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum TeleportResponse {
+    #[silkroad(value = 1)]
+    Success,
+    #[silkroad(value = 2)]
+    Error(TeleportError),
+}
+
+#[derive(Clone, Eq, PartialEq, Copy, Serialize, ByteSize, Debug)]
 pub enum JobType {
     #[silkroad(value = 0)]
     None,
@@ -39,7 +121,7 @@ pub enum JobType {
     Hunter,
 }
 
-#[derive(Clone, Eq, PartialEq, PartialOrd, Copy, Serialize, ByteSize)]
+#[derive(Clone, Eq, PartialEq, PartialOrd, Copy, Serialize, ByteSize, Debug)]
 pub enum PlayerKillState {
     #[silkroad(value = 0xFF)]
     None,
@@ -49,6 +131,18 @@ pub enum PlayerKillState {
     Red,
 }
 
+impl PlayerKillState {
+    /// The value of the state as used in the character data and in state updates, which, unlike the spawn of other
+    /// players, use zero for innocent players.
+    pub fn level(&self) -> u8 {
+        match self {
+            PlayerKillState::None => 0,
+            PlayerKillState::Purple => 1,
+            PlayerKillState::Red => 2,
+        }
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Copy, Serialize, ByteSize)]
 pub enum ActiveScroll {
     #[silkroad(value = 0)]
@@ -73,6 +167,19 @@ impl InteractOptions {
     }
 }
 
+/// What a character is currently interacting with, which is visible to others.
+#[derive(Clone, Eq, PartialEq, Copy, Serialize, ByteSize)]
+pub enum InteractMode {
+    #[silkroad(value = 0)]
+    None,
+    #[silkroad(value = 2)]
+    Exchange,
+    #[silkroad(value = 4)]
+    Stall,
+    #[silkroad(value = 6)]
+    Deal,
+}
+
 #[derive(Clone, Eq, PartialEq, PartialOrd, Copy, Serialize, ByteSize)]
 pub enum BodyState {
     #[silkroad(value = 0)]
@@ -93,7 +200,7 @@ pub enum BodyState {
     Invisible,
 }
 
-#[derive(Clone, Eq, PartialEq, PartialOrd, Copy, Serialize, ByteSize)]
+#[derive(Clone, Eq, PartialEq, PartialOrd, Copy, Serialize, ByteSize, Debug)]
 pub enum WeatherType {
     #[silkroad(value = 1)]
     Clear,
@@ -128,6 +235,8 @@ pub enum TargetEntityError {
 pub enum TargetEntityData {
     Monster { unknown: u32, interact_data: Option<u8> },
     NPC { talk_options: Option<InteractOptions> },
+    // ??? TODO
+    Player { unknown: u32 },
 }
 
 #[derive(Clone, Serialize, ByteSize)]
@@ -158,15 +267,23 @@ impl TargetEntityResult {
         }
     }
 
-    pub fn success_npc(unique_id: u32) -> Self {
+    pub fn success_npc(unique_id: u32, options: Vec<u8>) -> Self {
         TargetEntityResult::Success {
             unique_id,
             health: None,
             entity_data: TargetEntityData::NPC {
-                talk_options: Some(InteractOptions::talk(vec![])),
+                talk_options: Some(InteractOptions::talk(options)),
             },
         }
     }
+
+    pub fn success_player(unique_id: u32, health: u32) -> Self {
+        TargetEntityResult::Success {
+            unique_id,
+            health: Some(health),
+            entity_data: TargetEntityData::Player { unknown: 0 },
+        }
+    }
 }
 
 #[derive(Clone, Serialize, ByteSize)]
@@ -316,7 +433,7 @@ pub enum UpdatedState {
     #[silkroad(value = 8)]
     Battle(bool),
     #[silkroad(value = 11)]
-    Scroll(u8),
+    Scroll(ActiveScroll),
 }
 
 #[derive(Clone, Copy, Serialize, ByteSize)]
@@ -346,6 +463,20 @@ impl EntityUpdateState {
             update: UpdatedState::Body(new),
         }
     }
+
+    pub fn scroll(unique_id: u32, new: ActiveScroll) -> Self {
+        EntityUpdateState {
+            unique_id,
+            update: UpdatedState::Scroll(new),
+        }
+    }
+
+    pub fn player_kill(unique_id: u32, new: PlayerKillState) -> Self {
+        EntityUpdateState {
+            unique_id,
+            update: UpdatedState::Pvp(new.level()),
+        }
+    }
 }
 
 #[derive(Clone, Deserialize, ByteSize)]
@@ -364,6 +495,47 @@ impl TargetEntityResponse {
     }
 }
 
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize, ByteSize)]
+pub enum TalkOption {
+    #[silkroad(value = 1)]
+    Store,
+    // ??? TODO
+    #[silkroad(value = 3)]
+    Storage,
+}
+
+impl From<TalkOption> for u8 {
+    fn from(option: TalkOption) -> Self {
+        match option {
+            TalkOption::Store => 1,
+            TalkOption::Storage => 3,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, ByteSize)]
+pub struct TalkToNpc {
+    pub unique_id: u32,
+    pub option: TalkOption,
+}
+
+#[derive(Clone, Copy, Serialize, ByteSize)]
+#[silkroad(size = 2)]
+pub enum TalkToNpcError {
+    #[silkroad(value = 0x01)]
+    NoSuchOption,
+    #[silkroad(value = 0x1801)]
+    TooFarAway,
+}
+
+#[derive(Clone, Serialize, ByteSize)]
+pub enum TalkToNpcResponse {
+    #[silkroad(value = 1)]
+    Success(TalkOption),
+    #[silkroad(value = 2)]
+    Error(TalkToNpcError),
+}
+
 #[derive(Clone, Deserialize, ByteSize)]
 pub struct UnTargetEntity {
     pub unique_id: u32,
@@ -453,6 +625,19 @@ impl CharacterPointsUpdate {
     }
 }
 
+/// The current job level and experience of the player, sent whenever either of them changes.
+#[derive(Serialize, ByteSize, Copy, Clone)]
+pub struct JobExperienceUpdate {
+    pub level: u8,
+    pub experience: u32,
+}
+
+impl JobExperienceUpdate {
+    pub fn new(level: u8, experience: u32) -> Self {
+        JobExperienceUpdate { level, experience }
+    }
+}
+
 #[derive(Clone, Serialize, ByteSize, Debug)]
 pub struct CharacterEquipItem {
     pub entity: u32,